@@ -50,6 +50,60 @@ command-kit-not-enough-slots = Inventory doesn't have enough slots
 command-lantern-unequiped = Please equip a lantern first
 command-lantern-adjusted-strength = You adjusted flame strength.
 command-lantern-adjusted-strength-color = You adjusted flame strength and color.
+command-loot-roll-set = You will now { $choice } on items from boss encounters
+command-bounty-no-character = Only characters can have bounties on their heads
+command-bounty-no-noticeboard = You need to be in the middle of a town to reach its noticeboard
+command-bounty-none = There is no bounty on your head here
+command-bounty-info = There is a bounty of { $bounty } coins on your head here
+command-bounty-cannot-afford = You need { $bounty } coins to pay off your bounty
+command-bounty-paid = You paid off the { $bounty } coin bounty on your head
+command-house-no-character = Only characters can own houses
+command-house-none = You don't own or rent a house
+command-house-info-owned = You own the house at { $location }, its containers hold { $items } items
+command-house-info-rented = You rent the house at { $location } for another { $remaining }, its containers hold { $items } items
+command-house-not-in-house = You need to be inside the house you want
+command-house-already-owned = You already own this house
+command-house-taken = This house belongs to somebody else
+command-house-only-one = You can only have one house at a time
+command-house-cannot-afford = You need { $price } coins for this
+command-house-bought = You bought this house for { $price } coins. Use /build to build inside of it, and drop items next to a chest to store them
+command-house-rented = You rented this house for { $price } coins. Use /build to build inside of it, and drop items next to a chest to store them
+command-house-not-empty = Empty the containers in your house before leaving it
+command-house-left = You no longer live in this house
+command-house-not-yours = You can only furnish your own house
+command-house-no-space = There is no space for furniture here
+command-house-furnished = Placed furniture
+command-schematic-invalid-name = Schematic names may only contain letters, digits, '-' and '_'
+command-schematic-not-found = There is no schematic called { $name }
+command-schematic-failed = Failed to save or load the schematic: { $error }
+command-schematic-too-large = Schematics can't be larger than { $max } blocks
+command-schematic-not-build-area = The region must be inside an area you can build in
+command-schematic-not-loaded = Part of the region isn't loaded
+command-schematic-saved = Saved { $blocks } blocks to { $name }
+command-schematic-pasted = Pasted { $blocks } blocks of { $name }, { $skipped } blocks were outside of your build areas or couldn't be changed
+command-schematic-pasted-no-undo = Pasted { $blocks } blocks of { $name }, { $skipped } blocks were outside of your build areas or couldn't be changed. This was too many blocks to be undone
+command-schematic-list-empty = There are no schematics yet
+command-schematic-list = Schematics: { $names }
+command-edit-corner = Marked { $corner } at { $pos }, { $blocks } blocks are selected
+command-edit-deselected = Cleared your selection
+command-edit-no-selection = Mark the corners of a region with /edit pos1 and /edit pos2 first
+command-edit-not-build-area = The selection must be inside an area you can build in
+command-edit-not-loaded = Part of the selection isn't loaded
+command-edit-copied = Copied { $blocks } blocks
+command-edit-empty-clipboard = Copy or cut a region before pasting
+command-edit-too-large = Operations can't cover more than { $max } blocks
+command-edit-invalid-size = Sizes must be between 1 and { $max }
+command-edit-started = Changing up to { $blocks } blocks, { $ahead } operations are ahead of yours
+command-edit-done = Finished changing { $blocks } blocks, { $skipped } blocks were outside of your build areas or couldn't be changed
+command-edit-done-no-undo = Finished changing { $blocks } blocks, { $skipped } blocks were outside of your build areas or couldn't be changed. This was too many blocks to be undone
+command-edit-cancelled = Stopped your operations, the blocks they already changed can be put back with /undo
+command-edit-nothing-to-cancel = You have no operations in progress
+command-undo-nothing = There is nothing to undo
+command-redo-nothing = There is nothing to redo
+command-undo-done = Undid { $actions } edits, restoring { $blocks } blocks, { $skipped } blocks were outside of your build areas or couldn't be changed
+command-redo-done = Redid { $actions } edits, changing { $blocks } blocks, { $skipped } blocks were outside of your build areas or couldn't be changed
+command-undo-area-nothing = { $player } hasn't edited any blocks near here recently
+command-undo-area-done = Restored { $blocks } blocks edited by { $player }
 command-explosion-power-too-high = Explosion power mustn't be more than { $power }
 command-explosion-power-too-low = Explosion power must be more than { $power }
 # Note: Do not translate "confirm" here
@@ -78,7 +132,7 @@ command-location-deleted = Deleted location '{ $location }'
 command-locations-empty = No locations currently exist
 command-locations-list = Available locations: { $locations }
 # Note: Do not translate these weather names
-command-weather-valid-values = Valid values are 'clear', 'rain', 'wind' and 'storm'.
+command-weather-valid-values = Valid values are 'clear', 'cloudy', 'rain', 'snow', 'wind' and 'storm'.
 command-scale-set = Set scale to { $scale }
 command-repaired-items = Repaired all equipped items
 command-message-group-missing = You are using group chat but do not belong to a group. Use /world or
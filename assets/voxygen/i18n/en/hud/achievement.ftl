@@ -0,0 +1,29 @@
+hud-achievement-unlocked = Achievement unlocked: { $title }
+achievement-first_blood-title = First Blood
+achievement-first_blood-desc = Defeat your first enemy
+achievement-hunter-title = Hunter
+achievement-hunter-desc = Defeat 100 enemies
+achievement-slayer-title = Slayer
+achievement-slayer-desc = Defeat 1000 enemies
+achievement-minotaur_slayer-title = Into the Labyrinth
+achievement-minotaur_slayer-desc = Defeat a Minotaur
+achievement-mindflayer_slayer-title = Mind over Matter
+achievement-mindflayer_slayer-desc = Defeat a Mindflayer
+achievement-learning_to_fly-title = Learning to Fly
+achievement-learning_to_fly-desc = Die from fall damage 10 times
+achievement-persistent-title = Persistent
+achievement-persistent-desc = Die 100 times
+achievement-wanderer-title = Wanderer
+achievement-wanderer-desc = Travel 10,000 blocks
+achievement-voyager-title = Voyager
+achievement-voyager-desc = Travel 1,000,000 blocks
+achievement-apprentice_crafter-title = Apprentice Crafter
+achievement-apprentice_crafter-desc = Craft 10 items
+achievement-master_crafter-title = Master Crafter
+achievement-master_crafter-desc = Craft 1000 items
+achievement-dungeon_delver-title = Dungeon Delver
+achievement-dungeon_delver-desc = Clear a dungeon
+achievement-dungeon_master-title = Dungeon Master
+achievement-dungeon_master-desc = Clear 25 dungeons
+achievement-prospector-title = Prospector
+achievement-prospector-desc = Mine 100 blocks
@@ -0,0 +1,12 @@
+hud-encounter-engaged = { $name } has sealed the way out!
+hud-encounter-reset = { $name } has recovered and the way is open again
+hud-encounter-enraged = The boss has become enraged!
+hud-encounter-loot-won = { $player } won { $count ->
+    [1] an item
+   *[other] { $count } items
+} from the encounter
+hud-encounter-sahagin = The Tidal Warrior
+hud-encounter-sahagin-phase_tide = The tide turns, the Tidal Warrior quickens!
+hud-encounter-myrmidon = The Minotaur
+hud-encounter-myrmidon-phase_stand = The Minotaur hardens its stance!
+hud-encounter-myrmidon-phase_rampage = The Minotaur flies into a rampage!
@@ -0,0 +1,3 @@
+hud-house-evicted = Your rent has run out and you have been evicted from your house
+hud-house-items-returned = The { $count } items you stored in your old house were returned to you
+hud-house-items-partly-returned = { $count } items you stored in your old house were returned to you. Make space in your inventory for the rest
@@ -74,6 +74,7 @@ hud-init-stage-server-worldciv-civcreate = [{ -server }]: Generated { $generated
 hud-init-stage-server-worldciv-site = [{ -server }]: Generating sites...
 hud-init-stage-server-economysim = [{ -server }]: Simulating economy...
 hud-init-stage-server-spotgen = [{ -server }]: Generating spots...
+hud-init-stage-server-cavegraph = [{ -server }]: Mapping caves...
 hud-init-stage-server-starting = [{ -server }]: Starting server...
 hud-init-stage-multiplayer = Starting multiplayer
 hud-init-stage-client-connection-establish = [{ -client }]: Establishing connection to server...
@@ -0,0 +1,2 @@
+hud-pet-rename-invalid = Pet names must not be empty and may be at most { $max } characters long
+hud-pet-stable-not_in_settlement = You need to be in a settlement to use its stable
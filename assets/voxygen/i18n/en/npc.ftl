@@ -241,6 +241,39 @@ npc-speech-guard_thought =
     .a0 = My brother's out fighting ogres. What do I get? Guard duty...
     .a1 = Just one more patrol, then I can head home.
     .a2 = No bandits are going to get past me.
+npc-speech-guard_arrest =
+    .a0 = Halt, criminal! There's a bounty of { $bounty } coins on your head. Pay it at the noticeboard, or else!
+    .a1 = You're wanted for crimes against this town. Pay your { $bounty } coin bounty, and be quick about it.
+npc-speech-guard_resisting_arrest =
+    .a0 = You had your chance. Resisting arrest, are we?
+    .a1 = Enough! You'll answer for your crimes!
+npc-speech-guard_bounty_paid =
+    .a0 = Your debt is paid. Stay out of trouble.
+    .a1 = Alright, you're free to go. Don't let me catch you again.
+npc-speech-guard_wanted =
+    .a0 = It's the one on the wanted posters! Get them!
+    .a1 = Your crimes end here, scum!
+npc-speech-raid_start =
+    .a0 = To arms! We march on { $site }!
+    .a1 = { $site } will be ours before long.
+npc-speech-raid_won =
+    .a0 = Have you heard? We took { $site }!
+    .a1 = { $site } belongs to us now.
+npc-speech-raid_failed =
+    .a0 = Our raid on { $site } was a disaster.
+    .a1 = They drove us back from { $site }...
+npc-speech-caravan_setting_out =
+    .a0 = The caravan sets out for { $site }!
+    .a1 = Come on, these goods won't carry themselves to { $site }.
+npc-speech-caravan_escort_thanks =
+    .a0 = We made it! Thank you for keeping us safe on the road.
+    .a1 = I'd not have made it here in one piece without you. Thank you!
+npc-speech-raid_lost =
+    .a0 = We lost { $site } to raiders!
+    .a1 = Raiders took { $site }. Who will be next?
+npc-speech-raid_repelled =
+    .a0 = Raiders attacked { $site }, but we saw them off!
+    .a1 = They tried to take { $site }. They won't try again!
 npc-speech-merchant_sell_undirected =
     .a0 = All my goods are of the highest quality!
     .a1 = Does anybody want to buy my wares?
@@ -301,6 +334,44 @@ npc-speech-dist_near_to = very close
 
 npc-speech-arena = Let's sit over there!
 
+## NPC rumours
+
+npc-speech-rumour_hedge_witnessed = I saw it with my own eyes:
+npc-speech-rumour_hedge_reliable = I have it on good authority that
+npc-speech-rumour_hedge_hearsay = Word is that
+npc-speech-rumour_hedge_doubtful = I doubt it's true, but some say that
+npc-speech-rumour_someone = someone
+npc-speech-rumour_stranger = a stranger
+npc-speech-rumour_murder =
+    .a0 = { $hedge } { $killer } killed { $victim }.
+    .a1 = { $hedge } { $victim } was murdered by { $killer }.
+npc-speech-rumour_death =
+    .a0 = { $hedge } { $victim } has died.
+    .a1 = { $hedge } { $victim } is no longer with us.
+npc-speech-rumour_raid_captured =
+    .a0 = { $hedge } raiders took { $site }.
+    .a1 = { $hedge } { $site } fell to raiders.
+npc-speech-rumour_raid_repelled =
+    .a0 = { $hedge } raiders were driven back from { $site }.
+    .a1 = { $hedge } { $site } held out against raiders.
+npc-speech-rumour_theft =
+    .a0 = { $hedge } { $thief } has been stealing in { $site }.
+    .a1 = { $hedge } { $thief } was caught with their hands in the chests of { $site }.
+npc-speech-rumour_theft_wilds =
+    .a0 = { $hedge } { $thief } is a thief.
+npc-speech-rumour_assault =
+    .a0 = { $hedge } { $attacker } attacked { $victim }.
+    .a1 = { $hedge } { $victim } got into a fight with { $attacker }.
+npc-speech-rumour_trade =
+    .a0 = { $hedge } { $customer } has been doing business in { $site }.
+    .a1 = { $hedge } the merchants of { $site } have a new customer: { $customer }.
+npc-speech-rumour_monster =
+    .a0 = { $hedge } { $body } was spotted { $dir } of here, { $dist }.
+    .a1 = { $hedge } there's { $body } prowling { $dir } of here.
+npc-speech-rumour_heroics =
+    .a0 = { $hedge } { $hero } slew { $foe }!
+    .a1 = { $hedge } { $hero } fought { $foe } and won.
+
 ## NPC reactions
 
 npc-speech-witness_murder =
@@ -315,6 +386,16 @@ npc-speech-witness_death =
     .a0 = No!
     .a1 = This is terrible!
     .a2 = Oh my goodness!
+npc-speech-witness_theft =
+    .a0 = Stop, thief!
+    .a1 = That isn't yours to take!
+npc-speech-witness_assault =
+    .a0 = Leave them alone!
+    .a1 = Guards! Somebody's being attacked!
+npc-speech-witness_heroics =
+    .a0 = Well fought!
+    .a1 = You did it!
+    .a2 = What a fight!
 npc-speech-welcome-aboard =
     .a0 = Welcome aboard!
     .a1 = Can I see your ticket... just kidding it's free!
@@ -19,12 +19,14 @@ use common::{
     character::{CharacterId, CharacterItem},
     comp::{
         self,
+        achievement::Achievements,
         chat::KillSource,
         controller::CraftEvent,
         dialogue::Subject,
         group,
         inventory::item::{modular, tool, ItemKind},
         invite::{InviteKind, InviteResponse},
+        pet::{PetOrder, PetStance, StabledPet},
         skills::Skill,
         slot::{EquipSlot, InvSlotId, Slot},
         CharacterState, ChatMode, ControlAction, ControlEvent, Controller, ControllerInputs,
@@ -49,7 +51,7 @@ use common::{
     trade::{PendingTrade, SitePrices, TradeAction, TradeId, TradeResult},
     uid::{IdMaps, Uid},
     vol::RectVolSize,
-    weather::{CompressedWeather, SharedWeatherGrid, Weather, WeatherGrid},
+    weather::{CompressedWeather, Season, SharedWeatherGrid, Weather, WeatherGrid},
 };
 #[cfg(feature = "tracy")] use common_base::plot;
 use common_base::{prof_span, span};
@@ -297,6 +299,12 @@ pub struct Client {
     pending_invites: HashSet<Uid>,
     // The pending trade the client is involved in, and it's id
     pending_trade: Option<(TradeId, PendingTrade, Option<SitePrices>)>,
+    // Pets the current character has left at a stable
+    stabled_pets: Vec<StabledPet>,
+    // Lifetime statistics and unlocked achievements of the current character
+    achievements: Achievements,
+    // The region selected in build mode
+    build_selection: Option<Aabb<i32>>,
 
     network: Option<Network>,
     participant: Option<Participant>,
@@ -997,6 +1005,9 @@ impl Client {
             group_members: HashMap::new(),
             pending_invites: HashSet::new(),
             pending_trade: None,
+            stabled_pets: Vec::new(),
+            achievements: Achievements::default(),
+            build_selection: None,
 
             network: Some(network),
             participant: Some(participant),
@@ -1789,13 +1800,64 @@ impl Client {
     pub fn unmount(&mut self) { self.send_msg(ClientGeneral::ControlEvent(ControlEvent::Unmount)); }
 
     pub fn set_pet_stay(&mut self, entity: EcsEntity, stay: bool) {
+        self.set_pet_order(
+            entity,
+            if stay {
+                PetOrder::Stay
+            } else {
+                PetOrder::Follow
+            },
+        );
+    }
+
+    pub fn set_pet_order(&mut self, entity: EcsEntity, order: PetOrder) {
+        if let Some(uid) = self.state.read_component_copied(entity) {
+            self.send_msg(ClientGeneral::ControlEvent(ControlEvent::SetPetOrder(
+                uid, order,
+            )));
+        }
+    }
+
+    pub fn set_pet_stance(&mut self, entity: EcsEntity, stance: PetStance) {
+        if let Some(uid) = self.state.read_component_copied(entity) {
+            self.send_msg(ClientGeneral::ControlEvent(ControlEvent::SetPetStance(
+                uid, stance,
+            )));
+        }
+    }
+
+    pub fn rename_pet(&mut self, entity: EcsEntity, name: String) {
         if let Some(uid) = self.state.read_component_copied(entity) {
-            self.send_msg(ClientGeneral::ControlEvent(ControlEvent::SetPetStay(
-                uid, stay,
+            self.send_msg(ClientGeneral::ControlEvent(ControlEvent::RenamePet(
+                uid, name,
             )));
         }
     }
 
+    /// Leave a pet at the stable of the settlement the player is currently in
+    pub fn stable_pet(&mut self, entity: EcsEntity) {
+        if let Some(uid) = self.state.read_component_copied(entity) {
+            self.send_msg(ClientGeneral::ControlEvent(ControlEvent::StablePet(uid)));
+        }
+    }
+
+    /// Summon a pet from the stable, `index` refers to the position of the pet
+    /// in [`Client::stabled_pets`]
+    pub fn unstable_pet(&mut self, index: usize) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::UnstablePet(
+            index,
+        )));
+    }
+
+    /// The pets this character has left at a stable
+    pub fn stabled_pets(&self) -> &[StabledPet] { &self.stabled_pets }
+
+    /// The lifetime statistics and unlocked achievements of this character
+    pub fn achievements(&self) -> &Achievements { &self.achievements }
+
+    /// The region this player has selected in build mode
+    pub fn build_selection(&self) -> Option<Aabb<i32>> { self.build_selection }
+
     pub fn respawn(&mut self) {
         if self
             .state
@@ -1967,6 +2029,14 @@ impl Client {
             .map(|v| v.0)
     }
 
+    /// The current season, according to the year length of the server.
+    pub fn season(&self) -> Season {
+        Season::at(
+            TimeOfDay(self.state.get_time_of_day()),
+            self.connected_server_constants.year_length,
+        )
+    }
+
     /// Returns Weather::default if no player position exists.
     pub fn weather_at_player(&self) -> Weather {
         self.position()
@@ -2804,6 +2874,15 @@ impl Client {
             ServerGeneral::SpectatePosition(pos) => {
                 frontend_events.push(Event::SpectatePosition(pos));
             },
+            ServerGeneral::PetStableUpdate(pets) => {
+                self.stabled_pets = pets;
+            },
+            ServerGeneral::AchievementUpdate(achievements) => {
+                self.achievements = achievements;
+            },
+            ServerGeneral::BuildSelection(selection) => {
+                self.build_selection = selection;
+            },
             _ => unreachable!("Not a in_game message"),
         }
         Ok(())
@@ -3062,6 +3141,9 @@ impl Client {
     fn clean_state(&mut self) {
         // Clear pending trade
         self.pending_trade = None;
+        self.stabled_pets.clear();
+        self.achievements = Achievements::default();
+        self.build_selection = None;
 
         let client_uid = self.uid().expect("Client doesn't have a Uid!!!");
 
@@ -220,6 +220,14 @@ pub enum ServerGeneral {
     /// Suggest the client to spectate a position. Called after client has
     /// requested teleport etc.
     SpectatePosition(Vec3<f32>),
+    /// The pets the player's character has left at a stable
+    PetStableUpdate(Vec<comp::pet::StabledPet>),
+    /// The lifetime statistics and unlocked achievements of the player's
+    /// character
+    AchievementUpdate(comp::Achievements),
+    /// The region that the player has selected in build mode, to be shown as a
+    /// box
+    BuildSelection(Option<Aabb<i32>>),
     /// Plugin data requested from the server
     PluginData(Vec<u8>),
 }
@@ -345,7 +353,10 @@ impl ServerMsg {
                         | ServerGeneral::MapMarker(_)
                         | ServerGeneral::WeatherUpdate(_)
                         | ServerGeneral::LocalWindUpdate(_)
-                        | ServerGeneral::SpectatePosition(_) => {
+                        | ServerGeneral::SpectatePosition(_)
+                        | ServerGeneral::PetStableUpdate(_)
+                        | ServerGeneral::AchievementUpdate(_)
+                        | ServerGeneral::BuildSelection(_) => {
                             c_type == ClientType::Game && presence.is_some()
                         },
                         // Always possible
@@ -145,7 +145,7 @@ lazy_static! {
     .collect();
 
     static ref WEATHERS: Vec<String> = [
-        "clear", "cloudy", "rain", "wind", "storm"
+        "clear", "cloudy", "rain", "snow", "wind", "storm"
     ]
     .iter()
     .map(|s| s.to_string())
@@ -227,6 +227,12 @@ lazy_static! {
         .cloned()
         .collect();
 
+    static ref FURNITURE: Vec<String> = terrain::sprite::SPRITE_KINDS
+        .iter()
+        .filter(|(_, sprite)| sprite.is_furniture())
+        .map(|(name, _)| name.clone())
+        .collect();
+
     static ref ROLES: Vec<String> = ["admin", "moderator"].iter().copied().map(Into::into).collect();
 
     /// List of item's asset specifiers. Useful for tab completing.
@@ -328,6 +334,7 @@ pub enum ServerChatCommand {
     BattleMode,
     BattleModeForce,
     Body,
+    Bounty,
     Buff,
     Build,
     Campfire,
@@ -341,6 +348,7 @@ pub enum ServerChatCommand {
     Dismount,
     DropAll,
     Dummy,
+    Edit,
     Explosion,
     Faction,
     GiveItem,
@@ -352,6 +360,7 @@ pub enum ServerChatCommand {
     GroupPromote,
     Health,
     Help,
+    House,
     IntoNpc,
     JoinFaction,
     Jump,
@@ -363,6 +372,7 @@ pub enum ServerChatCommand {
     Light,
     Lightning,
     Location,
+    LootRoll,
     MakeBlock,
     MakeNpc,
     MakeSprite,
@@ -373,6 +383,7 @@ pub enum ServerChatCommand {
     PermitBuild,
     Players,
     Portal,
+    Redo,
     Region,
     ReloadChunks,
     RemoveLights,
@@ -388,6 +399,7 @@ pub enum ServerChatCommand {
     Safezone,
     Say,
     Scale,
+    Schematic,
     ServerPhysics,
     SetMotd,
     Ship,
@@ -398,10 +410,13 @@ pub enum ServerChatCommand {
     Sudo,
     Tell,
     Tether,
+    Threat,
     Time,
     TimeScale,
     Tp,
     Unban,
+    Undo,
+    UndoArea,
     Version,
     Waypoint,
     WeatherZone,
@@ -505,6 +520,15 @@ impl ServerChatCommand {
                 "Change your body to different species",
                 Some(Admin),
             ),
+            ServerChatCommand::Bounty => cmd(
+                vec![Enum(
+                    "action",
+                    ["info", "pay"].iter().copied().map(Into::into).collect(),
+                    Required,
+                )],
+                "Check or pay off your bounty at the noticeboard of the town you are in",
+                None,
+            ),
             ServerChatCommand::BattleModeForce => cmd(
                 vec![Enum(
                     "battle mode",
@@ -565,6 +589,29 @@ impl ServerChatCommand {
                 Some(Moderator),
             ),
             ServerChatCommand::Dummy => cmd(vec![], "Spawns a training dummy", Some(Admin)),
+            ServerChatCommand::Edit => cmd(
+                vec![
+                    Enum(
+                        "action",
+                        [
+                            "pos1", "pos2", "deselect", "fill", "replace", "hollow", "walls",
+                            "copy", "cut", "paste", "sphere", "cylinder", "cancel",
+                        ]
+                        .iter()
+                        .copied()
+                        .map(Into::into)
+                        .collect(),
+                        Required,
+                    ),
+                    Message(Optional),
+                ],
+                "Select a region by marking two corners where you stand, then fill it with a \
+                 block (kind r g b), replace one kind of block, hollow it out, build walls around \
+                 it, or copy or cut it to paste elsewhere. Spheres (radius) and cylinders (radius \
+                 height) are placed around you. Only blocks inside areas you can build in are \
+                 changed",
+                None,
+            ),
             ServerChatCommand::Explosion => cmd(
                 vec![Float("radius", 5.0, Required)],
                 "Explodes the ground around you",
@@ -622,6 +669,22 @@ impl ServerChatCommand {
                 "Display information about commands",
                 None,
             ),
+            ServerChatCommand::House => cmd(
+                vec![
+                    Enum(
+                        "action",
+                        ["info", "buy", "rent", "leave", "furnish"]
+                            .iter()
+                            .copied()
+                            .map(Into::into)
+                            .collect(),
+                        Required,
+                    ),
+                    Enum("furniture", FURNITURE.clone(), Optional),
+                ],
+                "Buy, rent, leave or furnish the house you are standing in",
+                None,
+            ),
             ServerChatCommand::Respawn => cmd(vec![], "Teleport to your waypoint", Some(Moderator)),
             ServerChatCommand::JoinFaction => ChatCommandData::new(
                 vec![Any("faction", Optional)],
@@ -748,6 +811,11 @@ impl ServerChatCommand {
                 "Revokes all build area permissions for player",
                 Some(Admin),
             ),
+            ServerChatCommand::Redo => cmd(
+                vec![Integer("count", 1, Optional)],
+                "Redo block edits that you have undone",
+                None,
+            ),
             ServerChatCommand::Region => cmd(
                 vec![Message(Optional)],
                 "Send messages to everyone in your region of the world",
@@ -897,6 +965,17 @@ impl ServerChatCommand {
                 "Remove the ban for the given username",
                 Some(Moderator),
             ),
+            ServerChatCommand::Undo => cmd(
+                vec![Integer("count", 1, Optional)],
+                "Undo your most recent block edits, such as placing or breaking blocks in build \
+                 mode and pasting schematics",
+                None,
+            ),
+            ServerChatCommand::UndoArea => cmd(
+                vec![PlayerName(Required), Integer("radius", 16, Optional)],
+                "Undo all of the recent block edits that a player made near you",
+                Some(Admin),
+            ),
             ServerChatCommand::Version => cmd(vec![], "Prints server version", None),
             ServerChatCommand::Waypoint => cmd(
                 vec![],
@@ -922,6 +1001,15 @@ impl ServerChatCommand {
             ServerChatCommand::Location => {
                 cmd(vec![Any("name", Required)], "Teleport to a location", None)
             },
+            ServerChatCommand::LootRoll => cmd(
+                vec![Enum(
+                    "choice",
+                    vec!["need".to_owned(), "greed".to_owned(), "pass".to_owned()],
+                    Required,
+                )],
+                "Choose how you roll for loot at the end of boss encounters",
+                None,
+            ),
             ServerChatCommand::CreateLocation => cmd(
                 vec![Any("name", Required)],
                 "Create a location at the current position",
@@ -952,6 +1040,25 @@ impl ServerChatCommand {
                 "Scale your character",
                 Some(Admin),
             ),
+            ServerChatCommand::Schematic => cmd(
+                vec![
+                    Enum(
+                        "action",
+                        ["save", "paste", "list"]
+                            .iter()
+                            .copied()
+                            .map(Into::into)
+                            .collect(),
+                        Required,
+                    ),
+                    Any("name", Optional),
+                    Message(Optional),
+                ],
+                "Save the blocks between two corners (x0 y0 z0 x1 y1 z1) to a schematic, or paste \
+                 one where you stand, optionally rotated by 90, 180 or 270 degrees and mirrored \
+                 along x or y. Only blocks inside areas you can build in are saved and pasted",
+                None,
+            ),
             ServerChatCommand::RepairEquipment => {
                 cmd(vec![], "Repairs all equipped items", Some(Admin))
             },
@@ -974,6 +1081,11 @@ impl ServerChatCommand {
                 "Dismount if you are riding, or dismount anything riding you",
                 Some(Admin),
             ),
+            ServerChatCommand::Threat => cmd(
+                vec![EntityTarget(Required)],
+                "Display the threat table of an NPC",
+                Some(Admin),
+            ),
         }
     }
 
@@ -991,6 +1103,7 @@ impl ServerChatCommand {
             ServerChatCommand::BattleMode => "battlemode",
             ServerChatCommand::BattleModeForce => "battlemode_force",
             ServerChatCommand::Body => "body",
+            ServerChatCommand::Bounty => "bounty",
             ServerChatCommand::Buff => "buff",
             ServerChatCommand::Build => "build",
             ServerChatCommand::Campfire => "campfire",
@@ -1000,6 +1113,7 @@ impl ServerChatCommand {
             ServerChatCommand::DisconnectAllPlayers => "disconnect_all_players",
             ServerChatCommand::DropAll => "dropall",
             ServerChatCommand::Dummy => "dummy",
+            ServerChatCommand::Edit => "edit",
             ServerChatCommand::Explosion => "explosion",
             ServerChatCommand::Faction => "faction",
             ServerChatCommand::GiveItem => "give_item",
@@ -1011,6 +1125,7 @@ impl ServerChatCommand {
             ServerChatCommand::GroupPromote => "group_promote",
             ServerChatCommand::Health => "health",
             ServerChatCommand::Help => "help",
+            ServerChatCommand::House => "house",
             ServerChatCommand::IntoNpc => "into_npc",
             ServerChatCommand::JoinFaction => "join_faction",
             ServerChatCommand::Jump => "jump",
@@ -1029,6 +1144,7 @@ impl ServerChatCommand {
             ServerChatCommand::PermitBuild => "permit_build",
             ServerChatCommand::Players => "players",
             ServerChatCommand::Portal => "portal",
+            ServerChatCommand::Redo => "redo",
             ServerChatCommand::Region => "region",
             ServerChatCommand::ReloadChunks => "reload_chunks",
             ServerChatCommand::RemoveLights => "remove_lights",
@@ -1054,6 +1170,8 @@ impl ServerChatCommand {
             ServerChatCommand::RtsimPurge => "rtsim_purge",
             ServerChatCommand::RtsimChunk => "rtsim_chunk",
             ServerChatCommand::Unban => "unban",
+            ServerChatCommand::Undo => "undo",
+            ServerChatCommand::UndoArea => "undo_area",
             ServerChatCommand::Version => "version",
             ServerChatCommand::Waypoint => "waypoint",
             ServerChatCommand::Wiring => "wiring",
@@ -1061,16 +1179,19 @@ impl ServerChatCommand {
             ServerChatCommand::World => "world",
             ServerChatCommand::MakeVolume => "make_volume",
             ServerChatCommand::Location => "location",
+            ServerChatCommand::LootRoll => "loot_roll",
             ServerChatCommand::CreateLocation => "create_location",
             ServerChatCommand::DeleteLocation => "delete_location",
             ServerChatCommand::WeatherZone => "weather_zone",
             ServerChatCommand::Lightning => "lightning",
             ServerChatCommand::Scale => "scale",
+            ServerChatCommand::Schematic => "schematic",
             ServerChatCommand::RepairEquipment => "repair_equipment",
             ServerChatCommand::Tether => "tether",
             ServerChatCommand::DestroyTethers => "destroy_tethers",
             ServerChatCommand::Mount => "mount",
             ServerChatCommand::Dismount => "dismount",
+            ServerChatCommand::Threat => "threat",
         }
     }
 
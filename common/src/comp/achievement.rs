@@ -0,0 +1,238 @@
+use crate::{
+    assets::{self, AssetExt, AssetHandle},
+    comp::{body::Body, chat::KillSource},
+};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use specs::{Component, DenseVecStorage};
+use std::collections::BTreeSet;
+
+/// Broad categories of death, used to keep track of how often a character has
+/// died to each of them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Killed by another player
+    Player,
+    /// Killed by an NPC
+    NonPlayer,
+    /// Killed by an effect that no longer has a source, such as a lingering
+    /// debuff
+    Environment,
+    FallDamage,
+    Suicide,
+    Other,
+}
+
+impl From<&KillSource> for DeathCause {
+    fn from(source: &KillSource) -> Self {
+        match source {
+            KillSource::Player(_, _) => Self::Player,
+            KillSource::NonPlayer(_, _) => Self::NonPlayer,
+            KillSource::NonExistent(_) => Self::Environment,
+            KillSource::FallDamage => Self::FallDamage,
+            KillSource::Suicide => Self::Suicide,
+            KillSource::Other => Self::Other,
+        }
+    }
+}
+
+/// Lifetime statistics of a character
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    /// Number of kills, keyed by the species of the killed entity (see
+    /// [`species_key`])
+    pub kills: HashMap<String, u64>,
+    pub deaths: HashMap<DeathCause, u64>,
+    /// Distance travelled in blocks
+    pub distance_travelled: f64,
+    pub items_crafted: u64,
+    pub dungeons_cleared: u64,
+    pub blocks_mined: u64,
+}
+
+impl Statistics {
+    pub fn total_kills(&self) -> u64 { self.kills.values().sum() }
+
+    pub fn total_deaths(&self) -> u64 { self.deaths.values().sum() }
+
+    /// Get the current value of the given statistic
+    pub fn get(&self, stat: &StatKind) -> u64 {
+        match stat {
+            StatKind::Kills => self.total_kills(),
+            StatKind::KillsOf(species) => self.kills.get(species).copied().unwrap_or(0),
+            StatKind::Deaths => self.total_deaths(),
+            StatKind::DeathsBy(cause) => self.deaths.get(cause).copied().unwrap_or(0),
+            StatKind::DistanceTravelled => self.distance_travelled as u64,
+            StatKind::ItemsCrafted => self.items_crafted,
+            StatKind::DungeonsCleared => self.dungeons_cleared,
+            StatKind::BlocksMined => self.blocks_mined,
+        }
+    }
+}
+
+/// A statistic that an achievement can be unlocked by
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatKind {
+    Kills,
+    /// Kills of a particular species, as given by [`species_key`]
+    KillsOf(String),
+    Deaths,
+    DeathsBy(DeathCause),
+    DistanceTravelled,
+    ItemsCrafted,
+    DungeonsCleared,
+    BlocksMined,
+}
+
+/// An achievement, unlocked once the given statistic reaches the threshold
+#[derive(Clone, Debug, Deserialize)]
+pub struct AchievementDef {
+    /// Unique identifier, this is what gets persisted when the achievement is
+    /// unlocked so it must not change
+    pub id: String,
+    /// i18n key of the achievement's title
+    pub title: String,
+    /// i18n key of the achievement's description
+    pub description: String,
+    pub stat: StatKind,
+    pub threshold: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AchievementManifest {
+    /// Species (as given by [`species_key`]) that guard the end of a dungeon,
+    /// killing one of them counts as clearing a dungeon
+    pub dungeon_bosses: Vec<String>,
+    pub achievements: Vec<AchievementDef>,
+}
+
+impl assets::Asset for AchievementManifest {
+    type Loader = assets::RonLoader;
+
+    const EXTENSION: &'static str = "ron";
+}
+
+impl AchievementManifest {
+    pub fn get(&self, id: &str) -> Option<&AchievementDef> {
+        self.achievements.iter().find(|def| def.id == id)
+    }
+
+    pub fn is_dungeon_boss(&self, body: &Body) -> bool {
+        let key = species_key(body);
+        self.dungeon_bosses.iter().any(|boss| *boss == key)
+    }
+}
+
+pub fn default_achievement_manifest() -> AssetHandle<AchievementManifest> {
+    AchievementManifest::load_expect("common.achievements")
+}
+
+/// The statistics and unlocked achievements of a character
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Achievements {
+    pub statistics: Statistics,
+    /// Ids of unlocked achievements
+    pub unlocked: BTreeSet<String>,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, id: &str) -> bool { self.unlocked.contains(id) }
+
+    /// Unlocks every achievement whose threshold has been reached, returning
+    /// the ids of those that were newly unlocked
+    pub fn unlock_reached(&mut self, manifest: &AchievementManifest) -> Vec<String> {
+        let newly_unlocked = manifest
+            .achievements
+            .iter()
+            .filter(|def| {
+                !self.unlocked.contains(&def.id) && self.statistics.get(&def.stat) >= def.threshold
+            })
+            .map(|def| def.id.clone())
+            .collect::<Vec<_>>();
+        self.unlocked.extend(newly_unlocked.iter().cloned());
+        newly_unlocked
+    }
+}
+
+impl Component for Achievements {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A stable identifier for the species of a body, of the form
+/// `BodyKind.Species`, e.g. `BipedLarge.Minotaur`. Bodies without species
+/// (objects, ships and item drops) are identified by their kind alone.
+pub fn species_key(body: &Body) -> String {
+    let species = match body {
+        Body::Humanoid(b) => format!("{:?}", b.species),
+        Body::QuadrupedSmall(b) => format!("{:?}", b.species),
+        Body::QuadrupedMedium(b) => format!("{:?}", b.species),
+        Body::BirdMedium(b) => format!("{:?}", b.species),
+        Body::FishMedium(b) => format!("{:?}", b.species),
+        Body::Dragon(b) => format!("{:?}", b.species),
+        Body::BirdLarge(b) => format!("{:?}", b.species),
+        Body::FishSmall(b) => format!("{:?}", b.species),
+        Body::BipedLarge(b) => format!("{:?}", b.species),
+        Body::BipedSmall(b) => format!("{:?}", b.species),
+        Body::Golem(b) => format!("{:?}", b.species),
+        Body::Theropod(b) => format!("{:?}", b.species),
+        Body::QuadrupedLow(b) => format!("{:?}", b.species),
+        Body::Arthropod(b) => format!("{:?}", b.species),
+        Body::Crustacean(b) => format!("{:?}", b.species),
+        Body::Object(_) | Body::Ship(_) | Body::ItemDrop(_) => return body.to_string(),
+    };
+    format!("{body}.{species}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp::{biped_large, object};
+
+    #[test]
+    fn test_species_key() {
+        let minotaur = Body::BipedLarge(biped_large::Body {
+            species: biped_large::Species::Minotaur,
+            body_type: biped_large::BodyType::Male,
+        });
+        assert_eq!(species_key(&minotaur), "BipedLarge.Minotaur");
+        assert_eq!(species_key(&Body::Object(object::Body::Crossbow)), "Object");
+    }
+
+    #[test]
+    fn test_unlock_reached() {
+        let manifest = AchievementManifest {
+            dungeon_bosses: Vec::new(),
+            achievements: vec![AchievementDef {
+                id: "miner".to_owned(),
+                title: String::new(),
+                description: String::new(),
+                stat: StatKind::BlocksMined,
+                threshold: 10,
+            }],
+        };
+        let mut achievements = Achievements::default();
+        achievements.statistics.blocks_mined = 9;
+        assert!(achievements.unlock_reached(&manifest).is_empty());
+        achievements.statistics.blocks_mined = 10;
+        assert_eq!(achievements.unlock_reached(&manifest), vec![
+            "miner".to_owned()
+        ]);
+        assert!(achievements.is_unlocked("miner"));
+        assert!(achievements.unlock_reached(&manifest).is_empty());
+    }
+
+    #[test]
+    fn test_all_achievements_valid() {
+        let manifest = default_achievement_manifest().read();
+        let mut ids = std::collections::HashSet::new();
+        for def in &manifest.achievements {
+            assert!(
+                ids.insert(def.id.as_str()),
+                "Duplicate achievement id {}",
+                def.id
+            );
+            assert!(def.threshold > 0, "Achievement {} has no threshold", def.id);
+        }
+    }
+}
@@ -10,7 +10,10 @@ use crate::{
 };
 use serde::{Deserialize, Serialize};
 use specs::{Component, DerefFlaggedStorage, Entity as EcsEntity};
-use std::{collections::VecDeque, fmt};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+};
 use strum::{EnumIter, IntoEnumIterator};
 use vek::*;
 
@@ -19,6 +22,22 @@ use super::{dialogue::Subject, Group, Pos};
 pub const DEFAULT_INTERACTION_TIME: f32 = 3.0;
 pub const TRADE_INTERACTION_TIME: f32 = 300.0;
 const SECONDS_BEFORE_FORGET_SOUNDS: f64 = 180.0;
+/// Time it takes for threat to halve once an entity stops generating it
+const THREAT_HALF_LIFE: f32 = 15.0;
+/// Threat below this is forgotten
+const MIN_THREAT: f32 = 1.0;
+/// Another entity needs this many times the threat of the current target
+/// before the agent switches to it, so that entities with similar threat
+/// can't make the agent rapidly flip between them
+const THREAT_SWITCH_MARGIN: f32 = 1.2;
+/// How long a taunt forces the agent to attack the taunter
+const TAUNT_DURATION: f64 = 4.0;
+/// Healing an agent's enemy generates this much threat per point of health
+/// healed
+pub const HEALING_THREAT_FACTOR: f32 = 0.5;
+/// Healers only generate threat with agents within this distance of the
+/// healed entity
+pub const HEALING_THREAT_RADIUS: f32 = 60.0;
 
 //intentionally very few concurrent action state variables are allowed. This is
 // to keep the complexity of our AI from getting too large, too quickly.
@@ -609,6 +628,90 @@ impl Timer {
     }
 }
 
+/// Keeps track of how threatening other entities are to an agent, which is
+/// used to decide who the agent attacks. Threat is generated by damaging the
+/// agent and by healing its enemies, and decays over time.
+#[derive(Clone, Debug, Default)]
+pub struct ThreatTable {
+    threat: HashMap<Uid, f32>,
+    /// The entity that last taunted the agent, and until when the taunt lasts
+    taunt: Option<(Uid, f64)>,
+}
+
+impl ThreatTable {
+    pub fn is_empty(&self) -> bool { self.threat.is_empty() }
+
+    pub fn get(&self, uid: Uid) -> f32 { self.threat.get(&uid).copied().unwrap_or(0.0) }
+
+    pub fn add(&mut self, uid: Uid, amount: f32) {
+        if amount > 0.0 {
+            *self.threat.entry(uid).or_insert(0.0) += amount;
+        }
+    }
+
+    /// Puts the taunter at the top of the table and forces the agent to attack
+    /// them for a while, regardless of the threat of others
+    pub fn taunt(&mut self, uid: Uid, time: f64) {
+        let top = self.threat.values().copied().fold(MIN_THREAT, f32::max);
+        let threat = self.threat.entry(uid).or_insert(0.0);
+        *threat = threat.max(top * THREAT_SWITCH_MARGIN);
+        self.taunt = Some((uid, time + TAUNT_DURATION));
+    }
+
+    pub fn remove(&mut self, uid: Uid) {
+        self.threat.remove(&uid);
+        if self.taunt.map_or(false, |(taunter, _)| taunter == uid) {
+            self.taunt = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.threat.clear();
+        self.taunt = None;
+    }
+
+    pub fn decay(&mut self, dt: f32) {
+        let factor = 0.5_f32.powf(dt / THREAT_HALF_LIFE);
+        self.threat.retain(|_, threat| {
+            *threat *= factor;
+            *threat >= MIN_THREAT
+        });
+    }
+
+    /// All entries, ordered by descending threat
+    pub fn entries(&self) -> Vec<(Uid, f32)> {
+        let mut entries = self
+            .threat
+            .iter()
+            .map(|(uid, threat)| (*uid, *threat))
+            .collect::<Vec<_>>();
+        entries.sort_by(|(a_uid, a), (b_uid, b)| b.total_cmp(a).then(a_uid.0.cmp(&b_uid.0)));
+        entries
+    }
+
+    /// The entity the agent should attack, given its current target. Only
+    /// entities for which `is_valid` holds are considered.
+    pub fn choose(
+        &self,
+        current: Option<Uid>,
+        time: f64,
+        is_valid: impl Fn(Uid) -> bool,
+    ) -> Option<Uid> {
+        if let Some((taunter, until)) = self.taunt
+            && time < until
+            && is_valid(taunter)
+        {
+            return Some(taunter);
+        }
+
+        let (top, top_threat) = self.entries().into_iter().find(|(uid, _)| is_valid(*uid))?;
+        match current.filter(|uid| is_valid(*uid)) {
+            Some(current) if top_threat < self.get(current) * THREAT_SWITCH_MARGIN => Some(current),
+            _ => Some(top),
+        }
+    }
+}
+
 /// For use with the builder pattern <https://doc.rust-lang.org/1.0.0/style/ownership/builders.html>
 #[derive(Clone, Debug)]
 pub struct Agent {
@@ -633,6 +736,7 @@ pub struct Agent {
     pub stay_pos: Option<Pos>,
     /// Inputs sent up to rtsim
     pub rtsim_outbox: Option<VecDeque<NpcInput>>,
+    pub threat: ThreatTable,
 }
 
 #[derive(Clone, Debug)]
@@ -740,6 +844,7 @@ impl Agent {
             stay_pos: None,
             awareness: Awareness::new(0.0),
             rtsim_outbox: None,
+            threat: ThreatTable::default(),
         }
     }
 
@@ -818,7 +923,11 @@ impl Component for Agent {
 
 #[cfg(test)]
 mod tests {
-    use super::{humanoid, Agent, Behavior, BehaviorCapability, BehaviorState, Body};
+    use super::{
+        humanoid, Agent, Behavior, BehaviorCapability, BehaviorState, Body, ThreatTable,
+        TAUNT_DURATION, THREAT_HALF_LIFE,
+    };
+    use crate::uid::Uid;
 
     /// Test to verify that Behavior is working correctly at its most basic
     /// usages
@@ -873,6 +982,65 @@ mod tests {
         agent = agent.with_aggro_no_warn();
         assert_eq!(agent.psyche.aggro_dist, None);
     }
+
+    #[test]
+    pub fn threat_highest_is_chosen() {
+        let mut threat = ThreatTable::default();
+        assert_eq!(threat.choose(None, 0.0, |_| true), None);
+
+        threat.add(Uid(1), 10.0);
+        threat.add(Uid(2), 30.0);
+        threat.add(Uid(3), 20.0);
+        assert_eq!(threat.choose(None, 0.0, |_| true), Some(Uid(2)));
+        // Invalid entities, e.g. dead ones, are skipped
+        assert_eq!(threat.choose(None, 0.0, |uid| uid != Uid(2)), Some(Uid(3)));
+        assert_eq!(threat.entries(), vec![
+            (Uid(2), 30.0),
+            (Uid(3), 20.0),
+            (Uid(1), 10.0)
+        ]);
+    }
+
+    #[test]
+    pub fn threat_switching_has_hysteresis() {
+        let mut threat = ThreatTable::default();
+        threat.add(Uid(1), 100.0);
+        threat.add(Uid(2), 110.0);
+        // Slightly more threat isn't enough to pull the agent away from its target
+        assert_eq!(threat.choose(Some(Uid(1)), 0.0, |_| true), Some(Uid(1)));
+        threat.add(Uid(2), 20.0);
+        assert_eq!(threat.choose(Some(Uid(1)), 0.0, |_| true), Some(Uid(2)));
+        // Targets that nobody generated threat with are dropped
+        assert_eq!(threat.choose(Some(Uid(3)), 0.0, |_| true), Some(Uid(2)));
+    }
+
+    #[test]
+    pub fn threat_decays() {
+        let mut threat = ThreatTable::default();
+        threat.add(Uid(1), 100.0);
+        threat.add(Uid(2), 1.5);
+        threat.decay(THREAT_HALF_LIFE);
+        assert!((threat.get(Uid(1)) - 50.0).abs() < 0.001);
+        // Negligible threat is forgotten entirely
+        assert_eq!(threat.get(Uid(2)), 0.0);
+        assert_eq!(threat.entries().len(), 1);
+    }
+
+    #[test]
+    pub fn taunt_forces_target() {
+        let mut threat = ThreatTable::default();
+        threat.add(Uid(1), 100.0);
+        threat.taunt(Uid(2), 10.0);
+        assert_eq!(threat.choose(Some(Uid(1)), 10.0, |_| true), Some(Uid(2)));
+        // Even once lots more threat is generated by someone else
+        threat.add(Uid(1), 1000.0);
+        assert_eq!(threat.choose(Some(Uid(2)), 11.0, |_| true), Some(Uid(2)));
+        // Until the taunt wears off
+        assert_eq!(
+            threat.choose(Some(Uid(2)), 10.0 + TAUNT_DURATION, |_| true),
+            Some(Uid(1))
+        );
+    }
 }
 
 /// PID controllers are used for automatically adapting nonlinear controls (like
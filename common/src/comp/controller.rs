@@ -7,6 +7,7 @@ use crate::{
             slot::{EquipSlot, InvSlotId, Slot},
         },
         invite::{InviteKind, InviteResponse},
+        pet::{PetOrder, PetStance},
         BuffKind,
     },
     mounting::VolumePos,
@@ -157,7 +158,13 @@ pub enum ControlEvent {
     Mount(Uid),
     MountVolume(VolumePos),
     Unmount,
-    SetPetStay(Uid, bool),
+    SetPetOrder(Uid, PetOrder),
+    SetPetStance(Uid, PetStance),
+    RenamePet(Uid, String),
+    /// Leave a pet at the stable of the settlement the owner is in
+    StablePet(Uid),
+    /// Summon the pet at the given index of the owner's stable
+    UnstablePet(usize),
     InventoryEvent(InventoryEvent),
     GroupManip(GroupManip),
     RemoveBuff(BuffKind),
@@ -1,4 +1,5 @@
 pub mod ability;
+pub mod achievement;
 mod admin;
 pub mod agent;
 pub mod anchor;
@@ -43,6 +44,7 @@ pub use self::{
         Ability, AbilityInput, ActiveAbilities, CharacterAbility, CharacterAbilityType, Stance,
         BASE_ABILITY_LIMIT,
     },
+    achievement::Achievements,
     admin::{Admin, AdminRole},
     agent::{
         Agent, Alignment, Behavior, BehaviorCapability, BehaviorState, PidController,
@@ -1,14 +1,55 @@
 use crate::comp::{body::Body, phys::Mass, quadruped_medium, quadruped_small};
 use crossbeam_utils::atomic::AtomicCell;
+use serde::{Deserialize, Serialize};
 use specs::Component;
 use std::{num::NonZeroU64, sync::Arc};
 
 pub type PetId = AtomicCell<Option<NonZeroU64>>;
 
+/// The maximum length of a name given to a pet by its owner
+pub const MAX_PET_NAME_LEN: usize = 32;
+
+/// Determines how eagerly a pet engages in combat
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PetStance {
+    /// Never attacks, not even to defend itself or its owner
+    Passive,
+    /// Only attacks entities that hurt the pet or its owner
+    Defensive,
+    /// Attacks any hostile entity it notices
+    #[default]
+    Aggressive,
+}
+
+/// The standing order an owner has given to their pet
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PetOrder {
+    /// Follow the owner around
+    #[default]
+    Follow,
+    /// Sit at the current position and don't engage in combat
+    Stay,
+    /// Remain near the current position, attacking enemies that come close
+    Guard,
+}
+
+/// Information about a pet that has been left at a stable, sent to its owner
+/// so that they can choose which pet to summon.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StabledPet {
+    pub name: String,
+    pub body: Body,
+}
+
 // TODO: move to server crate
 #[derive(Clone, Debug)]
 pub struct Pet {
     database_id: Arc<PetId>,
+    pub stance: PetStance,
+    pub order: PetOrder,
+    /// Whether the owner has left this pet at a stable, in which case it only
+    /// exists in its owner's persisted data and not in the world
+    pub stabled: bool,
 }
 
 impl Pet {
@@ -18,9 +59,17 @@ impl Pet {
     #[doc(hidden)]
     pub fn get_database_id(&self) -> Arc<PetId> { Arc::clone(&self.database_id) }
 
-    pub fn new_from_database(database_id: NonZeroU64) -> Self {
+    pub fn new_from_database(
+        database_id: NonZeroU64,
+        stance: PetStance,
+        order: PetOrder,
+        stabled: bool,
+    ) -> Self {
         Self {
             database_id: Arc::new(AtomicCell::new(Some(database_id))),
+            stance,
+            order,
+            stabled,
         }
     }
 }
@@ -29,10 +78,18 @@ impl Default for Pet {
     fn default() -> Self {
         Self {
             database_id: Arc::new(AtomicCell::new(None)),
+            stance: PetStance::default(),
+            order: PetOrder::default(),
+            stabled: false,
         }
     }
 }
 
+/// Checks whether a name chosen by an owner for their pet is acceptable
+pub fn is_valid_pet_name(name: &str) -> bool {
+    !name.trim().is_empty() && name.chars().count() <= MAX_PET_NAME_LEN
+}
+
 /// Determines whether an entity of a particular body variant is tameable.
 pub fn is_tameable(body: &Body) -> bool {
     // Currently only Quadruped animals can be tamed pending further work
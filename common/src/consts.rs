@@ -35,3 +35,5 @@ pub const TELEPORTER_RADIUS: f32 = 3.;
 
 // Map settings
 pub const DAY_LENGTH_DEFAULT: f64 = 30.0;
+/// Length of a year, in in-game days.
+pub const YEAR_LENGTH_DEFAULT: f64 = 96.0;
@@ -0,0 +1,156 @@
+//! Definitions for boss encounters: fights that take place in a sealed arena
+//! and progress through phases as the boss loses health.
+
+use crate::{assets, comp::BuffKind, resources::Secs};
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// How the loot dropped at the end of an encounter is shared between those
+/// who took part in it
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LootRule {
+    /// Loot is distributed as it would be for any other kill
+    #[default]
+    FreeForAll,
+    /// Each item is given to the next participant in turn
+    RoundRobin,
+    /// Each item is rolled for, participants that need loot win over those
+    /// that only greed for it
+    NeedGreed,
+}
+
+/// A participant's standing choice when items are rolled for with
+/// [`LootRule::NeedGreed`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LootRollChoice {
+    Need,
+    #[default]
+    Greed,
+    Pass,
+}
+
+/// A buff applied to the boss when a phase starts or the boss enrages
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct EncounterBuff {
+    pub kind: BuffKind,
+    pub strength: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PhaseDef {
+    /// The phase starts once the boss' health fraction drops to this value
+    pub health_threshold: f32,
+    /// Buffs applied to the boss for the rest of the encounter
+    #[serde(default)]
+    pub buffs: Vec<EncounterBuff>,
+    /// i18n key of the message sent to participants when the phase starts
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EnrageDef {
+    /// Time after engaging after which the boss enrages
+    pub after: Secs,
+    #[serde(default)]
+    pub buffs: Vec<EncounterBuff>,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EncounterDef {
+    /// i18n key of the encounter's name
+    pub name: String,
+    /// Phases, ordered by descending health threshold
+    #[serde(default)]
+    pub phases: Vec<PhaseDef>,
+    pub enrage: Option<EnrageDef>,
+    /// Once no participant has been inside the arena for this long, the
+    /// encounter resets
+    pub reset_after: Secs,
+    #[serde(default)]
+    pub loot_rule: LootRule,
+}
+
+impl assets::Asset for EncounterDef {
+    type Loader = assets::RonLoader;
+
+    const EXTENSION: &'static str = "ron";
+}
+
+impl EncounterDef {
+    /// The phases that should begin once the boss' health fraction has
+    /// dropped to `health_fraction`, given that `current` phases have
+    /// already begun
+    pub fn phases_reached(
+        &self,
+        current: usize,
+        health_fraction: f32,
+    ) -> impl Iterator<Item = (usize, &PhaseDef)> {
+        self.phases
+            .iter()
+            .enumerate()
+            .skip(current)
+            .take_while(move |(_, phase)| health_fraction <= phase.health_threshold)
+    }
+}
+
+/// Placement information for a boss encounter, provided by the site that
+/// spawns the boss
+#[derive(Clone, Debug)]
+pub struct EncounterInfo {
+    /// Asset specifier of the [`EncounterDef`]
+    pub def: String,
+    /// The space the fight takes place in, players inside it are considered
+    /// participants
+    pub arena: Aabb<i32>,
+    /// Openings into the arena that are sealed while the encounter is ongoing
+    pub doors: Vec<Aabb<i32>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::AssetExt;
+
+    #[test]
+    fn test_phases_reached() {
+        let phase = |health_threshold| PhaseDef {
+            health_threshold,
+            buffs: Vec::new(),
+            message: None,
+        };
+        let def = EncounterDef {
+            name: String::new(),
+            phases: vec![phase(0.75), phase(0.5), phase(0.25)],
+            enrage: None,
+            reset_after: Secs(30.0),
+            loot_rule: LootRule::FreeForAll,
+        };
+        let reached = |current, health| {
+            def.phases_reached(current, health)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+        assert!(reached(0, 1.0).is_empty());
+        assert_eq!(reached(0, 0.75), vec![0]);
+        // Big hits can skip straight through several phases
+        assert_eq!(reached(0, 0.4), vec![0, 1]);
+        assert_eq!(reached(2, 0.4), Vec::<usize>::new());
+        assert_eq!(reached(2, 0.1), vec![2]);
+    }
+
+    #[test]
+    fn test_all_encounters_valid() {
+        let defs = assets::load_rec_dir::<EncounterDef>("common.encounter")
+            .expect("Failed to load encounter directory");
+        for id in defs.read().ids() {
+            let def = EncounterDef::load_expect(id).read();
+            assert!(
+                def.phases
+                    .windows(2)
+                    .all(|w| w[0].health_threshold > w[1].health_threshold),
+                "Phases of {id} must be ordered by descending health threshold"
+            );
+        }
+    }
+}
@@ -6,8 +6,10 @@ use crate::{
         agent::Sound,
         dialogue::Subject,
         invite::{InviteKind, InviteResponse},
+        pet::{PetOrder, PetStance},
         DisconnectReason, LootOwner, Ori, Pos, UnresolvedChatMsg, Vel,
     },
+    encounter::EncounterInfo,
     generation::{EntityInfo, SpecialEntity},
     lottery::LootSpec,
     mounting::VolumePos,
@@ -64,6 +66,7 @@ pub struct NpcBuilder {
     pub pets: Vec<(NpcBuilder, Vec3<f32>)>,
     pub rtsim_entity: Option<RtSimEntity>,
     pub projectile: Option<comp::Projectile>,
+    pub encounter: Option<EncounterInfo>,
 }
 
 impl NpcBuilder {
@@ -83,6 +86,7 @@ impl NpcBuilder {
             rtsim_entity: None,
             projectile: None,
             pets: Vec::new(),
+            encounter: None,
         }
     }
 
@@ -140,6 +144,11 @@ impl NpcBuilder {
         self.pets = pets;
         self
     }
+
+    pub fn with_encounter(mut self, encounter: impl Into<Option<EncounterInfo>>) -> Self {
+        self.encounter = encounter.into();
+        self
+    }
 }
 
 pub struct ClientConnectedEvent {
@@ -268,7 +277,15 @@ pub struct MountVolumeEvent(pub EcsEntity, pub VolumePos);
 
 pub struct UnmountEvent(pub EcsEntity);
 
-pub struct SetPetStayEvent(pub EcsEntity, pub EcsEntity, pub bool);
+pub struct SetPetOrderEvent(pub EcsEntity, pub EcsEntity, pub PetOrder);
+
+pub struct SetPetStanceEvent(pub EcsEntity, pub EcsEntity, pub PetStance);
+
+pub struct RenamePetEvent(pub EcsEntity, pub EcsEntity, pub String);
+
+pub struct StablePetEvent(pub EcsEntity, pub EcsEntity);
+
+pub struct UnstablePetEvent(pub EcsEntity, pub usize);
 
 pub struct PossessEvent(pub Uid, pub Uid);
 
@@ -299,6 +316,7 @@ pub struct UpdateCharacterDataEvent {
         Vec<(comp::Pet, comp::Body, comp::Stats)>,
         comp::ActiveAbilities,
         Option<comp::MapMarker>,
+        comp::Achievements,
     ),
     pub metadata: UpdateCharacterMetadata,
 }
@@ -533,7 +551,11 @@ pub fn register_event_busses(ecs: &mut World) {
     ecs.insert(EventBus::<MountEvent>::default());
     ecs.insert(EventBus::<MountVolumeEvent>::default());
     ecs.insert(EventBus::<UnmountEvent>::default());
-    ecs.insert(EventBus::<SetPetStayEvent>::default());
+    ecs.insert(EventBus::<SetPetOrderEvent>::default());
+    ecs.insert(EventBus::<SetPetStanceEvent>::default());
+    ecs.insert(EventBus::<RenamePetEvent>::default());
+    ecs.insert(EventBus::<StablePetEvent>::default());
+    ecs.insert(EventBus::<UnstablePetEvent>::default());
     ecs.insert(EventBus::<PossessEvent>::default());
     ecs.insert(EventBus::<InitializeCharacterEvent>::default());
     ecs.insert(EventBus::<InitializeSpectatorEvent>::default());
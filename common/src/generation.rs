@@ -9,6 +9,7 @@ use crate::{
         misc::PortalData,
         Alignment, Body, Item,
     },
+    encounter::EncounterInfo,
     lottery::LootSpec,
     npc::{self, NPC_NAMES},
     resources::TimeOfDay,
@@ -218,6 +219,9 @@ pub struct EntityInfo {
 
     pub pets: Vec<EntityInfo>,
 
+    /// The boss encounter this entity is the boss of
+    pub encounter: Option<EncounterInfo>,
+
     // Economy
     // we can't use DHashMap, do we want to move that into common?
     pub trading_information: Option<SiteInformation>,
@@ -248,6 +252,7 @@ impl EntityInfo {
             make_loadout: None,
             skillset_asset: None,
             pets: Vec::new(),
+            encounter: None,
             trading_information: None,
             special_entity: None,
         }
@@ -441,6 +446,12 @@ impl EntityInfo {
         self
     }
 
+    #[must_use]
+    pub fn with_encounter(mut self, encounter: EncounterInfo) -> Self {
+        self.encounter = Some(encounter);
+        self
+    }
+
     #[must_use]
     pub fn with_alignment(mut self, alignment: Alignment) -> Self {
         self.alignment = alignment;
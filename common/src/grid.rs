@@ -8,6 +8,15 @@ pub struct Grid<T> {
     size: Vec2<i32>, // TODO: use u32
 }
 
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            size: Vec2::zero(),
+        }
+    }
+}
+
 impl<T> Grid<T> {
     pub fn from_raw(size: Vec2<i32>, raw: impl Into<Vec<T>>) -> Self {
         let cells = raw.into();
@@ -36,6 +36,7 @@ pub mod clock;
 pub mod cmd;
 pub mod depot;
 pub mod effect;
+pub mod encounter;
 pub mod event;
 pub mod explosion;
 pub mod figure;
@@ -3,7 +3,11 @@
 // `Agent`). When possible, this should be moved to the `rtsim`
 // module in `server`.
 
-use crate::{character::CharacterId, comp::dialogue::Subject, util::Dir};
+use crate::{
+    character::CharacterId,
+    comp::{dialogue::Subject, Alignment, Body},
+    util::Dir,
+};
 use common_i18n::Content;
 use rand::{seq::IteratorRandom, Rng};
 use serde::{Deserialize, Serialize};
@@ -127,6 +131,23 @@ impl Personality {
         }
     }
 
+    /// A personality that takes after two parents. Each trait comes from one
+    /// of the parents, or occasionally from neither.
+    pub fn inherit(a: &Self, b: &Self, rng: &mut impl Rng) -> Self {
+        let mut inherit = |a: u8, b: u8| match rng.gen_range(0..5) {
+            0 => Self::distributed_value(rng),
+            1 | 2 => a,
+            _ => b,
+        };
+        Self {
+            openness: inherit(a.openness, b.openness),
+            conscientiousness: inherit(a.conscientiousness, b.conscientiousness),
+            extraversion: inherit(a.extraversion, b.extraversion),
+            agreeableness: inherit(a.agreeableness, b.agreeableness),
+            neuroticism: inherit(a.neuroticism, b.neuroticism),
+        }
+    }
+
     pub fn is(&self, trait_: PersonalityTrait) -> bool {
         match trait_ {
             PersonalityTrait::Open => self.openness > Personality::HIGH_THRESHOLD,
@@ -299,6 +320,54 @@ pub enum ChunkResource {
     Ore, // Iron, copper, etc.
 }
 
+/// Broad groups of wild creatures whose populations are tracked by rtsim.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, enum_map::Enum)]
+pub enum WildlifeGroup {
+    /// Peaceful land animals like deer, rabbits and boars
+    #[serde(rename = "0")]
+    Prey,
+    /// Aggressive land animals like wolves, bears and snow leopards
+    #[serde(rename = "1")]
+    Predator,
+    /// Fish and other creatures of the rivers and seas
+    #[serde(rename = "2")]
+    Aquatic,
+}
+
+impl WildlifeGroup {
+    /// The group that a creature belongs to, if it's a wild creature at all.
+    pub fn of(body: &Body, alignment: &Alignment) -> Option<Self> {
+        match (body, alignment) {
+            (Body::FishSmall(_) | Body::FishMedium(_), Alignment::Wild | Alignment::Enemy) => {
+                Some(Self::Aquatic)
+            },
+            (
+                Body::QuadrupedSmall(_)
+                | Body::QuadrupedMedium(_)
+                | Body::QuadrupedLow(_)
+                | Body::BirdMedium(_)
+                | Body::BirdLarge(_)
+                | Body::Theropod(_)
+                | Body::Arthropod(_)
+                | Body::Crustacean(_),
+                Alignment::Wild,
+            ) => Some(Self::Prey),
+            (
+                Body::QuadrupedSmall(_)
+                | Body::QuadrupedMedium(_)
+                | Body::QuadrupedLow(_)
+                | Body::BirdMedium(_)
+                | Body::BirdLarge(_)
+                | Body::Theropod(_)
+                | Body::Arthropod(_)
+                | Body::Crustacean(_),
+                Alignment::Enemy,
+            ) => Some(Self::Predator),
+            _ => None,
+        }
+    }
+}
+
 // Note: the `serde(name = "...")` is to minimise the length of field
 // identifiers for the sake of rtsim persistence
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -309,8 +378,11 @@ pub enum Role {
     Wild,
     #[serde(rename = "2")]
     Monster,
-    #[serde(rename = "2")]
+    #[serde(rename = "3")]
     Vehicle,
+    /// An animal that carries goods for merchants' caravans
+    #[serde(rename = "4")]
+    PackAnimal,
 }
 
 // Note: the `serde(name = "...")` is to minimise the length of field
@@ -341,17 +413,24 @@ pub enum Profession {
     Herbalist,
     #[serde(rename = "11")]
     Captain,
+    #[serde(rename = "12")]
+    Miner,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WorldSettings {
     pub start_time: f64,
+    /// Whether snow should pile up and ponds should fill during bad weather,
+    /// changing the terrain
+    #[serde(default)]
+    pub weather_accumulation: bool,
 }
 
 impl Default for WorldSettings {
     fn default() -> Self {
         Self {
             start_time: 9.0 * 3600.0, // 9am
+            weather_accumulation: false,
         }
     }
 }
@@ -7,4 +7,7 @@ pub struct ServerConstants {
     /// How many times faster the in-game day/night cycle should be compared to
     /// real time.
     pub day_cycle_coefficient: f64,
+    /// How many in-game days there are in a year, which determines the length
+    /// of the seasons.
+    pub year_length: f64,
 }
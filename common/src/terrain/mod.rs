@@ -2,6 +2,7 @@ pub mod biome;
 pub mod block;
 pub mod chonk;
 pub mod map;
+pub mod schematic;
 pub mod site;
 pub mod sprite;
 pub mod structure;
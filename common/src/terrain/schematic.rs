@@ -0,0 +1,234 @@
+use super::{structure::load_base_structure, Block, BlockKind};
+use crate::vol::{ReadVol, SizedVol};
+use dot_vox::DotVoxData;
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// A region of terrain captured by a builder, which can be saved and pasted
+/// elsewhere.
+///
+/// Blocks keep their sprites and sprite orientation, but not the
+/// [`super::SpriteCfg`] of signs and keyholes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schematic {
+    size: Vec3<u32>,
+    /// Blocks in x, then y, then z order. `None` leaves the terrain as it is
+    /// when pasting.
+    blocks: Vec<Option<Block>>,
+}
+
+/// Formats that schematics are stored in. New formats must be added at the
+/// end so that existing files can still be loaded.
+#[derive(Serialize, Deserialize)]
+pub enum RawSchematic {
+    V1 {
+        size: Vec3<u32>,
+        blocks: Vec<Option<u32>>,
+    },
+}
+
+/// How a schematic is mirrored when pasting, which happens before it is
+/// rotated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Mirror {
+    pub x: bool,
+    pub y: bool,
+}
+
+impl Schematic {
+    /// Capture the blocks in `bounds` (inclusive), fails if any of them
+    /// aren't loaded.
+    pub fn capture<V: ReadVol<Vox = Block>>(vol: &V, bounds: Aabb<i32>) -> Option<Self> {
+        let bounds = bounds.made_valid();
+        let size = (bounds.max - bounds.min + 1).map(|e| e as u32);
+        let mut blocks = Vec::with_capacity(size.product() as usize);
+        for z in bounds.min.z..=bounds.max.z {
+            for y in bounds.min.y..=bounds.max.y {
+                for x in bounds.min.x..=bounds.max.x {
+                    blocks.push(Some(*vol.get(Vec3::new(x, y, z)).ok()?));
+                }
+            }
+        }
+        Some(Self { size, blocks })
+    }
+
+    /// Create a schematic from the first model in a `.vox` file. Voxels
+    /// become [`BlockKind::Misc`] blocks of the same colour, and empty space
+    /// is left as it is when pasting.
+    pub fn from_vox(bytes: &[u8]) -> Result<Self, &'static str> {
+        Ok(Self::from_dot_vox(&dot_vox::load_bytes(bytes)?))
+    }
+
+    fn from_dot_vox(dot_vox_data: &DotVoxData) -> Self {
+        let base = load_base_structure(dot_vox_data, |col| Some(Block::new(BlockKind::Misc, col)));
+        let size = base.vol.size();
+        let blocks = (0..size.z as i32)
+            .flat_map(|z| (0..size.y as i32).map(move |y| (y, z)))
+            .flat_map(|(y, z)| (0..size.x as i32).map(move |x| Vec3::new(x, y, z)))
+            .map(|pos| {
+                base.vol
+                    .get(pos)
+                    .ok()
+                    .copied()
+                    .flatten()
+                    .and_then(|index| base.palette[index.get() as usize])
+            })
+            .collect();
+        Self { size, blocks }
+    }
+
+    pub fn size(&self) -> Vec3<u32> { self.size }
+
+    fn index(size: Vec3<u32>, pos: Vec3<i32>) -> usize {
+        let pos = pos.map(|e| e as usize);
+        let size = size.map(|e| e as usize);
+        pos.x + (pos.y + pos.z * size.y) * size.x
+    }
+
+    fn position(size: Vec3<u32>, index: usize) -> Vec3<i32> {
+        let size = size.map(|e| e as usize);
+        Vec3::new(
+            index % size.x,
+            index / size.x % size.y,
+            index / (size.x * size.y),
+        )
+        .map(|e| e as i32)
+    }
+
+    /// The block that would be placed at a position relative to the minimum
+    /// corner, if any
+    pub fn get(&self, pos: Vec3<i32>) -> Option<Block> {
+        if pos
+            .map2(self.size, |e, size| e >= 0 && (e as u32) < size)
+            .reduce_and()
+        {
+            self.blocks[Self::index(self.size, pos)]
+        } else {
+            None
+        }
+    }
+
+    /// The blocks that would be placed, relative to the minimum corner
+    pub fn blocks(&self) -> impl Iterator<Item = (Vec3<i32>, Block)> + '_ {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| Some((Self::position(self.size, i), (*block)?)))
+    }
+
+    /// Mirror the schematic, then rotate it anticlockwise around the z axis by
+    /// a number of quarter turns. Sprites are turned to match.
+    #[must_use]
+    pub fn transformed(&self, mirror: Mirror, quarter_turns: u8) -> Self {
+        let quarter_turns = quarter_turns % 4;
+        let size = if quarter_turns % 2 == 1 {
+            Vec3::new(self.size.y, self.size.x, self.size.z)
+        } else {
+            self.size
+        };
+        let max = self.size.map(|e| e as i32 - 1);
+
+        let mut blocks = vec![None; self.blocks.len()];
+        for (pos, block) in self.blocks() {
+            let mut pos = pos;
+            // Sprite orientations are in eighths of a turn anticlockwise
+            let mut ori = block.get_ori();
+            if mirror.x {
+                pos.x = max.x - pos.x;
+                ori = ori.map(|ori| (8 - ori) % 8);
+            }
+            if mirror.y {
+                pos.y = max.y - pos.y;
+                ori = ori.map(|ori| (12 - ori) % 8);
+            }
+            let mut extent = max;
+            for _ in 0..quarter_turns {
+                pos = Vec3::new(extent.y - pos.y, pos.x, pos.z);
+                extent = Vec3::new(extent.y, extent.x, extent.z);
+                ori = ori.map(|ori| (ori + 2) % 8);
+            }
+            let block = ori.and_then(|ori| block.with_ori(ori)).unwrap_or(block);
+            blocks[Self::index(size, pos)] = Some(block);
+        }
+        Self { size, blocks }
+    }
+}
+
+impl From<&Schematic> for RawSchematic {
+    fn from(schematic: &Schematic) -> Self {
+        RawSchematic::V1 {
+            size: schematic.size,
+            blocks: schematic
+                .blocks
+                .iter()
+                .map(|block| block.map(Block::to_u32))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<RawSchematic> for Schematic {
+    type Error = &'static str;
+
+    fn try_from(raw: RawSchematic) -> Result<Self, Self::Error> {
+        match raw {
+            RawSchematic::V1 { size, blocks } => {
+                if blocks.len() != size.product() as usize {
+                    return Err("The number of blocks doesn't match the size");
+                }
+                Ok(Self {
+                    size,
+                    blocks: blocks
+                        .into_iter()
+                        .map(|block| {
+                            block.map(|block| Block::from_u32(block).unwrap_or_else(Block::empty))
+                        })
+                        .collect(),
+                })
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::SpriteKind;
+
+    #[test]
+    fn test_transform() {
+        let stone = Block::new(BlockKind::Rock, Rgb::new(100, 100, 100));
+        let chair = Block::air(SpriteKind::ChairSingle).with_ori(0).unwrap();
+        let schematic = Schematic {
+            size: Vec3::new(2, 1, 1),
+            blocks: vec![Some(stone), Some(chair)],
+        };
+
+        let turned = schematic.transformed(Mirror::default(), 1);
+        assert_eq!(turned.size(), Vec3::new(1, 2, 1));
+        assert_eq!(turned.blocks().collect::<Vec<_>>(), vec![
+            (Vec3::new(0, 0, 0), stone),
+            (Vec3::new(0, 1, 0), chair.with_ori(2).unwrap()),
+        ]);
+
+        let mirrored = schematic.transformed(Mirror { x: true, y: false }, 0);
+        assert_eq!(mirrored.blocks().next(), Some((Vec3::zero(), chair)));
+        assert_eq!(mirrored.get(Vec3::new(1, 0, 0)), Some(stone));
+        assert_eq!(mirrored.get(Vec3::new(2, 0, 0)), None);
+
+        // Four quarter turns is the same as doing nothing
+        assert_eq!(schematic.transformed(Mirror::default(), 4), schematic);
+        let turned_back = turned.transformed(Mirror::default(), 3);
+        assert_eq!(turned_back, schematic);
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        let schematic = Schematic {
+            size: Vec3::new(1, 1, 2),
+            blocks: vec![Some(Block::air(SpriteKind::Apple)), None],
+        };
+        let raw = RawSchematic::from(&schematic);
+        assert_eq!(Schematic::try_from(raw), Ok(schematic));
+    }
+}
@@ -724,6 +724,15 @@ impl SpriteKind {
         matches!(self.collectible_id(), Some(Some(LootSpec::LootTable(_))))
     }
 
+    /// Can this sprite be placed as furniture in a player's house?
+    #[inline]
+    pub fn is_furniture(&self) -> bool {
+        matches!(
+            self.category(),
+            Category::Furniture | Category::Decor | Category::Lamp
+        )
+    }
+
     /// Get the position and direction to mount this sprite if any.
     #[inline]
     pub fn mount_offset(&self) -> Option<(Vec3<f32>, Vec3<f32>)> {
@@ -3,7 +3,7 @@ use std::fmt;
 use serde::{Deserialize, Serialize};
 use vek::{Lerp, Vec2, Vec3};
 
-use crate::{grid::Grid, terrain::TerrainChunkSize, vol::RectVolSize};
+use crate::{grid::Grid, resources::TimeOfDay, terrain::TerrainChunkSize, vol::RectVolSize};
 
 /// Weather::default is Clear, 0 degrees C and no wind
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
@@ -12,17 +12,30 @@ pub struct Weather {
     pub cloud: f32,
     /// Rain per time, between 0 and 1
     pub rain: f32,
+    /// Snow per time, between 0 and 1
+    pub snow: f32,
+    /// Air temperature in degrees Celsius
+    pub temperature: f32,
     /// Wind velocity in block / second
     pub wind: Vec2<f32>,
 }
 
 impl Weather {
-    pub fn new(cloud: f32, rain: f32, wind: Vec2<f32>) -> Self { Self { cloud, rain, wind } }
+    pub fn new(cloud: f32, rain: f32, wind: Vec2<f32>) -> Self {
+        Self {
+            cloud,
+            rain,
+            wind,
+            ..Default::default()
+        }
+    }
 
     pub fn get_kind(&self) -> WeatherKind {
         // Over 24.5 m/s wind is a storm
         if self.wind.magnitude_squared() >= 24.5f32.powi(2) {
             WeatherKind::Storm
+        } else if (0.1..=1.0).contains(&self.snow) {
+            WeatherKind::Snow
         } else if (0.1..=1.0).contains(&self.rain) {
             WeatherKind::Rain
         } else if (0.2..=1.0).contains(&self.cloud) {
@@ -36,10 +49,15 @@ impl Weather {
         Self {
             cloud: f32::lerp_unclamped(self.cloud, to.cloud, t),
             rain: f32::lerp_unclamped(self.rain, to.rain, t),
+            snow: f32::lerp_unclamped(self.snow, to.snow, t),
+            temperature: f32::lerp_unclamped(self.temperature, to.temperature, t),
             wind: Vec2::<f32>::lerp_unclamped(self.wind, to.wind, t),
         }
     }
 
+    /// Whether it is cold enough for water to freeze
+    pub fn is_freezing(&self) -> bool { self.temperature < 0.0 }
+
     // Get the rain velocity for this weather
     pub fn rain_vel(&self) -> Vec3<f32> {
         const FALL_RATE: f32 = 30.0;
@@ -55,6 +73,7 @@ pub enum WeatherKind {
     Clear,
     Cloudy,
     Rain,
+    Snow,
     Storm,
 }
 
@@ -64,6 +83,7 @@ impl fmt::Display for WeatherKind {
             WeatherKind::Clear => write!(f, "Clear"),
             WeatherKind::Cloudy => write!(f, "Cloudy"),
             WeatherKind::Rain => write!(f, "Rain"),
+            WeatherKind::Snow => write!(f, "Snow"),
             WeatherKind::Storm => write!(f, "Storm"),
         }
     }
@@ -75,6 +95,60 @@ pub const CHUNKS_PER_CELL: u32 = 16;
 
 pub const CELL_SIZE: u32 = CHUNKS_PER_CELL * TerrainChunkSize::RECT_SIZE.x;
 
+/// The length of an in-game day, in seconds of [`TimeOfDay`]
+const DAY_SECONDS: f64 = 24.0 * 3600.0;
+
+/// The seasons of the in-game year, each lasting a quarter of the year.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// The season at a given time, for years that are `year_length` in-game
+    /// days long.
+    pub fn at(time_of_day: TimeOfDay, year_length: f64) -> Self {
+        match (year_progress(time_of_day, year_length) * 4.0) as u32 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Season::Spring => write!(f, "Spring"),
+            Season::Summer => write!(f, "Summer"),
+            Season::Autumn => write!(f, "Autumn"),
+            Season::Winter => write!(f, "Winter"),
+        }
+    }
+}
+
+/// How far through the year a given time is, from 0 at the start of spring to
+/// 1 at the end of winter.
+pub fn year_progress(time_of_day: TimeOfDay, year_length: f64) -> f32 {
+    (time_of_day.0 / (year_length.max(1.0) * DAY_SECONDS)).rem_euclid(1.0) as f32
+}
+
+/// How warm the season is at a given time, from -1 in the middle of winter to
+/// 1 in the middle of summer.
+pub fn seasonal_warmth(time_of_day: TimeOfDay, year_length: f64) -> f32 {
+    ((year_progress(time_of_day, year_length) - 0.375) * std::f32::consts::TAU).cos()
+}
+
+/// How warm the time of day is, from -1 in the small hours to 1 in the
+/// afternoon.
+pub fn daily_warmth(time_of_day: TimeOfDay) -> f32 {
+    (((time_of_day.day() / DAY_SECONDS) as f32 - 15.0 / 24.0) * std::f32::consts::TAU).cos()
+}
+
 #[derive(Debug, Clone)]
 pub struct WeatherGrid {
     weather: Grid<Weather>,
@@ -85,6 +159,9 @@ pub struct WeatherGrid {
 pub struct CompressedWeather {
     cloud: u8,
     rain: u8,
+    snow: u8,
+    /// Temperature in whole degrees Celsius
+    temperature: i8,
 }
 
 impl CompressedWeather {
@@ -92,6 +169,8 @@ impl CompressedWeather {
         Weather {
             cloud: f32::lerp_unclamped(self.cloud as f32, to.cloud as f32, t) / 255.0,
             rain: f32::lerp_unclamped(self.rain as f32, to.rain as f32, t) / 255.0,
+            snow: f32::lerp_unclamped(self.snow as f32, to.snow as f32, t) / 255.0,
+            temperature: f32::lerp_unclamped(self.temperature as f32, to.temperature as f32, t),
             wind: Vec2::zero(),
         }
     }
@@ -102,6 +181,8 @@ impl From<Weather> for CompressedWeather {
         Self {
             cloud: (weather.cloud * 255.0).round() as u8,
             rain: (weather.rain * 255.0).round() as u8,
+            snow: (weather.snow * 255.0).round() as u8,
+            temperature: weather.temperature.round() as i8,
         }
     }
 }
@@ -111,6 +192,8 @@ impl From<CompressedWeather> for Weather {
         Self {
             cloud: weather.cloud as f32 / 255.0,
             rain: weather.rain as f32 / 255.0,
+            snow: weather.snow as f32 / 255.0,
+            temperature: weather.temperature as f32,
             wind: Vec2::zero(),
         }
     }
@@ -257,9 +340,31 @@ impl WeatherGrid {
             .reduce(|a, b| Weather {
                 cloud: a.cloud.max(b.cloud),
                 rain: a.rain.max(b.rain),
+                snow: a.snow.max(b.snow),
+                temperature: a.temperature.max(b.temperature),
                 wind: a.wind.map2(b.wind, |a, b| a.max(b)),
             })
             // There will always be 9 elements in locality
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seasons_cycle_through_the_year() {
+        let year_length = 8.0;
+        let season_at_day = |day: f64| Season::at(TimeOfDay(day * DAY_SECONDS), year_length);
+
+        assert_eq!(season_at_day(0.0), Season::Spring);
+        assert_eq!(season_at_day(2.5), Season::Summer);
+        assert_eq!(season_at_day(4.0), Season::Autumn);
+        assert_eq!(season_at_day(7.9), Season::Winter);
+        assert_eq!(season_at_day(8.0), Season::Spring);
+        // Midsummer is the warmest time of the year, and midwinter the coldest
+        assert!(seasonal_warmth(TimeOfDay(3.0 * DAY_SECONDS), year_length) > 0.99);
+        assert!(seasonal_warmth(TimeOfDay(7.0 * DAY_SECONDS), year_length) < -0.99);
+    }
+}
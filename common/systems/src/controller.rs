@@ -17,7 +17,11 @@ event_emitters! {
     struct Events[EventEmitters] {
         mount: event::MountEvent,
         mount_volume: event::MountVolumeEvent,
-        set_pet_stay: event::SetPetStayEvent,
+        set_pet_order: event::SetPetOrderEvent,
+        set_pet_stance: event::SetPetStanceEvent,
+        rename_pet: event::RenamePetEvent,
+        stable_pet: event::StablePetEvent,
+        unstable_pet: event::UnstablePetEvent,
         unmount: event::UnmountEvent,
         lantern: event::SetLanternEvent,
         npc_interact: event::NpcInteractEvent,
@@ -83,11 +87,29 @@ impl<'a> System<'a> for Sys {
                             }
                         }
                     },
-                    ControlEvent::SetPetStay(pet_uid, stay) => {
+                    ControlEvent::SetPetOrder(pet_uid, order) => {
                         if let Some(pet_entity) = read_data.id_maps.uid_entity(pet_uid) {
-                            emitters.emit(event::SetPetStayEvent(entity, pet_entity, stay));
+                            emitters.emit(event::SetPetOrderEvent(entity, pet_entity, order));
                         }
                     },
+                    ControlEvent::SetPetStance(pet_uid, stance) => {
+                        if let Some(pet_entity) = read_data.id_maps.uid_entity(pet_uid) {
+                            emitters.emit(event::SetPetStanceEvent(entity, pet_entity, stance));
+                        }
+                    },
+                    ControlEvent::RenamePet(pet_uid, name) => {
+                        if let Some(pet_entity) = read_data.id_maps.uid_entity(pet_uid) {
+                            emitters.emit(event::RenamePetEvent(entity, pet_entity, name));
+                        }
+                    },
+                    ControlEvent::StablePet(pet_uid) => {
+                        if let Some(pet_entity) = read_data.id_maps.uid_entity(pet_uid) {
+                            emitters.emit(event::StablePetEvent(entity, pet_entity));
+                        }
+                    },
+                    ControlEvent::UnstablePet(index) => {
+                        emitters.emit(event::UnstablePetEvent(entity, index));
+                    },
                     ControlEvent::RemoveBuff(buff_id) => {
                         emitters.emit(event::BuffEvent {
                             entity,
@@ -81,6 +81,7 @@ mod tests {
             None,
             &ServerConstants {
                 day_cycle_coefficient: 24.0,
+                year_length: common::consts::YEAR_LENGTH_DEFAULT,
             },
             |_, _| {},
         );
@@ -67,6 +67,7 @@ pub fn tick(state: &mut State, dt: Duration) {
         None,
         &ServerConstants {
             day_cycle_coefficient: 24.0,
+            year_length: common::consts::YEAR_LENGTH_DEFAULT,
         },
         |_, _| {},
     );
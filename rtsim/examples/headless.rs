@@ -0,0 +1,129 @@
+//! Fast-forwards rtsim for a number of game days without running a server,
+//! writing statistics about the simulation as it goes.
+//!
+//! ```text
+//! cargo run --release -p veloren-rtsim --example headless -- --days 14 --csv stats.csv
+//! ```
+
+use clap::Parser;
+use common::{resources::TimeOfDay, rtsim::WorldSettings};
+use std::{fs, io, path::PathBuf};
+use tracing::{info, Level};
+use tracing_subscriber::EnvFilter;
+use veloren_rtsim::{
+    headless::{Config, Headless, Stats},
+    Data,
+};
+use world::{
+    sim::{FileOpts, WorldOpts, DEFAULT_WORLD_MAP, DEFAULT_WORLD_SEED},
+    World,
+};
+
+#[derive(Parser)]
+struct Cli {
+    /// Seed of the world
+    #[arg(long, default_value_t = DEFAULT_WORLD_SEED)]
+    seed: u32,
+    /// Map file to load, the default world is used if not given
+    #[arg(long)]
+    map: Option<PathBuf>,
+    /// Rtsim data to start from (such as a server's `data.dat`), fresh data
+    /// is generated if not given
+    #[arg(long)]
+    data: Option<PathBuf>,
+    /// How many game days to simulate
+    #[arg(long, default_value_t = 7.0)]
+    days: f64,
+    /// How often to take statistics, in game days
+    #[arg(long, default_value_t = 1.0)]
+    interval: f64,
+    /// Real seconds that pass each tick
+    #[arg(long, default_value_t = 1.0)]
+    dt: f32,
+    /// Write statistics to this file as CSV
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    /// Write statistics to this file as JSON
+    #[arg(long)]
+    json: Option<PathBuf>,
+    /// Save the rtsim data to this file once the run has finished
+    #[arg(long)]
+    save: Option<PathBuf>,
+}
+
+fn main() -> io::Result<()> {
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+
+    let threadpool = rayon::ThreadPoolBuilder::new().build().unwrap();
+    info!("Loading world");
+    let (world, index) = World::generate(
+        cli.seed,
+        WorldOpts {
+            seed_elements: true,
+            world_file: match cli.map {
+                Some(path) => FileOpts::Load(path),
+                None => FileOpts::LoadAsset(DEFAULT_WORLD_MAP.into()),
+            },
+            calendar: None,
+        },
+        &threadpool,
+        &|_| {},
+    );
+    let index = index.as_index_ref();
+
+    let data = match &cli.data {
+        Some(path) => match Data::from_reader(io::BufReader::new(fs::File::open(path)?)) {
+            Ok(data) => *data,
+            Err(err) => panic!("Could not load rtsim data: {err:?}"),
+        },
+        None => Data::generate(
+            &WorldSettings {
+                start_time: TimeOfDay::default().0,
+                weather_accumulation: false,
+            },
+            &world,
+            index,
+        ),
+    };
+
+    let mut headless = Headless::new(data, &world, index, Config {
+        dt: cli.dt,
+        ..Config::default()
+    });
+
+    let mut csv = format!("{}\n", Stats::CSV_HEADER);
+    let mut json = Vec::new();
+    headless.run(cli.days, cli.interval, |stats| {
+        let population = stats
+            .population
+            .values()
+            .flat_map(|roles| roles.values())
+            .sum::<u32>();
+        info!("Day {:.1}: {} NPCs", stats.day, population);
+        stats.write_csv_rows(&mut csv);
+        json.push(stats);
+    });
+
+    if let Some(path) = &cli.csv {
+        fs::write(path, csv)?;
+        info!("Wrote statistics to {}", path.display());
+    }
+    if let Some(path) = &cli.json {
+        fs::write(path, serde_json::to_string_pretty(&json)?)?;
+        info!("Wrote statistics to {}", path.display());
+    }
+    if let Some(path) = &cli.save {
+        headless
+            .data()
+            .write_to(io::BufWriter::new(fs::File::create(path)?))
+            .map_err(io::Error::other)?;
+        info!("Saved rtsim data to {}", path.display());
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 pub mod predicate;
+pub mod schedule;
 
 use predicate::Predicate;
 
@@ -0,0 +1,229 @@
+//! Daily routines for NPCs that live in a site: where they sleep, where they
+//! work and what they should be doing at each time of the day.
+//!
+//! Everything here is derived from the NPC's seed and profession, so it stays
+//! the same whether the NPC is loaded or simulated, and across restarts.
+
+use crate::data::Npc;
+use common::{resources::TimeOfDay, rtsim::Profession, store::Id};
+use rand::prelude::*;
+use world::{
+    site2::{Plot, PlotKind, Site},
+    util::RandomPerm,
+};
+
+/// The length of a day, in seconds of [`TimeOfDay`]
+const DAY: f64 = 24.0 * 3600.0;
+
+/// What an NPC with a home should be doing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Period {
+    Sleep,
+    Eat,
+    Work,
+    Leisure,
+}
+
+// Each routine is a list of the hours at which each period starts, in order
+// and starting at midnight.
+
+const WORKER: &[(f32, Period)] = &[
+    (0.0, Period::Sleep),
+    (6.5, Period::Eat),
+    (7.5, Period::Work),
+    (12.0, Period::Eat),
+    (13.0, Period::Work),
+    (18.0, Period::Eat),
+    (19.0, Period::Leisure),
+    (22.0, Period::Sleep),
+];
+
+const FARMER: &[(f32, Period)] = &[
+    (0.0, Period::Sleep),
+    (5.0, Period::Eat),
+    (5.5, Period::Work),
+    (11.5, Period::Eat),
+    (12.5, Period::Work),
+    (17.0, Period::Eat),
+    (18.0, Period::Leisure),
+    (21.0, Period::Sleep),
+];
+
+/// Chefs cook while everybody else is eating
+const CHEF: &[(f32, Period)] = &[
+    (0.0, Period::Sleep),
+    (5.0, Period::Eat),
+    (6.0, Period::Work),
+    (14.0, Period::Leisure),
+    (16.0, Period::Eat),
+    (17.0, Period::Work),
+    (22.0, Period::Leisure),
+    (23.0, Period::Sleep),
+];
+
+const DAY_WATCH: &[(f32, Period)] = &[
+    (0.0, Period::Sleep),
+    (5.5, Period::Eat),
+    (6.0, Period::Work),
+    (12.0, Period::Eat),
+    (12.5, Period::Work),
+    (19.0, Period::Eat),
+    (20.0, Period::Leisure),
+    (22.0, Period::Sleep),
+];
+
+const NIGHT_WATCH: &[(f32, Period)] = &[
+    (0.0, Period::Work),
+    (6.0, Period::Eat),
+    (7.0, Period::Sleep),
+    (15.0, Period::Leisure),
+    (18.0, Period::Eat),
+    (19.0, Period::Work),
+];
+
+#[derive(Copy, Clone, Debug)]
+pub struct Schedule {
+    routine: &'static [(f32, Period)],
+    /// How many hours later than usual the NPC does everything
+    offset: f32,
+    works_rest_days: bool,
+}
+
+impl Schedule {
+    pub fn for_npc(npc: &Npc) -> Self {
+        let mut rng = npc.rng(Npc::PERM_SCHEDULE);
+        let routine = match npc.profession() {
+            Some(Profession::Guard) if rng.gen_bool(0.3) => NIGHT_WATCH,
+            Some(Profession::Guard) => DAY_WATCH,
+            Some(Profession::Chef) => CHEF,
+            Some(Profession::Farmer) => FARMER,
+            _ => WORKER,
+        };
+        Self {
+            routine,
+            // Not everybody gets up at exactly the same time
+            offset: rng.gen_range(-0.75..0.75),
+            works_rest_days: matches!(npc.profession(), Some(Profession::Guard | Profession::Chef)),
+        }
+    }
+
+    pub fn period_at(&self, time_of_day: TimeOfDay) -> Period {
+        let hour = ((time_of_day.day() / 3600.0) as f32 - self.offset).rem_euclid(24.0);
+        let period = self
+            .routine
+            .iter()
+            .take_while(|(start, _)| *start <= hour)
+            .last()
+            .map_or(Period::Sleep, |(_, period)| *period);
+
+        if period == Period::Work && !self.works_rest_days && is_rest_day(time_of_day) {
+            Period::Leisure
+        } else {
+            period
+        }
+    }
+}
+
+/// Every seventh day, most people don't work.
+pub fn is_rest_day(time_of_day: TimeOfDay) -> bool {
+    (time_of_day.0 / DAY).floor().rem_euclid(7.0) == 6.0
+}
+
+/// How many settlers each house in a site can hold.
+const HOUSEHOLD_SIZE: usize = 4;
+
+/// Whether a plot is a house that NPCs can live in.
+pub fn is_house(kind: &PlotKind) -> bool {
+    matches!(
+        kind,
+        PlotKind::House(_)
+            | PlotKind::CoastalHouse(_)
+            | PlotKind::DesertCityMultiPlot(_)
+            | PlotKind::SavannahHut(_)
+            | PlotKind::CliffTower(_)
+    )
+}
+
+/// How many settlers the houses in a site have room for.
+pub fn housing_capacity(site2: &Site) -> usize {
+    site2.plots().filter(|plot| is_house(plot.kind())).count() * HOUSEHOLD_SIZE
+}
+
+/// The house in the site that the NPC lives in.
+///
+/// Members of a household share a house seed, so they all live in the same
+/// house.
+pub fn home_plot(npc: &Npc, site2: &Site) -> Option<Id<Plot>> {
+    site2
+        .plots
+        .iter()
+        .filter(|(_, plot)| is_house(plot.kind()))
+        .map(|(id, _)| id)
+        .choose(&mut RandomPerm::new(npc.house_seed()))
+}
+
+/// The building in the site that the NPC works in, for professions that work
+/// indoors.
+pub fn workplace(npc: &Npc, site2: &Site) -> Option<Id<Plot>> {
+    let is_workplace = |kind: &PlotKind| match npc.profession() {
+        Some(Profession::Blacksmith | Profession::Alchemist) => matches!(
+            kind,
+            PlotKind::Workshop(_) | PlotKind::CoastalWorkshop(_) | PlotKind::SavannahWorkshop(_)
+        ),
+        Some(Profession::Chef) => matches!(kind, PlotKind::Tavern(_)),
+        _ => false,
+    };
+    site2
+        .plots
+        .iter()
+        .filter(|(_, plot)| is_workplace(plot.kind()))
+        .map(|(id, _)| id)
+        .choose(&mut npc.rng(Npc::PERM_WORKPLACE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{comp, rtsim::Role};
+    use vek::*;
+
+    fn npc(seed: u32, profession: Option<Profession>) -> Npc {
+        Npc::new(
+            seed,
+            Vec3::zero(),
+            comp::Body::Humanoid(comp::humanoid::Body::random()),
+            Role::Civilised(profession),
+        )
+    }
+
+    fn at_hour(day: f64, hour: f64) -> TimeOfDay { TimeOfDay((day * 24.0 + hour) * 3600.0) }
+
+    #[test]
+    fn routines_are_ordered() {
+        for routine in [WORKER, FARMER, CHEF, DAY_WATCH, NIGHT_WATCH] {
+            assert_eq!(routine[0].0, 0.0);
+            assert!(routine.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn workers_sleep_at_night_and_rest_on_rest_days() {
+        for seed in 0..32 {
+            let schedule = Schedule::for_npc(&npc(seed, Some(Profession::Blacksmith)));
+            assert_eq!(schedule.period_at(at_hour(0.0, 3.0)), Period::Sleep);
+            assert_eq!(schedule.period_at(at_hour(0.0, 10.0)), Period::Work);
+            assert_eq!(schedule.period_at(at_hour(6.0, 10.0)), Period::Leisure);
+            assert_eq!(schedule.period_at(at_hour(7.0, 10.0)), Period::Work);
+        }
+    }
+
+    #[test]
+    fn some_guards_keep_watch_at_night() {
+        let night_periods = (0..64)
+            .map(|seed| Schedule::for_npc(&npc(seed, Some(Profession::Guard))))
+            .map(|schedule| schedule.period_at(at_hour(6.0, 2.0)))
+            .collect::<Vec<_>>();
+        assert!(night_periods.contains(&Period::Work));
+        assert!(night_periods.contains(&Period::Sleep));
+    }
+}
@@ -0,0 +1,50 @@
+use common::rtsim::Actor;
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// A crime that a site will put a bounty on the head of whoever committed it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Crime {
+    /// Attacking one of the site's people
+    Assault,
+    /// Killing one of the site's people
+    Murder,
+    /// Taking things from a container that doesn't belong to you
+    Theft,
+    /// Breaking the site's buildings
+    Vandalism,
+}
+
+impl Crime {
+    /// The bounty, in coins, that committing the crime adds.
+    pub fn bounty(&self) -> u32 {
+        match self {
+            Crime::Assault => 30,
+            Crime::Murder => 300,
+            Crime::Theft => 50,
+            Crime::Vandalism => 5,
+        }
+    }
+}
+
+/// The bounties that a site has put on the heads of those who broke its laws,
+/// in coins.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Bounties {
+    #[serde(rename = "b")]
+    bounties: HashMap<Actor, u32>,
+}
+
+impl Bounties {
+    /// Add to the bounty on an actor for a crime that they committed.
+    pub fn add(&mut self, actor: Actor, crime: Crime) {
+        let bounty = self.bounties.entry(actor).or_default();
+        *bounty = bounty.saturating_add(crime.bounty());
+    }
+
+    /// The bounty on an actor's head.
+    pub fn on(&self, actor: Actor) -> u32 { self.bounties.get(&actor).copied().unwrap_or(0) }
+
+    /// Clear the bounty on an actor's head, returning what it was.
+    pub fn clear(&mut self, actor: Actor) -> u32 { self.bounties.remove(&actor).unwrap_or(0) }
+}
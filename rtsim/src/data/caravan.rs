@@ -0,0 +1,102 @@
+use common::{
+    resources::TimeOfDay,
+    rtsim::{Actor, NpcId, SiteId},
+};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use slotmap::HopSlotMap;
+use std::ops::{Deref, DerefMut};
+use world::site::economy::TradeDelivery;
+
+slotmap::new_key_type! { pub struct CaravanId; }
+
+/// A merchant and their pack animals, carrying goods that one site has sold
+/// to another.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Caravan {
+    pub merchant: NpcId,
+    pub pack_animals: Vec<NpcId>,
+    /// The site that sold the goods
+    pub from: SiteId,
+    /// The site that bought the goods
+    pub to: SiteId,
+    pub stage: CaravanStage,
+    pub started: TimeOfDay,
+
+    /// The goods being carried. These are not persisted: whatever is being
+    /// carried when the server stops is lost.
+    #[serde(skip)]
+    pub cargo: Vec<TradeDelivery>,
+    /// How many times each character has been seen travelling alongside the
+    /// caravan while it carried its goods.
+    #[serde(skip)]
+    pub escort_sightings: HashMap<Actor, u32>,
+    /// How many times the caravan has looked around for escorts.
+    #[serde(skip)]
+    pub escort_checks: u32,
+    /// The characters that saw the caravan safely to its destination.
+    #[serde(skip)]
+    pub escorts: Vec<Actor>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaravanStage {
+    /// The merchant is on their way to pick up the goods
+    Gathering,
+    /// The goods are on their way to the site that bought them
+    Delivering,
+    /// The goods have been delivered and the caravan is heading back
+    Returning,
+}
+
+impl Caravan {
+    /// Lose a proportion of the cargo, for example when a pack animal is
+    /// killed.
+    pub fn lose_cargo(&mut self, proportion: f32) {
+        for delivery in &mut self.cargo {
+            delivery.lose(proportion);
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Caravans {
+    pub caravans: HopSlotMap<CaravanId, Caravan>,
+}
+
+impl Caravans {
+    pub fn create(&mut self, caravan: Caravan) -> CaravanId { self.caravans.insert(caravan) }
+
+    /// Find the caravan that the NPC is travelling with, if any.
+    pub fn caravan_of(&self, npc: NpcId) -> Option<(CaravanId, &Caravan)> {
+        self.caravans
+            .iter()
+            .find(|(_, caravan)| caravan.merchant == npc || caravan.pack_animals.contains(&npc))
+    }
+
+    /// How much of its caravan's cargo a pack animal is carrying, in the same
+    /// units as [`TradeDelivery::total_amount`].
+    pub fn cargo_of(&self, npc: NpcId) -> f32 {
+        self.caravans
+            .values()
+            .find(|caravan| caravan.pack_animals.contains(&npc))
+            .map_or(0.0, |caravan| {
+                caravan
+                    .cargo
+                    .iter()
+                    .map(TradeDelivery::total_amount)
+                    .sum::<f32>()
+                    / caravan.pack_animals.len() as f32
+            })
+    }
+}
+
+impl Deref for Caravans {
+    type Target = HopSlotMap<CaravanId, Caravan>;
+
+    fn deref(&self) -> &Self::Target { &self.caravans }
+}
+
+impl DerefMut for Caravans {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.caravans }
+}
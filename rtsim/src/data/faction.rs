@@ -1,6 +1,10 @@
-use crate::data::Sentiments;
-use common::rtsim::Actor;
+use crate::data::{Sentiment, Sentiments, Sites};
 pub use common::rtsim::FactionId;
+use common::{
+    grid::Grid,
+    resources::TimeOfDay,
+    rtsim::{Actor, NpcId, SiteId},
+};
 use serde::{Deserialize, Serialize};
 use slotmap::HopSlotMap;
 use std::ops::{Deref, DerefMut};
@@ -10,10 +14,15 @@ use vek::*;
 pub struct Faction {
     pub seed: u32,
     pub leader: Option<Actor>,
-    pub good_or_evil: bool, // TODO: Very stupid, get rid of this
 
+    /// How the faction feels about other factions (among other things). This
+    /// is what decides which factions are at war with one-another.
     #[serde(default)]
     pub sentiments: Sentiments,
+
+    /// The raid that the faction is currently carrying out, if any.
+    #[serde(default)]
+    pub raid: Option<Raid>,
 }
 
 impl Faction {
@@ -21,15 +30,45 @@ impl Faction {
         self.sentiments
             .cleanup(crate::data::sentiment::FACTION_MAX_SENTIMENTS);
     }
+
+    /// Whether the faction is willing to go to war with another faction.
+    pub fn is_hostile_to(&self, other: FactionId) -> bool {
+        self.sentiments.toward(other).is(Sentiment::ENEMY)
+    }
+}
+
+/// A group of NPCs sent by a faction to take a site from another faction.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Raid {
+    /// The site that the raiders set out from
+    pub from: SiteId,
+    pub target: SiteId,
+    pub raiders: Vec<NpcId>,
+    pub started: TimeOfDay,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Factions {
     pub factions: HopSlotMap<FactionId, Faction>,
+
+    /// Which faction controls each part of the world, derived from the sites
+    /// that each faction owns.
+    #[serde(skip_serializing, skip_deserializing)]
+    pub territory: Territory,
 }
 
 impl Factions {
     pub fn create(&mut self, faction: Faction) -> FactionId { self.factions.insert(faction) }
+
+    /// Find the faction that the NPC is raiding for, if it's taking part in a
+    /// raid.
+    pub fn raid_of(&self, faction: Option<FactionId>, npc: NpcId) -> Option<&Raid> {
+        self.factions
+            .get(faction?)?
+            .raid
+            .as_ref()
+            .filter(|raid| raid.raiders.contains(&npc))
+    }
 }
 
 impl Deref for Factions {
@@ -41,3 +80,72 @@ impl Deref for Factions {
 impl DerefMut for Factions {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.factions }
 }
+
+/// A coarse map of the world, recording which faction controls each area.
+///
+/// An area belongs to the faction that owns the closest site within
+/// [`Territory::MAX_REACH`] of it.
+#[derive(Clone)]
+pub struct Territory {
+    cells: Grid<Option<FactionId>>,
+}
+
+impl Default for Territory {
+    fn default() -> Self {
+        Self {
+            cells: Grid::new(Vec2::zero(), None),
+        }
+    }
+}
+
+impl Territory {
+    /// The width of each area of territory, in blocks.
+    pub const CELL_SIZE: i32 = 256;
+    /// The furthest that a site projects control, in blocks.
+    pub const MAX_REACH: i32 = 1536;
+
+    /// Work out the territory of each faction in a world of the given size (in
+    /// blocks).
+    pub fn compute(world_size: Vec2<i32>, sites: &Sites) -> Self {
+        let owned_sites = sites
+            .values()
+            .filter_map(|site| Some((site.wpos, site.faction?)))
+            .collect::<Vec<_>>();
+        let cells = Grid::populate_from(world_size / Self::CELL_SIZE + 1, |cell| {
+            let center = cell * Self::CELL_SIZE + Self::CELL_SIZE / 2;
+            owned_sites
+                .iter()
+                .map(|(wpos, faction)| (wpos.as_::<i64>().distance_squared(center.as_()), faction))
+                .filter(|(dist_sqr, _)| *dist_sqr < (Self::MAX_REACH as i64).pow(2))
+                .min_by_key(|(dist_sqr, _)| *dist_sqr)
+                .map(|(_, faction)| *faction)
+        });
+        Self { cells }
+    }
+
+    /// The faction that controls the given position, if any.
+    pub fn owner_at(&self, wpos: Vec2<i32>) -> Option<FactionId> {
+        self.cells
+            .get(wpos.map(|e| e.div_euclid(Self::CELL_SIZE)))
+            .copied()
+            .flatten()
+    }
+
+    /// Whether a faction controls any territory within `range` blocks of the
+    /// given position.
+    pub fn controls_near(&self, faction: FactionId, wpos: Vec2<i32>, range: i32) -> bool {
+        let center = wpos.map(|e| e.div_euclid(Self::CELL_SIZE));
+        let cells = range / Self::CELL_SIZE + 1;
+        (-cells..=cells)
+            .flat_map(|y| (-cells..=cells).map(move |x| Vec2::new(x, y)))
+            .any(|offset| self.cells.get(center + offset) == Some(&Some(faction)))
+    }
+
+    /// The area controlled by a faction, in units of territory cells.
+    pub fn area_of(&self, faction: FactionId) -> usize {
+        self.cells
+            .iter()
+            .filter(|(_, owner)| **owner == Some(faction))
+            .count()
+    }
+}
@@ -0,0 +1,474 @@
+//! Tools for looking at (and fixing) rtsim data from outside of the
+//! simulation, such as from the server CLI.
+
+use super::{caravan::CaravanId, npc::MountId, Data, FactionId, Npc, NpcId, SiteId};
+use common::{
+    comp,
+    rtsim::{Actor, Profession, Role},
+};
+use serde::Serialize;
+use slotmap::{Key, KeyData};
+use std::fmt;
+use vek::*;
+
+/// A human readable name for the role of an NPC, including their profession.
+pub fn role_name(role: &Role) -> String {
+    match role {
+        Role::Civilised(Some(Profession::Adventurer(_))) => "Adventurer".to_string(),
+        Role::Civilised(Some(profession)) => format!("{profession:?}"),
+        Role::Civilised(None) => "Civilian".to_string(),
+        Role::Wild => "Wild".to_string(),
+        Role::Monster => "Monster".to_string(),
+        Role::Vehicle => "Vehicle".to_string(),
+        Role::PackAnimal => "PackAnimal".to_string(),
+    }
+}
+
+/// Write an id as `INDEXvVERSION`, the same way that it appears in debug
+/// output.
+pub fn key_to_string<K: Key>(key: K) -> String {
+    let ffi = key.data().as_ffi();
+    format!("{}v{}", ffi & 0xFFFF_FFFF, ffi >> 32)
+}
+
+/// Parse an id written by [`key_to_string`].
+pub fn parse_key<K: Key>(s: &str) -> Result<K, String> {
+    let (idx, version) = s
+        .trim()
+        .split_once('v')
+        .ok_or_else(|| format!("expected an id like 12v1, got {s:?}"))?;
+    let idx = idx.parse::<u32>().map_err(|e| e.to_string())?;
+    let version = version.parse::<u32>().map_err(|e| e.to_string())?;
+    Ok(KeyData::from_ffi((u64::from(version) << 32) | u64::from(idx)).into())
+}
+
+/// Parse an id written by [`key_to_string`], or `none`.
+pub fn parse_link<K: Key>(s: &str) -> Result<Option<K>, String> {
+    if s.trim().eq_ignore_ascii_case("none") {
+        Ok(None)
+    } else {
+        parse_key(s).map(Some)
+    }
+}
+
+/// Which NPCs to include in a query. Every condition that is set must match.
+#[derive(Clone, Debug, Default)]
+pub struct NpcFilter {
+    pub home: Option<SiteId>,
+    /// Compared case-insensitively with [`role_name`]
+    pub role: Option<String>,
+    pub faction: Option<FactionId>,
+    /// A position and a radius around it, in blocks
+    pub near: Option<(Vec2<f32>, f32)>,
+    pub include_dead: bool,
+}
+
+impl NpcFilter {
+    pub fn matches(&self, npc: &Npc) -> bool {
+        (self.include_dead || !npc.is_dead)
+            && self.home.map_or(true, |home| npc.home == Some(home))
+            && self
+                .faction
+                .map_or(true, |faction| npc.faction == Some(faction))
+            && self
+                .role
+                .as_ref()
+                .map_or(true, |role| role_name(&npc.role).eq_ignore_ascii_case(role))
+            && self.near.map_or(true, |(wpos, radius)| {
+                npc.wpos.xy().distance_squared(wpos) <= radius.powi(2)
+            })
+    }
+}
+
+/// A readable overview of an NPC.
+#[derive(Clone, Debug, Serialize)]
+pub struct NpcSummary {
+    pub id: String,
+    pub uid: u64,
+    pub name: String,
+    pub body: comp::Body,
+    pub role: String,
+    pub wpos: [f32; 3],
+    pub home: Option<String>,
+    pub faction: Option<String>,
+    pub is_dead: bool,
+    /// How old the NPC is in years, if they age
+    pub age: Option<f32>,
+    pub partner: Option<String>,
+    /// The NPC that this one is riding, if any
+    pub riding: Option<String>,
+}
+
+impl NpcSummary {
+    pub fn new(data: &Data, id: NpcId, npc: &Npc) -> Self {
+        Self {
+            id: key_to_string(id),
+            uid: npc.uid,
+            name: npc.get_name(),
+            body: npc.body,
+            role: role_name(&npc.role),
+            wpos: npc.wpos.into_array(),
+            home: npc.home.map(key_to_string),
+            faction: npc.faction.map(key_to_string),
+            is_dead: npc.is_dead,
+            age: npc.age(data.time_of_day),
+            partner: npc.family.partner.map(key_to_string),
+            riding: data
+                .npcs
+                .mounts
+                .get_mount_link(id)
+                .map(|link| key_to_string(link.mount)),
+        }
+    }
+}
+
+/// A reference in rtsim data to something that does not exist.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LinkError {
+    NpcHome { npc: NpcId, site: SiteId },
+    NpcFaction { npc: NpcId, faction: FactionId },
+    SiteFaction { site: SiteId, faction: FactionId },
+    FactionLeader { faction: FactionId, npc: NpcId },
+    Mount { link: MountId, mount: NpcId },
+    Rider { link: MountId, rider: NpcId },
+    Relative { npc: NpcId, relative: NpcId },
+    CaravanMerchant { caravan: CaravanId, merchant: NpcId },
+    CaravanPackAnimal { caravan: CaravanId, animal: NpcId },
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NpcHome { npc, site } => write!(
+                f,
+                "npc {} has home site {} which does not exist",
+                key_to_string(*npc),
+                key_to_string(*site)
+            ),
+            Self::NpcFaction { npc, faction } => write!(
+                f,
+                "npc {} belongs to faction {} which does not exist",
+                key_to_string(*npc),
+                key_to_string(*faction)
+            ),
+            Self::SiteFaction { site, faction } => write!(
+                f,
+                "site {} belongs to faction {} which does not exist",
+                key_to_string(*site),
+                key_to_string(*faction)
+            ),
+            Self::FactionLeader { faction, npc } => write!(
+                f,
+                "faction {} is led by npc {} who does not exist",
+                key_to_string(*faction),
+                key_to_string(*npc)
+            ),
+            Self::Mount { link, mount } => write!(
+                f,
+                "mount link {} has mount npc {} which does not exist",
+                key_to_string(*link),
+                key_to_string(*mount)
+            ),
+            Self::Rider { link, rider } => write!(
+                f,
+                "mount link {} has rider npc {} who does not exist",
+                key_to_string(*link),
+                key_to_string(*rider)
+            ),
+            Self::Relative { npc, relative } => write!(
+                f,
+                "npc {} is related to npc {} who does not exist",
+                key_to_string(*npc),
+                key_to_string(*relative)
+            ),
+            Self::CaravanMerchant { caravan, merchant } => write!(
+                f,
+                "caravan {} is led by merchant npc {} who does not exist",
+                key_to_string(*caravan),
+                key_to_string(*merchant)
+            ),
+            Self::CaravanPackAnimal { caravan, animal } => write!(
+                f,
+                "caravan {} has pack animal npc {} which does not exist",
+                key_to_string(*caravan),
+                key_to_string(*animal)
+            ),
+        }
+    }
+}
+
+impl Data {
+    pub fn query_npcs<'a>(
+        &'a self,
+        filter: &'a NpcFilter,
+    ) -> impl Iterator<Item = (NpcId, &'a Npc)> + 'a {
+        self.npcs.iter().filter(|(_, npc)| filter.matches(npc))
+    }
+
+    /// Find every reference to an NPC, site or faction that does not exist.
+    pub fn validate_links(&self) -> Vec<LinkError> {
+        let mut errors = Vec::new();
+        for (npc_id, npc) in self.npcs.iter() {
+            if let Some(site) = npc.home
+                && !self.sites.contains_key(site)
+            {
+                errors.push(LinkError::NpcHome { npc: npc_id, site });
+            }
+            if let Some(faction) = npc.faction
+                && !self.factions.contains_key(faction)
+            {
+                errors.push(LinkError::NpcFaction {
+                    npc: npc_id,
+                    faction,
+                });
+            }
+            let family = &npc.family;
+            for relative in family
+                .partner
+                .iter()
+                .chain(&family.parents)
+                .chain(&family.children)
+            {
+                if !self.npcs.contains_key(*relative) {
+                    errors.push(LinkError::Relative {
+                        npc: npc_id,
+                        relative: *relative,
+                    });
+                }
+            }
+        }
+        for (site_id, site) in self.sites.iter() {
+            if let Some(faction) = site.faction
+                && !self.factions.contains_key(faction)
+            {
+                errors.push(LinkError::SiteFaction {
+                    site: site_id,
+                    faction,
+                });
+            }
+        }
+        for (faction_id, faction) in self.factions.iter() {
+            if let Some(Actor::Npc(npc)) = faction.leader
+                && !self.npcs.contains_key(npc)
+            {
+                errors.push(LinkError::FactionLeader {
+                    faction: faction_id,
+                    npc,
+                });
+            }
+        }
+        for link_id in self.npcs.mounts.ids() {
+            let Some(link) = self.npcs.mounts.get(link_id) else {
+                continue;
+            };
+            if !self.npcs.contains_key(link.mount) {
+                errors.push(LinkError::Mount {
+                    link: link_id,
+                    mount: link.mount,
+                });
+            }
+            if let Actor::Npc(rider) = link.rider
+                && !self.npcs.contains_key(rider)
+            {
+                errors.push(LinkError::Rider {
+                    link: link_id,
+                    rider,
+                });
+            }
+        }
+        for (caravan_id, caravan) in self.caravans.iter() {
+            if !self.npcs.contains_key(caravan.merchant) {
+                errors.push(LinkError::CaravanMerchant {
+                    caravan: caravan_id,
+                    merchant: caravan.merchant,
+                });
+            }
+            for animal in &caravan.pack_animals {
+                if !self.npcs.contains_key(*animal) {
+                    errors.push(LinkError::CaravanPackAnimal {
+                        caravan: caravan_id,
+                        animal: *animal,
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    /// Remove every reference found by [`Data::validate_links`], returning
+    /// the errors that were fixed.
+    pub fn fix_links(&mut self) -> Vec<LinkError> {
+        let errors = self.validate_links();
+        for error in &errors {
+            match *error {
+                LinkError::NpcHome { npc, .. } => self.npcs[npc].home = None,
+                LinkError::NpcFaction { npc, .. } => self.npcs[npc].faction = None,
+                LinkError::SiteFaction { site, .. } => self.sites[site].faction = None,
+                LinkError::FactionLeader { faction, .. } => self.factions[faction].leader = None,
+                LinkError::Mount { link, .. } | LinkError::Rider { link, .. } => {
+                    self.npcs.mounts.remove_link(link)
+                },
+                LinkError::Relative { npc, relative } => self.npcs[npc].family.forget(relative),
+                // Without the merchant, the goods never reach the buyer
+                LinkError::CaravanMerchant { caravan, .. } => {
+                    self.caravans.remove(caravan);
+                },
+                LinkError::CaravanPackAnimal { caravan, animal } => {
+                    if let Some(caravan) = self.caravans.get_mut(caravan) {
+                        caravan.pack_animals.retain(|other| *other != animal);
+                    }
+                },
+            }
+        }
+        errors
+    }
+
+    /// Remove an NPC along with everything that refers to it.
+    pub fn delete_npc(&mut self, id: NpcId) -> Option<Npc> {
+        let npc = self.npcs.remove(id)?;
+
+        let links = self
+            .npcs
+            .mounts
+            .ids()
+            .filter(|link_id| {
+                self.npcs.mounts.get(*link_id).map_or(false, |link| {
+                    link.mount == id || link.rider == Actor::Npc(id)
+                })
+            })
+            .collect::<Vec<_>>();
+        for link in links {
+            self.npcs.mounts.remove_link(link);
+        }
+        if let Some(home) = npc.home.and_then(|home| self.sites.get_mut(home)) {
+            home.population.remove(&id);
+        }
+        for faction in self.factions.values_mut() {
+            if faction.leader == Some(Actor::Npc(id)) {
+                faction.leader = None;
+            }
+            if let Some(raid) = &mut faction.raid {
+                raid.raiders.retain(|raider| *raider != id);
+            }
+        }
+        for other in self.npcs.values_mut() {
+            other.family.forget(id);
+        }
+        if let Some((caravan_id, caravan)) = self.caravans.caravan_of(id) {
+            if caravan.merchant == id {
+                self.caravans.remove(caravan_id);
+            } else {
+                self.caravans[caravan_id]
+                    .pack_animals
+                    .retain(|animal| *animal != id);
+            }
+        }
+
+        Some(npc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{
+        caravan::{Caravan, CaravanStage},
+        Site,
+    };
+    use common::resources::TimeOfDay;
+    use hashbrown::HashMap;
+
+    #[test]
+    fn keys_round_trip() {
+        let mut data = Data::empty();
+        let id = data.npcs.create_npc(Npc::test(Role::Wild));
+        assert_eq!(parse_key::<NpcId>(&key_to_string(id)), Ok(id));
+        assert_eq!(parse_link::<NpcId>("none"), Ok(None));
+        assert!(parse_key::<NpcId>("12").is_err());
+    }
+
+    #[test]
+    fn deleted_npcs_leave_no_broken_links() {
+        let mut data = Data::empty();
+        let site = data.sites.create(Site::test(Vec2::zero()));
+        let captain = data.spawn_npc(Npc::test(Role::Civilised(None)).with_home(site));
+        let ship = data.npcs.create_npc(Npc::test(Role::Vehicle));
+        data.npcs.mounts.steer(ship, captain).unwrap();
+
+        let filter = NpcFilter {
+            home: Some(site),
+            ..NpcFilter::default()
+        };
+        assert_eq!(data.query_npcs(&filter).count(), 1);
+
+        data.delete_npc(ship).unwrap();
+        assert_eq!(data.validate_links(), Vec::new());
+        assert!(data.npcs.mounts.get_mount_link(captain).is_none());
+
+        data.sites.remove(site);
+        assert_eq!(data.validate_links(), vec![LinkError::NpcHome {
+            npc: captain,
+            site
+        }]);
+        data.fix_links();
+        assert!(data.validate_links().is_empty());
+    }
+
+    #[test]
+    fn relatives_and_caravans_are_linked() {
+        let mut data = Data::empty();
+        let site = data.sites.create(Site::test(Vec2::zero()));
+        let merchant = data
+            .npcs
+            .create_npc(Npc::test(Role::Civilised(Some(Profession::Merchant))));
+        let partner = data.npcs.create_npc(Npc::test(Role::Civilised(None)));
+        let donkey = data.npcs.create_npc(Npc::test(Role::PackAnimal));
+        data.npcs[merchant].family.partner = Some(partner);
+        data.npcs[partner].family.partner = Some(merchant);
+        let caravan = data.caravans.create(Caravan {
+            merchant,
+            pack_animals: vec![donkey],
+            from: site,
+            to: site,
+            stage: CaravanStage::Delivering,
+            started: TimeOfDay(0.0),
+            cargo: Vec::new(),
+            escort_sightings: HashMap::default(),
+            escort_checks: 0,
+            escorts: Vec::new(),
+        });
+
+        // Deleted NPCs are forgotten by their relatives and caravans
+        data.delete_npc(donkey).unwrap();
+        data.delete_npc(partner).unwrap();
+        assert!(data.caravans[caravan].pack_animals.is_empty());
+        assert_eq!(data.npcs[merchant].family.partner, None);
+        assert!(data.validate_links().is_empty());
+
+        // Links that were broken some other way are found and fixed
+        let ghost = data.npcs.create_npc(Npc::test(Role::Wild));
+        data.npcs.remove(ghost);
+        data.npcs[merchant].family.children.push(ghost);
+        data.caravans[caravan].pack_animals.push(ghost);
+        assert_eq!(data.validate_links(), vec![
+            LinkError::Relative {
+                npc: merchant,
+                relative: ghost
+            },
+            LinkError::CaravanPackAnimal {
+                caravan,
+                animal: ghost
+            },
+        ]);
+        data.fix_links();
+        assert!(data.validate_links().is_empty());
+
+        data.npcs.remove(merchant);
+        assert_eq!(data.validate_links(), vec![LinkError::CaravanMerchant {
+            caravan,
+            merchant
+        }]);
+        data.fix_links();
+        assert!(!data.caravans.contains_key(caravan));
+    }
+}
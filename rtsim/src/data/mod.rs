@@ -1,20 +1,26 @@
+pub mod bounty;
+pub mod caravan;
 pub mod faction;
+pub mod inspect;
 pub mod nature;
 pub mod npc;
 pub mod report;
 pub mod sentiment;
 pub mod site;
+pub mod version;
 
 pub use self::{
-    faction::{Faction, FactionId, Factions},
+    bounty::{Bounties, Crime},
+    caravan::{Caravan, CaravanId, CaravanStage, Caravans},
+    faction::{Faction, FactionId, Factions, Raid, Territory},
     nature::Nature,
     npc::{Npc, NpcId, Npcs},
-    report::{Report, ReportId, ReportKind, Reports},
+    report::{Report, ReportId, ReportKind, Reports, Rumours},
     sentiment::{Sentiment, Sentiments},
     site::{Site, SiteId, Sites},
 };
 
-use common::resources::TimeOfDay;
+use common::{resources::TimeOfDay, rtsim::Actor};
 use enum_map::{enum_map, EnumArray, EnumMap};
 use serde::{de, ser, Deserialize, Serialize};
 use std::{
@@ -23,13 +29,16 @@ use std::{
     io::{Read, Write},
     marker::PhantomData,
 };
+use tracing::warn;
+use world::site::economy::TradeInformation;
 
 /// The current version of rtsim data.
 ///
 /// Note that this number does *not* need incrementing on every change: most
 /// field removals/additions are fine. This number should only be incremented
-/// when we wish to perform a *hard purge* of rtsim data.
-pub const CURRENT_VERSION: u32 = 5;
+/// when the meaning of existing data changes, alongside a migration from the
+/// previous version (see [`version`]).
+pub const CURRENT_VERSION: u32 = 6;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Data {
@@ -46,11 +55,25 @@ pub struct Data {
     pub factions: Factions,
     #[serde(default)]
     pub reports: Reports,
+    #[serde(default)]
+    pub caravans: Caravans,
 
     #[serde(default)]
     pub tick: u64,
     #[serde(default)]
     pub time_of_day: TimeOfDay,
+    /// The length of a year, in in-game days. This is a server setting, so it
+    /// is kept up to date by the server rather than persisted.
+    #[serde(skip, default = "default_year_length")]
+    pub year_length: f64,
+
+    /// The time of day at which site economies were last simulated.
+    #[serde(default)]
+    pub economy_time_of_day: Option<TimeOfDay>,
+    /// Goods being traded between sites. These are not persisted: whatever is
+    /// in transit when the server stops is lost.
+    #[serde(skip)]
+    pub trade: TradeInformation,
 
     // If true, rtsim data will be ignored (and, hence, overwritten on next save) on load.
     #[serde(default)]
@@ -59,7 +82,7 @@ pub struct Data {
 
 pub enum ReadError {
     Load(rmp_serde::decode::Error),
-    // Preserve old data
+    // The data could not be migrated to the current version. Preserve old data
     VersionMismatch(Box<Data>),
 }
 
@@ -84,14 +107,37 @@ impl Data {
         id
     }
 
+    /// Pay off the bounty on an actor's head across the jurisdiction of a site,
+    /// returning what it was. The site's people stop treating the actor as an
+    /// enemy.
+    pub fn pay_bounty(&mut self, site: SiteId, actor: Actor) -> u32 {
+        let bounty = self.sites.clear_bounty(site, actor);
+        let jurisdiction = self.sites.jurisdiction(site).collect::<Vec<_>>();
+        for npc in self
+            .npcs
+            .values_mut()
+            .filter(|npc| npc.home.map_or(false, |home| jurisdiction.contains(&home)))
+        {
+            npc.sentiments
+                .toward_mut(actor)
+                .limit_below(Sentiment::ENEMY);
+        }
+        bounty
+    }
+
     pub fn from_reader<R: Read>(reader: R) -> Result<Box<Self>, ReadError> {
         rmp_serde::decode::from_read(reader)
             .map_err(ReadError::Load)
-            .and_then(|data: Data| {
-                if data.version == CURRENT_VERSION {
-                    Ok(Box::new(data))
-                } else {
-                    Err(ReadError::VersionMismatch(Box::new(data)))
+            .and_then(|mut data: Data| {
+                let version = data.version;
+                match version::migrate(&mut data) {
+                    Ok(()) => Ok(Box::new(data)),
+                    Err(err) => {
+                        warn!("Rtsim data could not be migrated: {}", err);
+                        // Migrations may have been partially applied
+                        data.version = version;
+                        Err(ReadError::VersionMismatch(Box::new(data)))
+                    },
                 }
             })
     }
@@ -99,8 +145,30 @@ impl Data {
     pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), WriteError> {
         rmp_serde::encode::write_named(&mut writer, self)
     }
+
+    /// Data for a tiny world with nothing in it.
+    #[cfg(test)]
+    pub(crate) fn empty() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            nature: Nature::new(vek::Vec2::new(4, 4)),
+            npcs: Default::default(),
+            sites: Default::default(),
+            factions: Default::default(),
+            reports: Default::default(),
+            caravans: Default::default(),
+            tick: 0,
+            time_of_day: TimeOfDay(0.0),
+            year_length: default_year_length(),
+            economy_time_of_day: None,
+            trade: Default::default(),
+            should_purge: false,
+        }
+    }
 }
 
+fn default_year_length() -> f64 { common::consts::YEAR_LENGTH_DEFAULT }
+
 fn rugged_ser_enum_map<
     K: EnumArray<V> + Serialize,
     V: From<i16> + PartialEq + Serialize,
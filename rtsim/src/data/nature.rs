@@ -1,4 +1,7 @@
-use common::{grid::Grid, rtsim::ChunkResource};
+use common::{
+    grid::Grid,
+    rtsim::{ChunkResource, WildlifeGroup},
+};
 use enum_map::EnumMap;
 use serde::{Deserialize, Serialize};
 use vek::*;
@@ -13,14 +16,48 @@ use world::World;
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Nature {
     chunks: Grid<Chunk>,
+    /// Wildlife populations, per region of [`WILDLIFE_REGION_CHUNKS`] chunks
+    /// across.
+    #[serde(default)]
+    wildlife: Grid<Wildlife>,
 }
 
+/// The width, in chunks, of the regions that wildlife populations are tracked
+/// over.
+pub const WILDLIFE_REGION_CHUNKS: i32 = 16;
+
 impl Nature {
-    pub fn generate(world: &World) -> Self {
+    pub fn generate(world: &World) -> Self { Self::new(world.sim().get_size().map(|e| e as i32)) }
+
+    /// Nature of a world of the given size (in chunks) that has not yet been
+    /// touched.
+    pub fn new(size: Vec2<i32>) -> Self {
         Self {
-            chunks: Grid::populate_from(world.sim().get_size().map(|e| e as i32), |_| Chunk {
+            chunks: Grid::populate_from(size, |_| Chunk {
                 res: EnumMap::<_, f32>::default().map(|_, _| 1.0),
             }),
+            wildlife: Self::untouched_wildlife(size),
+        }
+    }
+
+    fn untouched_wildlife(size: Vec2<i32>) -> Grid<Wildlife> {
+        Grid::populate_from(
+            size.map(|e| (e + WILDLIFE_REGION_CHUNKS - 1) / WILDLIFE_REGION_CHUNKS),
+            |_| Wildlife {
+                pop: EnumMap::from_fn(Wildlife::capacity),
+            },
+        )
+    }
+
+    /// Populate wildlife at its natural levels if it's not yet being tracked,
+    /// for example because the data was saved before wildlife existed.
+    pub fn init_wildlife(&mut self) {
+        let regions = self
+            .chunks
+            .size()
+            .map(|e| (e + WILDLIFE_REGION_CHUNKS - 1) / WILDLIFE_REGION_CHUNKS);
+        if self.wildlife.size() != regions {
+            self.wildlife = Self::untouched_wildlife(self.chunks.size());
         }
     }
 
@@ -34,6 +71,63 @@ impl Nature {
             chunk.res = res;
         }
     }
+
+    /// The region containing the given chunk.
+    pub fn wildlife_region(key: Vec2<i32>) -> Vec2<i32> {
+        key.map(|e| e.div_euclid(WILDLIFE_REGION_CHUNKS))
+    }
+
+    pub fn wildlife(&self) -> &Grid<Wildlife> { &self.wildlife }
+
+    pub fn wildlife_mut(&mut self) -> &mut Grid<Wildlife> { &mut self.wildlife }
+
+    /// How plentiful each group of wildlife is in the region around a chunk,
+    /// relative to how plentiful it would be had the world been left alone.
+    pub fn get_chunk_wildlife(&self, key: Vec2<i32>) -> EnumMap<WildlifeGroup, f32> {
+        self.wildlife
+            .get(Self::wildlife_region(key))
+            .map(|w| w.abundance())
+            .unwrap_or_else(|| EnumMap::from_fn(|_| 1.0))
+    }
+
+    /// Remove a creature that has been killed from the population of the
+    /// region around a chunk.
+    pub fn kill_wildlife(&mut self, key: Vec2<i32>, group: WildlifeGroup) {
+        if let Some(wildlife) = self.wildlife.get_mut(Self::wildlife_region(key)) {
+            wildlife.pop[group] = (wildlife.pop[group] - 1.0).max(0.0);
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Wildlife {
+    /// The number of creatures of each group living in the region.
+    ///
+    /// These aren't the creatures that are loaded in the world at any one
+    /// time, they're the population that spawned creatures are drawn from.
+    #[serde(rename = "p")]
+    #[serde(serialize_with = "crate::data::rugged_ser_enum_map::<_, _, _, 0>")]
+    #[serde(deserialize_with = "crate::data::rugged_de_enum_map::<_, _, _, 0>")]
+    pub pop: EnumMap<WildlifeGroup, f32>,
+}
+
+impl Wildlife {
+    /// The number of creatures of a group that a region can support when left
+    /// alone.
+    // TODO: Derive this from the terrain of the region rather than treating all
+    // regions the same.
+    pub fn capacity(group: WildlifeGroup) -> f32 {
+        match group {
+            WildlifeGroup::Prey => 120.0,
+            WildlifeGroup::Predator => 30.0,
+            WildlifeGroup::Aquatic => 80.0,
+        }
+    }
+
+    /// The population of each group, relative to the region's capacity.
+    pub fn abundance(&self) -> EnumMap<WildlifeGroup, f32> {
+        self.pop.map(|group, pop| pop / Self::capacity(group))
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
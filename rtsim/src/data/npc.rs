@@ -1,6 +1,6 @@
 use crate::{
     ai::Action,
-    data::{Reports, Sentiments},
+    data::{Reports, Rumours, Sentiments},
     gen::name,
 };
 pub use common::rtsim::{NpcId, Profession};
@@ -8,6 +8,7 @@ use common::{
     character::CharacterId,
     comp,
     grid::Grid,
+    resources::TimeOfDay,
     rtsim::{
         Actor, ChunkResource, FactionId, NpcAction, NpcActivity, NpcInput, Personality, ReportId,
         Role, SiteId,
@@ -28,6 +29,7 @@ use tracing::error;
 use vek::*;
 use world::{
     civ::Track,
+    layer::cave::CaveRoute,
     site::Site as WorldSite,
     util::{RandomPerm, LOCALITY},
 };
@@ -60,6 +62,8 @@ pub struct Controller {
     pub activity: Option<NpcActivity>,
     pub new_home: Option<SiteId>,
     pub look_dir: Option<Dir>,
+    /// Reports that the NPC has told other NPCs about.
+    pub gossip: Vec<(NpcId, ReportId)>,
 }
 
 impl Controller {
@@ -92,6 +96,8 @@ impl Controller {
     }
 
     pub fn set_new_home(&mut self, new_home: SiteId) { self.new_home = Some(new_home); }
+
+    pub fn tell(&mut self, target: NpcId, report: ReportId) { self.gossip.push((target, report)); }
 }
 
 pub struct Brain {
@@ -115,12 +121,21 @@ pub struct Npc {
 
     /// The [`Report`]s that the NPC is aware of.
     pub known_reports: HashSet<ReportId>,
+    /// How much the NPC believes the reports that it heard second-hand.
+    #[serde(default)]
+    pub rumours: Rumours,
 
     #[serde(default)]
     pub personality: Personality,
     #[serde(default)]
     pub sentiments: Sentiments,
 
+    /// When the NPC was born, if they age.
+    #[serde(default)]
+    pub born: Option<TimeOfDay>,
+    #[serde(default)]
+    pub family: Family,
+
     // Unpersisted state
     #[serde(skip)]
     pub chunk_pos: Option<Vec2<i32>>,
@@ -141,6 +156,10 @@ pub struct Npc {
 
     #[serde(skip)]
     pub brain: Option<Brain>,
+    /// The route through the caves that the NPC is following while loaded, so
+    /// that it doesn't need to be found again every tick
+    #[serde(skip)]
+    pub cave_route: Option<CaveRoute>,
 }
 
 impl Clone for Npc {
@@ -155,9 +174,12 @@ impl Clone for Npc {
             faction: self.faction,
             is_dead: self.is_dead,
             known_reports: self.known_reports.clone(),
+            rumours: self.rumours.clone(),
             body: self.body,
             personality: self.personality,
             sentiments: self.sentiments.clone(),
+            born: self.born,
+            family: self.family.clone(),
             // Not persisted
             chunk_pos: None,
             current_site: Default::default(),
@@ -165,13 +187,18 @@ impl Clone for Npc {
             inbox: Default::default(),
             mode: Default::default(),
             brain: Default::default(),
+            cave_route: None,
         }
     }
 }
 
 impl Npc {
     pub const PERM_ENTITY_CONFIG: u32 = 1;
+    pub const PERM_HOME_PLOT: u32 = 3;
+    const PERM_LIFESPAN: u32 = 5;
     const PERM_NAME: u32 = 0;
+    pub const PERM_SCHEDULE: u32 = 2;
+    pub const PERM_WORKPLACE: u32 = 4;
 
     pub fn new(seed: u32, wpos: Vec3<f32>, body: comp::Body, role: Role) -> Self {
         Self {
@@ -183,20 +210,36 @@ impl Npc {
             body,
             personality: Default::default(),
             sentiments: Default::default(),
+            born: None,
+            family: Default::default(),
             role,
             home: None,
             faction: None,
             is_dead: false,
             known_reports: Default::default(),
+            rumours: Default::default(),
             chunk_pos: None,
             current_site: None,
             controller: Default::default(),
             inbox: Default::default(),
             mode: SimulationMode::Simulated,
             brain: None,
+            cave_route: None,
         }
     }
 
+    /// A humanoid NPC with the given role that isn't linked to the world, for
+    /// tests.
+    #[cfg(test)]
+    pub(crate) fn test(role: Role) -> Self {
+        Self::new(
+            0,
+            Vec3::zero(),
+            comp::Body::Humanoid(comp::humanoid::Body::random()),
+            role,
+        )
+    }
+
     // TODO: have a dedicated `NpcBuilder` type for this.
     pub fn with_personality(mut self, personality: Personality) -> Self {
         self.personality = personality;
@@ -225,6 +268,12 @@ impl Npc {
         self
     }
 
+    // TODO: have a dedicated `NpcBuilder` type for this.
+    pub fn with_birth(mut self, born: TimeOfDay) -> Self {
+        self.born = Some(born);
+        self
+    }
+
     pub fn rng(&self, perm: u32) -> impl Rng { RandomPerm::new(self.seed.wrapping_add(perm)) }
 
     // TODO: Don't make this depend on deterministic RNG, actually persist names
@@ -234,10 +283,68 @@ impl Npc {
     pub fn profession(&self) -> Option<Profession> {
         match &self.role {
             Role::Civilised(profession) => profession.clone(),
-            Role::Monster | Role::Wild | Role::Vehicle => None,
+            Role::Monster | Role::Wild | Role::Vehicle | Role::PackAnimal => None,
         }
     }
 
+    /// Whether the NPC lives in a site for the long term, and so takes part in
+    /// its population growing old, having children and dying.
+    pub fn is_settler(&self) -> bool {
+        self.home.is_some()
+            && matches!(self.body, comp::Body::Humanoid(_))
+            && matches!(
+                self.profession(),
+                Some(
+                    Profession::Farmer
+                        | Profession::Hunter
+                        | Profession::Guard
+                        | Profession::Blacksmith
+                        | Profession::Chef
+                        | Profession::Alchemist
+                        | Profession::Herbalist
+                        | Profession::Miner
+                )
+            )
+    }
+
+    /// Whether the NPC is an animal that carries goods for merchants' caravans.
+    pub fn is_pack_animal(&self) -> bool { matches!(self.role, Role::PackAnimal) }
+
+    /// The seed that decides which house the NPC lives in. Members of a
+    /// household share the same house seed.
+    pub fn house_seed(&self) -> u32 {
+        self.family
+            .house
+            .unwrap_or_else(|| self.seed.wrapping_add(Self::PERM_HOME_PLOT))
+    }
+
+    /// How old the NPC is, in years, if they age.
+    pub fn age(&self, time_of_day: TimeOfDay) -> Option<f32> {
+        self.born
+            .map(|born| ((time_of_day.0 - born.0) / YEAR_OF_AGING).max(0.0) as f32)
+    }
+
+    /// NPCs that don't age are always adults.
+    pub fn is_adult(&self, time_of_day: TimeOfDay) -> bool {
+        self.age(time_of_day).map_or(true, |age| age >= ADULT_AGE)
+    }
+
+    /// Children are too young to work, and the elderly have retired.
+    pub fn is_working_age(&self, time_of_day: TimeOfDay) -> bool {
+        self.age(time_of_day)
+            .map_or(true, |age| (ADULT_AGE..RETIREMENT_AGE).contains(&age))
+    }
+
+    /// How many years the NPC will live for before dying of old age.
+    pub fn lifespan(&self) -> f32 { self.rng(Self::PERM_LIFESPAN).gen_range(60.0..90.0) }
+
+    /// Children are smaller than adults, growing to full size as they come of
+    /// age.
+    pub fn body_scale(&self, time_of_day: TimeOfDay) -> f32 {
+        self.age(time_of_day)
+            .map_or(1.0, |age| 0.5 + 0.5 * (age / ADULT_AGE).min(1.0))
+    }
+
     pub fn cleanup(&mut self, reports: &Reports) {
         // Clear old or superfluous sentiments
         // TODO: It might be worth giving more important NPCs a higher sentiment
@@ -247,9 +354,64 @@ impl Npc {
         // Clear reports that have been forgotten
         self.known_reports
             .retain(|report| reports.contains_key(*report));
+        self.rumours.cleanup(reports);
         // TODO: Limit number of reports
         // TODO: Clear old inbox items
     }
+
+    /// Hear of a report second-hand, returning `true` if the report is news to
+    /// the NPC.
+    pub fn hear_report(&mut self, report: ReportId, credibility: f32) -> bool {
+        if self.known_reports.contains(&report) {
+            // Hearsay can't make us doubt what we saw with our own eyes
+            if self.rumours.is_rumour(report) {
+                self.rumours.hear(report, credibility);
+            }
+            false
+        } else {
+            let is_news = !self.rumours.is_rumour(report);
+            self.rumours.hear(report, credibility);
+            is_news
+        }
+    }
+}
+
+/// How long it takes NPCs to age by a year, in seconds of [`TimeOfDay`]. This
+/// is a rate of aging, not the length of the calendar year
+/// ([`Data::year_length`](super::Data::year_length)): NPCs age much faster than
+/// the seasons turn so that they live through several generations over the life
+/// of a server.
+pub const YEAR_OF_AGING: f64 = 10.0 * 24.0 * 3600.0;
+/// The age, in years, at which NPCs become adults.
+pub const ADULT_AGE: f32 = 16.0;
+/// The age, in years, at which NPCs stop working.
+pub const RETIREMENT_AGE: f32 = 60.0;
+
+/// The relatives of an NPC.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Family {
+    pub partner: Option<NpcId>,
+    pub parents: Vec<NpcId>,
+    pub children: Vec<NpcId>,
+    /// The house seed of the household that the NPC has moved into, if they
+    /// don't live in a house of their own.
+    pub house: Option<u32>,
+}
+
+impl Family {
+    /// Whether the other NPC is a close relative.
+    pub fn is_related_to(&self, other: NpcId) -> bool {
+        self.parents.contains(&other) || self.children.contains(&other)
+    }
+
+    /// Remove every mention of an NPC, such as one that no longer exists.
+    pub fn forget(&mut self, other: NpcId) {
+        if self.partner == Some(other) {
+            self.partner = None;
+        }
+        self.parents.retain(|parent| *parent != other);
+        self.children.retain(|child| *child != other);
+    }
 }
 
 #[derive(Default, Clone, Serialize, Deserialize)]
@@ -550,6 +712,15 @@ impl Npcs {
                     .flatten(),
             )
     }
+
+    /// The position of a player character, if they're in the world.
+    pub fn character_wpos(&self, character: CharacterId) -> Option<Vec3<f32>> {
+        self.character_map
+            .values()
+            .flatten()
+            .find(|(c, _)| *c == character)
+            .map(|(_, wpos)| *wpos)
+    }
 }
 
 impl Deref for Npcs {
@@ -1,4 +1,9 @@
-use common::{resources::TimeOfDay, rtsim::Actor};
+use common::{
+    comp,
+    resources::TimeOfDay,
+    rtsim::{Actor, FactionId, NpcId, SiteId},
+};
+use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use slotmap::HopSlotMap;
 use std::ops::Deref;
@@ -37,13 +42,109 @@ impl Report {
                     DAYS * 5.0
                 }
             },
+            ReportKind::Raid { captured, .. } => {
+                if *captured {
+                    // People remember who rules them for a long time
+                    DAYS * 30.0
+                } else {
+                    DAYS * 10.0
+                }
+            },
+            ReportKind::Theft { .. } => DAYS * 7.0,
+            ReportKind::Assault { .. } => DAYS * 3.0,
+            // Monsters move around and trade deals are old news quickly
+            ReportKind::Trade { .. } | ReportKind::MonsterSighting { .. } => DAYS * 2.0,
+            // Tales of heroism get told for a long time
+            ReportKind::Heroics { .. } => DAYS * 20.0,
         }
     }
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum ReportKind {
-    Death { actor: Actor, killer: Option<Actor> },
+    Death {
+        actor: Actor,
+        killer: Option<Actor>,
+    },
+    /// A faction sent raiders to take a site from another faction.
+    Raid {
+        site: SiteId,
+        attacker: FactionId,
+        defender: Option<FactionId>,
+        captured: bool,
+    },
+    /// Somebody took things that didn't belong to them.
+    Theft {
+        thief: Actor,
+        site: Option<SiteId>,
+    },
+    /// Somebody attacked somebody else, without necessarily killing them.
+    Assault {
+        attacker: Actor,
+        victim: Actor,
+    },
+    /// A trader did business with a site.
+    Trade {
+        site: SiteId,
+        customer: Option<Actor>,
+    },
+    /// A monster was seen roaming the land.
+    MonsterSighting {
+        monster: NpcId,
+        body: comp::Body,
+        wpos: Vec2<f32>,
+    },
+    /// Somebody slew a monster.
+    Heroics {
+        hero: Actor,
+        foe: comp::Body,
+    },
+}
+
+/// How much the holder of some reports believes them.
+///
+/// Reports witnessed first-hand are entirely credible. Each time a report is
+/// passed on from one holder to another, it becomes a little less credible.
+/// Only reports that were heard second-hand are tracked here: any other report
+/// is assumed to have been witnessed first-hand.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Rumours {
+    credibility: HashMap<ReportId, f32>,
+}
+
+impl Rumours {
+    /// Reports with less credibility than this aren't worth passing on.
+    pub const MIN_CREDIBILITY: f32 = 0.2;
+    /// The proportion of credibility that a report keeps when passed on.
+    pub const SPREAD_FACTOR: f32 = 0.7;
+
+    /// How credible a known report is, between 0 and 1.
+    pub fn credibility(&self, report: ReportId) -> f32 {
+        self.credibility.get(&report).copied().unwrap_or(1.0)
+    }
+
+    /// Whether the report was heard second-hand.
+    pub fn is_rumour(&self, report: ReportId) -> bool { self.credibility.contains_key(&report) }
+
+    /// The credibility that a known report would have if passed on to
+    /// somebody else, if it's worth passing on at all.
+    pub fn pass_on(&self, report: ReportId) -> Option<f32> {
+        Some(self.credibility(report) * Self::SPREAD_FACTOR)
+            .filter(|credibility| *credibility >= Self::MIN_CREDIBILITY)
+    }
+
+    /// Hear a report second-hand. Hearing the same report from a more credible
+    /// source makes it more believable.
+    pub fn hear(&mut self, report: ReportId, credibility: f32) {
+        let existing = self.credibility.entry(report).or_insert(0.0);
+        *existing = existing.max(credibility);
+    }
+
+    pub fn cleanup(&mut self, reports: &Reports) {
+        // Forget rumours about reports that have been forgotten
+        self.credibility
+            .retain(|report, _| reports.contains_key(*report));
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
@@ -67,3 +168,50 @@ impl Deref for Reports {
 
     fn deref(&self) -> &Self::Target { &self.reports }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rumours_lose_credibility_as_they_spread() {
+        let mut reports = Reports::default();
+        let report = reports.create(Report {
+            kind: ReportKind::Trade {
+                site: SiteId::default(),
+                customer: None,
+            },
+            at: TimeOfDay(0.0),
+        });
+
+        // Whoever saw the report happen believes it entirely
+        let mut witness = Rumours::default();
+        assert_eq!(witness.credibility(report), 1.0);
+        assert!(!witness.is_rumour(report));
+
+        // Each retelling makes the report less credible, until it's not worth
+        // passing on any more
+        let mut teller = witness.clone();
+        let mut retellings = 0;
+        while let Some(credibility) = teller.pass_on(report) {
+            let mut listener = Rumours::default();
+            listener.hear(report, credibility);
+            assert!(listener.credibility(report) < teller.credibility(report));
+            teller = listener;
+            retellings += 1;
+        }
+        assert!(retellings > 1);
+        assert!(teller.credibility(report) >= Rumours::MIN_CREDIBILITY);
+
+        // Hearing a report from a more credible source makes it more believable
+        witness.hear(report, 0.3);
+        witness.hear(report, 0.5);
+        witness.hear(report, 0.4);
+        assert_eq!(witness.credibility(report), 0.5);
+
+        // Forgotten reports are forgotten as rumours too
+        reports.cleanup(TimeOfDay(60.0 * 60.0 * 24.0 * 30.0));
+        witness.cleanup(&reports);
+        assert!(!witness.is_rumour(report));
+    }
+}
@@ -1,15 +1,16 @@
-use crate::data::{ReportId, Reports};
+use crate::data::{Bounties, ReportId, Reports, Rumours};
 pub use common::rtsim::SiteId;
 use common::{
-    rtsim::{FactionId, NpcId},
+    rtsim::{Actor, FactionId, NpcId},
     store::Id,
+    weather::Weather,
 };
 use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use slotmap::HopSlotMap;
 use std::ops::{Deref, DerefMut};
 use vek::*;
-use world::site::Site as WorldSite;
+use world::site::{economy::Economy, Site as WorldSite};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Site {
@@ -20,6 +21,21 @@ pub struct Site {
     /// The [`Report`]s that the site tracks (you can imagine them being on a
     /// noticeboard or something).
     pub known_reports: HashSet<ReportId>,
+    /// How much the site believes the reports that travellers brought to it.
+    #[serde(default)]
+    pub rumours: Rumours,
+    /// The bounties that the site has put on the heads of those who broke its
+    /// laws.
+    #[serde(default)]
+    pub bounties: Bounties,
+
+    /// The economy of the site, for sites that take part in the economic
+    /// simulation.
+    ///
+    /// Only the parts of the economy that change over time are persisted, the
+    /// rest is restored from the world site when rtsim is set up.
+    #[serde(default)]
+    pub economy: Option<Economy>,
 
     /// The site generated during initial worldgen that this site corresponds
     /// to.
@@ -47,9 +63,31 @@ pub struct Site {
     /// 'important' to the current one
     #[serde(skip_serializing, skip_deserializing)]
     pub nearby_sites_by_size: Vec<SiteId>,
+
+    /// The current weather at the site, if the server is simulating weather.
+    #[serde(skip_serializing, skip_deserializing)]
+    pub weather: Option<Weather>,
 }
 
 impl Site {
+    /// A site at the given position that isn't linked to the world, for tests.
+    #[cfg(test)]
+    pub(crate) fn test(wpos: Vec2<i32>) -> Self {
+        Self {
+            seed: 0,
+            wpos,
+            faction: None,
+            known_reports: Default::default(),
+            rumours: Default::default(),
+            bounties: Default::default(),
+            economy: None,
+            world_site: None,
+            population: Default::default(),
+            nearby_sites_by_size: Vec::new(),
+            weather: None,
+        }
+    }
+
     pub fn with_faction(mut self, faction: impl Into<Option<FactionId>>) -> Self {
         self.faction = faction.into();
         self
@@ -59,8 +97,16 @@ impl Site {
         // Clear reports that have been forgotten
         self.known_reports
             .retain(|report| reports.contains_key(*report));
+        self.rumours.cleanup(reports);
         // TODO: Limit number of reports
     }
+
+    /// Hear of a report second-hand, from a traveller or a resident.
+    pub fn hear_report(&mut self, report: ReportId, credibility: f32) {
+        if self.known_reports.insert(report) || self.rumours.is_rumour(report) {
+            self.rumours.hear(report, credibility);
+        }
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
@@ -80,6 +126,40 @@ impl Sites {
         }
         key
     }
+
+    /// The sites that uphold the same laws as the given site: those that belong
+    /// to the same faction, or just the site itself if it has no faction.
+    pub fn jurisdiction(&self, site: SiteId) -> impl Iterator<Item = SiteId> + '_ {
+        let faction = self.sites.get(site).and_then(|site| site.faction);
+        self.sites
+            .iter()
+            .filter(move |(id, other)| {
+                *id == site || (faction.is_some() && other.faction == faction)
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// The bounty on an actor's head across the jurisdiction of a site.
+    pub fn bounty_on(&self, site: SiteId, actor: Actor) -> u32 {
+        self.jurisdiction(site)
+            .filter_map(|site| self.sites.get(site))
+            .map(|site| site.bounties.on(actor))
+            .fold(0, u32::saturating_add)
+    }
+
+    /// Clear the bounty on an actor's head across the jurisdiction of a site,
+    /// returning what it was.
+    pub fn clear_bounty(&mut self, site: SiteId, actor: Actor) -> u32 {
+        self.jurisdiction(site)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|site| {
+                self.sites
+                    .get_mut(site)
+                    .map_or(0, |site| site.bounties.clear(actor))
+            })
+            .fold(0, u32::saturating_add)
+    }
 }
 
 impl Deref for Sites {
@@ -0,0 +1,198 @@
+//! Migrations between versions of rtsim data.
+//!
+//! Most changes to rtsim data don't need a migration: new fields can be given a
+//! `#[serde(default)]` and removed fields are ignored. When the meaning of
+//! existing data changes, [`CURRENT_VERSION`] should be incremented and a
+//! migration from the previous version added to [`MIGRATIONS`]. Older data is
+//! then upgraded one version at a time when it is loaded.
+//!
+//! Data older than [`OLDEST_SUPPORTED_VERSION`] (or newer than
+//! [`CURRENT_VERSION`]) cannot be upgraded and gets purged.
+
+use super::{Data, CURRENT_VERSION};
+use common::{comp, rtsim::Role};
+use std::fmt;
+use tracing::info;
+
+/// The oldest version of rtsim data that can be migrated to
+/// [`CURRENT_VERSION`].
+pub const OLDEST_SUPPORTED_VERSION: u32 = 5;
+
+/// A migration that takes data of one version to the next.
+type Migration = fn(&mut Data) -> Result<(), MigrationError>;
+
+/// Migrations, indexed by the version that they migrate *from*, starting at
+/// [`OLDEST_SUPPORTED_VERSION`].
+const MIGRATIONS: [Migration; (CURRENT_VERSION - OLDEST_SUPPORTED_VERSION) as usize] = [v5_to_v6];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The data is from a version that we have no migrations for.
+    Unsupported(u32),
+    /// A migration was attempted but the data could not be made sense of.
+    Invalid { from: u32, reason: String },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsupported(version) => write!(
+                f,
+                "version {version} cannot be migrated, only versions {OLDEST_SUPPORTED_VERSION} \
+                 to {CURRENT_VERSION} are supported"
+            ),
+            Self::Invalid { from, reason } => {
+                write!(f, "migration from version {from} failed: {reason}")
+            },
+        }
+    }
+}
+
+/// Upgrade the data to [`CURRENT_VERSION`], one version at a time.
+pub fn migrate(data: &mut Data) -> Result<(), MigrationError> {
+    if !(OLDEST_SUPPORTED_VERSION..=CURRENT_VERSION).contains(&data.version) {
+        return Err(MigrationError::Unsupported(data.version));
+    }
+
+    while data.version < CURRENT_VERSION {
+        let from = data.version;
+        info!("Migrating rtsim data from version {} to {}", from, from + 1);
+        MIGRATIONS[(from - OLDEST_SUPPORTED_VERSION) as usize](data)?;
+        data.version = from + 1;
+    }
+
+    Ok(())
+}
+
+/// Up to version 5, [`Role::Vehicle`] and [`Role::Monster`] were serialized
+/// with the same name, so vehicles were loaded as monsters. Vehicles are the
+/// only NPCs with ship bodies, so we can tell them apart again.
+fn v5_to_v6(data: &mut Data) -> Result<(), MigrationError> {
+    for npc in data.npcs.values_mut() {
+        if matches!(npc.role, Role::Monster) && matches!(npc.body, comp::Body::Ship(_)) {
+            npc.role = Role::Vehicle;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Npc, ReadError};
+    use common::{comp::body, resources::TimeOfDay, rtsim::ChunkResource};
+    use vek::*;
+
+    /// Data written by current code but labelled as `version`, for checking
+    /// which versions are accepted. Real saves of older versions, for checking
+    /// that migrations work, are kept in `tests/samples`.
+    fn sample(version: u32, npcs: impl IntoIterator<Item = Npc>) -> Vec<u8> {
+        let mut data = Data {
+            version,
+            tick: 1000,
+            time_of_day: TimeOfDay(12345.0),
+            ..Data::empty()
+        };
+        for npc in npcs {
+            data.npcs.create_npc(npc);
+        }
+        let mut bytes = Vec::new();
+        data.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn airship() -> Npc {
+        Npc::new(
+            0,
+            Vec3::zero(),
+            comp::Body::Ship(body::ship::Body::DefaultAirship),
+            Role::Monster,
+        )
+    }
+
+    fn monster() -> Npc {
+        Npc::new(
+            1,
+            Vec3::zero(),
+            comp::Body::BipedLarge(body::biped_large::Body::random()),
+            Role::Monster,
+        )
+    }
+
+    #[test]
+    fn current_version_is_loaded_as_is() {
+        let data = Data::from_reader(&*sample(CURRENT_VERSION, [monster()])).unwrap();
+        assert_eq!(data.version, CURRENT_VERSION);
+        assert_eq!(data.npcs.len(), 1);
+        assert_eq!(data.tick, 1000);
+        // Not persisted, but must still be usable before the server sets it
+        assert_eq!(data.year_length, common::consts::YEAR_LENGTH_DEFAULT);
+    }
+
+    /// A save written by a server running version 5, with an airship (which
+    /// version 5 wrote with the same role as monsters), an ogre and one chunk
+    /// with depleted ore.
+    const V5_SAVE: &[u8] = include_bytes!("../../tests/samples/v5.dat");
+
+    #[test]
+    fn v5_save_is_migrated() {
+        let data = Data::from_reader(V5_SAVE).unwrap();
+        assert_eq!(data.version, CURRENT_VERSION);
+        assert_eq!(data.tick, 1000);
+        assert_eq!(data.time_of_day.0, 12345.0);
+        assert_eq!(
+            data.nature.get_chunk_resources(Vec2::new(1, 1))[ChunkResource::Ore],
+            0.25
+        );
+
+        let roles = data
+            .npcs
+            .values()
+            .map(|npc| (npc.seed, npc.body, npc.role.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(roles.len(), 2);
+        for (seed, npc_body, role) in roles {
+            match seed {
+                0 => {
+                    assert!(matches!(
+                        npc_body,
+                        comp::Body::Ship(body::ship::Body::DefaultAirship)
+                    ));
+                    assert!(matches!(role, Role::Vehicle));
+                },
+                _ => {
+                    assert!(matches!(npc_body, comp::Body::BipedLarge(_)));
+                    assert!(matches!(role, Role::Monster));
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn v5_to_v6_only_changes_ships() {
+        let mut data = Data {
+            version: 5,
+            ..Data::empty()
+        };
+        data.npcs.create_npc(airship());
+        data.npcs.create_npc(monster());
+        v5_to_v6(&mut data).unwrap();
+        for npc in data.npcs.values() {
+            match npc.seed {
+                0 => assert!(matches!(npc.role, Role::Vehicle)),
+                _ => assert!(matches!(npc.role, Role::Monster)),
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        for version in [0, OLDEST_SUPPORTED_VERSION - 1, CURRENT_VERSION + 1] {
+            match Data::from_reader(&*sample(version, [monster()])) {
+                Err(ReadError::VersionMismatch(data)) => assert_eq!(data.version, version),
+                Err(err) => panic!("version {version} failed to load: {err:?}"),
+                Ok(_) => panic!("version {version} was not rejected"),
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::{RtState, Rule};
 use common::{
     mounting::VolumePos,
     resources::{Time, TimeOfDay},
-    rtsim::{Actor, NpcId},
+    rtsim::{Actor, NpcId, SiteId, WildlifeGroup},
 };
 use vek::*;
 use world::{IndexRef, World};
@@ -35,9 +35,61 @@ pub struct OnDeath {
     pub actor: Actor,
     pub wpos: Option<Vec3<f32>>,
     pub killer: Option<Actor>,
+    pub cause: DeathCause,
 }
 impl Event for OnDeath {}
 
+/// Why an actor died.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    /// The actor was killed in the world, perhaps by `OnDeath::killer`.
+    Killed,
+    /// The actor died of old age.
+    OldAge,
+}
+
+/// An actor was hurt by another actor.
+#[derive(Clone)]
+pub struct OnHurt {
+    pub actor: Actor,
+    pub wpos: Option<Vec3<f32>>,
+    pub attacker: Actor,
+}
+impl Event for OnHurt {}
+
+/// An actor took the contents of a container, such as a chest.
+#[derive(Clone)]
+pub struct OnLootContainer {
+    pub actor: Actor,
+    pub wpos: Vec3<i32>,
+}
+impl Event for OnLootContainer {}
+
+/// An actor broke a block, such as by mining it.
+#[derive(Clone)]
+pub struct OnMineBlock {
+    pub actor: Actor,
+    pub wpos: Vec3<i32>,
+}
+impl Event for OnMineBlock {}
+
+/// Goods were exchanged with a site.
+#[derive(Clone)]
+pub struct OnTrade {
+    pub site: SiteId,
+    pub customer: Option<Actor>,
+}
+impl Event for OnTrade {}
+
+/// A wild creature that isn't simulated by rtsim was killed.
+#[derive(Clone)]
+pub struct OnWildlifeKilled {
+    pub group: WildlifeGroup,
+    pub wpos: Vec3<f32>,
+    pub killer: Option<Actor>,
+}
+impl Event for OnWildlifeKilled {}
+
 #[derive(Clone)]
 pub struct OnMountVolume {
     pub actor: Actor,
@@ -1,14 +1,94 @@
-use crate::data::Faction;
+use crate::data::{Faction, FactionId, Factions, Sentiment, Sites};
+use hashbrown::HashMap;
 use rand::prelude::*;
-use world::{IndexRef, World};
+use world::{site::SiteKind, IndexRef, World};
 
 impl Faction {
     pub fn generate(_world: &World, _index: IndexRef, rng: &mut impl Rng) -> Self {
         Self {
             seed: rng.gen(),
             leader: None,
-            good_or_evil: rng.gen(),
             sentiments: Default::default(),
+            raid: None,
+        }
+    }
+}
+
+/// Whether a kind of site is lived in by civilised folk (`Some(true)`), by
+/// wicked folk like cultists and bandits (`Some(false)`), or by nobody in
+/// particular (`None`).
+pub fn is_civilised(kind: &SiteKind) -> Option<bool> {
+    match kind {
+        SiteKind::Refactor(_)
+        | SiteKind::CliffTown(_)
+        | SiteKind::DesertCity(_)
+        | SiteKind::SavannahPit(_)
+        | SiteKind::CoastalTown(_) => Some(true),
+        SiteKind::Dungeon(_)
+        | SiteKind::ChapelSite(_)
+        | SiteKind::Terracotta(_)
+        | SiteKind::Gnarling(_)
+        | SiteKind::Cultist(_)
+        | SiteKind::PirateHideout(_)
+        | SiteKind::JungleRuin(_)
+        | SiteKind::RockCircle(_)
+        | SiteKind::TrollCave(_)
+        | SiteKind::Camp(_)
+        | SiteKind::Haniwa(_)
+        | SiteKind::Adlet(_)
+        | SiteKind::DwarvenMine(_) => Some(false),
+        SiteKind::Settlement(_)
+        | SiteKind::Castle(_)
+        | SiteKind::Tree(_)
+        | SiteKind::GiantTree(_)
+        | SiteKind::Bridge(_) => None,
+    }
+}
+
+impl Factions {
+    /// Give factions that have never had dealings with one-another a starting
+    /// opinion of each other, based on the kinds of sites that they own.
+    ///
+    /// Civilised and wicked factions despise each other, while factions of the
+    /// same kind start out somewhere between rivals and allies.
+    pub fn seed_relations(&mut self, sites: &Sites, index: IndexRef, rng: &mut impl Rng) {
+        let mut civilised_sites = HashMap::<FactionId, (u32, u32)>::new();
+        for site in sites.values() {
+            if let Some(faction) = site.faction
+                && let Some(civilised) = site
+                    .world_site
+                    .and_then(|ws| is_civilised(&index.sites.get(ws).kind))
+            {
+                let (civilised_count, total) = civilised_sites.entry(faction).or_default();
+                *civilised_count += civilised as u32;
+                *total += 1;
+            }
+        }
+        let civilised = |faction| {
+            civilised_sites
+                .get(&faction)
+                .map(|(civilised, total)| *civilised * 2 >= *total)
+        };
+
+        let ids = self.keys().collect::<Vec<_>>();
+        for &a in &ids {
+            for &b in &ids {
+                let (Some(a_civilised), Some(b_civilised)) = (civilised(a), civilised(b)) else {
+                    continue;
+                };
+                if a == b || self[a].sentiments.toward(b).value() != 0.0 {
+                    continue;
+                }
+                let opinion = if a_civilised == b_civilised {
+                    rng.gen_range(Sentiment::RIVAL..Sentiment::ALLY)
+                } else {
+                    rng.gen_range(Sentiment::VILLAIN..Sentiment::ENEMY - 0.05)
+                };
+                self[a]
+                    .sentiments
+                    .toward_mut(b)
+                    .change_by(opinion, opinion.abs());
+            }
         }
     }
 }
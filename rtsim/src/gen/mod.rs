@@ -10,6 +10,7 @@ use crate::data::{
 };
 use common::{
     comp::{self, Body},
+    consts::YEAR_LENGTH_DEFAULT,
     resources::TimeOfDay,
     rtsim::{Personality, Role, WorldSettings},
     terrain::{BiomeKind, CoordinateConversions, TerrainChunkSize},
@@ -35,9 +36,13 @@ impl Data {
             sites: Default::default(),
             factions: Default::default(),
             reports: Default::default(),
+            caravans: Default::default(),
 
             tick: 0,
             time_of_day: TimeOfDay(settings.start_time),
+            year_length: YEAR_LENGTH_DEFAULT,
+            economy_time_of_day: None,
+            trade: Default::default(),
             should_purge: false,
         };
 
@@ -50,23 +55,18 @@ impl Data {
                     .map2(TerrainChunkSize::RECT_SIZE, |e, sz| {
                         rng.gen_range(0..(e * sz) as i32)
                     });
-                (wpos, this.factions.create(faction))
+                // Half of the factions are made up of civilised folk, half of wicked folk
+                (wpos, this.factions.create(faction), rng.gen_bool(0.5))
             })
             .collect::<Vec<_>>();
         info!("Generated {} rtsim factions.", this.factions.len());
 
         // Register sites with rtsim
         for (world_site_id, _) in index.sites.iter() {
-            let site = Site::generate(
-                world_site_id,
-                world,
-                index,
-                &initial_factions,
-                &this.factions,
-                &mut rng,
-            );
+            let site = Site::generate(world_site_id, world, index, &initial_factions, &mut rng);
             this.sites.create(site);
         }
+        this.factions.seed_relations(&this.sites, index, &mut rng);
         info!(
             "Registering {} rtsim sites from world sites.",
             this.sites.len()
@@ -84,13 +84,9 @@ impl Data {
                 _ => None,
             })?)))
         {
-            let Some(good_or_evil) = site
-                .faction
-                .and_then(|f| this.factions.get(f))
-                .map(|f| f.good_or_evil)
-            else {
+            if site.faction.is_none() {
                 continue;
-            };
+            }
 
             let rand_wpos = |rng: &mut SmallRng, matches_plot: fn(&PlotKind) -> bool| {
                 let wpos2d = site2
@@ -126,58 +122,41 @@ impl Data {
                 )
             }) as _;
             let matches_plazas = (|kind: &PlotKind| matches!(kind, PlotKind::Plaza)) as _;
-            if good_or_evil {
-                for _ in 0..site2.plots().len() {
-                    this.npcs.create_npc(
-                        Npc::new(
-                            rng.gen(),
-                            rand_wpos(&mut rng, matches_buildings),
-                            random_humanoid(&mut rng),
-                            Role::Civilised(Some(match rng.gen_range(0..20) {
-                                0 => Profession::Hunter,
-                                1 => Profession::Blacksmith,
-                                2 => Profession::Chef,
-                                3 => Profession::Alchemist,
-                                5..=8 => Profession::Farmer,
-                                9..=10 => Profession::Herbalist,
-                                11..=16 => Profession::Guard,
-                                _ => Profession::Adventurer(rng.gen_range(0..=3)),
-                            })),
-                        )
-                        .with_faction(site.faction)
-                        .with_home(site_id)
-                        .with_personality(Personality::random(&mut rng)),
-                    );
-                }
-            } else {
-                for _ in 0..15 {
-                    this.npcs.create_npc(
-                        Npc::new(
-                            rng.gen(),
-                            rand_wpos(&mut rng, matches_buildings),
-                            random_humanoid(&mut rng),
-                            Role::Civilised(Some(Profession::Cultist)),
-                        )
-                        .with_personality(Personality::random_evil(&mut rng))
-                        .with_faction(site.faction)
-                        .with_home(site_id),
-                    );
-                }
+            for _ in 0..site2.plots().len() {
+                this.npcs.create_npc(
+                    Npc::new(
+                        rng.gen(),
+                        rand_wpos(&mut rng, matches_buildings),
+                        random_humanoid(&mut rng),
+                        Role::Civilised(Some(match rng.gen_range(0..20) {
+                            0 => Profession::Hunter,
+                            1 => Profession::Blacksmith,
+                            2 => Profession::Chef,
+                            3 => Profession::Alchemist,
+                            4 => Profession::Miner,
+                            5..=8 => Profession::Farmer,
+                            9..=10 => Profession::Herbalist,
+                            11..=16 => Profession::Guard,
+                            _ => Profession::Adventurer(rng.gen_range(0..=3)),
+                        })),
+                    )
+                    .with_faction(site.faction)
+                    .with_home(site_id)
+                    .with_personality(Personality::random(&mut rng)),
+                );
             }
             // Merchants
-            if good_or_evil {
-                for _ in 0..(site2.plots().len() / 6) + 1 {
-                    this.npcs.create_npc(
-                        Npc::new(
-                            rng.gen(),
-                            rand_wpos(&mut rng, matches_plazas),
-                            random_humanoid(&mut rng),
-                            Role::Civilised(Some(Profession::Merchant)),
-                        )
-                        .with_home(site_id)
-                        .with_personality(Personality::random_good(&mut rng)),
-                    );
-                }
+            for _ in 0..(site2.plots().len() / 6) + 1 {
+                this.npcs.create_npc(
+                    Npc::new(
+                        rng.gen(),
+                        rand_wpos(&mut rng, matches_plazas),
+                        random_humanoid(&mut rng),
+                        Role::Civilised(Some(Profession::Merchant)),
+                    )
+                    .with_home(site_id)
+                    .with_personality(Personality::random_good(&mut rng)),
+                );
             }
 
             for plot in site2
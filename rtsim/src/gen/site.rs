@@ -1,76 +1,49 @@
-use crate::data::{FactionId, Factions, Site};
+use super::faction::is_civilised;
+use crate::data::{FactionId, Site};
 use common::store::Id;
 use rand::prelude::*;
 use vek::*;
-use world::{
-    site::{Site as WorldSite, SiteKind},
-    IndexRef, World,
-};
+use world::{site::Site as WorldSite, IndexRef, World};
 
 impl Site {
+    /// `nearby_factions` are the factions that might own the site, along with
+    /// where they're based and whether they're civilised.
     pub fn generate(
         world_site_id: Id<WorldSite>,
         _world: &World,
         index: IndexRef,
-        nearby_factions: &[(Vec2<i32>, FactionId)],
-        factions: &Factions,
+        nearby_factions: &[(Vec2<i32>, FactionId, bool)],
         rng: &mut impl Rng,
     ) -> Self {
         let world_site = index.sites.get(world_site_id);
         let wpos = world_site.get_origin();
 
-        // TODO: This is stupid, do better
-        let good_or_evil = match &world_site.kind {
-            // Good
-            SiteKind::Refactor(_)
-            | SiteKind::CliffTown(_)
-            | SiteKind::DesertCity(_)
-            | SiteKind::SavannahPit(_)
-            | SiteKind::CoastalTown(_) => Some(true),
-            // Evil
-            SiteKind::Dungeon(_)
-            | SiteKind::ChapelSite(_)
-            | SiteKind::Terracotta(_)
-            | SiteKind::Gnarling(_)
-            | SiteKind::Cultist(_)
-            | SiteKind::PirateHideout(_)
-            | SiteKind::JungleRuin(_)
-            | SiteKind::RockCircle(_)
-            | SiteKind::TrollCave(_)
-            | SiteKind::Camp(_)
-            | SiteKind::Haniwa(_)
-            | SiteKind::Adlet(_) => Some(false),
-            SiteKind::DwarvenMine(_) => Some(false),
-            // Neutral
-            SiteKind::Settlement(_)
-            | SiteKind::Castle(_)
-            | SiteKind::Tree(_)
-            | SiteKind::GiantTree(_)
-            | SiteKind::Bridge(_) => None,
-        };
+        let civilised = is_civilised(&world_site.kind);
 
         Self {
             seed: rng.gen(),
             wpos,
             world_site: Some(world_site_id),
-            faction: good_or_evil.and_then(|good_or_evil| {
+            economy: world_site
+                .do_economic_simulation()
+                .then(|| world_site.economy.clone()),
+            faction: civilised.and_then(|civilised| {
                 nearby_factions
                     .iter()
-                    .filter(|(_, faction)| {
-                        factions
-                            .get(*faction)
-                            .map_or(false, |f| f.good_or_evil == good_or_evil)
-                    })
-                    .min_by_key(|(faction_wpos, _)| {
+                    .filter(|(_, _, faction_civilised)| *faction_civilised == civilised)
+                    .min_by_key(|(faction_wpos, _, _)| {
                         faction_wpos
                             .as_::<i64>()
                             .distance_squared(wpos.as_::<i64>())
                     })
-                    .map(|(_, faction)| *faction)
+                    .map(|(_, faction, _)| *faction)
             }),
             population: Default::default(),
             known_reports: Default::default(),
+            rumours: Default::default(),
+            bounties: Default::default(),
             nearby_sites_by_size: Vec::new(),
+            weather: None,
         }
     }
 }
@@ -0,0 +1,264 @@
+//! Runs rtsim without a server, so that the effect of rules on populations,
+//! sentiments and sites over long stretches of game time can be studied
+//! offline.
+//!
+//! Every NPC is simulated, since there are no players around to load them.
+
+use crate::{
+    data::{inspect::role_name, npc::SimulationMode, Data},
+    event::{DeathCause, EventCtx, OnDeath, OnSetup},
+    RtState, Rule, RuleError,
+};
+use common::{
+    consts::DAY_LENGTH_DEFAULT,
+    resources::{Time, TimeOfDay},
+    rtsim::Actor,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    ops::{Deref, DerefMut},
+};
+use world::{IndexRef, World};
+
+/// The length of a game day, in units of [`TimeOfDay`]
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+
+pub struct Config {
+    /// The real time that passes with each tick, in seconds. Larger steps are
+    /// faster to run but less faithful to a real server.
+    pub dt: f32,
+    /// How much faster game time passes than real time, as with the server's
+    /// `day_length` setting.
+    pub day_cycle_coefficient: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            dt: 1.0,
+            day_cycle_coefficient: 1440.0 / DAY_LENGTH_DEFAULT,
+        }
+    }
+}
+
+pub struct Headless<'a> {
+    state: RtState,
+    world: &'a World,
+    index: IndexRef<'a>,
+    config: Config,
+    time: Time,
+    start_time_of_day: f64,
+}
+
+impl<'a> Headless<'a> {
+    pub fn new(mut data: Data, world: &'a World, index: IndexRef<'a>, config: Config) -> Self {
+        for npc in data.npcs.values_mut() {
+            npc.mode = SimulationMode::Simulated;
+        }
+        let start_time_of_day = data.time_of_day.0;
+
+        let mut state = RtState::new(data).with_resource(Deaths::default());
+        state.start_rule::<RecordDeaths>();
+        state.emit(OnSetup, world, index);
+
+        Self {
+            state,
+            world,
+            index,
+            config,
+            time: Time(0.0),
+            start_time_of_day,
+        }
+    }
+
+    /// The number of game days that have passed since the start of the run
+    pub fn days(&self) -> f64 { (self.state.data().time_of_day.0 - self.start_time_of_day) / DAY }
+
+    pub fn tick(&mut self) {
+        let dt = self.config.dt;
+        self.time.0 += dt as f64;
+        let time_of_day = TimeOfDay(
+            self.state.data().time_of_day.0 + dt as f64 * self.config.day_cycle_coefficient,
+        );
+        self.state
+            .tick(self.world, self.index, time_of_day, self.time, dt);
+    }
+
+    /// Run until `days` game days have passed since the start of the run,
+    /// taking statistics every `interval` days
+    pub fn run(&mut self, days: f64, interval: f64, mut on_stats: impl FnMut(Stats)) {
+        let mut next_stats = self.days();
+        while self.days() < days {
+            if self.days() >= next_stats {
+                on_stats(self.stats());
+                next_stats += interval;
+            }
+            self.tick();
+        }
+        on_stats(self.stats());
+    }
+
+    pub fn stats(&self) -> Stats { Stats::collect(&self.state, self.index, self.days()) }
+
+    pub fn data(&self) -> impl Deref<Target = Data> + '_ { self.state.data() }
+
+    pub fn data_mut(&self) -> impl DerefMut<Target = Data> + '_ { self.state.data_mut() }
+}
+
+/// A snapshot of the simulation
+#[derive(Serialize)]
+pub struct Stats {
+    pub day: f64,
+    pub tick: u64,
+    /// The number of living NPCs by home site (or `"homeless"`), and then by
+    /// role
+    pub population: BTreeMap<String, BTreeMap<String, u32>>,
+    /// The number of NPCs that have died since the start of the run, by cause
+    pub deaths: BTreeMap<String, u32>,
+    /// How each faction feels about every other faction, from -1 to 1
+    pub faction_sentiment: BTreeMap<String, BTreeMap<String, f32>>,
+    /// The number of sites owned by each faction
+    pub faction_sites: BTreeMap<String, u32>,
+    /// The area controlled by each faction, in units of
+    /// [`crate::data::Territory::CELL_SIZE`] squared
+    pub faction_territory: BTreeMap<String, usize>,
+}
+
+impl Stats {
+    /// The header of the CSV written by [`Stats::write_csv_rows`]
+    pub const CSV_HEADER: &'static str = "day,tick,stat,subject,key,value";
+
+    fn collect(state: &RtState, index: IndexRef, day: f64) -> Self {
+        let data = state.data();
+
+        let mut population = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for npc in data.npcs.values().filter(|npc| !npc.is_dead) {
+            let site = npc
+                .home
+                .and_then(|home| data.sites.get(home))
+                .and_then(|site| site.world_site)
+                .map_or_else(
+                    || "homeless".to_string(),
+                    |site| index.sites.get(site).name().to_string(),
+                );
+            *population
+                .entry(site)
+                .or_default()
+                .entry(role_name(&npc.role))
+                .or_default() += 1;
+        }
+
+        let faction_sentiment = data
+            .factions
+            .iter()
+            .map(|(id, faction)| {
+                let towards = data
+                    .factions
+                    .keys()
+                    .filter(|other| *other != id)
+                    .map(|other| {
+                        (
+                            format!("{other:?}"),
+                            faction.sentiments.toward(other).value(),
+                        )
+                    })
+                    .collect();
+                (format!("{id:?}"), towards)
+            })
+            .collect();
+
+        let mut faction_sites = BTreeMap::new();
+        for faction in data.sites.values().filter_map(|site| site.faction) {
+            *faction_sites.entry(format!("{faction:?}")).or_default() += 1;
+        }
+
+        let faction_territory = data
+            .factions
+            .keys()
+            .map(|id| (format!("{id:?}"), data.factions.territory.area_of(id)))
+            .collect();
+
+        Self {
+            day,
+            tick: data.tick,
+            population,
+            deaths: state.resource::<Deaths>().0.clone(),
+            faction_sentiment,
+            faction_sites,
+            faction_territory,
+        }
+    }
+
+    /// Write the snapshot as CSV rows, one for each value
+    pub fn write_csv_rows(&self, out: &mut String) {
+        let mut row = |stat: &str, subject: &str, key: &str, value: &dyn std::fmt::Display| {
+            let _ = writeln!(
+                out,
+                "{:.3},{},{},{},{},{}",
+                self.day,
+                self.tick,
+                stat,
+                csv_escape(subject),
+                csv_escape(key),
+                value
+            );
+        };
+        for (site, roles) in &self.population {
+            for (role, count) in roles {
+                row("population", site, role, count);
+            }
+        }
+        for (cause, count) in &self.deaths {
+            row("deaths", "", cause, count);
+        }
+        for (faction, towards) in &self.faction_sentiment {
+            for (other, sentiment) in towards {
+                row("faction_sentiment", faction, other, sentiment);
+            }
+        }
+        for (faction, sites) in &self.faction_sites {
+            row("faction_sites", faction, "", sites);
+        }
+        for (faction, area) in &self.faction_territory {
+            row("faction_territory", faction, "", area);
+        }
+    }
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// The number of deaths so far, by cause
+#[derive(Default)]
+struct Deaths(BTreeMap<String, u32>);
+
+struct RecordDeaths;
+
+impl Rule for RecordDeaths {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnDeath>(on_death);
+
+        Ok(Self)
+    }
+}
+
+fn on_death(ctx: EventCtx<RecordDeaths, OnDeath>) {
+    let cause = match (ctx.event.cause, ctx.event.killer) {
+        (DeathCause::OldAge, _) => "old_age",
+        (DeathCause::Killed, Some(Actor::Npc(_))) => "killed_by_npc",
+        (DeathCause::Killed, Some(Actor::Character(_))) => "killed_by_player",
+        (DeathCause::Killed, None) => "other",
+    };
+    *ctx.state
+        .resource_mut::<Deaths>()
+        .0
+        .entry(cause.to_string())
+        .or_default() += 1;
+}
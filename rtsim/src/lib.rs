@@ -14,6 +14,7 @@ pub mod ai;
 pub mod data;
 pub mod event;
 pub mod gen;
+pub mod headless;
 pub mod rule;
 
 pub use self::{
@@ -35,10 +36,12 @@ pub struct RtState {
     resources: SendSyncAnyMap,
     rules: SendSyncAnyMap,
     event_handlers: SendSyncAnyMap,
+    deferred_events: AtomicRefCell<Vec<DeferredEvent>>,
 }
 
 type RuleState<R> = AtomicRefCell<R>;
 type EventHandlersOf<E> = Vec<Box<dyn Fn(&RtState, &World, IndexRef, &E) + Send + Sync + 'static>>;
+type DeferredEvent = Box<dyn FnOnce(&mut RtState, &World, IndexRef) + Send + Sync + 'static>;
 
 impl RtState {
     pub fn new(data: Data) -> Self {
@@ -46,8 +49,10 @@ impl RtState {
             resources: SendSyncAnyMap::new(),
             rules: SendSyncAnyMap::new(),
             event_handlers: SendSyncAnyMap::new(),
+            deferred_events: AtomicRefCell::new(Vec::new()),
         }
-        .with_resource(data);
+        .with_resource(data)
+        .with_resource(rule::report::AssaultCooldowns::default());
 
         this.start_default_rules();
 
@@ -63,7 +68,13 @@ impl RtState {
         info!("Starting default rtsim rules...");
         self.start_rule::<rule::migrate::Migrate>();
         self.start_rule::<rule::replenish_resources::ReplenishResources>();
+        self.start_rule::<rule::simulate_wildlife::SimulateWildlife>();
+        self.start_rule::<rule::simulate_economy::SimulateEconomy>();
+        self.start_rule::<rule::trade_caravans::TradeCaravans>();
+        self.start_rule::<rule::simulate_factions::SimulateFactions>();
+        self.start_rule::<rule::lifecycle::Lifecycle>();
         self.start_rule::<rule::report::ReportEvents>();
+        self.start_rule::<rule::justice::Justice>();
         self.start_rule::<rule::sync_npcs::SyncNpcs>();
         self.start_rule::<rule::simulate_npcs::SimulateNpcs>();
         self.start_rule::<rule::npc_ai::NpcAi>();
@@ -162,6 +173,23 @@ impl RtState {
         if let Some(handlers) = self.event_handlers.get::<EventHandlersOf<E>>() {
             handlers.iter().for_each(|f| f(self, world, index, &e));
         }
+
+        // Handle any events that were emitted by the handlers above
+        let deferred = std::mem::take(self.deferred_events.get_mut());
+        for emit in deferred {
+            emit(self, world, index);
+        }
+    }
+
+    /// Emit an event from within an event handler. Handlers only have shared
+    /// access to the state, so the event gets handled once the event currently
+    /// being handled has finished.
+    pub fn emit_later<E: Event + Send + Sync>(&self, e: E) {
+        self.deferred_events
+            .borrow_mut()
+            .push(Box::new(move |state, world, index| {
+                state.emit(e, world, index)
+            }));
     }
 
     pub fn tick(
@@ -1,10 +1,16 @@
 pub mod cleanup;
+pub mod justice;
+pub mod lifecycle;
 pub mod migrate;
 pub mod npc_ai;
 pub mod replenish_resources;
 pub mod report;
+pub mod simulate_economy;
+pub mod simulate_factions;
 pub mod simulate_npcs;
+pub mod simulate_wildlife;
 pub mod sync_npcs;
+pub mod trade_caravans;
 
 use super::RtState;
 use std::fmt;
@@ -0,0 +1,219 @@
+use crate::{
+    data::{Crime, Data, Sentiment},
+    event::{EventCtx, OnDeath, OnHurt, OnLootContainer, OnMineBlock},
+    rule::report::{site_at, AssaultCooldowns, WITNESS_RANGE},
+    RtState, Rule, RuleError,
+};
+use common::rtsim::{Actor, NpcId, Role, SiteId};
+use vek::*;
+
+/// A rule that lets sites put bounties on the heads of players who commit
+/// crimes against them and their people.
+pub struct Justice;
+
+impl Rule for Justice {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnHurt>(on_hurt);
+        rtstate.bind::<Self, OnDeath>(on_death);
+        rtstate.bind::<Self, OnLootContainer>(on_loot_container);
+        rtstate.bind::<Self, OnMineBlock>(on_mine_block);
+
+        Ok(Self)
+    }
+}
+
+/// The site whose people a victim belongs to, if they're a civilised NPC.
+fn victim_site(data: &Data, victim: Actor) -> Option<SiteId> {
+    let npc = data.npcs.get(victim.npc()?)?;
+    match npc.role {
+        Role::Civilised(_) => npc.home,
+        _ => None,
+    }
+}
+
+/// Whether an attack on a victim was self-defence: the victim was already
+/// hostile toward the attacker, who hadn't broken any laws.
+fn is_self_defence(data: &Data, site: SiteId, victim: Actor, attacker: Actor) -> bool {
+    victim
+        .npc()
+        .and_then(|npc| data.npcs.get(npc))
+        .map_or(false, |npc| {
+            npc.sentiments.toward(attacker).is(Sentiment::ENEMY)
+        })
+        && data.sites.bounty_on(site, attacker) == 0
+}
+
+/// Whether any of the people of the site saw a crime happen, not counting the
+/// victim.
+fn is_witnessed(data: &Data, site: SiteId, wpos: Vec3<f32>, victim: Option<NpcId>) -> bool {
+    let faction = data.sites.get(site).and_then(|site| site.faction);
+    data.npcs
+        .nearby(victim, wpos, WITNESS_RANGE)
+        .filter_map(|actor| data.npcs.get(actor.npc()?))
+        .any(|npc| {
+            matches!(npc.role, Role::Civilised(_))
+                && (npc.home == Some(site) || (faction.is_some() && npc.faction == faction))
+        })
+}
+
+/// Put a bounty on a player's head for a crime against a site.
+fn punish(data: &mut Data, site: SiteId, criminal: Actor, crime: Crime) {
+    // Rtsim only keeps track of crimes committed by players
+    if !matches!(criminal, Actor::Character(_)) {
+        return;
+    }
+    if let Some(site) = data.sites.get_mut(site) {
+        site.bounties.add(criminal, crime);
+    }
+}
+
+fn on_hurt(ctx: EventCtx<Justice, OnHurt>) {
+    let data = &mut *ctx.state.data_mut();
+    let (victim, attacker) = (ctx.event.actor, ctx.event.attacker);
+    let Some(site) = victim_site(data, victim) else {
+        return;
+    };
+    if is_self_defence(data, site, victim, attacker) {
+        return;
+    }
+
+    let now = data.time_of_day.0;
+    // Every blow of a fight isn't a new crime
+    if !ctx
+        .state
+        .resource_mut::<AssaultCooldowns>()
+        .is_new(attacker, victim, now)
+    {
+        return;
+    }
+
+    // The victim knows who attacked them, so there's no need for witnesses
+    punish(data, site, attacker, Crime::Assault);
+}
+
+fn on_death(ctx: EventCtx<Justice, OnDeath>) {
+    let data = &mut *ctx.state.data_mut();
+    let (victim, Some(killer), Some(wpos)) = (ctx.event.actor, ctx.event.killer, ctx.event.wpos)
+    else {
+        return;
+    };
+    let Some(site) = victim_site(data, victim) else {
+        return;
+    };
+
+    if !is_self_defence(data, site, victim, killer) && is_witnessed(data, site, wpos, victim.npc())
+    {
+        punish(data, site, killer, Crime::Murder);
+    }
+}
+
+fn on_loot_container(ctx: EventCtx<Justice, OnLootContainer>) {
+    let data = &mut *ctx.state.data_mut();
+    let wpos = ctx.event.wpos.as_::<f32>();
+
+    // Taking things from a container in the wilderness isn't stealing
+    if let Some(site) = site_at(data, ctx.world, wpos.xy())
+        && is_witnessed(data, site, wpos, None)
+    {
+        punish(data, site, ctx.event.actor, Crime::Theft);
+    }
+}
+
+fn on_mine_block(ctx: EventCtx<Justice, OnMineBlock>) {
+    let data = &mut *ctx.state.data_mut();
+    let wpos = ctx.event.wpos.as_::<f32>();
+
+    // Only blocks that are part of the site's plots, such as its buildings and
+    // fields, belong to it
+    if let Some(site) = site_at(data, ctx.world, wpos.xy())
+        && let Some(site2) = data
+            .sites
+            .get(site)
+            .and_then(|site| site.world_site)
+            .and_then(|ws| ctx.index.sites.get(ws).site2())
+        && site2.wpos_tile(ctx.event.wpos.xy()).plot.is_some()
+        && is_witnessed(data, site, wpos, None)
+    {
+        punish(data, site, ctx.event.actor, Crime::Vandalism);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Faction, Npc, Site};
+    use common::{character::CharacterId, rtsim::Profession};
+
+    #[test]
+    fn bounties_apply_across_a_faction() {
+        let mut data = Data::empty();
+        let faction = data.factions.create(Faction {
+            seed: 0,
+            leader: None,
+            sentiments: Default::default(),
+            raid: None,
+        });
+        let town = data
+            .sites
+            .create(Site::test(Vec2::zero()).with_faction(faction));
+        let other_town = data
+            .sites
+            .create(Site::test(Vec2::zero()).with_faction(faction));
+        let hamlet = data.sites.create(Site::test(Vec2::zero()));
+        let criminal = Actor::Character(CharacterId(1));
+
+        punish(&mut data, town, criminal, Crime::Theft);
+        punish(&mut data, other_town, criminal, Crime::Assault);
+        punish(&mut data, hamlet, criminal, Crime::Vandalism);
+        // Crimes by NPCs aren't tracked
+        punish(&mut data, town, Actor::Npc(NpcId::default()), Crime::Murder);
+
+        let faction_bounty = Crime::Theft.bounty() + Crime::Assault.bounty();
+        assert_eq!(data.sites.bounty_on(town, criminal), faction_bounty);
+        assert_eq!(data.sites.bounty_on(other_town, criminal), faction_bounty);
+        assert_eq!(
+            data.sites.bounty_on(hamlet, criminal),
+            Crime::Vandalism.bounty()
+        );
+        assert_eq!(data.sites.bounty_on(town, Actor::Npc(NpcId::default())), 0);
+
+        // Paying off the bounty in one town clears it across the faction, but not
+        // elsewhere, and the faction's guards stop hunting the criminal
+        let guard =
+            data.spawn_npc(Npc::test(Role::Civilised(Some(Profession::Guard))).with_home(town));
+        data.npcs[guard]
+            .sentiments
+            .toward_mut(criminal)
+            .change_by(Sentiment::VILLAIN, Sentiment::VILLAIN);
+        assert_eq!(data.pay_bounty(other_town, criminal), faction_bounty);
+        assert_eq!(data.sites.bounty_on(town, criminal), 0);
+        assert!(
+            !data.npcs[guard]
+                .sentiments
+                .toward(criminal)
+                .is(Sentiment::ENEMY)
+        );
+        assert_eq!(
+            data.sites.bounty_on(hamlet, criminal),
+            Crime::Vandalism.bounty()
+        );
+    }
+
+    #[test]
+    fn rules_agree_on_which_blows_start_a_fight() {
+        let mut cooldowns = AssaultCooldowns::default();
+        let (attacker, victim) = (
+            Actor::Character(CharacterId(1)),
+            Actor::Npc(NpcId::default()),
+        );
+
+        // Both the report and justice rules ask about the first blow
+        assert!(cooldowns.is_new(attacker, victim, 0.0));
+        assert!(cooldowns.is_new(attacker, victim, 0.0));
+        // Later blows are part of the same fight
+        assert!(!cooldowns.is_new(attacker, victim, 60.0));
+        assert!(cooldowns.is_new(victim, attacker, 60.0));
+        // Until the fight has cooled down
+        assert!(cooldowns.is_new(attacker, victim, 60.0 * 60.0));
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    ai::schedule,
+    data::{
+        npc::{SimulationMode, ADULT_AGE, YEAR_OF_AGING},
+        report::ReportKind,
+        Data, Npc, NpcId, Report, SiteId,
+    },
+    event::{DeathCause, OnDeath, OnSetup, OnTick},
+    RtState, Rule, RuleError,
+};
+use common::{
+    comp::{self, Body},
+    resources::TimeOfDay,
+    rtsim::{Actor, Personality, Profession, Role},
+};
+use hashbrown::{HashMap, HashSet};
+use rand::prelude::*;
+use rand_chacha::ChaChaRng;
+use world::{IndexRef, World};
+
+/// A rule that lets the populations of settlements change over time: settlers
+/// grow old, find partners, have children and eventually die, while newcomers
+/// arrive at towns that have room for them.
+pub struct Lifecycle {
+    last_update: Option<TimeOfDay>,
+}
+
+/// Only age NPCs every few ticks
+const LIFECYCLE_TICK_SKIP: u64 = 150;
+/// The chance that an unpartnered adult finds a partner over a year
+const PARTNER_CHANCE_PER_YEAR: f64 = 0.5;
+/// The chance that a couple has a child over a year
+const BIRTH_CHANCE_PER_YEAR: f64 = 0.4;
+/// The oldest age at which NPCs have children
+const MAX_PARENT_AGE: f32 = 45.0;
+/// The largest age difference between partners
+const MAX_AGE_GAP: f32 = 12.0;
+/// Newcomers only arrive at towns that have less than this proportion of their
+/// housing filled
+const IMMIGRATION_THRESHOLD: f32 = 0.5;
+/// The chance that a newcomer arrives at a town with room for them over a year
+const IMMIGRATION_CHANCE_PER_YEAR: f64 = 2.0;
+
+impl Rule for Lifecycle {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnSetup>(|ctx| {
+            let data = &mut *ctx.state.data_mut();
+            let mut rng = ChaChaRng::from_seed(thread_rng().gen::<[u8; 32]>());
+            let now = data.time_of_day;
+
+            // Settlers from before NPCs aged are already adults
+            for npc in data.npcs.values_mut() {
+                if npc.born.is_none() && npc.is_settler() {
+                    npc.born = Some(TimeOfDay(
+                        now.0 - rng.gen_range(ADULT_AGE..50.0) as f64 * YEAR_OF_AGING,
+                    ));
+                }
+            }
+
+            // Forget partners that no longer exist
+            let ids = data.npcs.keys().collect::<Vec<_>>();
+            for npc_id in ids {
+                if let Some(partner) = data.npcs[npc_id].family.partner
+                    && !data.npcs.contains_key(partner)
+                {
+                    data.npcs[npc_id].family.partner = None;
+                }
+            }
+        });
+
+        rtstate.bind::<Self, OnDeath>(|ctx| {
+            if let Actor::Npc(npc_id) = ctx.event.actor {
+                widow(&mut ctx.state.data_mut(), npc_id);
+            }
+        });
+
+        rtstate.bind::<Self, OnTick>(|ctx| {
+            if ctx.event.tick % LIFECYCLE_TICK_SKIP != 0 {
+                return;
+            }
+            let now = ctx.event.time_of_day;
+            let elapsed_years = ctx.rule.last_update.replace(now).map_or(0.0, |last| {
+                ((now.0 - last.0) / YEAR_OF_AGING).clamp(0.0, 1.0)
+            });
+
+            let data = &mut *ctx.state.data_mut();
+            let mut rng = ChaChaRng::from_seed(thread_rng().gen::<[u8; 32]>());
+            let capacities = housing_capacities(data, ctx.index);
+
+            for npc_id in die_of_old_age(data, now) {
+                ctx.state.emit_later(OnDeath {
+                    actor: Actor::Npc(npc_id),
+                    // The death is reported to the NPC's home site rather than to whoever
+                    // happens to be nearby
+                    wpos: None,
+                    killer: None,
+                    cause: DeathCause::OldAge,
+                });
+            }
+            find_partners(data, now, elapsed_years, &mut rng);
+            have_children(data, &capacities, now, elapsed_years, &mut rng);
+            attract_newcomers(data, ctx.world, &capacities, now, elapsed_years, &mut rng);
+        });
+
+        Ok(Self { last_update: None })
+    }
+}
+
+/// How many settlers the houses of each site have room for.
+fn housing_capacities(data: &Data, index: IndexRef) -> HashMap<SiteId, usize> {
+    data.sites
+        .iter()
+        .filter_map(|(site_id, site)| {
+            let site2 = index.sites.get(site.world_site?).site2()?;
+            Some((site_id, schedule::housing_capacity(site2)))
+        })
+        .collect()
+}
+
+/// How many living settlers call each site home.
+fn settler_counts(data: &Data) -> HashMap<SiteId, usize> {
+    let mut counts = HashMap::new();
+    for npc in data.npcs.values() {
+        if let Some(home) = npc.home
+            && !npc.is_dead
+            && npc.is_settler()
+        {
+            *counts.entry(home).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// Whether two NPCs are too closely related to become partners.
+fn are_related(data: &Data, a: NpcId, b: NpcId) -> bool {
+    let (Some(a_npc), Some(b_npc)) = (data.npcs.get(a), data.npcs.get(b)) else {
+        return false;
+    };
+    a_npc.family.is_related_to(b)
+        || b_npc.family.is_related_to(a)
+        // Siblings
+        || a_npc
+            .family
+            .parents
+            .iter()
+            .any(|parent| b_npc.family.parents.contains(parent))
+}
+
+/// Leave the partner of an NPC that has died free to find somebody else.
+fn widow(data: &mut Data, npc_id: NpcId) {
+    if let Some(partner) = data
+        .npcs
+        .get_mut(npc_id)
+        .and_then(|npc| npc.family.partner.take())
+        && let Some(partner) = data.npcs.get_mut(partner)
+    {
+        partner.family.partner = None;
+    }
+}
+
+/// Find the NPCs that have died of old age and report their deaths to their
+/// homes. `OnDeath` still needs emitting for each of the returned NPCs.
+fn die_of_old_age(data: &mut Data, now: TimeOfDay) -> Vec<NpcId> {
+    // NPCs that are loaded die of old age once they're unloaded
+    let dying = data
+        .npcs
+        .iter()
+        .filter(|(_, npc)| {
+            !npc.is_dead
+                && matches!(npc.mode, SimulationMode::Simulated)
+                && npc.age(now).map_or(false, |age| age >= npc.lifespan())
+        })
+        .map(|(npc_id, npc)| (npc_id, npc.home))
+        .collect::<Vec<_>>();
+
+    for &(npc_id, home) in &dying {
+        // The death becomes known around town
+        let report = data.reports.create(Report {
+            kind: ReportKind::Death {
+                actor: Actor::Npc(npc_id),
+                killer: None,
+            },
+            at: now,
+        });
+        if let Some(home) = home.and_then(|home| data.sites.get_mut(home)) {
+            home.known_reports.insert(report);
+        }
+    }
+
+    dying.into_iter().map(|(npc_id, _)| npc_id).collect()
+}
+
+fn find_partners(data: &mut Data, now: TimeOfDay, elapsed_years: f64, rng: &mut impl Rng) {
+    let chance = (elapsed_years * PARTNER_CHANCE_PER_YEAR).min(1.0);
+    let singles = data
+        .npcs
+        .iter()
+        .filter(|(_, npc)| {
+            !npc.is_dead && npc.is_settler() && npc.family.partner.is_none() && npc.is_adult(now)
+        })
+        .filter_map(|(npc_id, npc)| Some((npc_id, npc.home?, npc.age(now)?)))
+        .collect::<Vec<_>>();
+
+    let mut taken = HashSet::new();
+    for &(a, home, age) in &singles {
+        if taken.contains(&a) || !rng.gen_bool(chance) {
+            continue;
+        }
+        let Some(b) = singles
+            .iter()
+            .filter(|(b, b_home, b_age)| {
+                *b != a
+                    && *b_home == home
+                    && (age - b_age).abs() < MAX_AGE_GAP
+                    && !taken.contains(b)
+                    && !are_related(data, a, *b)
+            })
+            .map(|(b, _, _)| *b)
+            .choose(rng)
+        else {
+            continue;
+        };
+        taken.insert(a);
+        taken.insert(b);
+
+        // Partners move in together
+        let house = data.npcs[a].house_seed();
+        data.npcs[a].family.partner = Some(b);
+        data.npcs[b].family.partner = Some(a);
+        data.npcs[b].family.house = Some(house);
+    }
+}
+
+fn have_children(
+    data: &mut Data,
+    capacities: &HashMap<SiteId, usize>,
+    now: TimeOfDay,
+    elapsed_years: f64,
+    rng: &mut impl Rng,
+) {
+    let chance = (elapsed_years * BIRTH_CHANCE_PER_YEAR).min(1.0);
+    let is_fertile = |npc: &Npc| {
+        !npc.is_dead
+            && npc.is_settler()
+            && npc
+                .age(now)
+                .map_or(false, |age| (ADULT_AGE..MAX_PARENT_AGE).contains(&age))
+    };
+    let couples = data
+        .npcs
+        .iter()
+        .filter(|(_, npc)| is_fertile(npc))
+        .filter_map(|(a, npc)| Some((a, npc.family.partner?, npc.home?)))
+        // Only consider each couple once
+        .filter(|(a, b, _)| a < b && data.npcs.get(*b).map_or(false, is_fertile))
+        .collect::<Vec<_>>();
+
+    let mut settlers = settler_counts(data);
+    for (a, b, home) in couples {
+        // Couples only have children if there's room for them in the town
+        let population = settlers.entry(home).or_default();
+        if *population >= capacities.get(&home).copied().unwrap_or(0) || !rng.gen_bool(chance) {
+            continue;
+        }
+        *population += 1;
+
+        let (a_npc, b_npc) = (&data.npcs[a], &data.npcs[b]);
+        let parent = if rng.gen() { a_npc } else { b_npc };
+        let body = match parent.body {
+            Body::Humanoid(body) => {
+                Body::Humanoid(comp::humanoid::Body::random_with(rng, &body.species))
+            },
+            body => body,
+        };
+        let mut child = Npc::new(rng.gen(), parent.wpos, body, parent.role.clone())
+            .with_personality(Personality::inherit(
+                &a_npc.personality,
+                &b_npc.personality,
+                rng,
+            ))
+            .with_home(home)
+            .with_faction(parent.faction)
+            .with_birth(now);
+        child.family.parents = vec![a, b];
+        child.family.house = Some(a_npc.house_seed());
+
+        let child = data.spawn_npc(child);
+        data.npcs[a].family.children.push(child);
+        data.npcs[b].family.children.push(child);
+    }
+}
+
+fn attract_newcomers(
+    data: &mut Data,
+    world: &World,
+    capacities: &HashMap<SiteId, usize>,
+    now: TimeOfDay,
+    elapsed_years: f64,
+    rng: &mut impl Rng,
+) {
+    let chance = (elapsed_years * IMMIGRATION_CHANCE_PER_YEAR).min(1.0);
+    let settlers = settler_counts(data);
+
+    for (&site_id, &capacity) in capacities {
+        let Some(site) = data.sites.get(site_id) else {
+            continue;
+        };
+        // Only towns that belong to a faction take in newcomers
+        let Some(faction) = site.faction else {
+            continue;
+        };
+        let population = settlers.get(&site_id).copied().unwrap_or(0);
+        if (population as f32) >= capacity as f32 * IMMIGRATION_THRESHOLD || !rng.gen_bool(chance) {
+            continue;
+        }
+
+        // Newcomers take up the same kinds of work as the people already living there
+        let profession = site
+            .population
+            .iter()
+            .filter_map(|npc| data.npcs.get(*npc))
+            .filter(|npc| npc.is_settler())
+            .filter_map(|npc| npc.profession())
+            .choose(rng)
+            .unwrap_or(Profession::Farmer);
+        let wpos2d = site.wpos.map(|e| e + rng.gen_range(-10..10));
+        let wpos = wpos2d
+            .map(|e| e as f32 + 0.5)
+            .with_z(world.sim().get_alt_approx(wpos2d).unwrap_or(0.0));
+        let species = comp::humanoid::ALL_SPECIES.choose(rng).unwrap();
+        let born = TimeOfDay(now.0 - rng.gen_range(ADULT_AGE..35.0) as f64 * YEAR_OF_AGING);
+
+        data.spawn_npc(
+            Npc::new(
+                rng.gen(),
+                wpos,
+                Body::Humanoid(comp::humanoid::Body::random_with(rng, species)),
+                Role::Civilised(Some(profession)),
+            )
+            .with_personality(Personality::random(rng))
+            .with_home(site_id)
+            .with_faction(faction)
+            .with_birth(born),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Site;
+    use vek::*;
+
+    #[test]
+    fn settlers_form_families_and_grow_old() {
+        let mut data = Data::empty();
+        let mut rng = ChaChaRng::from_seed([0; 32]);
+        let home = data.sites.create(Site::test(Vec2::zero()));
+        let years = |n: f64| TimeOfDay(n * YEAR_OF_AGING);
+        let mut settler = |seed, born| {
+            data.spawn_npc(
+                Npc {
+                    seed,
+                    ..Npc::test(Role::Civilised(Some(Profession::Farmer)))
+                }
+                .with_home(home)
+                .with_birth(born),
+            )
+        };
+        let a = settler(1, years(0.0));
+        let b = settler(2, years(2.0));
+        // Long enough for finding a partner and having a child to be certain
+        let partner_years = 1.0 / PARTNER_CHANCE_PER_YEAR;
+        let birth_years = 1.0 / BIRTH_CHANCE_PER_YEAR;
+
+        // Children don't find partners
+        find_partners(&mut data, years(10.0), partner_years, &mut rng);
+        assert!(data.npcs[a].family.partner.is_none());
+
+        // Adults do, and move in together
+        find_partners(&mut data, years(25.0), partner_years, &mut rng);
+        assert_eq!(data.npcs[a].family.partner, Some(b));
+        assert_eq!(data.npcs[b].family.partner, Some(a));
+        assert_eq!(data.npcs[a].house_seed(), data.npcs[b].house_seed());
+
+        // Couples don't have children if there's no room for them
+        have_children(
+            &mut data,
+            &HashMap::new(),
+            years(25.0),
+            birth_years,
+            &mut rng,
+        );
+        assert_eq!(data.npcs.len(), 2);
+
+        let capacities = [(home, 4)].into_iter().collect();
+        have_children(&mut data, &capacities, years(25.0), birth_years, &mut rng);
+        let [child] = data.npcs[a].family.children.as_slice() else {
+            panic!("couple should have had a child");
+        };
+        let child = &data.npcs[*child];
+        assert_eq!(child.home, Some(home));
+        assert_eq!(child.house_seed(), data.npcs[a].house_seed());
+        assert!(!child.is_adult(years(25.0)));
+        assert!(child.is_adult(years(25.0 + ADULT_AGE as f64)));
+
+        // Eventually, everybody dies of old age
+        let dead = die_of_old_age(&mut data, years(200.0));
+        assert_eq!(dead.len(), data.npcs.len());
+        for npc_id in dead {
+            widow(&mut data, npc_id);
+        }
+        assert!(data.npcs[b].family.partner.is_none());
+        assert!(!data.sites[home].known_reports.is_empty());
+    }
+}
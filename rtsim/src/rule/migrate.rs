@@ -26,6 +26,16 @@ impl Rule for Migrate {
                 {
                     site.world_site = Some(world_site_id);
                     data.sites.world_site_map.insert(world_site_id, site_id);
+
+                    // Only part of a site's economy is persisted, the rest comes from worldgen
+                    let world_site = ctx.index.sites.get(world_site_id);
+                    if !world_site.do_economic_simulation() {
+                        site.economy = None;
+                    } else if let Some(economy) = &mut site.economy {
+                        economy.link(&world_site.economy);
+                    } else {
+                        site.economy = Some(world_site.economy.clone());
+                    }
                     true
                 } else {
                     warn!(
@@ -55,7 +65,6 @@ impl Rule for Migrate {
                         ctx.world,
                         ctx.index,
                         &[],
-                        &data.factions,
                         &mut rng,
                     ));
                 }
@@ -4,11 +4,12 @@ use crate::{
     ai::{
         casual, choose, finish, important, just, now,
         predicate::{every_range, timeout, Chance, EveryRange, Predicate},
+        schedule::{self, Period, Schedule},
         seq, until, Action, NpcCtx, State,
     },
     data::{
         npc::{Brain, PathData, SimulationMode},
-        ReportKind, Sentiment, Sites,
+        CaravanId, CaravanStage, Npc, ReportId, ReportKind, Sentiment, Sites,
     },
     event::OnTick,
     RtState, Rule, RuleError,
@@ -28,6 +29,7 @@ use common::{
     terrain::{CoordinateConversions, TerrainChunkSize},
     time::DayPeriod,
     util::Dir,
+    weather::WeatherKind,
 };
 use fxhash::FxHasher64;
 use itertools::{Either, Itertools};
@@ -37,8 +39,13 @@ use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
 use vek::*;
 use world::{
     civ::{self, Track},
+    layer::cave::CaveNode,
     site::{Site as WorldSite, SiteKind},
-    site2::{self, plot::tavern, PlotKind, TileKind},
+    site2::{
+        self,
+        plot::{tavern, DesertCityArena, Tavern},
+        Plot, PlotKind, TileKind,
+    },
     util::NEIGHBORS,
     IndexRef, World,
 };
@@ -312,13 +319,34 @@ impl Rule for NpcAi {
 
             // Reinsert NPC brains
             let mut data = ctx.state.data_mut();
-            for (npc_id, controller, inbox, sentiments, known_reports, brain) in npc_data {
+            let mut gossip = Vec::new();
+            for (npc_id, mut controller, inbox, sentiments, known_reports, brain) in npc_data {
+                gossip.extend(
+                    controller
+                        .gossip
+                        .drain(..)
+                        .map(|(tgt, report)| (npc_id, tgt, report)),
+                );
                 data.npcs[npc_id].controller = controller;
                 data.npcs[npc_id].brain = Some(brain);
                 data.npcs[npc_id].inbox = inbox;
                 data.npcs[npc_id].sentiments = sentiments;
                 data.npcs[npc_id].known_reports = known_reports;
             }
+
+            // Pass on the reports that NPCs told one-another about, which become less
+            // credible each time they're retold
+            for (teller, tgt, report) in gossip {
+                if let Some(credibility) = data
+                    .npcs
+                    .get(teller)
+                    .and_then(|teller| teller.rumours.pass_on(report))
+                    && let Some(tgt) = data.npcs.get_mut(tgt)
+                    && tgt.hear_report(report, credibility)
+                {
+                    tgt.inbox.push_back(NpcInput::Report(report));
+                }
+            }
         });
 
         Ok(Self)
@@ -530,6 +558,68 @@ fn travel_to_point<S: State>(wpos: Vec2<f32>, speed_factor: f32) -> impl Action<
     .debug(move || format!("travel to point {}, {}", wpos.x, wpos.y))
 }
 
+/// Walk toward a 3D position, such as one in a cave, without moving it to the
+/// surface.
+fn goto_3d<S: State>(wpos: Vec3<f32>, speed_factor: f32, goal_dist: f32) -> impl Action<S> {
+    just(move |ctx, _| ctx.controller.do_goto(wpos, speed_factor))
+        .repeat()
+        .stop_if(move |ctx: &mut NpcCtx| ctx.npc.wpos.distance_squared(wpos) < goal_dist.powi(2))
+        .debug(move || format!("goto 3d {}, {}, {}", wpos.x, wpos.y, wpos.z))
+        .map(|_, _| {})
+}
+
+/// Follow a route through the caves, as found with
+/// [`world::layer::cave::CaveGraph::route`].
+fn traverse_cave<S: State>(route: Vec<Vec3<i32>>, speed_factor: f32) -> impl Action<S> {
+    seq(route.into_iter().map(move |wpos| {
+        goto_3d(wpos.as_() + Vec3::new(0.5, 0.5, 0.0), speed_factor, 4.0)
+            // Don't get stuck forever if the tunnel turns out to be blocked
+            .stop_if(timeout(60.0))
+            .map(|_, _| {})
+    }))
+    .debug(|| "traverse cave")
+}
+
+/// Find a route from the nearest cave entrance down to one of the cave's
+/// deeper parts, if there's a cave close enough to be worth the trip.
+fn find_cave_route(ctx: &mut NpcCtx) -> Option<Vec<Vec3<i32>>> {
+    // How far away a cave can be for it to be worth the trip
+    const MAX_CAVE_DIST: f32 = 2000.0;
+
+    let caves = &ctx.index.caves;
+    let entrance = caves
+        .nearest(ctx.npc.wpos, CaveNode::is_entrance)
+        .filter(|entrance| {
+            caves.node(*entrance).wpos.as_().distance(ctx.npc.wpos) < MAX_CAVE_DIST
+        })?;
+    let entrance_wpos = caves.node(entrance).wpos.xy().as_::<f32>();
+    let (target, _) = caves
+        .nodes()
+        .filter(|(_, node)| {
+            !node.is_entrance() && node.wpos.xy().as_().distance(entrance_wpos) < MAX_CAVE_DIST
+        })
+        .choose(&mut ctx.rng)?;
+    caves.route(entrance, target)
+}
+
+/// Head into the nearest cave, explore down to one of its deeper parts and
+/// then come back out again.
+fn explore_caves<S: State>() -> impl Action<S> {
+    now(|ctx, _| {
+        if let Some(route) = find_cave_route(ctx) {
+            let entrance = route[0].as_::<f32>();
+            let way_back = route.iter().rev().copied().collect();
+            travel_to_point(entrance.xy(), 0.6)
+                .then(traverse_cave(route, WALKING_SPEED))
+                .then(traverse_cave(way_back, WALKING_SPEED))
+                .boxed()
+        } else {
+            finish().boxed()
+        }
+    })
+    .debug(|| "explore caves")
+}
+
 /// Try to travel to a site. Where practical, paths will be taken.
 fn travel_to_site<S: State>(tgt_site: SiteId, speed_factor: f32) -> impl Action<S> {
     now(move |ctx, _| {
@@ -632,6 +722,7 @@ fn talk_to<S: State>(tgt: Actor, _subject: Option<Subject>) -> impl Action<S> +
             // some sort of 'bored of conversation' system
             idle().l()
         } else {
+            let mut told = None;
             // Mention nearby sites
             let comment = if ctx.rng.gen_bool(0.3)
                 && let Some(current_site) = ctx.npc.current_site
@@ -692,6 +783,18 @@ fn talk_to<S: State>(tgt: Actor, _subject: Option<Subject>) -> impl Action<S> +
                             .localize_npc(),
                     ),
                 ])
+            // Pass on a rumour
+            } else if ctx.rng.gen_bool(0.3)
+                && let Some(report) = ctx
+                    .known_reports
+                    .iter()
+                    .filter(|report| ctx.npc.rumours.pass_on(**report).is_some())
+                    .choose(&mut ctx.rng)
+                    .copied()
+                && let Some(rumour) = describe_report(ctx, report)
+            {
+                told = Some(report);
+                rumour
             // Specific night dialog
             } else if ctx.rng.gen_bool(0.6) && DayPeriod::from(ctx.time_of_day.0).is_dark() {
                 Content::localized("npc-speech-night")
@@ -707,12 +810,130 @@ fn talk_to<S: State>(tgt: Actor, _subject: Option<Subject>) -> impl Action<S> +
             idle()
                 .repeat()
                 .stop_if(timeout(wait))
-                .then(just(move |ctx, _| ctx.controller.say(tgt, comment.clone())))
+                .then(just(move |ctx, _| {
+                    ctx.controller.say(tgt, comment.clone());
+                    // Other NPCs might pass the rumour on themselves
+                    if let (Actor::Npc(tgt), Some(report)) = (tgt, told) {
+                        ctx.controller.tell(tgt, report);
+                    }
+                }))
                 .r()
         }
     })
 }
 
+/// Describe a known report as a piece of gossip, hedged according to how much
+/// the NPC believes it.
+fn describe_report(ctx: &NpcCtx, report_id: ReportId) -> Option<Content> {
+    let data = ctx.state.data();
+    let report = data.reports.get(report_id)?;
+
+    let credibility = ctx.npc.rumours.credibility(report_id);
+    let hedge = Content::localized(if !ctx.npc.rumours.is_rumour(report_id) {
+        "npc-speech-rumour_hedge_witnessed"
+    } else if credibility >= 0.6 {
+        "npc-speech-rumour_hedge_reliable"
+    } else if credibility >= 0.35 {
+        "npc-speech-rumour_hedge_hearsay"
+    } else {
+        "npc-speech-rumour_hedge_doubtful"
+    });
+
+    let actor_name = |actor: Actor| match actor {
+        Actor::Npc(npc) => match data.npcs.get(npc) {
+            Some(npc) if matches!(npc.role, Role::Civilised(_)) => Content::Plain(npc.get_name()),
+            Some(npc) => npc.body.localize_npc(),
+            None => Content::localized("npc-speech-rumour_someone"),
+        },
+        // TODO: Use character names, once rtsim knows about them
+        Actor::Character(_) => Content::localized("npc-speech-rumour_stranger"),
+    };
+    let site_name = |site: SiteId| {
+        data.sites
+            .get(site)?
+            .world_site
+            .map(|ws| Content::Plain(ctx.index.sites.get(ws).name().to_string()))
+    };
+
+    Some(match report.kind {
+        ReportKind::Death {
+            actor,
+            killer: Some(killer),
+        } => Content::localized_with_args("npc-speech-rumour_murder", [
+            ("hedge", hedge),
+            ("killer", actor_name(killer)),
+            ("victim", actor_name(actor)),
+        ]),
+        ReportKind::Death {
+            actor,
+            killer: None,
+        } => Content::localized_with_args("npc-speech-rumour_death", [
+            ("hedge", hedge),
+            ("victim", actor_name(actor)),
+        ]),
+        ReportKind::Raid { site, captured, .. } => Content::localized_with_args(
+            if captured {
+                "npc-speech-rumour_raid_captured"
+            } else {
+                "npc-speech-rumour_raid_repelled"
+            },
+            [("hedge", hedge), ("site", site_name(site)?)],
+        ),
+        ReportKind::Theft { thief, site } => match site.and_then(site_name) {
+            Some(site) => Content::localized_with_args("npc-speech-rumour_theft", [
+                ("hedge", hedge),
+                ("thief", actor_name(thief)),
+                ("site", site),
+            ]),
+            None => Content::localized_with_args("npc-speech-rumour_theft_wilds", [
+                ("hedge", hedge),
+                ("thief", actor_name(thief)),
+            ]),
+        },
+        ReportKind::Assault { attacker, victim } => {
+            Content::localized_with_args("npc-speech-rumour_assault", [
+                ("hedge", hedge),
+                ("attacker", actor_name(attacker)),
+                ("victim", actor_name(victim)),
+            ])
+        },
+        ReportKind::Trade { site, customer } => {
+            Content::localized_with_args("npc-speech-rumour_trade", [
+                ("hedge", hedge),
+                (
+                    "customer",
+                    customer.map_or_else(
+                        || Content::localized("npc-speech-rumour_someone"),
+                        actor_name,
+                    ),
+                ),
+                ("site", site_name(site)?),
+            ])
+        },
+        ReportKind::MonsterSighting { body, wpos, .. } => {
+            Content::localized_with_args("npc-speech-rumour_monster", [
+                ("hedge", hedge),
+                ("body", body.localize_npc()),
+                (
+                    "dir",
+                    Direction::from_dir(wpos - ctx.npc.wpos.xy()).localize_npc(),
+                ),
+                (
+                    "dist",
+                    Distance::from_length(wpos.distance(ctx.npc.wpos.xy()) as i32).localize_npc(),
+                ),
+            ])
+        },
+        ReportKind::Heroics { hero, foe } => {
+            Content::localized_with_args("npc-speech-rumour_heroics", [
+                ("hedge", hedge),
+                ("hero", actor_name(hero)),
+                ("foe", foe.localize_npc()),
+            ])
+        },
+    })
+}
+
 fn socialize() -> impl Action<EveryRange> + Clone {
     now(move |ctx, socialize: &mut EveryRange| {
         // Skip most socialising actions if we're not loaded
@@ -750,8 +971,13 @@ fn socialize() -> impl Action<EveryRange> + Clone {
 
 fn adventure() -> impl Action<DefaultState> {
     choose(|ctx, _| {
+        // Adventurers sometimes go looking for treasure underground
+        if matches!(ctx.npc.profession(), Some(Profession::Adventurer(_)))
+            && ctx.rng.gen_bool(0.2)
+        {
+            important(explore_caves().boxed())
         // Choose a random site that's fairly close by
-        if let Some(tgt_site) = ctx
+        } else if let Some(tgt_site) = ctx
             .state
             .data()
             .sites
@@ -846,6 +1072,433 @@ fn choose_plaza(ctx: &mut NpcCtx, site: SiteId) -> Option<Vec2<f32>> {
 
 const WALKING_SPEED: f32 = 0.35;
 
+/// Whether the weather at a site is bad enough that people would rather be
+/// indoors.
+fn is_bad_weather(ctx: &NpcCtx, site: SiteId) -> bool {
+    ctx.state
+        .data()
+        .sites
+        .get(site)
+        .and_then(|site| site.weather)
+        .map_or(false, |weather| {
+            matches!(
+                weather.get_kind(),
+                WeatherKind::Rain | WeatherKind::Snow | WeatherKind::Storm
+            )
+        })
+}
+
+/// Find the centre of one of the NPC's plots in a site.
+fn plot_wpos(
+    ctx: &NpcCtx,
+    site: SiteId,
+    plot: impl FnOnce(&Npc, &site2::Site) -> Option<Id<Plot>>,
+) -> Option<Vec2<f32>> {
+    ctx.state
+        .data()
+        .sites
+        .get(site)
+        .and_then(|site| ctx.index.sites.get(site.world_site?).site2())
+        .and_then(|site2| {
+            let plot = site2.plot(plot(ctx.npc, site2)?);
+            Some(site2.tile_center_wpos(plot.root_tile()).as_())
+        })
+}
+
+fn find_field(ctx: &NpcCtx, site: SiteId) -> Option<Vec2<f32>> {
+    ctx.state
+        .data()
+        .sites
+        .get(site)
+        .and_then(|site| ctx.index.sites.get(site.world_site?).site2())
+        .and_then(|site2| {
+            // Farmers look after the fields nearest to their house
+            let near = schedule::home_plot(ctx.npc, site2)
+                .map_or_else(Vec2::zero, |plot| site2.plot(plot).root_tile());
+            let (_, tile) = site2.tiles.find_near(near, |_, tile| {
+                matches!(tile.kind, TileKind::Field).then_some(())
+            })?;
+            Some(site2.tile_center_wpos(tile).as_())
+        })
+}
+
+/// Go inside and stay there
+fn stay_inside(wpos: Vec2<f32>) -> impl Action<DefaultState> {
+    travel_to_point(wpos, 0.65)
+        .debug(|| "go inside")
+        .then(
+            socialize()
+                .repeat()
+                .map_state(|state: &mut DefaultState| &mut state.socialize_timer)
+                .debug(|| "wait inside"),
+        )
+        .map(|_, _| ())
+}
+
+fn sleep<S: State>() -> impl Action<S> {
+    just(|ctx, _| ctx.controller.do_sit(None, None))
+        .repeat()
+        .debug(|| "sleep")
+        .map(|_, _| ())
+}
+
+/// Walk to an arena seat, cheer, sit and dance
+fn watch_arena(ctx: &mut NpcCtx, arena: &DesertCityArena) -> impl Action<DefaultState> {
+    let wait_time = ctx.rng.gen_range(100.0..300.0);
+    // We don't use Z coordinates for seats because they are complicated to
+    // calculate from the Ramp procedural generation and using goto_2d seems to
+    // work just fine. However it also means that NPC will never go seat on the
+    // stands on the first floor of the arena. This is a compromise that was
+    // made because in the current arena procedural generation there is also no
+    // pathways to the stands on the first floor for NPCs.
+    let arena_center = Vec3::new(arena.center.x, arena.center.y, arena.base).as_::<f32>();
+    let stand_dist = arena.stand_dist as f32;
+    let seat_var_width = ctx.rng.gen_range(0..arena.stand_width) as f32;
+    let seat_var_length = ctx.rng.gen_range(-arena.stand_length..arena.stand_length) as f32;
+    // Select a seat on one of the 4 arena stands
+    let seat = match ctx.rng.gen_range(0..4) {
+        0 => Vec3::new(
+            arena_center.x - stand_dist + seat_var_width,
+            arena_center.y + seat_var_length,
+            arena_center.z,
+        ),
+        1 => Vec3::new(
+            arena_center.x + stand_dist - seat_var_width,
+            arena_center.y + seat_var_length,
+            arena_center.z,
+        ),
+        2 => Vec3::new(
+            arena_center.x + seat_var_length,
+            arena_center.y - stand_dist + seat_var_width,
+            arena_center.z,
+        ),
+        _ => Vec3::new(
+            arena_center.x + seat_var_length,
+            arena_center.y + stand_dist - seat_var_width,
+            arena_center.z,
+        ),
+    };
+    let look_dir = Dir::from_unnormalized(arena_center - seat);
+    just(move |ctx, _| ctx.controller.say(None, Content::localized("npc-speech-arena")))
+        .then(goto_2d(seat.xy(), 0.6, 1.0).debug(|| "go to arena"))
+        // Turn toward the centre of the arena and watch the action!
+        .then(choose(move |ctx, _| if ctx.rng.gen_bool(0.3) {
+            casual(just(move |ctx,_| ctx.controller.do_cheer(look_dir)).repeat().stop_if(timeout(5.0)))
+        } else if ctx.rng.gen_bool(0.15) {
+            casual(just(move |ctx,_| ctx.controller.do_dance(look_dir)).repeat().stop_if(timeout(5.0)))
+        } else {
+            casual(just(move |ctx,_| ctx.controller.do_sit(look_dir, None)).repeat().stop_if(timeout(15.0)))
+        })
+            .repeat()
+            .stop_if(timeout(wait_time)))
+        .map(|_, _| ())
+}
+
+/// Where the bar of a tavern is, or somewhere else sensible if it doesn't
+/// have one
+fn tavern_bar_pos(ctx: &mut NpcCtx, tavern: &Tavern) -> Vec3<i32> {
+    let (stage_aabr, stage_z) = tavern_stage(ctx, tavern);
+    tavern
+        .rooms
+        .values()
+        .flat_map(|room| {
+            room.details.iter().filter_map(|detail| match detail {
+                tavern::Detail::Bar { aabr } => {
+                    let side =
+                        site2::util::Dir::from_vec2(room.bounds.center().xy() - aabr.center());
+                    let pos = side.select_aabr_with(*aabr, aabr.center()) + side.to_vec2();
+
+                    Some(pos.with_z(room.bounds.min.z))
+                },
+                _ => None,
+            })
+        })
+        .choose(&mut ctx.rng)
+        .unwrap_or(stage_aabr.center().with_z(stage_z))
+}
+
+fn tavern_stage(ctx: &mut NpcCtx, tavern: &Tavern) -> (Aabr<i32>, i32) {
+    tavern
+        .rooms
+        .values()
+        .flat_map(|room| {
+            room.details.iter().filter_map(|detail| match detail {
+                tavern::Detail::Stage { aabr } => Some((*aabr, room.bounds.min.z + 1)),
+                _ => None,
+            })
+        })
+        .choose(&mut ctx.rng)
+        .unwrap_or((tavern.bounds, tavern.door_wpos.z))
+}
+
+/// Spend `wait_time` seconds in a tavern, dancing, drinking and chatting
+fn visit_tavern(ctx: &mut NpcCtx, tavern: &Tavern, wait_time: f64) -> impl Action<DefaultState> {
+    let (stage_aabr, stage_z) = tavern_stage(ctx, tavern);
+    let bar_pos = tavern_bar_pos(ctx, tavern);
+
+    // Pick a chair that is theirs for the stay
+    let chair_pos = tavern.rooms.values().flat_map(|room| {
+        let z = room.bounds.min.z;
+        room.details.iter().filter_map(move |detail| match detail {
+            tavern::Detail::Table { pos, chairs } => Some(chairs.into_iter().map(move |dir| pos.with_z(z) + dir.to_vec2())),
+            _ => None,
+        })
+        .flatten()
+    }
+    ).choose(&mut ctx.rng)
+    // This path is possible, but highly unlikely.
+    .unwrap_or(bar_pos);
+
+    let stage_aabr = stage_aabr.as_::<f32>();
+    let stage_z = stage_z as f32;
+
+    travel_to_point(tavern.door_wpos.xy().as_() + 0.5, 0.8)
+        .then(
+            choose(move |ctx, (last_action, _)| {
+                let action = [0, 1, 2]
+                    .into_iter()
+                    .filter(|i| *last_action != Some(*i))
+                    .choose(&mut ctx.rng)
+                    .expect("We have at least 2 elements");
+                let socialize = socialize().map_state(|(_, timer)| timer).repeat();
+                match action {
+                    // Go and dance on a stage.
+                    0 => casual(
+                        now(move |ctx, (last_action, _)| {
+                            *last_action = Some(action);
+                            goto(
+                                stage_aabr
+                                    .min
+                                    .map2(stage_aabr.max, |a, b| ctx.rng.gen_range(a..b))
+                                    .with_z(stage_z),
+                                WALKING_SPEED,
+                                1.0,
+                            )
+                        })
+                        .then(
+                            just(move |ctx, _| ctx.controller.do_dance(None))
+                                .repeat()
+                                .stop_if(timeout(ctx.rng.gen_range(20.0..30.0))),
+                        )
+                        .map(|_, _| ()),
+                    ),
+                    // Go and sit at a table.
+                    1 => casual(now(move |ctx, (last_action, _)| {
+                        *last_action = Some(action);
+                        goto(chair_pos.as_() + 0.5, WALKING_SPEED, 1.0)
+                            .then(just(move |ctx, _| {
+                                ctx.controller.do_sit(None, Some(chair_pos))
+                            }))
+                            .then(
+                                socialize
+                                    .clone()
+                                    .stop_if(timeout(ctx.rng.gen_range(30.0..60.0))),
+                            )
+                            .map(|_, _| ())
+                    })),
+                    // Go to the bar.
+                    _ => casual(now(move |ctx, (last_action, _)| {
+                        *last_action = Some(action);
+                        goto(bar_pos.as_() + 0.5, WALKING_SPEED, 1.0)
+                            .then(
+                                socialize
+                                    .clone()
+                                    .stop_if(timeout(ctx.rng.gen_range(10.0..25.0))),
+                            )
+                            .map(|_, _| ())
+                    })),
+                }
+            })
+            .with_state((None::<u32>, every_range(5.0..10.0)))
+            .repeat()
+            .stop_if(timeout(wait_time)),
+        )
+        .map(|_, _| ())
+}
+
+/// Find a tavern in the site and go and spend some time there.
+fn go_to_tavern(
+    ctx: &mut NpcCtx,
+    site: SiteId,
+    wait_time: f64,
+) -> Option<Box<dyn Action<DefaultState>>> {
+    let index = ctx.index;
+    let ws = index
+        .sites
+        .get(ctx.state.data().sites.get(site)?.world_site?)
+        .site2()?;
+    let tavern = ws
+        .plots()
+        .filter_map(|p| match p.kind() {
+            PlotKind::Tavern(t) => Some(t),
+            _ => None,
+        })
+        .choose(&mut ctx.rng)?;
+    Some(visit_tavern(ctx, tavern, wait_time).boxed())
+}
+
+/// Go to work, depending on profession.
+fn work(ctx: &mut NpcCtx, site: SiteId) -> Option<Box<dyn Action<DefaultState>>> {
+    match ctx.npc.profession()? {
+        Profession::Herbalist => {
+            let forest_wpos = find_forest(ctx)?;
+            Some(
+                travel_to_point(forest_wpos, 0.5)
+                    .debug(|| "walk to forest")
+                    .then({
+                        let wait_time = ctx.rng.gen_range(10.0..30.0);
+                        gather_ingredients().repeat().stop_if(timeout(wait_time))
+                    })
+                    .map(|_, _| ())
+                    .boxed(),
+            )
+        },
+        Profession::Hunter => {
+            let forest_wpos = find_forest(ctx)?;
+            Some(
+                just(|ctx, _| {
+                    ctx.controller
+                        .say(None, Content::localized("npc-speech-start_hunting"))
+                })
+                .then(travel_to_point(forest_wpos, 0.75))
+                .debug(|| "walk to forest")
+                .then({
+                    let wait_time = ctx.rng.gen_range(30.0..60.0);
+                    hunt_animals().repeat().stop_if(timeout(wait_time))
+                })
+                .map(|_, _| ())
+                .boxed(),
+            )
+        },
+        Profession::Farmer => {
+            let field_wpos = find_field(ctx, site)?;
+            Some(
+                travel_to_point(field_wpos, 0.5)
+                    .debug(|| "walk to field")
+                    .then({
+                        let wait_time = ctx.rng.gen_range(60.0..120.0);
+                        just(|ctx, _| {
+                            ctx.controller
+                                .do_gather(&[ChunkResource::Plant, ChunkResource::Vegetable])
+                        })
+                        .repeat()
+                        .stop_if(timeout(wait_time))
+                        .debug(|| "tend field")
+                    })
+                    .map(|_, _| ())
+                    .boxed(),
+            )
+        },
+        Profession::Guard => {
+            let plaza_wpos = choose_plaza(ctx, site)?;
+            Some(
+                travel_to_point(plaza_wpos, 0.4)
+                    .debug(|| "patrol")
+                    .interrupt_with(move |ctx, _| {
+                        if ctx.rng.gen_bool(0.0003) {
+                            Some(just(move |ctx, _| {
+                                ctx.controller
+                                    .say(None, Content::localized("npc-speech-guard_thought"))
+                            }))
+                        } else {
+                            None
+                        }
+                    })
+                    .map(|_, _| ())
+                    .boxed(),
+            )
+        },
+        Profession::Chef => {
+            let index = ctx.index;
+            let ws = index
+                .sites
+                .get(ctx.state.data().sites.get(site)?.world_site?)
+                .site2()?;
+            let PlotKind::Tavern(tavern) = ws.plot(schedule::workplace(ctx.npc, ws)?).kind() else {
+                return None;
+            };
+            let bar_pos = tavern_bar_pos(ctx, tavern);
+            Some(
+                travel_to_point(tavern.door_wpos.xy().as_() + 0.5, 0.6)
+                    .debug(|| "walk to tavern")
+                    .then(goto(bar_pos.as_() + 0.5, WALKING_SPEED, 1.0))
+                    .then(
+                        socialize()
+                            .repeat()
+                            .map_state(|state: &mut DefaultState| &mut state.socialize_timer)
+                            .stop_if(timeout(ctx.rng.gen_range(60.0..120.0)))
+                            .debug(|| "serve customers"),
+                    )
+                    .map(|_, _| ())
+                    .boxed(),
+            )
+        },
+        Profession::Blacksmith | Profession::Alchemist => {
+            let workplace_wpos = plot_wpos(ctx, site, schedule::workplace)?;
+            Some(
+                travel_to_point(workplace_wpos, 0.6)
+                    .debug(|| "walk to workshop")
+                    .then(
+                        socialize()
+                            .repeat()
+                            .map_state(|state: &mut DefaultState| &mut state.socialize_timer)
+                            .stop_if(timeout(ctx.rng.gen_range(60.0..120.0)))
+                            .debug(|| "work in workshop"),
+                    )
+                    .map(|_, _| ())
+                    .boxed(),
+            )
+        },
+        Profession::Miner => {
+            let route = find_cave_route(ctx)?;
+            let entrance = route[0].as_::<f32>();
+            let way_back = route.iter().rev().copied().collect();
+            Some(
+                travel_to_point(entrance.xy(), 0.5)
+                    .debug(|| "walk to cave")
+                    .then(traverse_cave(route, WALKING_SPEED))
+                    .then({
+                        let wait_time = ctx.rng.gen_range(60.0..120.0);
+                        just(|ctx, _| ctx.controller.do_gather(&[ChunkResource::Ore]))
+                            .repeat()
+                            .stop_if(timeout(wait_time))
+                            .debug(|| "mine ore")
+                    })
+                    .then(traverse_cave(way_back, WALKING_SPEED))
+                    .map(|_, _| ())
+                    .boxed(),
+            )
+        },
+        Profession::Merchant => Some(
+            just(|ctx, _| {
+                // Try to direct our speech at nearby actors, if there are any
+                let (target, phrase) = if ctx.rng.gen_bool(0.3)
+                    && let Some(other) = ctx
+                        .state
+                        .data()
+                        .npcs
+                        .nearby(Some(ctx.npc_id), ctx.npc.wpos, 8.0)
+                        .choose(&mut ctx.rng)
+                {
+                    (Some(other), "npc-speech-merchant_sell_directed")
+                } else {
+                    // Otherwise, resort to generic expressions
+                    (None, "npc-speech-merchant_sell_undirected")
+                };
+
+                ctx.controller.say(target, Content::localized(phrase));
+            })
+            .then(idle().repeat().stop_if(timeout(8.0)))
+            .repeat()
+            .stop_if(timeout(60.0))
+            .debug(|| "sell wares")
+            .map(|_, _| ())
+            .boxed(),
+        ),
+        _ => None,
+    }
+}
+
 fn villager(visiting_site: SiteId) -> impl Action<DefaultState> {
     choose(move |ctx, state: &mut DefaultState| {
         // Consider moving home if the home site gets too full
@@ -854,9 +1507,9 @@ fn villager(visiting_site: SiteId) -> impl Action<DefaultState> {
             && Some(home) == ctx.npc.current_site
             && let Some(home_pop_ratio) = ctx.state.data().sites.get(home)
                 .and_then(|site| Some((site, ctx.index.sites.get(site.world_site?).site2()?)))
-                .map(|(site, site2)| site.population.len() as f32 / site2.plots().len() as f32)
-                // Only consider moving if the population is more than 1.5x the number of homes
-                .filter(|pop_ratio| *pop_ratio > 1.5)
+                .map(|(site, site2)| site.population.len() as f32 / schedule::housing_capacity(site2) as f32)
+                // Only consider moving if there isn't enough room in the houses for everybody
+                .filter(|pop_ratio| *pop_ratio > 1.0)
             && let Some(new_home) = ctx
                 .state
                 .data()
@@ -877,7 +1530,7 @@ fn villager(visiting_site: SiteId) -> impl Action<DefaultState> {
                     Some((site_id, site, site2))
                 })
                 // Only select sites that are less densely populated than our own
-                .filter(|(_, site, site2)| (site.population.len() as f32 / site2.plots().len() as f32) < home_pop_ratio)
+                .filter(|(_, site, site2)| (site.population.len() as f32 / schedule::housing_capacity(site2) as f32) < home_pop_ratio)
                 // Find the closest of the candidate sites
                 .min_by_key(|(_, site, _)| site.wpos.as_().distance(ctx.npc.wpos.xy()) as i32)
                 .map(|(site_id, _, _)| site_id)
@@ -892,281 +1545,144 @@ fn villager(visiting_site: SiteId) -> impl Action<DefaultState> {
                 .then(travel_to_site(new_home, 0.5))
                 .then(just(move |ctx, _| ctx.controller.set_new_home(new_home))));
         }
-        let day_period = DayPeriod::from(ctx.time_of_day.0);
-        let is_weekend = ctx.time_of_day.day() as u64 % 6 == 0;
-        if day_period.is_dark()
-            && !matches!(ctx.npc.profession(), Some(Profession::Guard))
-        {
-            return important(
-                now(move |ctx, _| {
-                    if let Some(house_wpos) = ctx
-                        .state
-                        .data()
-                        .sites
-                        .get(visiting_site)
-                        .and_then(|site| ctx.index.sites.get(site.world_site?).site2())
-                        .and_then(|site2| {
-                            // Find a house in the site we're visiting
-                            let house = site2
-                                .plots()
-                                .filter(|p| matches!(p.kind(), PlotKind::House(_)))
-                                .choose(&mut ctx.rng)?;
-                            Some(site2.tile_center_wpos(house.root_tile()).as_())
-                        })
-                    {
-                        just(|ctx, _| {
-                            ctx.controller
-                                .say(None, Content::localized("npc-speech-night_time"))
-                        })
-                        .then(travel_to_point(house_wpos, 0.65))
-                        .debug(|| "walk to house")
-                        .then(socialize().repeat().map_state(|state: &mut DefaultState| &mut state.socialize_timer).debug(|| "wait in house"))
-                        .stop_if(|ctx: &mut NpcCtx| DayPeriod::from(ctx.time_of_day.0).is_light())
-                        .then(just(|ctx, _| {
-                            ctx.controller
-                                .say(None, Content::localized("npc-speech-day_time"))
-                        }))
-                        .map(|_, _| ())
-                        .boxed()
-                    } else {
-                        finish().boxed()
-                    }
-                })
-                .debug(|| "find somewhere to sleep"),
-            );
-        }
-        // Go do something fun on evenings and holidays, or on random days.
-        else if
-            // Ain't no rest for the wicked
-            !matches!(ctx.npc.profession(), Some(Profession::Guard))
-            && (matches!(day_period, DayPeriod::Evening) || is_weekend || ctx.rng.gen_bool(0.05)) {
-            let mut fun_stuff = Vec::new();
-
-            if let Some(ws_id) = ctx.state.data().sites[visiting_site].world_site
-                && let Some(ws) = ctx.index.sites.get(ws_id).site2() {
-                if let Some(arena) = ws.plots().find_map(|p| match p.kind() { PlotKind::DesertCityArena(a) => Some(a), _ => None}) {
-                    let wait_time = ctx.rng.gen_range(100.0..300.0);
-                    // We don't use Z coordinates for seats because they are complicated to calculate from the Ramp procedural generation
-                    // and using goto_2d seems to work just fine. However it also means that NPC will never go seat on the stands
-                    // on the first floor of the arena. This is a compromise that was made because in the current arena procedural generation
-                    // there is also no pathways to the stands on the first floor for NPCs.
-                    let arena_center = Vec3::new(arena.center.x, arena.center.y, arena.base).as_::<f32>();
-                    let stand_dist = arena.stand_dist as f32;
-                    let seat_var_width = ctx.rng.gen_range(0..arena.stand_width) as f32;
-                    let seat_var_length = ctx.rng.gen_range(-arena.stand_length..arena.stand_length) as f32;
-                    // Select a seat on one of the 4 arena stands
-                    let seat = match ctx.rng.gen_range(0..4) {
-                        0 => Vec3::new(arena_center.x - stand_dist + seat_var_width, arena_center.y + seat_var_length, arena_center.z),
-                        1 => Vec3::new(arena_center.x + stand_dist - seat_var_width, arena_center.y + seat_var_length, arena_center.z),
-                        2 => Vec3::new(arena_center.x + seat_var_length, arena_center.y - stand_dist + seat_var_width, arena_center.z),
-                        _ => Vec3::new(arena_center.x + seat_var_length, arena_center.y + stand_dist - seat_var_width, arena_center.z),
-                    };
-                    let look_dir = Dir::from_unnormalized(arena_center - seat);
-                    // Walk to an arena seat, cheer, sit and dance
-                    let action = casual(just(move |ctx, _| ctx.controller.say(None, Content::localized("npc-speech-arena")))
-                            .then(goto_2d(seat.xy(), 0.6, 1.0).debug(|| "go to arena"))
-                            // Turn toward the centre of the arena and watch the action!
-                            .then(choose(move |ctx, _| if ctx.rng.gen_bool(0.3) {
-                                casual(just(move |ctx,_| ctx.controller.do_cheer(look_dir)).repeat().stop_if(timeout(5.0)))
-                            } else if ctx.rng.gen_bool(0.15) {
-                                casual(just(move |ctx,_| ctx.controller.do_dance(look_dir)).repeat().stop_if(timeout(5.0)))
-                            } else {
-                                casual(just(move |ctx,_| ctx.controller.do_sit(look_dir, None)).repeat().stop_if(timeout(15.0)))
-                            })
-                                .repeat()
-                                .stop_if(timeout(wait_time)))
-                            .map(|_, _| ())
-                            .boxed());
-                    fun_stuff.push(action);
-                }
-                if let Some(tavern) = ws.plots().filter_map(|p| match p.kind() {  PlotKind::Tavern(a) => Some(a), _ => None }).choose(&mut ctx.rng) {
-                    let wait_time = ctx.rng.gen_range(100.0..300.0);
-
-                    let (stage_aabr, stage_z) = tavern.rooms.values().flat_map(|room| {
-                        room.details.iter().filter_map(|detail| match detail {
-                            tavern::Detail::Stage { aabr } => Some((*aabr, room.bounds.min.z + 1)),
-                            _ => None,
-                        })
-                    }).choose(&mut ctx.rng).unwrap_or((tavern.bounds, tavern.door_wpos.z));
-
-                    let bar_pos = tavern.rooms.values().flat_map(|room|
-                        room.details.iter().filter_map(|detail| match detail {
-                            tavern::Detail::Bar { aabr } => {
-                                let side = site2::util::Dir::from_vec2(room.bounds.center().xy() - aabr.center());
-                                let pos = side.select_aabr_with(*aabr, aabr.center()) + side.to_vec2();
-
-                                Some(pos.with_z(room.bounds.min.z))
-                            }
-                            _ => None,
-                        })
-                    ).choose(&mut ctx.rng).unwrap_or(stage_aabr.center().with_z(stage_z));
-
-                    // Pick a chair that is theirs for the stay
-                    let chair_pos = tavern.rooms.values().flat_map(|room| {
-                        let z = room.bounds.min.z;
-                        room.details.iter().filter_map(move |detail| match detail {
-                            tavern::Detail::Table { pos, chairs } => Some(chairs.into_iter().map(move |dir| pos.with_z(z) + dir.to_vec2())),
-                            _ => None,
-                        })
-                        .flatten()
-                    }
-                    ).choose(&mut ctx.rng)
-                    // This path is possible, but highly unlikely.
-                    .unwrap_or(bar_pos);
-
-                    let stage_aabr = stage_aabr.as_::<f32>();
-                    let stage_z = stage_z as f32;
-
-                    let action = casual(travel_to_point(tavern.door_wpos.xy().as_() + 0.5, 0.8).then(choose(move |ctx, (last_action, _)| {
-                            let action = [0, 1, 2].into_iter().filter(|i| *last_action != Some(*i)).choose(&mut ctx.rng).expect("We have at least 2 elements");
-                            let socialize = socialize().map_state(|(_, timer)| timer).repeat();
-                            match action {
-                                // Go and dance on a stage.
-                                0 => {
-                                    casual(now(move |ctx, (last_action, _)| {
-                                        *last_action = Some(action);
-                                        goto(stage_aabr.min.map2(stage_aabr.max, |a, b| ctx.rng.gen_range(a..b)).with_z(stage_z), WALKING_SPEED, 1.0)
-                                    })
-                                    .then(just(move |ctx,_| ctx.controller.do_dance(None)).repeat().stop_if(timeout(ctx.rng.gen_range(20.0..30.0))))
-                                    .map(|_, _| ())
-                                    )
-                                },
-                                // Go and sit at a table.
-                                1 => {
-                                    casual(
-                                        now(move |ctx, (last_action, _)| {
-                                            *last_action = Some(action);
-                                            goto(chair_pos.as_() + 0.5, WALKING_SPEED, 1.0).then(just(move |ctx, _| ctx.controller.do_sit(None, Some(chair_pos)))).then(socialize.clone().stop_if(timeout(ctx.rng.gen_range(30.0..60.0)))).map(|_, _| ())
-                                        })
-                                    )
-                                },
-                                // Go to the bar.
-                                _ => {
-                                    casual(
-                                        now(move |ctx, (last_action, _)| {
-                                            *last_action = Some(action);
-                                            goto(bar_pos.as_() + 0.5, WALKING_SPEED, 1.0).then(socialize.clone().stop_if(timeout(ctx.rng.gen_range(10.0..25.0)))).map(|_, _| ())
-                                        })
-                                    )
-                                },
-                            }
-                        })
-                        .with_state((None::<u32>, every_range(5.0..10.0)))
-                        .repeat()
-                        .stop_if(timeout(wait_time)))
-                        .map(|_, _| ())
-                        .boxed()
-                    );
-
-                    fun_stuff.push(action);
-                }
-            }
 
+        let schedule = Schedule::for_npc(ctx.npc);
+        let period = schedule.period_at(ctx.time_of_day);
+        // Whatever we decide to do, we stop once it's time to do something else
+        let period_over = move |ctx: &mut NpcCtx| schedule.period_at(ctx.time_of_day) != period;
 
-            if !fun_stuff.is_empty() {
-                let i = ctx.rng.gen_range(0..fun_stuff.len());
-                return fun_stuff.swap_remove(i);
-            }
-        }
-        // Villagers with roles should perform those roles
-        else if matches!(ctx.npc.profession(), Some(Profession::Herbalist)) && ctx.rng.gen_bool(0.8)
-        {
-            if let Some(forest_wpos) = find_forest(ctx) {
-                return casual(
-                    travel_to_point(forest_wpos, 0.5)
-                        .debug(|| "walk to forest")
-                        .then({
-                            let wait_time = ctx.rng.gen_range(10.0..30.0);
-                            gather_ingredients().repeat().stop_if(timeout(wait_time))
-                        })
-                        .map(|_, _| ()),
-                );
-            }
-        } else if matches!(ctx.npc.profession(), Some(Profession::Hunter)) && ctx.rng.gen_bool(0.8) {
-            if let Some(forest_wpos) = find_forest(ctx) {
-                return casual(
-                    just(|ctx, _| {
-                        ctx.controller
-                            .say(None, Content::localized("npc-speech-start_hunting"))
+        match period {
+            Period::Sleep => {
+                important(
+                    now(move |ctx, _| {
+                        if let Some(house_wpos) = plot_wpos(ctx, visiting_site, schedule::home_plot) {
+                            just(|ctx, _| {
+                                ctx.controller
+                                    .say(None, Content::localized("npc-speech-night_time"))
+                            })
+                            .then(travel_to_point(house_wpos, 0.65))
+                            .debug(|| "walk home")
+                            .then(sleep())
+                            .stop_if(period_over)
+                            .then(just(|ctx, _| {
+                                ctx.controller
+                                    .say(None, Content::localized("npc-speech-day_time"))
+                            }))
+                            .map(|_, _| ())
+                            .boxed()
+                        } else {
+                            // Nowhere to sleep, so sleep where we are
+                            sleep().stop_if(period_over).map(|_, _| ()).boxed()
+                        }
                     })
-                    .then(travel_to_point(forest_wpos, 0.75))
-                    .debug(|| "walk to forest")
-                    .then({
-                        let wait_time = ctx.rng.gen_range(30.0..60.0);
-                        hunt_animals().repeat().stop_if(timeout(wait_time))
+                    .debug(|| "go to bed"),
+                )
+            },
+            // Outdoor work and fun can wait until the weather improves
+            Period::Work | Period::Leisure
+                if is_bad_weather(ctx, visiting_site)
+                    && (period == Period::Leisure
+                        || matches!(
+                            ctx.npc.profession(),
+                            Some(Profession::Herbalist | Profession::Hunter | Profession::Farmer)
+                        )) =>
+            {
+                important(
+                    now(move |ctx, _| {
+                        if period == Period::Leisure && let Some(tavern) = go_to_tavern(ctx, visiting_site, f64::INFINITY) {
+                            tavern
+                        } else if let Some(house_wpos) = plot_wpos(ctx, visiting_site, schedule::home_plot) {
+                            stay_inside(house_wpos).boxed()
+                        } else {
+                            idle().repeat().map(|_, _| ()).boxed()
+                        }
                     })
+                    .stop_if(move |ctx: &mut NpcCtx| period_over(ctx) || !is_bad_weather(ctx, visiting_site))
+                    .debug(|| "shelter from the weather")
                     .map(|_, _| ()),
-                );
-            }
-        } else if matches!(ctx.npc.profession(), Some(Profession::Guard)) && ctx.rng.gen_bool(0.7) {
-            if let Some(plaza_wpos) = choose_plaza(ctx, visiting_site) {
-                return casual(
-                    travel_to_point(plaza_wpos, 0.4)
-                        .debug(|| "patrol")
-                        .interrupt_with(move |ctx, _| {
-                            if ctx.rng.gen_bool(0.0003) {
-                                Some(just(move |ctx, _| {
-                                    ctx.controller
-                                        .say(None, Content::localized("npc-speech-guard_thought"))
-                                }))
-                            } else {
-                                None
-                            }
-                        })
+                )
+            },
+            Period::Eat => {
+                casual(
+                    now(move |ctx, _| {
+                        if let Some(tavern) = go_to_tavern(ctx, visiting_site, f64::INFINITY) {
+                            tavern
+                        } else if let Some(house_wpos) = plot_wpos(ctx, visiting_site, schedule::home_plot) {
+                            stay_inside(house_wpos).boxed()
+                        } else {
+                            finish().boxed()
+                        }
+                    })
+                    .stop_if(period_over)
+                    .debug(|| "eat")
+                    .map(|_, _| ()),
+                )
+            },
+            // Children and the elderly don't work, so they just wander around
+            Period::Work if !ctx.npc.is_working_age(ctx.time_of_day) => {
+                casual(wander(visiting_site).stop_if(period_over).debug(|| "play").map(|_, _| ()))
+            },
+            Period::Work => {
+                casual(
+                    now(move |ctx, _| work(ctx, visiting_site).unwrap_or_else(|| wander(visiting_site).boxed()))
+                        .stop_if(period_over)
+                        .debug(|| "work")
                         .map(|_, _| ()),
-                );
-            }
-        } else if matches!(ctx.npc.profession(), Some(Profession::Merchant)) && ctx.rng.gen_bool(0.8)
-        {
-            return casual(
-                just(|ctx, _| {
-                    // Try to direct our speech at nearby actors, if there are any
-                    let (target, phrase) = if ctx.rng.gen_bool(0.3) && let Some(other) = ctx
-                        .state
-                        .data()
-                        .npcs
-                        .nearby(Some(ctx.npc_id), ctx.npc.wpos, 8.0)
-                        .choose(&mut ctx.rng)
-                    {
-                        (Some(other), "npc-speech-merchant_sell_directed")
-                    } else {
-                        // Otherwise, resort to generic expressions
-                        (None, "npc-speech-merchant_sell_undirected")
-                    };
+                )
+            },
+            Period::Leisure => {
+                casual(
+                    now(move |ctx, _| {
+                        let mut fun_stuff = Vec::new();
+
+                        let index = ctx.index;
+                        let world_site = ctx.state.data().sites[visiting_site].world_site;
+                        if let Some(ws) = world_site.and_then(|ws| index.sites.get(ws).site2()) {
+                            if let Some(arena) = ws.plots().find_map(|p| match p.kind() { PlotKind::DesertCityArena(a) => Some(a), _ => None}) {
+                                fun_stuff.push(watch_arena(ctx, arena).boxed());
+                            }
+                            if let Some(tavern) = ws.plots().filter_map(|p| match p.kind() {  PlotKind::Tavern(a) => Some(a), _ => None }).choose(&mut ctx.rng) {
+                                let wait_time = ctx.rng.gen_range(100.0..300.0);
+                                fun_stuff.push(visit_tavern(ctx, tavern, wait_time).boxed());
+                            }
+                        }
+                        fun_stuff.push(wander(visiting_site).boxed());
 
-                    ctx.controller.say(target, Content::localized(phrase));
-                })
-                .then(idle().repeat().stop_if(timeout(8.0)))
-                .repeat()
-                .stop_if(timeout(60.0))
-                .debug(|| "sell wares")
-                .map(|_, _| ()),
-            );
+                        let i = ctx.rng.gen_range(0..fun_stuff.len());
+                        fun_stuff.swap_remove(i)
+                    })
+                    .stop_if(period_over)
+                    .debug(|| "have fun")
+                    .map(|_, _| ()),
+                )
+            },
         }
-
-        // If nothing else needs doing, walk between plazas and socialize
-        casual(now(move |ctx, _| {
-            // Choose a plaza in the site we're visiting to walk to
-            if let Some(plaza_wpos) = choose_plaza(ctx, visiting_site) {
-                // Walk to the plaza...
-                Either::Left(travel_to_point(plaza_wpos, 0.5)
-                    .debug(|| "walk to plaza"))
-            } else {
-                // No plazas? :(
-                Either::Right(finish())
-            }
-                // ...then socialize for some time before moving on
-                .then(socialize()
-                    .repeat()
-                    .map_state(|state: &mut DefaultState| &mut state.socialize_timer)
-                    .stop_if(timeout(ctx.rng.gen_range(30.0..90.0)))
-                    .debug(|| "wait at plaza"))
-                .map(|_, _| ())
-        }))
     })
     .debug(move || format!("villager at site {:?}", visiting_site))
 }
 
+/// Walk between plazas and socialize
+fn wander(visiting_site: SiteId) -> impl Action<DefaultState> {
+    now(move |ctx, _| {
+        // Choose a plaza in the site we're visiting to walk to
+        if let Some(plaza_wpos) = choose_plaza(ctx, visiting_site) {
+            // Walk to the plaza...
+            Either::Left(travel_to_point(plaza_wpos, 0.5)
+                .debug(|| "walk to plaza"))
+        } else {
+            // No plazas? :(
+            Either::Right(finish())
+        }
+            // ...then socialize for some time before moving on
+            .then(socialize()
+                .repeat()
+                .map_state(|state: &mut DefaultState| &mut state.socialize_timer)
+                .stop_if(timeout(ctx.rng.gen_range(30.0..90.0)))
+                .debug(|| "wait at plaza"))
+            .map(|_, _| ())
+    })
+}
+
 /*
 fn follow(npc: NpcId, distance: f32) -> impl Action {
     const STEP_DIST: f32 = 1.0;
@@ -1261,59 +1777,151 @@ fn captain<S: State>() -> impl Action<S> {
     .map(|_, _| ())
 }
 
+/// Decide how to react to learning of a report, returning something to say if
+/// the report is worth remarking upon.
+///
+/// Reports heard second-hand have less of an effect than those witnessed
+/// first-hand, according to their credibility.
+fn react_to_report(ctx: &mut NpcCtx, report_id: ReportId) -> Option<(Option<Actor>, Content)> {
+    let kind = ctx.state.data().reports.get(report_id)?.kind;
+    let credibility = ctx.npc.rumours.credibility(report_id);
+    let witnessed = !ctx.npc.rumours.is_rumour(report_id);
+    let is_civilised = matches!(&ctx.npc.role, Role::Civilised(_));
+
+    match kind {
+        ReportKind::Death { killer, actor, .. } if is_civilised => {
+            // TODO: Don't report self
+            let phrase = if let Some(killer) = killer {
+                // TODO: For now, we don't make sentiment changes if the killer was an
+                // NPC because NPCs can't hurt one-another.
+                // This should be changed in the future.
+                if !matches!(killer, Actor::Npc(_)) {
+                    // TODO: Don't hard-code sentiment change
+                    let mut change = -0.7 * credibility;
+                    if ctx.sentiments.toward(actor).is(Sentiment::ENEMY) {
+                        // Like the killer if we have negative sentiment towards the
+                        // killed.
+                        change *= -1.0;
+                    }
+                    ctx.sentiments
+                        .toward_mut(killer)
+                        .change_by(change, Sentiment::VILLAIN);
+                }
+
+                // This is a murder of a player. Feel bad for the player and stop
+                // attacking them.
+                if let Actor::Character(_) = actor {
+                    ctx.sentiments
+                        .toward_mut(actor)
+                        .limit_below(Sentiment::ENEMY)
+                }
+
+                if ctx.sentiments.toward(actor).is(Sentiment::ENEMY) {
+                    "npc-speech-witness_enemy_murder"
+                } else {
+                    "npc-speech-witness_murder"
+                }
+            } else {
+                "npc-speech-witness_death"
+            };
+            // Only cry out at deaths that we saw happen
+            witnessed.then(|| (killer, Content::localized(phrase)))
+        },
+        // Raids on our own faction are worth talking about
+        ReportKind::Raid {
+            site,
+            attacker,
+            defender,
+            captured,
+        } if ctx.npc.faction.is_some()
+            && (ctx.npc.faction == defender || ctx.npc.faction == Some(attacker)) =>
+        {
+            let phrase = match (ctx.npc.faction == Some(attacker), captured) {
+                (true, true) => "npc-speech-raid_won",
+                (true, false) => "npc-speech-raid_failed",
+                (false, true) => "npc-speech-raid_lost",
+                (false, false) => "npc-speech-raid_repelled",
+            };
+            let site_name = ctx
+                .state
+                .data()
+                .sites
+                .get(site)
+                .and_then(|site| site.world_site)
+                .map(|ws| ctx.index.sites.get(ws).name().to_string())
+                .unwrap_or_default();
+            Some((
+                None,
+                Content::localized_with_args(phrase, [("site", site_name)]),
+            ))
+        },
+        ReportKind::Theft { thief, .. } if is_civilised && !matches!(thief, Actor::Npc(_)) => {
+            ctx.sentiments
+                .toward_mut(thief)
+                .change_by(-0.3 * credibility, Sentiment::RIVAL);
+            witnessed.then(|| (Some(thief), Content::localized("npc-speech-witness_theft")))
+        },
+        ReportKind::Assault { attacker, victim }
+            if is_civilised
+                && !matches!(attacker, Actor::Npc(_))
+                && victim != Actor::Npc(ctx.npc_id) =>
+        {
+            // Somebody attacking our enemies is no bad thing
+            let change = if ctx.sentiments.toward(victim).is(Sentiment::ENEMY) {
+                0.1
+            } else {
+                -0.2
+            };
+            ctx.sentiments
+                .toward_mut(attacker)
+                .change_by(change * credibility, Sentiment::RIVAL);
+            (witnessed && change < 0.0).then(|| {
+                (
+                    Some(attacker),
+                    Content::localized("npc-speech-witness_assault"),
+                )
+            })
+        },
+        // People who bring business to our home are welcome
+        ReportKind::Trade {
+            site,
+            customer: Some(customer @ Actor::Character(_)),
+        } if is_civilised && ctx.npc.home == Some(site) => {
+            ctx.sentiments
+                .toward_mut(customer)
+                .change_by(0.05 * credibility, Sentiment::POSITIVE);
+            None
+        },
+        ReportKind::Heroics { hero, .. } if is_civilised => {
+            ctx.sentiments
+                .toward_mut(hero)
+                .change_by(0.2 * credibility, Sentiment::FRIEND);
+            witnessed.then(|| (Some(hero), Content::localized("npc-speech-witness_heroics")))
+        },
+        // Everything else is only of interest as gossip
+        ReportKind::Death { .. }
+        | ReportKind::Raid { .. }
+        | ReportKind::Theft { .. }
+        | ReportKind::Assault { .. }
+        | ReportKind::Trade { .. }
+        | ReportKind::MonsterSighting { .. }
+        | ReportKind::Heroics { .. } => None,
+    }
+}
+
 fn check_inbox<S: State>(ctx: &mut NpcCtx) -> Option<impl Action<S>> {
     loop {
         match ctx.inbox.pop_front() {
             Some(NpcInput::Report(report_id)) if !ctx.known_reports.contains(&report_id) => {
-                #[allow(clippy::single_match)]
-                match ctx.state.data().reports.get(report_id).map(|r| r.kind) {
-                    Some(ReportKind::Death { killer, actor, .. })
-                        if matches!(&ctx.npc.role, Role::Civilised(_)) =>
-                    {
-                        // TODO: Don't report self
-                        let phrase = if let Some(killer) = killer {
-                            // TODO: For now, we don't make sentiment changes if the killer was an
-                            // NPC because NPCs can't hurt one-another.
-                            // This should be changed in the future.
-                            if !matches!(killer, Actor::Npc(_)) {
-                                // TODO: Don't hard-code sentiment change
-                                let mut change = -0.7;
-                                if ctx.sentiments.toward(actor).is(Sentiment::ENEMY) {
-                                    // Like the killer if we have negative sentiment towards the
-                                    // killed.
-                                    change *= -1.0;
-                                }
-                                ctx.sentiments
-                                    .toward_mut(killer)
-                                    .change_by(change, Sentiment::VILLAIN);
-                            }
-
-                            // This is a murder of a player. Feel bad for the player and stop
-                            // attacking them.
-                            if let Actor::Character(_) = actor {
-                                ctx.sentiments
-                                    .toward_mut(actor)
-                                    .limit_below(Sentiment::ENEMY)
-                            }
-
-                            if ctx.sentiments.toward(actor).is(Sentiment::ENEMY) {
-                                "npc-speech-witness_enemy_murder"
-                            } else {
-                                "npc-speech-witness_murder"
-                            }
-                        } else {
-                            "npc-speech-witness_death"
-                        };
-                        ctx.known_reports.insert(report_id);
-                        break Some(
-                            just(move |ctx, _| {
-                                ctx.controller.say(killer, Content::localized(phrase))
-                            })
-                            .l(),
-                        );
-                    },
-                    Some(ReportKind::Death { .. }) => {}, // We don't care about death
-                    None => {},                           // Stale report, ignore
+                // Stale reports are ignored
+                if !ctx.state.data().reports.contains_key(report_id) {
+                    continue;
+                }
+                ctx.known_reports.insert(report_id);
+                if let Some((target, comment)) = react_to_report(ctx, report_id) {
+                    break Some(
+                        just(move |ctx, _| ctx.controller.say(target, comment.clone())).l(),
+                    );
                 }
             },
             Some(NpcInput::Report(_)) => {}, // Reports we already know of are ignored
@@ -1338,10 +1946,287 @@ fn check_for_enemies<S: State>(ctx: &mut NpcCtx) -> Option<impl Action<S>> {
         .map(|enemy| just(move |ctx, _| ctx.controller.attack(enemy)))
 }
 
+/// The bounty at which guards will try to arrest a criminal.
+const ARREST_BOUNTY: u32 = 50;
+/// The bounty at which guards will attack a criminal on sight.
+const WANTED_BOUNTY: u32 = 300;
+/// How long, in seconds, a criminal has to pay off their bounty once a guard
+/// has tried to arrest them.
+const ARREST_GRACE: f64 = 60.0;
+
+fn check_for_wanted<S: State>(ctx: &mut NpcCtx) -> Option<impl Action<S>> {
+    if !matches!(ctx.npc.profession(), Some(Profession::Guard)) {
+        return None;
+    }
+    let site = ctx.npc.home?;
+    let data = ctx.state.data();
+    // Criminals that we're already dealing with are skipped
+    let (criminal, bounty) = data
+        .npcs
+        .nearby(Some(ctx.npc_id), ctx.npc.wpos, 24.0)
+        .filter(|actor| !ctx.sentiments.toward(*actor).is(Sentiment::RIVAL))
+        .map(|actor| (actor, data.sites.bounty_on(site, actor)))
+        .filter(|(_, bounty)| *bounty >= ARREST_BOUNTY)
+        .max_by_key(|(_, bounty)| *bounty)?;
+
+    Some(if bounty >= WANTED_BOUNTY {
+        just(move |ctx, _| {
+            ctx.sentiments
+                .toward_mut(criminal)
+                .change_by(Sentiment::VILLAIN, Sentiment::VILLAIN);
+            ctx.controller
+                .say(criminal, Content::localized("npc-speech-guard_wanted"));
+            ctx.controller.attack(criminal);
+        })
+        .l()
+    } else {
+        arrest(criminal, site).r()
+    })
+}
+
+/// Confront a criminal, giving them the chance to pay off their bounty before
+/// resorting to force.
+fn arrest<S: State>(criminal: Actor, site: SiteId) -> impl Action<S> {
+    let bounty_paid = move |ctx: &mut NpcCtx| ctx.state.data().sites.bounty_on(site, criminal) == 0;
+    just(move |ctx, _| {
+        let bounty = ctx.state.data().sites.bounty_on(site, criminal);
+        // We don't trust the criminal, but we won't use force just yet
+        ctx.sentiments
+            .toward_mut(criminal)
+            .change_by(-0.5, Sentiment::ENEMY);
+        ctx.controller.say(
+            criminal,
+            Content::localized_with_args("npc-speech-guard_arrest", [(
+                "bounty",
+                Content::Plain(bounty.to_string()),
+            )]),
+        );
+    })
+    .then(
+        now(move |ctx, _| {
+            let wpos = match criminal {
+                Actor::Character(character) => ctx.state.data().npcs.character_wpos(character),
+                Actor::Npc(npc) => ctx.state.data().npcs.get(npc).map(|npc| npc.wpos),
+            };
+            if let Some(wpos) = wpos {
+                goto(wpos, 0.6, 3.0).boxed()
+            } else {
+                idle().boxed()
+            }
+        })
+        .repeat()
+        .stop_if(bounty_paid)
+        .stop_if(timeout(ARREST_GRACE)),
+    )
+    .then(just(move |ctx, _| {
+        if bounty_paid(ctx) {
+            ctx.controller
+                .say(criminal, Content::localized("npc-speech-guard_bounty_paid"));
+        } else {
+            ctx.sentiments
+                .toward_mut(criminal)
+                .change_by(Sentiment::VILLAIN, Sentiment::VILLAIN);
+            ctx.controller.say(
+                criminal,
+                Content::localized("npc-speech-guard_resisting_arrest"),
+            );
+        }
+    }))
+    .debug(move || format!("arrest {:?}", criminal))
+}
+
 fn react_to_events<S: State>(ctx: &mut NpcCtx, _: &mut S) -> Option<impl Action<S>> {
     check_inbox::<S>(ctx)
         .map(|action| action.boxed())
         .or_else(|| check_for_enemies(ctx).map(|action| action.boxed()))
+        .or_else(|| check_for_wanted(ctx).map(|action| action.boxed()))
+}
+
+/// Travel to a site with the rest of a raiding party and attack anybody there
+/// who belongs to the faction that owns it.
+fn raid_site(target: SiteId) -> impl Action<DefaultState> {
+    now(move |ctx, _| {
+        let site_name = ctx
+            .state
+            .data()
+            .sites
+            .get(target)
+            .and_then(|site| site.world_site)
+            .map(|ws| ctx.index.sites.get(ws).name().to_string())
+            .unwrap_or_default();
+        just(move |ctx, _| {
+            ctx.controller.say(
+                None,
+                Content::localized_with_args("npc-speech-raid_start", [(
+                    "site",
+                    site_name.clone(),
+                )]),
+            )
+        })
+    })
+    .then(travel_to_site(target, 0.75))
+    .then(choose(move |ctx, _| {
+        let data = ctx.state.data();
+        let defender = data.sites.get(target).and_then(|site| site.faction);
+        let enemy = data
+            .npcs
+            .nearby(Some(ctx.npc_id), ctx.npc.wpos, 24.0)
+            .find(|actor| {
+                actor
+                    .npc()
+                    .and_then(|npc| data.npcs.get(npc))
+                    .map_or(false, |npc| {
+                        !npc.is_dead && npc.faction.is_some() && npc.faction == defender
+                    })
+            });
+        if let Some(enemy) = enemy {
+            important(
+                just(move |ctx, _| ctx.controller.attack(enemy))
+                    .repeat()
+                    .stop_if(timeout(5.0))
+                    .map(|_, _| ()),
+            )
+        } else {
+            // Search the site for defenders
+            casual(now(move |ctx, _| {
+                let site_wpos = ctx
+                    .state
+                    .data()
+                    .sites
+                    .get(target)
+                    .map_or(ctx.npc.wpos.xy(), |site| site.wpos.as_());
+                let offset = Vec2::new(
+                    ctx.rng.gen_range(-48.0..48.0),
+                    ctx.rng.gen_range(-48.0..48.0),
+                );
+                goto_2d(site_wpos + offset, 0.6, 4.0)
+            }))
+        }
+    }))
+    .stop_if(move |ctx: &mut NpcCtx| {
+        ctx.state
+            .data()
+            .factions
+            .raid_of(ctx.npc.faction, ctx.npc_id)
+            .map_or(true, |raid| raid.target != target)
+    })
+    .map(|_, _| ())
+    .debug(move || format!("raid site {:?}", target))
+}
+
+/// Lead a caravan: pick up the goods from the site that sold them, carry them
+/// to the site that bought them, and then head back.
+fn lead_caravan(caravan: CaravanId) -> impl Action<DefaultState> {
+    let stage_of = move |ctx: &mut NpcCtx| ctx.state.data().caravans.get(caravan).map(|c| c.stage);
+    now(move |ctx, _| {
+        let Some((from, to, stage, escorts)) =
+            ctx.state.data().caravans.get(caravan).map(|caravan| {
+                (
+                    caravan.from,
+                    caravan.to,
+                    caravan.stage,
+                    caravan.escorts.clone(),
+                )
+            })
+        else {
+            return finish().boxed();
+        };
+        let (target, speed_factor) = match stage {
+            CaravanStage::Gathering | CaravanStage::Returning => (from, 0.6),
+            // Go steady so that the pack animals can keep up
+            CaravanStage::Delivering => (to, 0.5),
+        };
+        let site_name = ctx
+            .state
+            .data()
+            .sites
+            .get(to)
+            .and_then(|site| site.world_site)
+            .map(|ws| ctx.index.sites.get(ws).name().to_string())
+            .unwrap_or_default();
+
+        just(move |ctx, _| match stage {
+            CaravanStage::Delivering => ctx.controller.say(
+                None,
+                Content::localized_with_args("npc-speech-caravan_setting_out", [(
+                    "site",
+                    site_name.clone(),
+                )]),
+            ),
+            // Thank those who saw the caravan safely to its destination
+            CaravanStage::Returning => {
+                for escort in &escorts {
+                    ctx.sentiments
+                        .toward_mut(*escort)
+                        .change_by(Sentiment::POSITIVE, Sentiment::ALLY);
+                }
+                if let Some(escort) = escorts.first() {
+                    ctx.controller.say(
+                        *escort,
+                        Content::localized("npc-speech-caravan_escort_thanks"),
+                    );
+                }
+            },
+            CaravanStage::Gathering => {},
+        })
+        .then(travel_to_site(target, speed_factor))
+        // Wait for the caravan to move on to its next stage
+        .then(idle().repeat())
+        .stop_if(move |ctx: &mut NpcCtx| stage_of(ctx) != Some(stage))
+        .map(|_, _| ())
+        .boxed()
+    })
+    .debug(move || format!("lead caravan {:?}", caravan))
+}
+
+/// Pack animals follow the merchant leading their caravan, and otherwise make
+/// their way back to where they're kept.
+fn pack_animal() -> impl Action<DefaultState> {
+    const FOLLOW_DIST: f32 = 6.0;
+
+    now(|ctx, _| {
+        let caravan = ctx
+            .state
+            .data()
+            .caravans
+            .caravan_of(ctx.npc_id)
+            .map(|(caravan_id, _)| caravan_id);
+        if let Some(caravan) = caravan {
+            just(move |ctx, _| {
+                let data = ctx.state.data();
+                let leader = data
+                    .caravans
+                    .get(caravan)
+                    .and_then(|caravan| data.npcs.get(caravan.merchant))
+                    .map(|merchant| merchant.wpos);
+                match leader {
+                    Some(wpos)
+                        if wpos.xy().distance_squared(ctx.npc.wpos.xy()) > FOLLOW_DIST.powi(2) =>
+                    {
+                        ctx.controller.do_goto(wpos, 0.7)
+                    },
+                    _ => ctx.controller.do_idle(),
+                }
+            })
+            .repeat()
+            .stop_if(move |ctx: &mut NpcCtx| !ctx.state.data().caravans.contains_key(caravan))
+            .map(|_, _| ())
+            .debug(|| "follow caravan")
+            .l()
+        } else if let Some(home_wpos) = ctx
+            .npc
+            .home
+            .and_then(|home| ctx.state.data().sites.get(home).map(|site| site.wpos.as_()))
+            .filter(|home_wpos: &Vec2<f32>| home_wpos.distance(ctx.npc.wpos.xy()) > 64.0)
+        {
+            travel_to_point(home_wpos, 0.5)
+                .debug(|| "return home")
+                .l()
+                .r()
+        } else {
+            idle().r().r()
+        }
+    })
 }
 
 fn humanoid() -> impl Action<DefaultState> {
@@ -1367,6 +2252,15 @@ fn humanoid() -> impl Action<DefaultState> {
                     socialize().map_state(|state: &mut DefaultState| &mut state.socialize_timer),
                 )
             }
+        } else if let Some((caravan, _)) = ctx.state.data().caravans.caravan_of(ctx.npc_id) {
+            important(lead_caravan(caravan))
+        } else if let Some(raid) = ctx
+            .state
+            .data()
+            .factions
+            .raid_of(ctx.npc.faction, ctx.npc_id)
+        {
+            important(raid_site(raid.target))
         } else {
             let action = if matches!(
                 ctx.npc.profession(),
@@ -1527,8 +2421,8 @@ fn think() -> impl Action<DefaultState> {
                 .r()
                 .l(),
             Role::Monster => monster().r().r().l(),
-            Role::Wild => idle().r(),
-            Role::Vehicle => idle().r(),
+            Role::PackAnimal => pack_animal().l().r(),
+            Role::Wild | Role::Vehicle => idle().r().r(),
         },
     })
 }
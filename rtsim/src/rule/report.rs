@@ -1,29 +1,107 @@
 use crate::{
-    data::{report::ReportKind, Report},
-    event::{EventCtx, OnDeath},
+    data::{report::ReportKind, Data, Report, ReportId, Rumours},
+    event::{EventCtx, OnDeath, OnHurt, OnLootContainer, OnTick, OnTrade},
     RtState, Rule, RuleError,
 };
-use common::rtsim::NpcInput;
+use common::{
+    rtsim::{Actor, NpcId, NpcInput, Role, SiteId},
+    terrain::CoordinateConversions,
+};
+use hashbrown::HashMap;
+use vek::*;
+use world::World;
+
+/// How far away NPCs can be and still witness an event.
+pub(crate) const WITNESS_RANGE: f32 = 32.0;
+/// How far away NPCs can spot a monster from.
+// TODO: Monsters are big, they should be visible from further away than this,
+// but the NPC grid can't efficiently find NPCs much further away.
+const SIGHTING_RANGE: f32 = 32.0;
+/// How many ticks should pass between checking whether each monster has been
+/// spotted.
+const SIGHTING_TICK_SKIP: u64 = 600;
+/// How long, in in-game seconds, before the same monster will be reported
+/// again.
+const SIGHTING_COOLDOWN: f64 = 60.0 * 60.0 * 6.0;
+/// How long, in in-game seconds, before the same attacker hurting the same
+/// victim counts as a new assault.
+const ASSAULT_COOLDOWN: f64 = 60.0 * 30.0;
+/// How far away a site can be and still hear tell of heroics.
+const HEROICS_RANGE: f32 = 2000.0;
+
+/// When each attacker started assaulting each victim, so that every blow of a
+/// fight isn't treated as a new assault. This is shared by all rules as an
+/// [`RtState`] resource, so that they agree on which blows start a fight.
+#[derive(Default)]
+pub(crate) struct AssaultCooldowns {
+    assaults: HashMap<(Actor, Actor), f64>,
+}
 
-pub struct ReportEvents;
+impl AssaultCooldowns {
+    /// Whether a blow starts a new assault, rather than being part of a fight
+    /// that has already started. Every rule that asks about the same blow gets
+    /// the same answer.
+    pub(crate) fn is_new(&mut self, attacker: Actor, victim: Actor, now: f64) -> bool {
+        self.assaults
+            .retain(|_, started| now - *started < ASSAULT_COOLDOWN);
+        *self.assaults.entry((attacker, victim)).or_insert(now) == now
+    }
+}
+
+pub struct ReportEvents {
+    /// When each monster was last reported being sighted.
+    last_sightings: HashMap<NpcId, f64>,
+}
 
 impl Rule for ReportEvents {
     fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
         rtstate.bind::<Self, OnDeath>(on_death);
+        rtstate.bind::<Self, OnHurt>(on_hurt);
+        rtstate.bind::<Self, OnLootContainer>(on_loot_container);
+        rtstate.bind::<Self, OnTrade>(on_trade);
+        rtstate.bind::<Self, OnTick>(on_tick);
 
-        Ok(Self)
+        Ok(Self {
+            last_sightings: HashMap::default(),
+        })
     }
 }
 
+/// The NPCs close enough to an event to witness it.
+fn witnesses(data: &Data, wpos: Vec3<f32>, range: f32) -> Vec<NpcId> {
+    data.npcs
+        .nearby(None, wpos, range)
+        .filter_map(|actor| actor.npc())
+        .collect()
+}
+
+/// Tell witnesses about a report.
+// TODO: Don't push report to NPC inboxes, have a dedicated data structure that
+// tracks reports by chunks and then have NPCs decide to query this data
+// structure in their own time.
+fn inform(data: &mut Data, witnesses: &[NpcId], report: ReportId) {
+    for npc_id in witnesses {
+        if let Some(npc) = data.npcs.get_mut(*npc_id) {
+            npc.inbox.push_back(NpcInput::Report(report));
+        }
+    }
+}
+
+/// The site at the given position, if any.
+pub(crate) fn site_at(data: &Data, world: &World, wpos: Vec2<f32>) -> Option<SiteId> {
+    world
+        .sim()
+        .get(wpos.as_::<i32>().wpos_to_cpos())?
+        .sites
+        .iter()
+        .find_map(|site| data.sites.world_site_map.get(site).copied())
+}
+
 fn on_death(ctx: EventCtx<ReportEvents, OnDeath>) {
     let data = &mut *ctx.state.data_mut();
 
     if let Some(wpos) = ctx.event.wpos {
-        let nearby = data
-            .npcs
-            .nearby(None, wpos, 32.0)
-            .filter_map(|actor| actor.npc())
-            .collect::<Vec<_>>();
+        let nearby = witnesses(data, wpos, WITNESS_RANGE);
 
         if !nearby.is_empty() {
             let report = data.reports.create(Report {
@@ -33,15 +111,200 @@ fn on_death(ctx: EventCtx<ReportEvents, OnDeath>) {
                 },
                 at: data.time_of_day,
             });
+            inform(data, &nearby, report);
+        }
+
+        // Slaying a monster is a heroic deed that people will talk about, even if
+        // nobody saw it happen
+        if let Some(hero @ Actor::Character(_)) = ctx.event.killer
+            && let Some(foe) = ctx
+                .event
+                .actor
+                .npc()
+                .and_then(|npc| data.npcs.get(npc))
+                .filter(|npc| matches!(npc.role, Role::Monster))
+                .map(|npc| npc.body)
+        {
+            let report = data.reports.create(Report {
+                kind: ReportKind::Heroics { hero, foe },
+                at: data.time_of_day,
+            });
+            inform(data, &nearby, report);
 
-            // TODO: Don't push report to NPC inboxes, have a dedicated data structure that
-            // tracks reports by chunks and then have NPCs decide to query this
-            // data structure in their own time.
-            for npc_id in nearby {
-                if let Some(npc) = data.npcs.get_mut(npc_id) {
-                    npc.inbox.push_back(NpcInput::Report(report));
-                }
+            // Word of the deed makes its way to the nearest site
+            if let Some(site) = data
+                .sites
+                .values_mut()
+                .map(|site| (site.wpos.as_::<f32>().distance(wpos.xy()), site))
+                .filter(|(dist, _)| *dist < HEROICS_RANGE)
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, site)| site)
+            {
+                site.hear_report(report, Rumours::SPREAD_FACTOR);
             }
         }
     }
 }
+
+fn on_hurt(ctx: EventCtx<ReportEvents, OnHurt>) {
+    let data = &mut *ctx.state.data_mut();
+    let (actor, attacker) = (ctx.event.actor, ctx.event.attacker);
+
+    // Don't report every blow of a fight
+    let now = data.time_of_day.0;
+    if !ctx
+        .state
+        .resource_mut::<AssaultCooldowns>()
+        .is_new(attacker, actor, now)
+    {
+        return;
+    }
+
+    // Nobody cares about monsters getting hurt
+    if actor
+        .npc()
+        .and_then(|npc| data.npcs.get(npc))
+        .map_or(false, |npc| !matches!(npc.role, Role::Civilised(_)))
+    {
+        return;
+    }
+
+    if let Some(wpos) = ctx.event.wpos {
+        let nearby = witnesses(data, wpos, WITNESS_RANGE);
+
+        if !nearby.is_empty() {
+            let report = data.reports.create(Report {
+                kind: ReportKind::Assault {
+                    attacker,
+                    victim: actor,
+                },
+                at: data.time_of_day,
+            });
+            inform(data, &nearby, report);
+        }
+    }
+}
+
+fn on_loot_container(ctx: EventCtx<ReportEvents, OnLootContainer>) {
+    let data = &mut *ctx.state.data_mut();
+    let wpos = ctx.event.wpos.as_::<f32>();
+
+    // Taking things from a container in the wilderness isn't stealing
+    let Some(site) = site_at(data, ctx.world, wpos.xy()) else {
+        return;
+    };
+    let Some(faction) = data.sites.get(site).and_then(|site| site.faction) else {
+        return;
+    };
+
+    // Only those who live in the site would know that the container wasn't the
+    // thief's to take
+    let nearby = witnesses(data, wpos, WITNESS_RANGE)
+        .into_iter()
+        .filter(|npc| {
+            data.npcs
+                .get(*npc)
+                .map_or(false, |npc| npc.faction == Some(faction))
+        })
+        .collect::<Vec<_>>();
+
+    if !nearby.is_empty() {
+        let report = data.reports.create(Report {
+            kind: ReportKind::Theft {
+                thief: ctx.event.actor,
+                site: Some(site),
+            },
+            at: data.time_of_day,
+        });
+        inform(data, &nearby, report);
+        if let Some(site) = data.sites.get_mut(site) {
+            site.known_reports.insert(report);
+        }
+    }
+}
+
+fn on_trade(ctx: EventCtx<ReportEvents, OnTrade>) {
+    let data = &mut *ctx.state.data_mut();
+    let now = data.time_of_day.0;
+
+    let Some(site) = data.sites.get(ctx.event.site) else {
+        return;
+    };
+    // Regular customers only get talked about once a day
+    let recently_traded = site.known_reports.iter().any(|report| {
+        data.reports
+            .get(*report)
+            .map_or(false, |report| match report.kind {
+                ReportKind::Trade { customer, .. } => {
+                    customer == ctx.event.customer && now - report.at.0 < 60.0 * 60.0 * 24.0
+                },
+                _ => false,
+            })
+    });
+
+    if !recently_traded {
+        let report = data.reports.create(Report {
+            kind: ReportKind::Trade {
+                site: ctx.event.site,
+                customer: ctx.event.customer,
+            },
+            at: data.time_of_day,
+        });
+        if let Some(site) = data.sites.get_mut(ctx.event.site) {
+            site.known_reports.insert(report);
+        }
+    }
+}
+
+fn on_tick(ctx: EventCtx<ReportEvents, OnTick>) {
+    let data = &mut *ctx.state.data_mut();
+    let now = data.time_of_day.0;
+
+    ctx.rule
+        .last_sightings
+        .retain(|_, last| now - *last < SIGHTING_COOLDOWN);
+
+    // Find monsters that have been spotted by civilised folk
+    let sightings = data
+        .npcs
+        .iter()
+        // Only check monsters every few ticks
+        .filter(|(_, npc)| (npc.seed as u64 + ctx.event.tick) % SIGHTING_TICK_SKIP == 0)
+        .filter(|(npc_id, npc)| {
+            !npc.is_dead
+                && matches!(npc.role, Role::Monster)
+                && !ctx.rule.last_sightings.contains_key(npc_id)
+        })
+        .filter_map(|(npc_id, npc)| {
+            let spotters = witnesses(data, npc.wpos, SIGHTING_RANGE)
+                .into_iter()
+                .filter(|spotter| {
+                    data.npcs
+                        .get(*spotter)
+                        .map_or(false, |spotter| matches!(spotter.role, Role::Civilised(_)))
+                })
+                .collect::<Vec<_>>();
+            (!spotters.is_empty()).then(|| {
+                (
+                    ReportKind::MonsterSighting {
+                        monster: npc_id,
+                        body: npc.body,
+                        wpos: npc.wpos.xy(),
+                    },
+                    spotters,
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (kind, spotters) in sightings {
+        if let ReportKind::MonsterSighting { monster, .. } = kind {
+            ctx.rule.last_sightings.insert(monster, now);
+        }
+        let report = data.reports.create(Report {
+            kind,
+            at: data.time_of_day,
+        });
+        inform(data, &spotters, report);
+    }
+}
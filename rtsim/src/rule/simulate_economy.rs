@@ -0,0 +1,44 @@
+use crate::{event::OnTick, RtState, Rule, RuleError};
+use world::{site::economy, util::DHashMap};
+
+pub struct SimulateEconomy;
+
+/// How often (in in-game seconds) site economies are simulated.
+pub const ECONOMY_TICK: f64 = 24.0 * 3600.0;
+/// The most time (in in-game days) that will be simulated at once, so that
+/// jumping forward in time doesn't destabilise the economy. This matches the
+/// step used during world generation.
+pub const MAX_ECONOMY_DT: f32 = 90.0;
+
+impl Rule for SimulateEconomy {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnTick>(|ctx| {
+            let data = &mut *ctx.state.data_mut();
+            let now = ctx.event.time_of_day;
+
+            let Some(last) = data.economy_time_of_day else {
+                data.economy_time_of_day = Some(now);
+                return;
+            };
+            let elapsed = now.0 - last.0;
+            if elapsed < 0.0 {
+                // Time was set backwards, start counting again from here
+                data.economy_time_of_day = Some(now);
+                return;
+            } else if elapsed < ECONOMY_TICK {
+                return;
+            }
+            data.economy_time_of_day = Some(now);
+
+            let dt = ((elapsed / ECONOMY_TICK) as f32).min(MAX_ECONOMY_DT);
+            let mut economies = data
+                .sites
+                .values_mut()
+                .filter_map(|site| Some((site.world_site?, site.economy.as_mut()?)))
+                .collect::<DHashMap<_, _>>();
+            economy::tick_live(&mut economies, &mut data.trade, dt);
+        });
+
+        Ok(Self)
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    data::{report::ReportKind, Data, FactionId, Raid, Report, Sentiment, Territory},
+    event::{OnSetup, OnTick},
+    RtState, Rule, RuleError,
+};
+use common::{
+    resources::TimeOfDay,
+    rtsim::{NpcInput, Profession, Role},
+    terrain::TerrainChunkSize,
+    vol::RectVolSize,
+};
+use rand::prelude::*;
+use rand_chacha::ChaChaRng;
+use tracing::info;
+use world::World;
+
+/// A rule that lets factions fight over sites: factions that are at war send
+/// raiding parties to each other's sites, and sites that fall to a raid change
+/// hands.
+pub struct SimulateFactions {
+    last_update: Option<TimeOfDay>,
+}
+
+/// Only let factions make decisions every few ticks
+const FACTION_TICK_SKIP: u64 = 120;
+/// The length of a game day, in units of [`TimeOfDay`]
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+/// The chance that a faction at war starts a raid over a day
+const RAID_CHANCE_PER_DAY: f64 = 0.3;
+/// The furthest (in blocks) that raiders are willing to travel
+const RAID_RANGE: f32 = 3000.0;
+/// How close (in blocks) to the faction's own territory a site must be for it
+/// to be raided. Sites deeper in enemy territory are out of reach until the
+/// sites in front of them fall.
+const RAID_FRONTIER: i32 = 768;
+/// How close (in blocks) raiders must get to a site to attack it
+const RAID_SITE_RADIUS: f32 = 80.0;
+const MIN_RAIDERS: usize = 3;
+const MAX_RAIDERS: usize = 8;
+/// How long (in in-game seconds) raiders take to reach their target before
+/// they give up
+const RAID_TIMEOUT: f64 = 2.0 * DAY;
+/// Even a site with no defenders puts up some resistance
+const SITE_DEFENCE: f32 = 4.0;
+/// How far opinions between neighbouring factions wander each day
+const RIVALRY_DRIFT: f32 = 0.05;
+
+impl Rule for SimulateFactions {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnSetup>(|ctx| {
+            let data = &mut *ctx.state.data_mut();
+            let mut rng = ChaChaRng::from_seed(thread_rng().gen::<[u8; 32]>());
+
+            // Data from before factions had relations has factions that have never met
+            data.factions
+                .seed_relations(&data.sites, ctx.index, &mut rng);
+
+            // Forget raids on sites that no longer exist
+            for faction in data.factions.values_mut() {
+                if let Some(raid) = &mut faction.raid {
+                    raid.raiders.retain(|npc| data.npcs.contains_key(*npc));
+                    if !data.sites.contains_key(raid.from) || !data.sites.contains_key(raid.target)
+                    {
+                        faction.raid = None;
+                    }
+                }
+            }
+
+            update_territory(data, ctx.world);
+        });
+
+        rtstate.bind::<Self, OnTick>(|ctx| {
+            if ctx.event.tick % FACTION_TICK_SKIP != 0 {
+                return;
+            }
+            let now = ctx.event.time_of_day;
+            let elapsed_days = ctx
+                .rule
+                .last_update
+                .replace(now)
+                .map_or(0.0, |last| ((now.0 - last.0) / DAY).clamp(0.0, 1.0));
+
+            let data = &mut *ctx.state.data_mut();
+            let mut rng = ChaChaRng::from_seed(thread_rng().gen::<[u8; 32]>());
+
+            if rng.gen_bool(elapsed_days) {
+                drift_rivalries(data, &mut rng);
+            }
+
+            let mut territory_changed = false;
+            for faction_id in data.factions.keys().collect::<Vec<_>>() {
+                if let Some(raid) = data.factions[faction_id].raid.clone() {
+                    territory_changed |= progress_raid(data, faction_id, raid, &mut rng);
+                } else if rng.gen_bool(elapsed_days * RAID_CHANCE_PER_DAY) {
+                    data.factions[faction_id].raid = plan_raid(data, faction_id, &mut rng);
+                }
+            }
+
+            if territory_changed {
+                update_territory(data, ctx.world);
+            }
+        });
+
+        Ok(Self { last_update: None })
+    }
+}
+
+fn update_territory(data: &mut Data, world: &World) {
+    let world_size = world
+        .sim()
+        .get_size()
+        .map2(TerrainChunkSize::RECT_SIZE, |e, sz| (e * sz) as i32);
+    data.factions.territory = Territory::compute(world_size, &data.sites);
+}
+
+/// Factions that live close to one-another slowly grow to like or dislike
+/// each other, which might eventually lead to war.
+fn drift_rivalries(data: &mut Data, rng: &mut impl Rng) {
+    let owned_sites = data
+        .sites
+        .values()
+        .filter_map(|site| Some((site.faction?, site.wpos.as_::<f32>())))
+        .collect::<Vec<_>>();
+    for (a, a_wpos) in &owned_sites {
+        for (b, b_wpos) in &owned_sites {
+            if a != b
+                && a_wpos.distance_squared(*b_wpos) < RAID_RANGE.powi(2)
+                && let Some(faction) = data.factions.get_mut(*a)
+            {
+                faction.sentiments.toward_mut(*b).change_by(
+                    rng.gen_range(-RIVALRY_DRIFT..RIVALRY_DRIFT * 0.5),
+                    Sentiment::VILLAIN,
+                );
+            }
+        }
+    }
+}
+
+/// How much a single NPC counts for in a fight over a site.
+fn fighting_strength(role: &Role) -> f32 {
+    match role {
+        Role::Civilised(Some(Profession::Guard | Profession::Cultist)) => 2.0,
+        Role::Civilised(Some(Profession::Adventurer(_) | Profession::Hunter)) => 1.5,
+        Role::Civilised(_) => 0.5,
+        Role::Monster => 4.0,
+        Role::Wild | Role::Vehicle | Role::PackAnimal => 0.0,
+    }
+}
+
+/// Choose a site on the border of our territory belonging to a faction that
+/// we're at war with, and gather a party to raid it.
+fn plan_raid(data: &Data, faction_id: FactionId, rng: &mut impl Rng) -> Option<Raid> {
+    let faction = data.factions.get(faction_id)?;
+    let (from, target) = data
+        .sites
+        .iter()
+        .filter(|(_, site)| site.faction == Some(faction_id))
+        .flat_map(|(from_id, from)| {
+            data.sites
+                .iter()
+                .filter(|(_, target)| {
+                    target
+                        .faction
+                        .map_or(false, |owner| faction.is_hostile_to(owner))
+                        && data.factions.territory.controls_near(
+                            faction_id,
+                            target.wpos,
+                            RAID_FRONTIER,
+                        )
+                })
+                .map(move |(target_id, target)| {
+                    (
+                        from_id,
+                        target_id,
+                        from.wpos.as_::<f32>().distance(target.wpos.as_()),
+                    )
+                })
+        })
+        .filter(|(_, _, dist)| *dist < RAID_RANGE)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .map(|(from, target, _)| (from, target))?;
+
+    let raiders = data
+        .npcs
+        .iter()
+        .filter(|(npc_id, npc)| {
+            !npc.is_dead
+                && npc.home == Some(from)
+                && npc.faction == Some(faction_id)
+                && fighting_strength(&npc.role) >= 1.5
+                && data.npcs.mounts.get_mount_link(*npc_id).is_none()
+        })
+        .map(|(npc_id, _)| npc_id)
+        .choose_multiple(rng, MAX_RAIDERS);
+
+    (raiders.len() >= MIN_RAIDERS).then_some(Raid {
+        from,
+        target,
+        raiders,
+        started: data.time_of_day,
+    })
+}
+
+/// Check whether the raiders have reached their target and, if they have,
+/// fight for the site. Returns `true` if the site changed hands.
+fn progress_raid(data: &mut Data, attacker: FactionId, raid: Raid, rng: &mut impl Rng) -> bool {
+    let Some(target) = data.sites.get(raid.target) else {
+        data.factions[attacker].raid = None;
+        return false;
+    };
+    let defender = target.faction;
+    if defender == Some(attacker) {
+        // Somebody beat us to it
+        data.factions[attacker].raid = None;
+        return false;
+    }
+
+    let target_wpos = target.wpos.as_::<f32>();
+    let raiders = raid
+        .raiders
+        .iter()
+        .filter_map(|npc_id| data.npcs.get(*npc_id))
+        .filter(|npc| !npc.is_dead)
+        .collect::<Vec<_>>();
+    let arrived = raiders
+        .iter()
+        .filter(|npc| npc.wpos.xy().distance_squared(target_wpos) < RAID_SITE_RADIUS.powi(2))
+        .collect::<Vec<_>>();
+
+    if arrived.is_empty() || arrived.len() * 2 < raiders.len() {
+        if raiders.is_empty() || data.time_of_day.0 - raid.started.0 > RAID_TIMEOUT {
+            // The raiders never made it
+            data.factions[attacker].raid = None;
+        }
+        return false;
+    }
+
+    let attack = arrived
+        .iter()
+        .map(|npc| fighting_strength(&npc.role))
+        .sum::<f32>();
+    let defence = SITE_DEFENCE
+        + data
+            .npcs
+            .values()
+            .filter(|npc| !npc.is_dead && npc.home == Some(raid.target) && npc.faction == defender)
+            .map(|npc| fighting_strength(&npc.role))
+            .sum::<f32>();
+    let captured = rng.gen_bool((attack / (attack + defence)) as f64);
+
+    data.factions[attacker].raid = None;
+    if let Some(defender) = defender.and_then(|defender| data.factions.get_mut(defender)) {
+        defender
+            .sentiments
+            .toward_mut(attacker)
+            .change_by(-0.3, Sentiment::VILLAIN);
+    }
+    if captured {
+        info!(
+            "Faction {:?} captured site {:?} from {:?}",
+            attacker, raid.target, defender
+        );
+        data.sites[raid.target].faction = Some(attacker);
+        // The people of the site are absorbed into the faction that captured it
+        for npc in data.npcs.values_mut() {
+            if npc.home == Some(raid.target) && npc.faction == defender {
+                npc.faction = Some(attacker);
+            }
+        }
+    } else if let Some(defender) = defender {
+        data.factions[attacker]
+            .sentiments
+            .toward_mut(defender)
+            .change_by(-0.1, Sentiment::VILLAIN);
+    }
+
+    let report = data.reports.create(Report {
+        kind: ReportKind::Raid {
+            site: raid.target,
+            attacker,
+            defender,
+            captured,
+        },
+        at: data.time_of_day,
+    });
+    for site in [raid.target, raid.from] {
+        if let Some(site) = data.sites.get_mut(site) {
+            site.known_reports.insert(report);
+        }
+    }
+    let witnesses = data
+        .npcs
+        .nearby(None, target_wpos.with_z(0.0), RAID_SITE_RADIUS)
+        .filter_map(|actor| actor.npc())
+        .chain(raid.raiders.iter().copied())
+        .collect::<Vec<_>>();
+    for npc_id in witnesses {
+        if let Some(npc) = data.npcs.get_mut(npc_id) {
+            npc.inbox.push_back(NpcInput::Report(report));
+        }
+    }
+
+    captured
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{Faction, Npc, Site};
+    use vek::*;
+
+    fn faction(data: &mut Data) -> FactionId {
+        data.factions.create(Faction {
+            seed: 0,
+            leader: None,
+            sentiments: Default::default(),
+            raid: None,
+        })
+    }
+
+    #[test]
+    fn factions_at_war_raid_and_capture_sites() {
+        let mut data = Data::empty();
+        let mut rng = ChaChaRng::from_seed([0; 32]);
+        let (red, blue) = (faction(&mut data), faction(&mut data));
+        let red_town = data
+            .sites
+            .create(Site::test(Vec2::new(0, 0)).with_faction(red));
+        let blue_town = data
+            .sites
+            .create(Site::test(Vec2::new(1000, 0)).with_faction(blue));
+        for _ in 0..MAX_RAIDERS {
+            data.spawn_npc(
+                Npc::test(Role::Civilised(Some(Profession::Guard)))
+                    .with_home(red_town)
+                    .with_faction(red),
+            );
+        }
+        let world_size = Vec2::broadcast(4096);
+        data.factions.territory = Territory::compute(world_size, &data.sites);
+        assert!(data.factions.territory.area_of(blue) > 0);
+
+        // Factions at peace don't raid each other
+        assert!(plan_raid(&data, red, &mut rng).is_none());
+
+        data.factions[red]
+            .sentiments
+            .toward_mut(blue)
+            .change_by(Sentiment::VILLAIN, Sentiment::VILLAIN);
+
+        // Sites far from the border of our territory can't be reached
+        data.sites[blue_town].wpos = Vec2::new(2800, 0);
+        data.factions.territory = Territory::compute(world_size, &data.sites);
+        assert!(plan_raid(&data, red, &mut rng).is_none());
+        data.sites[blue_town].wpos = Vec2::new(1000, 0);
+        data.factions.territory = Territory::compute(world_size, &data.sites);
+
+        let raid = plan_raid(&data, red, &mut rng).expect("red should raid blue");
+        assert_eq!(raid.target, blue_town);
+        assert_eq!(raid.raiders.len(), MAX_RAIDERS);
+
+        // Nothing happens until the raiders arrive
+        data.factions[red].raid = Some(raid.clone());
+        assert!(!progress_raid(&mut data, red, raid.clone(), &mut rng));
+        assert!(data.factions[red].raid.is_some());
+
+        for npc_id in &raid.raiders {
+            data.npcs[*npc_id].wpos = Vec3::new(1000.0, 0.0, 0.0);
+        }
+        // An undefended site falls to eight guards most of the time
+        let mut captures = 0;
+        for _ in 0..20 {
+            data.sites[blue_town].faction = Some(blue);
+            data.factions[red].raid = Some(raid.clone());
+            captures += progress_raid(&mut data, red, raid.clone(), &mut rng) as u32;
+            assert!(data.factions[red].raid.is_none());
+        }
+        assert!(captures > 10);
+        assert!(data.factions[blue].is_hostile_to(red));
+        assert!(!data.sites[blue_town].known_reports.is_empty());
+    }
+}
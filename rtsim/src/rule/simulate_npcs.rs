@@ -17,6 +17,10 @@ use tracing::{error, warn};
 use vek::{Clamp, Vec2};
 use world::{site::SiteKind, CONFIG};
 
+/// How far below the surface a destination must be for simulated NPCs to
+/// travel underground
+const CAVE_DEPTH: f32 = 16.0;
+
 pub struct SimulateNpcs;
 
 impl Rule for SimulateNpcs {
@@ -57,6 +61,11 @@ fn on_death(ctx: EventCtx<SimulateNpcs, OnDeath>) {
 
             // Respawn dead NPCs
             let details = match npc.body {
+                // Settlers aren't replaced, their settlements grow back through births and
+                // newcomers instead
+                Body::Humanoid(_) if npc.is_settler() => None,
+                // Pack animals are bought as caravans need them
+                _ if npc.is_pack_animal() => None,
                 Body::Humanoid(_) => {
                     if let Some((site_id, site)) = data
                         .sites
@@ -284,10 +293,17 @@ fn on_tick(ctx: EventCtx<SimulateNpcs, OnTick>) {
                     );
                 },
                 _ => {
-                    npc.wpos = clamped_wpos.with_z(
-                        ctx.world.sim().get_surface_alt_approx(clamped_wpos.as_())
-                            + npc.body.flying_height(),
+                    let surface_alt = ctx.world.sim().get_surface_alt_approx(clamped_wpos.as_());
+                    // NPCs that are making their way through caves stay underground
+                    let is_underground = matches!(
+                        activity,
+                        Some(NpcActivity::Goto(target, _)) if target.z < surface_alt - CAVE_DEPTH,
                     );
+                    npc.wpos = clamped_wpos.with_z(if is_underground {
+                        npc.wpos.z
+                    } else {
+                        surface_alt + npc.body.flying_height()
+                    });
                 },
             }
         }
@@ -0,0 +1,296 @@
+use crate::{
+    data::nature::{Wildlife, WILDLIFE_REGION_CHUNKS},
+    event::{OnSetup, OnTick, OnWildlifeKilled},
+    RtState, Rule, RuleError,
+};
+use common::{
+    grid::Grid, resources::TimeOfDay, rtsim::WildlifeGroup, terrain::CoordinateConversions,
+    weather::seasonal_warmth,
+};
+use vek::*;
+use world::World;
+
+/// A rule that lets wildlife populations change over time: they're reduced by
+/// hunting, grow back, prey on one another and migrate with the seasons.
+pub struct SimulateWildlife {
+    last_update: Option<TimeOfDay>,
+    /// What each region is like for the wildlife that lives there.
+    habitats: Grid<Habitat>,
+}
+
+struct Habitat {
+    /// The average temperature of the region
+    temp: f32,
+    /// The proportion of the region that's dry land
+    land: f32,
+    /// The proportion of the region that's rivers, lakes or sea
+    water: f32,
+}
+
+impl Habitat {
+    /// How much of the region the given group can live in.
+    fn suitability(&self, group: WildlifeGroup) -> f32 {
+        match group {
+            WildlifeGroup::Prey | WildlifeGroup::Predator => self.land,
+            WildlifeGroup::Aquatic => self.water,
+        }
+    }
+}
+
+/// Only simulate wildlife every few ticks
+const WILDLIFE_TICK_SKIP: u64 = 300;
+/// The length of a game day, in units of [`TimeOfDay`]
+const DAY: f64 = 24.0 * 60.0 * 60.0;
+/// How quickly each group breeds, relative to its population, per day
+const PREY_GROWTH: f32 = 0.2;
+const PREDATOR_GROWTH: f32 = 0.1;
+const AQUATIC_GROWTH: f32 = 0.3;
+/// How strongly an over- or under-abundance of predators changes the number of
+/// prey, per day
+const PREDATION: f32 = 0.15;
+/// The most plentiful that a group can become, relative to the region's
+/// capacity
+const MAX_ABUNDANCE: f32 = 2.0;
+/// How quickly wildlife spreads from plentiful regions to sparse ones, per day
+const DISPERSAL_RATE: f32 = 0.1;
+/// How quickly land animals move towards warmer regions in the winter (and
+/// back again in the summer), per day and per unit of temperature difference
+const MIGRATION_RATE: f32 = 1.0;
+/// The most of a region's population that can leave it in a single update
+const MAX_EMIGRATION: f32 = 0.25;
+
+impl Rule for SimulateWildlife {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnSetup>(|ctx| {
+            // Data from before wildlife was tracked has no populations yet
+            ctx.state.data_mut().nature.init_wildlife();
+            ctx.rule.habitats = survey_habitats(ctx.world);
+        });
+
+        rtstate.bind::<Self, OnWildlifeKilled>(|ctx| {
+            let chunk = ctx.event.wpos.xy().as_::<i32>().wpos_to_cpos();
+            ctx.state
+                .data_mut()
+                .nature
+                .kill_wildlife(chunk, ctx.event.group);
+        });
+
+        rtstate.bind::<Self, OnTick>(|ctx| {
+            if ctx.event.tick % WILDLIFE_TICK_SKIP != 0 {
+                return;
+            }
+            let now = ctx.event.time_of_day;
+            let elapsed_days = ctx
+                .rule
+                .last_update
+                .replace(now)
+                .map_or(0.0, |last| ((now.0 - last.0) / DAY).clamp(0.0, 1.0))
+                as f32;
+            if elapsed_days <= 0.0 {
+                return;
+            }
+
+            let data = &mut *ctx.state.data_mut();
+            let warmth = seasonal_warmth(now, data.year_length);
+            let wildlife = data.nature.wildlife_mut();
+
+            for (_, region) in wildlife.iter_mut() {
+                breed(region, elapsed_days);
+            }
+            roam(wildlife, &ctx.rule.habitats, warmth, elapsed_days);
+        });
+
+        Ok(Self {
+            last_update: None,
+            habitats: Grid::default(),
+        })
+    }
+}
+
+/// Find out what each wildlife region is like from the terrain within it.
+fn survey_habitats(world: &World) -> Grid<Habitat> {
+    let sim = world.sim();
+    let regions = sim
+        .get_size()
+        .map(|e| (e as i32 + WILDLIFE_REGION_CHUNKS - 1) / WILDLIFE_REGION_CHUNKS);
+    Grid::populate_from(regions, |region| {
+        let chunks = (0..WILDLIFE_REGION_CHUNKS)
+            .flat_map(|y| (0..WILDLIFE_REGION_CHUNKS).map(move |x| Vec2::new(x, y)))
+            .filter_map(|offs| sim.get(region * WILDLIFE_REGION_CHUNKS + offs))
+            .collect::<Vec<_>>();
+        let count = chunks.len().max(1) as f32;
+        Habitat {
+            temp: chunks.iter().map(|chunk| chunk.temp).sum::<f32>() / count,
+            land: chunks.iter().filter(|chunk| !chunk.is_underwater()).count() as f32 / count,
+            water: chunks
+                .iter()
+                .filter(|chunk| chunk.river.near_water())
+                .count() as f32
+                / count,
+        }
+    })
+}
+
+/// Let the wildlife of a region breed and hunt one another.
+///
+/// Left alone, each group settles at the capacity of the region. Prey are
+/// eaten by predators, so they become more plentiful when predators are hunted
+/// out, and predators go hungry when their prey is hunted out.
+fn breed(region: &mut Wildlife, days: f32) {
+    let abundance = region.abundance();
+    let prey = abundance[WildlifeGroup::Prey];
+    let predators = abundance[WildlifeGroup::Predator];
+    let aquatic = abundance[WildlifeGroup::Aquatic];
+
+    let changes = [
+        (
+            WildlifeGroup::Prey,
+            PREY_GROWTH * prey * (1.0 - prey) - PREDATION * prey * (predators - 1.0),
+        ),
+        (
+            WildlifeGroup::Predator,
+            PREDATOR_GROWTH * predators * (prey - predators),
+        ),
+        (
+            WildlifeGroup::Aquatic,
+            AQUATIC_GROWTH * aquatic * (1.0 - aquatic),
+        ),
+    ];
+    for (group, change) in changes {
+        region.pop[group] = ((abundance[group] + change * days).clamp(0.0, MAX_ABUNDANCE))
+            * Wildlife::capacity(group);
+    }
+}
+
+/// Let wildlife move between neighbouring regions: it spreads out from
+/// plentiful regions into sparse ones, and land animals head for warmer
+/// regions in the winter and cooler ones in the summer.
+fn roam(wildlife: &mut Grid<Wildlife>, habitats: &Grid<Habitat>, warmth: f32, days: f32) {
+    let mut moves = Vec::new();
+    for (pos, region) in wildlife.iter() {
+        let Some(habitat) = habitats.get(pos) else {
+            continue;
+        };
+        for neighbour_pos in [pos + Vec2::unit_x(), pos + Vec2::unit_y()] {
+            let (Some(neighbour), Some(neighbour_habitat)) =
+                (wildlife.get(neighbour_pos), habitats.get(neighbour_pos))
+            else {
+                continue;
+            };
+            for (group, abundance) in region.abundance() {
+                let neighbour_abundance = neighbour.abundance()[group];
+                let passable = habitat
+                    .suitability(group)
+                    .min(neighbour_habitat.suitability(group));
+
+                // Positive flows are towards the neighbour
+                let dispersal = DISPERSAL_RATE * (abundance - neighbour_abundance);
+                let migration = match group {
+                    WildlifeGroup::Prey | WildlifeGroup::Predator => {
+                        let towards_warmth = -warmth * (neighbour_habitat.temp - habitat.temp);
+                        let source = if towards_warmth > 0.0 {
+                            abundance
+                        } else {
+                            neighbour_abundance
+                        };
+                        MIGRATION_RATE * towards_warmth * source
+                    },
+                    WildlifeGroup::Aquatic => 0.0,
+                };
+                let flow = (dispersal + migration) * passable * days;
+                let flow = if flow > 0.0 {
+                    flow.min(abundance * MAX_EMIGRATION)
+                } else {
+                    flow.max(-neighbour_abundance * MAX_EMIGRATION)
+                };
+                moves.push((pos, neighbour_pos, group, flow * Wildlife::capacity(group)));
+            }
+        }
+    }
+
+    for (from, to, group, amount) in moves {
+        if let Some(region) = wildlife.get_mut(from) {
+            region.pop[group] = (region.pop[group] - amount).max(0.0);
+        }
+        if let Some(region) = wildlife.get_mut(to) {
+            region.pop[group] = (region.pop[group] + amount).max(0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enum_map::EnumMap;
+
+    fn region(prey: f32, predators: f32) -> Wildlife {
+        Wildlife {
+            pop: EnumMap::from_fn(|group| {
+                Wildlife::capacity(group)
+                    * match group {
+                        WildlifeGroup::Prey => prey,
+                        WildlifeGroup::Predator => predators,
+                        WildlifeGroup::Aquatic => 1.0,
+                    }
+            }),
+        }
+    }
+
+    fn settle(mut region: Wildlife) -> EnumMap<WildlifeGroup, f32> {
+        for _ in 0..1000 {
+            breed(&mut region, 0.5);
+        }
+        region.abundance()
+    }
+
+    #[test]
+    fn hunting_has_lasting_consequences() {
+        // Untouched wildlife stays as it is
+        let untouched = settle(region(1.0, 1.0));
+        assert!((untouched[WildlifeGroup::Prey] - 1.0).abs() < 0.01);
+        assert!((untouched[WildlifeGroup::Predator] - 1.0).abs() < 0.01);
+
+        // Over-hunted prey recovers, but only slowly
+        let mut hunted = region(0.1, 1.0);
+        breed(&mut hunted, 1.0);
+        assert!(hunted.abundance()[WildlifeGroup::Prey] < 0.2);
+        let recovered = settle(hunted);
+        assert!((recovered[WildlifeGroup::Prey] - 1.0).abs() < 0.05);
+
+        // Without prey, predators starve
+        let mut starving = region(0.0, 1.0);
+        for _ in 0..100 {
+            breed(&mut starving, 1.0);
+            starving.pop[WildlifeGroup::Prey] = 0.0;
+        }
+        assert!(starving.abundance()[WildlifeGroup::Predator] < 0.1);
+    }
+
+    #[test]
+    fn wildlife_migrates_with_the_seasons() {
+        let mut wildlife = Grid::populate_from(Vec2::new(2, 1), |_| region(1.0, 1.0));
+        let habitats = Grid::populate_from(Vec2::new(2, 1), |pos| Habitat {
+            temp: pos.x as f32 * 0.5,
+            land: 1.0,
+            water: 0.0,
+        });
+        let prey = |wildlife: &Grid<Wildlife>, x| {
+            wildlife.get(Vec2::new(x, 0)).unwrap().abundance()[WildlifeGroup::Prey]
+        };
+
+        // In the winter, animals head for the warmer region
+        roam(&mut wildlife, &habitats, -1.0, 1.0);
+        assert!(prey(&wildlife, 1) > prey(&wildlife, 0));
+        // Fish have nowhere to go on land
+        assert_eq!(
+            wildlife.get(Vec2::new(0, 0)).unwrap().abundance()[WildlifeGroup::Aquatic],
+            1.0
+        );
+
+        // In the summer, they head back again
+        for _ in 0..10 {
+            roam(&mut wildlife, &habitats, 1.0, 1.0);
+        }
+        assert!(prey(&wildlife, 0) > prey(&wildlife, 1));
+    }
+}
@@ -8,6 +8,10 @@ use common::{
     terrain::CoordinateConversions,
 };
 
+/// How many ticks should pass between NPCs swapping reports with the site
+/// they're in.
+const REPORT_SHARE_TICK_SKIP: u64 = 30;
+
 pub struct SyncNpcs;
 
 impl Rule for SyncNpcs {
@@ -97,21 +101,26 @@ fn on_tick(ctx: EventCtx<SyncNpcs, OnTick>) {
                     .find_map(|site| data.sites.world_site_map.get(site).copied())
             });
 
-        // Share known reports with current site, if it's our home
-        // TODO: Only share new reports
+        // Swap reports with the current site, wherever it is. Travellers carry news
+        // from one site to the next, but it becomes less credible each time
+        // it's passed on.
         if let Some(current_site) = npc.current_site
-            && Some(current_site) == npc.home
+            && (npc.seed as u64 + ctx.event.tick) % REPORT_SHARE_TICK_SKIP == 0
+            && let Some(site) = data.sites.get_mut(current_site)
         {
-            if let Some(site) = data.sites.get_mut(current_site) {
-                // TODO: Sites should have an inbox and their own AI code
-                site.known_reports.extend(npc.known_reports.iter().copied());
-                npc.inbox.extend(
-                    site.known_reports
-                        .iter()
-                        .copied()
-                        .filter(|report| !npc.known_reports.contains(report))
-                        .map(NpcInput::Report),
-                );
+            // TODO: Sites should have an inbox and their own AI code
+            // TODO: Only share new reports
+            for report in npc.known_reports.iter().copied() {
+                if let Some(credibility) = npc.rumours.pass_on(report) {
+                    site.hear_report(report, credibility);
+                }
+            }
+            for report in site.known_reports.iter().copied() {
+                if let Some(credibility) = site.rumours.pass_on(report)
+                    && npc.hear_report(report, credibility)
+                {
+                    npc.inbox.push_back(NpcInput::Report(report));
+                }
             }
         }
 
@@ -0,0 +1,366 @@
+use crate::{
+    data::{Caravan, CaravanId, CaravanStage, Data, Npc, NpcId, SiteId},
+    event::{OnDeath, OnTick},
+    RtState, Rule, RuleError,
+};
+use common::{
+    comp::{self, Body},
+    resources::TimeOfDay,
+    rtsim::{Actor, Personality, Profession, Role},
+    store::Id,
+};
+use hashbrown::{HashMap, HashSet};
+use rand::prelude::*;
+use rand_chacha::ChaChaRng;
+use world::{
+    site::{economy::TradeDelivery, Site as WorldSite, SiteKind},
+    IndexRef, World,
+};
+
+/// A rule that has merchants carry the goods that sites trade with each other,
+/// so that goods only arrive if the caravan carrying them does.
+pub struct TradeCaravans;
+
+/// Only check on caravans every few ticks
+const CARAVAN_TICK_SKIP: u64 = 60;
+/// How close (in blocks) a caravan must get to a site to have arrived there
+const ARRIVAL_RADIUS: f32 = 48.0;
+/// How much cargo each pack animal can carry
+const CARGO_PER_ANIMAL: f32 = 50.0;
+const MAX_PACK_ANIMALS: usize = 3;
+/// How close (in blocks) characters must stay to a caravan to be escorting it
+const ESCORT_RANGE: f32 = 32.0;
+/// How long (in in-game seconds) a caravan can take before the goods are
+/// handed over regardless, so that caravans that get stuck don't starve
+/// sites forever
+const CARAVAN_TIMEOUT: f64 = 3.0 * 24.0 * 60.0 * 60.0;
+
+impl Rule for TradeCaravans {
+    fn start(rtstate: &mut RtState) -> Result<Self, RuleError> {
+        rtstate.bind::<Self, OnTick>(|ctx| {
+            if ctx.event.tick % CARAVAN_TICK_SKIP != 0 {
+                return;
+            }
+            let data = &mut *ctx.state.data_mut();
+            let mut rng = ChaChaRng::from_seed(thread_rng().gen::<[u8; 32]>());
+
+            form_caravans(data, ctx.world, ctx.index, &mut rng);
+            for caravan_id in data.caravans.keys().collect::<Vec<_>>() {
+                progress_caravan(data, caravan_id, ctx.event.time_of_day);
+            }
+        });
+
+        rtstate.bind::<Self, OnDeath>(|ctx| {
+            let data = &mut *ctx.state.data_mut();
+            if let Actor::Npc(npc_id) = ctx.event.actor
+                && let Some((caravan_id, _)) = data.caravans.caravan_of(npc_id)
+            {
+                let caravan = &mut data.caravans[caravan_id];
+                if caravan.merchant == npc_id {
+                    // Without the merchant, the goods never reach the buyer
+                    data.caravans.remove(caravan_id);
+                } else {
+                    // Whatever the pack animal was carrying is lost to the caravan. If
+                    // the animal was loaded, the server has already dropped it as loot
+                    // for whoever killed it.
+                    let carriers = caravan.pack_animals.len() as f32;
+                    caravan.pack_animals.retain(|animal| *animal != npc_id);
+                    caravan.lose_cargo(1.0 / carriers);
+                }
+            }
+        });
+
+        Ok(Self)
+    }
+}
+
+/// Whether the caravan's merchant has arrived at a site.
+fn has_arrived(data: &Data, merchant: &Npc, site: SiteId) -> bool {
+    merchant.current_site == Some(site)
+        || data.sites.get(site).map_or(false, |site| {
+            site.wpos.as_::<f32>().distance(merchant.wpos.xy()) < ARRIVAL_RADIUS
+        })
+}
+
+/// Hand the goods that a caravan is carrying to the site that bought them.
+fn deliver(data: &mut Data, caravan_id: CaravanId) {
+    let Some(caravan) = data.caravans.get_mut(caravan_id) else {
+        return;
+    };
+    let cargo = std::mem::take(&mut caravan.cargo);
+    let Some(site) = data.sites.get_mut(caravan.to) else {
+        return;
+    };
+    if let Some(economy) = &mut site.economy {
+        for delivery in cargo {
+            economy.receive(delivery);
+        }
+    } else if let Some(world_site) = site.world_site {
+        for delivery in cargo {
+            data.trade.deliver(world_site, delivery);
+        }
+    }
+}
+
+/// Send merchants out with the goods that sites have traded. Goods that no
+/// merchant is available to carry are delivered without a caravan.
+fn form_caravans(data: &mut Data, world: &World, index: IndexRef, rng: &mut impl Rng) {
+    let mut shipments = HashMap::<(Id<WorldSite>, Id<WorldSite>), Vec<TradeDelivery>>::new();
+    for (receiver, delivery) in data.trade.take_deliveries() {
+        shipments
+            .entry((delivery.supplier(), receiver))
+            .or_default()
+            .push(delivery);
+    }
+    if shipments.is_empty() {
+        return;
+    }
+
+    let mut busy = data
+        .caravans
+        .values()
+        .flat_map(|caravan| caravan.pack_animals.iter().chain([&caravan.merchant]))
+        .copied()
+        .collect::<HashSet<_>>();
+
+    for ((supplier, receiver), cargo) in shipments {
+        let route = data
+            .sites
+            .world_site_map
+            .get(&supplier)
+            .zip(data.sites.world_site_map.get(&receiver))
+            .map(|(from, to)| (*from, *to));
+        let merchant = route.and_then(|(from, _)| {
+            let from_wpos = data.sites.get(from)?.wpos.as_::<f32>();
+            data.npcs
+                .iter()
+                .filter(|(npc_id, npc)| {
+                    !npc.is_dead
+                        && npc.home == Some(from)
+                        && matches!(npc.profession(), Some(Profession::Merchant))
+                        && !busy.contains(npc_id)
+                })
+                .min_by_key(|(_, npc)| npc.wpos.xy().distance(from_wpos) as i32)
+                .map(|(npc_id, _)| npc_id)
+        });
+        let (Some((from, to)), Some(merchant)) = (route, merchant) else {
+            for delivery in cargo {
+                data.trade.deliver(receiver, delivery);
+            }
+            continue;
+        };
+        busy.insert(merchant);
+
+        // Load the goods onto pack animals, buying more if the site doesn't have
+        // enough of them
+        let total_cargo = cargo.iter().map(TradeDelivery::total_amount).sum::<f32>();
+        let needed = ((total_cargo / CARGO_PER_ANIMAL).ceil() as usize).clamp(1, MAX_PACK_ANIMALS);
+        let mut pack_animals = data
+            .npcs
+            .iter()
+            .filter(|(npc_id, npc)| {
+                !npc.is_dead
+                    && npc.home == Some(from)
+                    && npc.is_pack_animal()
+                    && !busy.contains(npc_id)
+            })
+            .map(|(npc_id, _)| npc_id)
+            .take(needed)
+            .collect::<Vec<_>>();
+        while pack_animals.len() < needed {
+            pack_animals.push(spawn_pack_animal(data, world, index, from, rng));
+        }
+        busy.extend(pack_animals.iter().copied());
+
+        data.caravans.create(Caravan {
+            merchant,
+            pack_animals,
+            from,
+            to,
+            stage: CaravanStage::Gathering,
+            started: data.time_of_day,
+            cargo,
+            escort_sightings: HashMap::default(),
+            escort_checks: 0,
+            escorts: Vec::new(),
+        });
+    }
+}
+
+fn spawn_pack_animal(
+    data: &mut Data,
+    world: &World,
+    index: IndexRef,
+    home: SiteId,
+    rng: &mut impl Rng,
+) -> NpcId {
+    let site = &data.sites[home];
+    // Camels are better suited to the desert
+    let species = match site.world_site.map(|ws| &index.sites.get(ws).kind) {
+        Some(SiteKind::DesertCity(_) | SiteKind::SavannahPit(_)) => {
+            comp::quadruped_medium::Species::Camel
+        },
+        _ => *[
+            comp::quadruped_medium::Species::Donkey,
+            comp::quadruped_medium::Species::Horse,
+        ]
+        .choose(rng)
+        .unwrap(),
+    };
+    let wpos2d = site.wpos.map(|e| e + rng.gen_range(-10..10));
+    let wpos = wpos2d
+        .map(|e| e as f32 + 0.5)
+        .with_z(world.sim().get_alt_approx(wpos2d).unwrap_or(0.0));
+
+    data.spawn_npc(
+        Npc::new(
+            rng.gen(),
+            wpos,
+            Body::QuadrupedMedium(comp::quadruped_medium::Body::random_with(rng, &species)),
+            Role::PackAnimal,
+        )
+        .with_personality(Personality::random(rng))
+        .with_home(home),
+    )
+}
+
+fn progress_caravan(data: &mut Data, caravan_id: CaravanId, now: TimeOfDay) {
+    let Some(caravan) = data.caravans.get(caravan_id) else {
+        return;
+    };
+    let Some(merchant) = data.npcs.get(caravan.merchant).filter(|npc| !npc.is_dead) else {
+        data.caravans.remove(caravan_id);
+        return;
+    };
+
+    // Caravans that get stuck along the way make it eventually
+    if now.0 - caravan.started.0 > CARAVAN_TIMEOUT {
+        deliver(data, caravan_id);
+        data.caravans.remove(caravan_id);
+        return;
+    }
+
+    match caravan.stage {
+        CaravanStage::Gathering => {
+            if has_arrived(data, merchant, caravan.from) {
+                data.caravans[caravan_id].stage = CaravanStage::Delivering;
+            }
+        },
+        CaravanStage::Delivering => {
+            let arrived = has_arrived(data, merchant, caravan.to);
+            let nearby_characters = data
+                .npcs
+                .nearby(None, merchant.wpos, ESCORT_RANGE)
+                .filter(|actor| matches!(actor, Actor::Character(_)))
+                .collect::<Vec<_>>();
+
+            let caravan = &mut data.caravans[caravan_id];
+            caravan.escort_checks += 1;
+            for character in nearby_characters {
+                *caravan.escort_sightings.entry(character).or_default() += 1;
+            }
+
+            if arrived {
+                // Those who travelled alongside the caravan for most of the way
+                // escorted it
+                let checks = caravan.escort_checks;
+                caravan.escorts = caravan
+                    .escort_sightings
+                    .drain()
+                    .filter(|(_, sightings)| sightings * 2 >= checks)
+                    .map(|(character, _)| character)
+                    .collect();
+                caravan.stage = CaravanStage::Returning;
+                deliver(data, caravan_id);
+            }
+        },
+        CaravanStage::Returning => {
+            if has_arrived(data, merchant, caravan.from) {
+                data.caravans.remove(caravan_id);
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Site;
+    use vek::*;
+
+    #[test]
+    fn caravans_deliver_and_return_home() {
+        let mut data = Data::empty();
+        let (from, to) = (
+            data.sites.create(Site::test(Vec2::zero())),
+            data.sites.create(Site::test(Vec2::new(1000, 0))),
+        );
+        let merchant =
+            data.spawn_npc(Npc::test(Role::Civilised(Some(Profession::Merchant))).with_home(from));
+        let donkey = data.spawn_npc(
+            Npc {
+                body: Body::QuadrupedMedium(comp::quadruped_medium::Body::random()),
+                ..Npc::test(Role::PackAnimal)
+            }
+            .with_home(from),
+        );
+        let caravan = data.caravans.create(Caravan {
+            merchant,
+            pack_animals: vec![donkey],
+            from,
+            to,
+            stage: CaravanStage::Gathering,
+            started: TimeOfDay(0.0),
+            cargo: Vec::new(),
+            escort_sightings: HashMap::default(),
+            escort_checks: 0,
+            escorts: Vec::new(),
+        });
+        assert!(data.npcs[donkey].is_pack_animal());
+        assert_eq!(
+            data.caravans.caravan_of(donkey).map(|(id, _)| id),
+            Some(caravan)
+        );
+
+        // The merchant is already where the goods are
+        progress_caravan(&mut data, caravan, TimeOfDay(0.0));
+        assert_eq!(data.caravans[caravan].stage, CaravanStage::Delivering);
+
+        // Nothing happens until the caravan reaches the buyer
+        progress_caravan(&mut data, caravan, TimeOfDay(0.0));
+        assert_eq!(data.caravans[caravan].stage, CaravanStage::Delivering);
+        data.npcs[merchant].wpos = Vec3::new(1000.0, 0.0, 0.0);
+        progress_caravan(&mut data, caravan, TimeOfDay(0.0));
+        assert_eq!(data.caravans[caravan].stage, CaravanStage::Returning);
+
+        // Once home, the caravan disbands
+        data.npcs[merchant].wpos = Vec3::zero();
+        progress_caravan(&mut data, caravan, TimeOfDay(0.0));
+        assert!(data.caravans.caravan_of(merchant).is_none());
+    }
+
+    #[test]
+    fn caravans_give_up_eventually() {
+        let mut data = Data::empty();
+        let (from, to) = (
+            data.sites.create(Site::test(Vec2::zero())),
+            data.sites.create(Site::test(Vec2::new(1000, 0))),
+        );
+        let merchant =
+            data.spawn_npc(Npc::test(Role::Civilised(Some(Profession::Merchant))).with_home(from));
+        let caravan = data.caravans.create(Caravan {
+            merchant,
+            pack_animals: Vec::new(),
+            from,
+            to,
+            stage: CaravanStage::Delivering,
+            started: TimeOfDay(0.0),
+            cargo: Vec::new(),
+            escort_sightings: HashMap::default(),
+            escort_checks: 0,
+            escorts: Vec::new(),
+        });
+        progress_caravan(&mut data, caravan, TimeOfDay(CARAVAN_TIMEOUT + 1.0));
+        assert!(!data.caravans.contains_key(caravan));
+    }
+}
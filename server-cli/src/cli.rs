@@ -2,11 +2,14 @@
     clippy::needless_pass_by_ref_mut //until we find a better way for specs
 )]
 
-use clap::Parser;
+use clap::{Args, Parser, ValueEnum};
 use common::comp;
+use rtsim::data::inspect::{parse_key, NpcFilter, NpcSummary};
+use serde::Deserialize;
 use server::persistence::SqlLogMode;
-use std::sync::mpsc::Sender;
+use std::{path::PathBuf, sync::mpsc::Sender};
 use tracing::error;
+use vek::*;
 
 #[derive(Clone, Debug, Parser)]
 pub enum Admin {
@@ -40,6 +43,176 @@ pub enum Shutdown {
     Cancel,
 }
 
+/// Tools for the modified blocks kept by terrain persistence. A snapshot is
+/// either an archive made with `export` or a copy of a terrain directory.
+///
+/// These work on the files directly, so the server must not be running.
+#[cfg(feature = "persistent_world")]
+#[derive(Clone, Debug, Parser)]
+pub enum Terrain {
+    /// Exports the modified blocks in a region to an archive
+    Export {
+        /// Region in world block coordinates, as `MIN_X,MIN_Y,MAX_X,MAX_Y`
+        #[arg(long, value_parser = parse_region, allow_hyphen_values = true)]
+        region: Aabr<i32>,
+        /// File to write the archive to
+        archive: PathBuf,
+    },
+    /// Imports an archive, replacing the region it was exported from
+    Import {
+        archive: PathBuf,
+        /// Number of chunks to move the archive by, as `X,Y`
+        #[arg(long, value_parser = parse_offset, allow_hyphen_values = true)]
+        offset: Option<Vec2<i32>>,
+    },
+    /// Lists the chunks that differ between two snapshots
+    Diff {
+        old: PathBuf,
+        /// Defaults to the current terrain
+        new: Option<PathBuf>,
+        /// Only compare this region, as `MIN_X,MIN_Y,MAX_X,MAX_Y`
+        #[arg(long, value_parser = parse_region, allow_hyphen_values = true)]
+        region: Option<Aabr<i32>>,
+    },
+    /// Rolls a region back to how it was in an earlier snapshot
+    Rollback {
+        snapshot: PathBuf,
+        /// Region in world block coordinates, as `MIN_X,MIN_Y,MAX_X,MAX_Y`
+        #[arg(long, value_parser = parse_region, allow_hyphen_values = true)]
+        region: Aabr<i32>,
+    },
+}
+
+fn parse_nums<T: std::str::FromStr, const N: usize>(s: &str) -> Result<[T; N], String>
+where
+    T::Err: ToString,
+{
+    s.split(',')
+        .map(|e| e.trim().parse::<T>().map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?
+        .try_into()
+        .map_err(|_| format!("expected {} comma separated numbers", N))
+}
+
+#[cfg(feature = "persistent_world")]
+fn parse_region(s: &str) -> Result<Aabr<i32>, String> {
+    let [min_x, min_y, max_x, max_y] = parse_nums(s)?;
+    Ok(Aabr {
+        min: Vec2::new(min_x, min_y),
+        max: Vec2::new(max_x, max_y),
+    }
+    .made_valid())
+}
+
+#[cfg(feature = "persistent_world")]
+fn parse_offset(s: &str) -> Result<Vec2<i32>, String> { parse_nums(s).map(Vec2::from) }
+
+fn parse_wpos(s: &str) -> Result<Vec3<f32>, String> { parse_nums(s).map(Vec3::from) }
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum DumpFormat {
+    /// Everything, with map keys that aren't strings written as RON
+    Json,
+    /// Everything
+    Ron,
+}
+
+/// Tools for inspecting and editing rtsim data. Ids are written as
+/// `INDEXvVERSION`, as shown by `query`.
+///
+/// These work on the file directly, so the server must not be running.
+#[derive(Clone, Debug, Parser)]
+pub enum Rtsim {
+    /// Writes the rtsim data out in a readable format
+    Dump {
+        #[arg(long, value_enum, default_value_t = DumpFormat::Ron)]
+        format: DumpFormat,
+        /// File to write to, defaults to stdout
+        out: Option<PathBuf>,
+    },
+    /// Lists the NPCs that match all of the given conditions, as JSON
+    Query(NpcQuery),
+    /// Changes an NPC
+    Edit {
+        npc: String,
+        /// Id of the new home site, or `none`
+        #[arg(long)]
+        home: Option<String>,
+        /// Id of the new faction, or `none`
+        #[arg(long)]
+        faction: Option<String>,
+        /// New position in world block coordinates, as `X,Y,Z`
+        #[arg(long, value_parser = parse_wpos, allow_hyphen_values = true)]
+        wpos: Option<Vec3<f32>>,
+        #[arg(long)]
+        dead: Option<bool>,
+    },
+    /// Deletes NPCs, along with anything that refers to them
+    Delete {
+        #[arg(required = true)]
+        npcs: Vec<String>,
+    },
+    /// Checks that every NPC, site and faction referred to exists
+    Validate {
+        /// Remove the references that are broken
+        #[arg(long)]
+        fix: bool,
+    },
+}
+
+/// Conditions for finding rtsim NPCs, from the command line or the web API
+#[derive(Clone, Debug, Args, Deserialize)]
+#[serde(default)]
+pub struct NpcQuery {
+    /// Id of the NPC's home site
+    #[arg(long)]
+    pub home: Option<String>,
+    /// Role or profession, such as `Merchant` or `Monster`
+    #[arg(long)]
+    pub role: Option<String>,
+    /// Id of the NPC's faction
+    #[arg(long)]
+    pub faction: Option<String>,
+    /// Only NPCs near this position, as `X,Y`
+    #[arg(long, allow_hyphen_values = true)]
+    pub near: Option<String>,
+    /// The radius of `near`, in blocks
+    #[arg(long, default_value_t = 64.0)]
+    pub radius: f32,
+    /// Include dead NPCs
+    #[arg(long)]
+    pub dead: bool,
+}
+
+impl Default for NpcQuery {
+    fn default() -> Self {
+        Self {
+            home: None,
+            role: None,
+            faction: None,
+            near: None,
+            radius: 64.0,
+            dead: false,
+        }
+    }
+}
+
+impl NpcQuery {
+    pub fn to_filter(&self) -> Result<NpcFilter, String> {
+        Ok(NpcFilter {
+            home: self.home.as_deref().map(parse_key).transpose()?,
+            role: self.role.clone(),
+            faction: self.faction.as_deref().map(parse_key).transpose()?,
+            near: self
+                .near
+                .as_deref()
+                .map(|near| parse_nums(near).map(|pos| (Vec2::from(pos), self.radius)))
+                .transpose()?,
+            include_dead: self.dead,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Parser)]
 pub enum SharedCommand {
     /// Perform operations on the admin list
@@ -79,12 +252,15 @@ pub enum Message {
     SendGlobalMsg {
         msg: String,
     },
+    /// Lists the rtsim NPCs that match all of the given conditions
+    QueryNpcs(NpcQuery),
 }
 
 #[derive(Debug, Clone)]
 pub enum MessageReturn {
     Players(Vec<String>),
     Logs(Vec<String>),
+    Npcs(Result<Vec<NpcSummary>, String>),
 }
 
 #[derive(Parser)]
@@ -117,6 +293,17 @@ pub enum ArgvCommand {
     /// Load an area, run the server for some time, and then exit (useful for
     /// profiling).
     Bench(BenchParams),
+    /// Export, import, compare and roll back modified terrain
+    #[cfg(feature = "persistent_world")]
+    Terrain {
+        #[command(subcommand)]
+        command: Terrain,
+    },
+    /// Inspect and edit rtsim data
+    Rtsim {
+        #[command(subcommand)]
+        command: Rtsim,
+    },
 }
 
 #[derive(Parser)]
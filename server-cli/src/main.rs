@@ -17,9 +17,12 @@ mod shutdown_coordinator;
 mod tui_runner;
 mod tuilog;
 mod web;
+#[cfg(feature = "persistent_world")]
+use crate::cli::Terrain;
 use crate::{
     cli::{
-        Admin, ArgvApp, ArgvCommand, BenchParams, Message, MessageReturn, SharedCommand, Shutdown,
+        Admin, ArgvApp, ArgvCommand, BenchParams, DumpFormat, Message, MessageReturn, Rtsim,
+        SharedCommand, Shutdown,
     },
     settings::Settings,
     shutdown_coordinator::ShutdownCoordinator,
@@ -35,18 +38,224 @@ use common_base::span;
 use core::sync::atomic::{AtomicUsize, Ordering};
 use server::{persistence::DatabaseSettings, settings::Protocol, Event, Input, Server};
 use std::{
-    io,
+    fs, io,
+    path::Path,
     sync::{atomic::AtomicBool, Arc},
     time::{Duration, Instant},
 };
 use tokio::sync::Notify;
-use tracing::{info, trace};
+use tracing::{error, info, trace, warn};
 
 lazy_static::lazy_static! {
     pub static ref LOG: TuiLog<'static> = TuiLog::default();
 }
 const TPS: u64 = 30;
 
+#[cfg(feature = "persistent_world")]
+fn run_terrain_command(command: Terrain, terrain_dir: &std::path::Path) -> io::Result<()> {
+    use server::terrain_persistence::Snapshot;
+
+    match command {
+        Terrain::Export { region, archive } => {
+            let snapshot = Snapshot::load(terrain_dir)?.extract(region);
+            snapshot.export(&archive)?;
+            info!(
+                "Exported {} modified blocks to {}",
+                snapshot.len(),
+                archive.display()
+            );
+        },
+        Terrain::Import { archive, offset } => {
+            let snapshot = Snapshot::load(&archive)?.translate(offset.unwrap_or_default());
+            let Some(region) = snapshot.region() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Only archives can be imported, use rollback to restore from a terrain \
+                     directory",
+                ));
+            };
+            let changed = snapshot.restore(terrain_dir, region)?;
+            info!(
+                "Imported {} modified blocks, {} chunks were changed",
+                snapshot.len(),
+                changed
+            );
+        },
+        Terrain::Diff { old, new, region } => {
+            let old = Snapshot::load(&old)?;
+            let new = Snapshot::load(new.as_deref().unwrap_or(terrain_dir))?;
+            let diff = old.diff(&new, region);
+            for (key, chunk) in &diff {
+                info!(
+                    "Chunk {},{}: {} added, {} removed, {} changed",
+                    key.x, key.y, chunk.added, chunk.removed, chunk.changed
+                );
+            }
+            info!("{} chunks differ", diff.len());
+        },
+        Terrain::Rollback { snapshot, region } => {
+            let changed = Snapshot::load(&snapshot)?.restore(terrain_dir, region)?;
+            info!("Rolled back {} chunks", changed);
+        },
+    }
+    Ok(())
+}
+
+fn run_rtsim_command(command: Rtsim, data_file: &Path) -> io::Result<()> {
+    use rtsim::data::{
+        inspect::{parse_key, parse_link, NpcFilter, NpcSummary},
+        Data, ReadError,
+    };
+
+    let invalid_input = |err: String| io::Error::new(io::ErrorKind::InvalidInput, err);
+    let summaries = |data: &Data, filter: &NpcFilter| {
+        data.query_npcs(filter)
+            .map(|(id, npc)| NpcSummary::new(data, id, npc))
+            .collect::<Vec<_>>()
+    };
+
+    let mut data = match Data::from_reader(io::BufReader::new(fs::File::open(data_file)?)) {
+        Ok(data) => data,
+        Err(ReadError::Load(err)) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        Err(ReadError::VersionMismatch(_)) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Rtsim data is from an unsupported version",
+            ));
+        },
+    };
+
+    match command {
+        Rtsim::Dump { format, out } => {
+            let dump = match format {
+                DumpFormat::Json => {
+                    // Go through the saved form of the data, which keeps the names of fields
+                    // and enum variants, since JSON can't have the keys that the data uses
+                    let mut bytes = Vec::new();
+                    data.write_to(&mut bytes).map_err(io::Error::other)?;
+                    let value: ron::Value =
+                        rmp_serde::from_slice(&bytes).map_err(io::Error::other)?;
+                    serde_json::to_string_pretty(&ron_to_json(value))?
+                },
+                DumpFormat::Ron => {
+                    ron::ser::to_string_pretty(&*data, ron::ser::PrettyConfig::default())
+                        .map_err(io::Error::other)?
+                },
+            };
+            match out {
+                Some(path) => fs::write(path, dump)?,
+                None => println!("{dump}"),
+            }
+        },
+        Rtsim::Query(query) => {
+            let npcs = summaries(&data, &query.to_filter().map_err(invalid_input)?);
+            println!("{}", serde_json::to_string_pretty(&npcs)?);
+            info!("{} NPCs matched", npcs.len());
+        },
+        Rtsim::Edit {
+            npc: npc_id,
+            home,
+            faction,
+            wpos,
+            dead,
+        } => {
+            let npc = parse_key(&npc_id)
+                .ok()
+                .and_then(|id| data.npcs.get_mut(id))
+                .ok_or_else(|| invalid_input(format!("There is no NPC {npc_id}")))?;
+            if let Some(home) = home {
+                npc.home = parse_link(&home).map_err(invalid_input)?;
+            }
+            if let Some(faction) = faction {
+                npc.faction = parse_link(&faction).map_err(invalid_input)?;
+            }
+            if let Some(wpos) = wpos {
+                npc.wpos = wpos;
+            }
+            if let Some(dead) = dead {
+                npc.is_dead = dead;
+            }
+            write_rtsim_data(&data, data_file)?;
+            info!("Edited NPC {}", npc_id);
+        },
+        Rtsim::Delete { npcs } => {
+            for npc_id in &npcs {
+                parse_key(npc_id)
+                    .ok()
+                    .and_then(|id| data.delete_npc(id))
+                    .ok_or_else(|| invalid_input(format!("There is no NPC {npc_id}")))?;
+            }
+            write_rtsim_data(&data, data_file)?;
+            info!("Deleted {} NPCs", npcs.len());
+        },
+        Rtsim::Validate { fix } => {
+            let errors = if fix {
+                data.fix_links()
+            } else {
+                data.validate_links()
+            };
+            for error in &errors {
+                warn!("{}", error);
+            }
+            if fix && !errors.is_empty() {
+                write_rtsim_data(&data, data_file)?;
+                info!("Fixed {} broken links", errors.len());
+            } else {
+                info!("Found {} broken links", errors.len());
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Converts a RON value into JSON. JSON only allows strings as keys, so other
+/// keys are written as RON.
+fn ron_to_json(value: ron::Value) -> serde_json::Value {
+    use ron::{Number, Value};
+    match value {
+        Value::Bool(b) => b.into(),
+        Value::Char(c) => c.to_string().into(),
+        Value::String(s) => s.into(),
+        Value::Number(Number::Integer(i)) => i.into(),
+        Value::Number(Number::Float(f)) => f.get().into(),
+        Value::Option(value) => value.map_or(serde_json::Value::Null, |value| ron_to_json(*value)),
+        Value::Unit => serde_json::Value::Null,
+        Value::Seq(values) => values.into_iter().map(ron_to_json).collect(),
+        Value::Map(map) => map
+            .into_iter()
+            .map(|(key, value)| {
+                let key = match key {
+                    Value::String(key) => key,
+                    key => ron::to_string(&key).unwrap_or_default(),
+                };
+                (key, ron_to_json(value))
+            })
+            .collect(),
+    }
+}
+
+/// Writes rtsim data back to disk, as long as all of its links are valid.
+fn write_rtsim_data(data: &rtsim::Data, data_file: &Path) -> io::Result<()> {
+    let errors = data.validate_links();
+    if !errors.is_empty() {
+        for error in &errors {
+            error!("{}", error);
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Refusing to write rtsim data with broken links, these can be removed with `rtsim \
+             validate --fix`",
+        ));
+    }
+
+    let tmp_file = data_file.with_extension("dat.tmp");
+    let mut writer = io::BufWriter::new(fs::File::create(&tmp_file)?);
+    data.write_to(&mut writer).map_err(io::Error::other)?;
+    io::Write::flush(&mut writer)?;
+    drop(writer);
+    fs::rename(tmp_file, data_file)
+}
+
 fn main() -> io::Result<()> {
     #[cfg(feature = "tracy")]
     common_base::tracy_client::Client::start();
@@ -179,6 +388,16 @@ fn main() -> io::Result<()> {
                     },
                 };
             },
+            #[cfg(feature = "persistent_world")]
+            ArgvCommand::Terrain { command } => {
+                return run_terrain_command(
+                    command,
+                    &server::terrain_persistence::terrain_dir(server_data_dir),
+                );
+            },
+            ArgvCommand::Rtsim { command } => {
+                return run_rtsim_command(command, &server::rtsim::data_file(server_data_dir));
+            },
             ArgvCommand::Bench(params) => {
                 bench = Some(params);
                 // If we are trying to benchmark, don't limit the server view distance.
@@ -404,6 +623,23 @@ fn server_loop(
                     let msg = ChatType::Meta.into_plain_msg(msg);
                     server.state().send_chat(msg);
                 },
+                Message::QueryNpcs(query) => {
+                    use rtsim::data::inspect::NpcSummary;
+                    let npcs = query.to_filter().and_then(|filter| {
+                        let rtsim = server
+                            .state()
+                            .ecs()
+                            .try_fetch::<server::rtsim::RtSim>()
+                            .ok_or_else(|| "Rtsim is not running".to_string())?;
+                        let data = rtsim.state().data();
+                        let npcs = data
+                            .query_npcs(&filter)
+                            .map(|(id, npc)| NpcSummary::new(&data, id, npc))
+                            .collect();
+                        Ok(npcs)
+                    });
+                    let _ = response.send(MessageReturn::Npcs(npcs));
+                },
             }
             false
         };
@@ -419,6 +655,16 @@ fn server_loop(
                     match msg_answ {
                         MessageReturn::Players(players) => info!("Players: {:?}", players),
                         MessageReturn::Logs(_) => info!("skipp sending logs to tui"),
+                        MessageReturn::Npcs(Ok(npcs)) => {
+                            for npc in &npcs {
+                                info!(
+                                    "{} {} ({}) at {:?}, home: {:?}",
+                                    npc.id, npc.name, npc.role, npc.wpos, npc.home
+                                );
+                            }
+                            info!("{} NPCs matched", npcs.len());
+                        },
+                        MessageReturn::Npcs(Err(err)) => warn!("Could not query NPCs: {}", err),
                     };
                 }
             }
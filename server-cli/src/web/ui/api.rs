@@ -1,6 +1,6 @@
-use crate::cli::{Message, MessageReturn};
+use crate::cli::{Message, MessageReturn, NpcQuery};
 use axum::{
-    extract::{ConnectInfo, State},
+    extract::{ConnectInfo, Query, State},
     http::header::COOKIE,
     middleware::Next,
     response::{IntoResponse, Response},
@@ -73,6 +73,7 @@ pub fn router(web_ui_request_s: UiRequestSender, secret_token: String) -> Router
         .route("/players", get(players))
         .route("/logs", get(logs))
         .route("/send_global_msg", post(send_global_msg))
+        .route("/rtsim/npcs", get(rtsim_npcs))
         .layer(axum::middleware::from_fn_with_state(ip_addrs, log_users))
         .layer(axum::middleware::from_fn_with_state(token, validate_secret))
         .with_state(web_ui_request_s)
@@ -121,3 +122,21 @@ async fn send_global_msg(
         .await;
     Ok(())
 }
+
+async fn rtsim_npcs(
+    State(web_ui_request_s): State<UiRequestSender>,
+    Query(query): Query<NpcQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let _ = web_ui_request_s
+        .send((Message::QueryNpcs(query), sender))
+        .await;
+    match receiver
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    {
+        MessageReturn::Npcs(Ok(npcs)) => Ok(Json(npcs)),
+        MessageReturn::Npcs(Err(_)) => Err(StatusCode::BAD_REQUEST),
+        _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
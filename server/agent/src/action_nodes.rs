@@ -22,6 +22,7 @@ use common::{
             ConsumableKind, Effects, Item, ItemDesc, ItemKind,
         },
         item_drop,
+        pet::{PetOrder, PetStance},
         projectile::ProjectileConstructor,
         Agent, Alignment, Body, CharacterState, Content, ControlAction, ControlEvent, Controller,
         HealthChange, InputKind, InventoryAction, Pos, Scale, UnresolvedChatMsg, UtteranceKind,
@@ -1800,6 +1801,50 @@ impl<'a> AgentData<'a> {
         }
     }
 
+    /// Targets whoever is at the top of the agent's threat table, returns
+    /// whether there was anybody to target
+    pub fn target_by_threat(
+        &self,
+        agent: &mut Agent,
+        controller: &mut Controller,
+        read_data: &ReadData,
+    ) -> bool {
+        // Entities that are far away are no longer a threat, even if they hurt us
+        let max_dist_sqrd = agent.psyche.search_dist().powi(2);
+        let is_valid = |uid| {
+            get_entity_by_id(uid, read_data).map_or(false, |entity| {
+                entity != *self.entity
+                    && !is_dead_or_invulnerable(entity, read_data)
+                    && read_data.positions.get(entity).map_or(false, |pos| {
+                        pos.0.distance_squared(self.pos.0) < max_dist_sqrd
+                    })
+            })
+        };
+        let current = agent
+            .target
+            .filter(|target| target.hostile)
+            .and_then(|target| read_data.uids.get(target.target).copied());
+
+        let Some(chosen) = agent.threat.choose(current, read_data.time.0, is_valid) else {
+            return false;
+        };
+        if Some(chosen) != current
+            && let Some(entity) = get_entity_by_id(chosen, read_data)
+        {
+            if agent.target.is_none() {
+                controller.push_utterance(UtteranceKind::Angry);
+            }
+            agent.target = Some(Target::new(
+                entity,
+                true,
+                read_data.time.0,
+                true,
+                read_data.positions.get(entity).map(|pos| pos.0),
+            ));
+        }
+        true
+    }
+
     pub fn attack_target_attacker(
         &self,
         agent: &mut Agent,
@@ -1957,11 +2002,29 @@ impl<'a> AgentData<'a> {
         let other_alignment = read_data.alignments.get(entity);
 
         (entity != *self.entity)
+            && self.may_seek_enemies()
             && !self.passive_towards(entity, read_data)
             && (are_our_owners_hostile(self.alignment, other_alignment, read_data)
                 || (is_villager(self.alignment) && is_dressed_as_cultist(entity, read_data)))
     }
 
+    /// Whether this agent looks for enemies by itself. Pets only do so if
+    /// their owner has allowed it through their stance and order.
+    pub fn may_seek_enemies(&self) -> bool {
+        self.pet.map_or(true, |pet| match pet.order {
+            PetOrder::Stay => false,
+            PetOrder::Guard => pet.stance != PetStance::Passive,
+            PetOrder::Follow => pet.stance == PetStance::Aggressive,
+        })
+    }
+
+    /// Whether this agent is a pet that must not fight, not even to defend
+    /// itself or its owner
+    pub fn is_passive_pet(&self) -> bool {
+        self.pet
+            .map_or(false, |pet| pet.stance == PetStance::Passive)
+    }
+
     pub fn is_hunting_animal(&self, entity: EcsEntity, read_data: &ReadData) -> bool {
         (entity != *self.entity)
             && !self.friendly_towards(entity, read_data)
@@ -6,6 +6,9 @@ pub const MAX_PATH_DIST: f32 = 170.0;
 /// If the pet is any further than this value from its stay position, it will
 /// start walking back there
 pub const MAX_STAY_DISTANCE: f32 = 10.0;
+/// Pets staying at or guarding a position give up on enemies that are further
+/// than this value from it
+pub const MAX_GUARD_DISTANCE: f32 = 20.0;
 pub const PARTIAL_PATH_DIST: f32 = 50.0;
 pub const SEPARATION_DIST: f32 = 10.0;
 pub const SEPARATION_BIAS: f32 = 0.8;
@@ -13,8 +13,8 @@ use common::{
             slot::EquipSlot,
         },
         ActiveAbilities, Alignment, Body, CharacterState, Combo, Energy, Health, Inventory,
-        LightEmitter, LootOwner, Ori, PhysicsState, Poise, Pos, Presence, Scale, SkillSet, Stance,
-        Stats, Vel,
+        LightEmitter, LootOwner, Ori, Pet, PhysicsState, Poise, Pos, Presence, Scale, SkillSet,
+        Stance, Stats, Vel,
     },
     consts::GRAVITY,
     event, event_emitters,
@@ -52,6 +52,7 @@ pub struct AgentData<'a> {
     pub skill_set: &'a SkillSet,
     pub physics_state: &'a PhysicsState,
     pub alignment: Option<&'a Alignment>,
+    pub pet: Option<&'a Pet>,
     pub traversal_config: TraversalConfig,
     pub scale: f32,
     pub damage: f32,
@@ -401,6 +402,7 @@ pub struct ReadData<'a> {
     pub groups: ReadStorage<'a, group::Group>,
     pub terrain: ReadExpect<'a, TerrainGrid>,
     pub alignments: ReadStorage<'a, Alignment>,
+    pub pets: ReadStorage<'a, Pet>,
     pub bodies: ReadStorage<'a, Body>,
     pub is_mounts: ReadStorage<'a, Is<Mount>>,
     pub is_riders: ReadStorage<'a, Is<Rider>>,
@@ -0,0 +1,351 @@
+//! Region operations for build mode, which builders run with `/edit`.
+//!
+//! Builders select a region by marking two corners, which their client shows
+//! as a box. Operations on the region are queued and only change a limited
+//! number of blocks each tick, so that large regions don't stall the server.
+//! Blocks outside of the builder's build areas are left alone, and each
+//! operation is recorded in the [`EditJournal`] so that it can be undone as a
+//! whole.
+
+use crate::edit_journal::{self, BlockEdit, EditJournal, MAX_HISTORY_BLOCKS};
+use common::{
+    comp::Content,
+    terrain::{schematic::Schematic, Block, BlockKind, TerrainGrid},
+    uuid::Uuid,
+    vol::ReadVol,
+};
+use hashbrown::HashMap;
+use specs::{Entity as EcsEntity, WorldExt};
+use std::collections::VecDeque;
+use vek::*;
+
+/// The most blocks that a single operation can cover
+pub const MAX_REGION_BLOCKS: u64 = 1 << 22;
+/// The largest radius or height of spheres and cylinders
+pub const MAX_BRUSH_SIZE: i32 = 64;
+/// How many blocks are visited each tick, across all queued operations
+const BLOCKS_PER_TICK: usize = 8192;
+
+/// Two corners marked by a builder. Until both are marked, the selection is
+/// just the one block.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Selection {
+    pub first: Option<Vec3<i32>>,
+    pub second: Option<Vec3<i32>>,
+}
+
+impl Selection {
+    /// The selected blocks (inclusive)
+    pub fn region(&self) -> Option<Aabb<i32>> {
+        let (first, second) = match (self.first, self.second) {
+            (Some(first), Some(second)) => (first, second),
+            (Some(corner), None) | (None, Some(corner)) => (corner, corner),
+            (None, None) => return None,
+        };
+        Some(
+            Aabb {
+                min: first,
+                max: second,
+            }
+            .made_valid(),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// Set every block
+    Fill(Block),
+    /// Set blocks of one kind to another block
+    Replace { from: BlockKind, to: Block },
+    /// Empty everything but the outer shell
+    Hollow,
+    /// Set the blocks on the vertical sides
+    Walls(Block),
+    /// Set the blocks within a sphere
+    Sphere {
+        center: Vec3<i32>,
+        radius: i32,
+        block: Block,
+    },
+    /// Set the blocks within an upright cylinder, standing on `base`
+    Cylinder {
+        base: Vec3<i32>,
+        radius: i32,
+        height: i32,
+        block: Block,
+    },
+    /// Place a schematic with its minimum corner at the minimum corner of the
+    /// region
+    Paste(Schematic),
+}
+
+impl Operation {
+    /// The block that `pos` should become, if it should change
+    fn block_at(&self, bounds: Aabb<i32>, pos: Vec3<i32>, old: Block) -> Option<Block> {
+        let Aabb { min, max } = bounds;
+        match self {
+            Operation::Fill(block) => Some(*block),
+            Operation::Replace { from, to } => (old.kind() == *from).then_some(*to),
+            Operation::Hollow => pos
+                .map3(min, max, |e, min, max| e > min && e < max)
+                .reduce_and()
+                .then(Block::empty),
+            Operation::Walls(block) => {
+                (pos.x == min.x || pos.x == max.x || pos.y == min.y || pos.y == max.y)
+                    .then_some(*block)
+            },
+            Operation::Sphere {
+                center,
+                radius,
+                block,
+            } => ((pos - center).magnitude_squared() <= radius.pow(2)).then_some(*block),
+            Operation::Cylinder {
+                base,
+                radius,
+                height,
+                block,
+            } => {
+                let in_circle = (pos.xy() - base.xy()).magnitude_squared() <= radius.pow(2);
+                (in_circle && (base.z..base.z + height).contains(&pos.z)).then_some(*block)
+            },
+            Operation::Paste(schematic) => schematic.get(pos - min),
+        }
+    }
+}
+
+/// An operation that is being carried out over several ticks
+struct Job {
+    owner: Uuid,
+    /// Who is told once the operation has finished
+    entity: EcsEntity,
+    operation: Operation,
+    bounds: Aabb<i32>,
+    /// The build areas of the owner when the operation was started
+    areas: Vec<Aabb<i32>>,
+    /// The index of the next block to visit, in x, then y, then z order
+    next: usize,
+    /// How many blocks have been changed so far
+    changed: usize,
+    /// The blocks that have been changed so far, unless there are too many to
+    /// be undone
+    edits: Vec<BlockEdit>,
+    skipped: usize,
+}
+
+impl Job {
+    fn size(&self) -> Vec3<usize> { (self.bounds.size() + 1).map(|e| e as usize).into() }
+
+    fn is_finished(&self) -> bool { self.next >= self.size().product() }
+
+    fn position(&self, index: usize) -> Vec3<i32> {
+        let size = self.size();
+        self.bounds.min
+            + Vec3::new(
+                index % size.x,
+                index / size.x % size.y,
+                index / (size.x * size.y),
+            )
+            .map(|e| e as i32)
+    }
+}
+
+#[derive(Default)]
+pub struct BuildTools {
+    selections: HashMap<Uuid, Selection>,
+    clipboards: HashMap<Uuid, Schematic>,
+    jobs: VecDeque<Job>,
+}
+
+impl BuildTools {
+    /// Mark one of the corners of a player's selection, returning the region
+    /// that is now selected
+    pub fn set_corner(&mut self, player: Uuid, second: bool, pos: Vec3<i32>) -> Option<Aabb<i32>> {
+        let selection = self.selections.entry(player).or_default();
+        if second {
+            selection.second = Some(pos);
+        } else {
+            selection.first = Some(pos);
+        }
+        selection.region()
+    }
+
+    pub fn clear_selection(&mut self, player: Uuid) { self.selections.remove(&player); }
+
+    pub fn selection(&self, player: Uuid) -> Option<Aabb<i32>> {
+        self.selections.get(&player)?.region()
+    }
+
+    pub fn set_clipboard(&mut self, player: Uuid, schematic: Schematic) {
+        self.clipboards.insert(player, schematic);
+    }
+
+    pub fn clipboard(&self, player: Uuid) -> Option<&Schematic> { self.clipboards.get(&player) }
+
+    /// Queue an operation, returning how many operations are ahead of it
+    pub fn start(
+        &mut self,
+        owner: Uuid,
+        entity: EcsEntity,
+        operation: Operation,
+        bounds: Aabb<i32>,
+        areas: Vec<Aabb<i32>>,
+    ) -> usize {
+        self.jobs.push_back(Job {
+            owner,
+            entity,
+            operation,
+            bounds: bounds.made_valid(),
+            areas,
+            next: 0,
+            changed: 0,
+            edits: Vec::new(),
+            skipped: 0,
+        });
+        self.jobs.len() - 1
+    }
+
+    /// Stop a player's queued operations, keeping whatever they have changed
+    /// so far. Returns whether there were any.
+    pub fn cancel(&mut self, player: Uuid, journal: &mut EditJournal) -> bool {
+        let (cancelled, jobs): (VecDeque<_>, _) =
+            self.jobs.drain(..).partition(|job| job.owner == player);
+        self.jobs = jobs;
+        let any = !cancelled.is_empty();
+        for job in cancelled {
+            journal.record(job.owner, job.edits);
+        }
+        any
+    }
+}
+
+/// Carry on with the queued operations, returning messages for the players
+/// whose operations have finished
+pub fn maintain(ecs: &specs::World) -> Vec<(EcsEntity, Content)> {
+    let mut build_tools = ecs.write_resource::<BuildTools>();
+    let mut budget = BLOCKS_PER_TICK;
+    let mut finished = Vec::new();
+    while budget > 0 {
+        let Some(job) = build_tools.jobs.front_mut() else {
+            break;
+        };
+        let changes = {
+            let terrain = ecs.read_resource::<TerrainGrid>();
+            let mut changes = Vec::new();
+            while budget > 0 && !job.is_finished() {
+                let pos = job.position(job.next);
+                job.next += 1;
+                budget -= 1;
+                match terrain.get(pos) {
+                    Ok(old) => {
+                        if let Some(block) = job.operation.block_at(job.bounds, pos, *old) {
+                            changes.push((pos, block, None));
+                        }
+                    },
+                    Err(_) => job.skipped += 1,
+                }
+            }
+            changes
+        };
+        let (edits, skipped) = edit_journal::apply_changes(ecs, Some(&job.areas), changes);
+        job.changed += edits.len();
+        if job.changed <= MAX_HISTORY_BLOCKS {
+            job.edits.extend(edits);
+        } else {
+            // Don't hold on to edits that can never be undone
+            job.edits = Vec::new();
+        }
+        job.skipped += skipped;
+
+        if job.is_finished() {
+            if let Some(job) = build_tools.jobs.pop_front() {
+                let msg = if job.changed <= MAX_HISTORY_BLOCKS {
+                    "command-edit-done"
+                } else {
+                    "command-edit-done-no-undo"
+                };
+                ecs.write_resource::<EditJournal>()
+                    .record(job.owner, job.edits);
+                finished.push((
+                    job.entity,
+                    Content::localized_with_args(msg, [
+                        ("blocks", job.changed.to_string()),
+                        ("skipped", job.skipped.to_string()),
+                    ]),
+                ));
+            }
+        }
+    }
+    finished
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operations() {
+        let bounds = Aabb {
+            min: Vec3::zero(),
+            max: Vec3::broadcast(4),
+        };
+        let stone = Block::new(BlockKind::Rock, Rgb::new(100, 100, 100));
+        let air = Block::empty();
+
+        assert_eq!(
+            Operation::Hollow.block_at(bounds, Vec3::new(2, 2, 2), stone),
+            Some(air)
+        );
+        assert_eq!(
+            Operation::Hollow.block_at(bounds, Vec3::new(0, 2, 2), stone),
+            None
+        );
+        assert_eq!(
+            Operation::Walls(stone).block_at(bounds, Vec3::new(2, 2, 0), air),
+            None
+        );
+        assert_eq!(
+            Operation::Walls(stone).block_at(bounds, Vec3::new(4, 2, 2), air),
+            Some(stone)
+        );
+        let replace = Operation::Replace {
+            from: BlockKind::Air,
+            to: stone,
+        };
+        assert_eq!(replace.block_at(bounds, Vec3::zero(), air), Some(stone));
+        assert_eq!(replace.block_at(bounds, Vec3::zero(), stone), None);
+        let sphere = Operation::Sphere {
+            center: Vec3::broadcast(2),
+            radius: 2,
+            block: stone,
+        };
+        assert_eq!(
+            sphere.block_at(bounds, Vec3::new(2, 2, 4), air),
+            Some(stone)
+        );
+        assert_eq!(sphere.block_at(bounds, Vec3::new(4, 4, 4), air), None);
+    }
+
+    #[test]
+    fn test_selection() {
+        let player = Uuid::new_v4();
+        let mut build_tools = BuildTools::default();
+        assert_eq!(build_tools.selection(player), None);
+        assert_eq!(
+            build_tools.set_corner(player, false, Vec3::new(5, 0, 3)),
+            Some(Aabb {
+                min: Vec3::new(5, 0, 3),
+                max: Vec3::new(5, 0, 3),
+            })
+        );
+        assert_eq!(
+            build_tools.set_corner(player, true, Vec3::new(1, 2, 3)),
+            Some(Aabb {
+                min: Vec3::new(1, 0, 3),
+                max: Vec3::new(5, 2, 3),
+            })
+        );
+        build_tools.clear_selection(player);
+        assert_eq!(build_tools.selection(player), None);
+    }
+}
@@ -66,11 +66,15 @@ impl ChunkGenerator {
         let rtsim_resources = Some(rtsim.get_chunk_resources(key));
         #[cfg(not(feature = "worldgen"))]
         let rtsim_resources = None;
+        #[cfg(feature = "worldgen")]
+        let rtsim_wildlife = Some(rtsim.get_chunk_wildlife(key));
+        #[cfg(not(feature = "worldgen"))]
+        let rtsim_wildlife = None;
 
         slowjob_pool.spawn("CHUNK_GENERATOR", move || {
             let index = index.as_index_ref();
             let payload = world
-                .generate_chunk(index, key, rtsim_resources, || cancel.load(Ordering::Relaxed), Some(time))
+                .generate_chunk(index, key, rtsim_resources, rtsim_wildlife, || cancel.load(Ordering::Relaxed), Some(time))
                 // FIXME: Since only the first entity who cancels a chunk is notified, we end up
                 // delaying chunk re-requests for up to 3 seconds for other clients, which isn't
                 // great.  We *could* store all the other requesting clients here, but it could
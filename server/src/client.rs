@@ -193,7 +193,10 @@ impl Client {
                     | ServerGeneral::MapMarker(_)
                     | ServerGeneral::WeatherUpdate(_)
                     | ServerGeneral::LocalWindUpdate(_)
-                    | ServerGeneral::SpectatePosition(_) => {
+                    | ServerGeneral::SpectatePosition(_)
+                    | ServerGeneral::PetStableUpdate(_)
+                    | ServerGeneral::AchievementUpdate(_)
+                    | ServerGeneral::BuildSelection(_) => {
                         PreparedMsg::new(2, &g, &self.in_game_stream_params)
                     },
                     // Terrain
@@ -5,6 +5,8 @@
 use crate::weather::WeatherJob;
 use crate::{
     client::Client,
+    edit_journal::{self, BlockEdit, EditJournal},
+    encounter::LootPreference,
     location::Locations,
     login_provider::LoginProvider,
     settings::{
@@ -42,6 +44,7 @@ use common::{
     },
     depot,
     effect::Effect,
+    encounter::LootRollChoice,
     event::{
         ClientDisconnectEvent, CreateNpcEvent, CreateSpecialEntityEvent, EventBus, ExplosionEvent,
         GroupManipEvent, InitiateInviteEvent, TamePetEvent,
@@ -55,7 +58,7 @@ use common::{
     resources::{BattleMode, PlayerPhysicsSettings, ProgramTime, Secs, Time, TimeOfDay, TimeScale},
     rtsim::{Actor, Role},
     spiral::Spiral2d,
-    terrain::{Block, BlockKind, CoordinateConversions, SpriteKind},
+    terrain::{schematic::Mirror, Block, BlockKind, CoordinateConversions, SpriteKind},
     tether::Tethered,
     uid::Uid,
     vol::ReadVol,
@@ -147,6 +150,7 @@ fn do_command(
         ServerChatCommand::BattleMode => handle_battlemode,
         ServerChatCommand::BattleModeForce => handle_battlemode_force,
         ServerChatCommand::Body => handle_body,
+        ServerChatCommand::Bounty => handle_bounty,
         ServerChatCommand::Buff => handle_buff,
         ServerChatCommand::Build => handle_build,
         ServerChatCommand::Campfire => handle_spawn_campfire,
@@ -156,6 +160,7 @@ fn do_command(
         ServerChatCommand::DisconnectAllPlayers => handle_disconnect_all_players,
         ServerChatCommand::DropAll => handle_drop_all,
         ServerChatCommand::Dummy => handle_spawn_training_dummy,
+        ServerChatCommand::Edit => handle_edit,
         ServerChatCommand::Explosion => handle_explosion,
         ServerChatCommand::Faction => handle_faction,
         ServerChatCommand::GiveItem => handle_give_item,
@@ -167,6 +172,7 @@ fn do_command(
         ServerChatCommand::GroupPromote => handle_group_promote,
         ServerChatCommand::Health => handle_health,
         ServerChatCommand::Help => handle_help,
+        ServerChatCommand::House => handle_house,
         ServerChatCommand::IntoNpc => handle_into_npc,
         ServerChatCommand::JoinFaction => handle_join_faction,
         ServerChatCommand::Jump => handle_jump,
@@ -184,6 +190,7 @@ fn do_command(
         ServerChatCommand::PermitBuild => handle_permit_build,
         ServerChatCommand::Players => handle_players,
         ServerChatCommand::Portal => handle_spawn_portal,
+        ServerChatCommand::Redo => handle_redo,
         ServerChatCommand::Region => handle_region,
         ServerChatCommand::ReloadChunks => handle_reload_chunks,
         ServerChatCommand::RemoveLights => handle_remove_lights,
@@ -210,6 +217,8 @@ fn do_command(
         ServerChatCommand::RtsimPurge => handle_rtsim_purge,
         ServerChatCommand::RtsimChunk => handle_rtsim_chunk,
         ServerChatCommand::Unban => handle_unban,
+        ServerChatCommand::Undo => handle_undo,
+        ServerChatCommand::UndoArea => handle_undo_area,
         ServerChatCommand::Version => handle_version,
         ServerChatCommand::Waypoint => handle_waypoint,
         ServerChatCommand::Wiring => handle_spawn_wiring,
@@ -217,16 +226,19 @@ fn do_command(
         ServerChatCommand::World => handle_world,
         ServerChatCommand::MakeVolume => handle_make_volume,
         ServerChatCommand::Location => handle_location,
+        ServerChatCommand::LootRoll => handle_loot_roll,
         ServerChatCommand::CreateLocation => handle_create_location,
         ServerChatCommand::DeleteLocation => handle_delete_location,
         ServerChatCommand::WeatherZone => handle_weather_zone,
         ServerChatCommand::Lightning => handle_lightning,
         ServerChatCommand::Scale => handle_scale,
+        ServerChatCommand::Schematic => handle_schematic,
         ServerChatCommand::RepairEquipment => handle_repair_equipment,
         ServerChatCommand::Tether => handle_tether,
         ServerChatCommand::DestroyTethers => handle_destroy_tethers,
         ServerChatCommand::Mount => handle_mount,
         ServerChatCommand::Dismount => handle_dismount,
+        ServerChatCommand::Threat => handle_threat,
     };
 
     handler(server, client, target, args, cmd)
@@ -608,6 +620,9 @@ fn handle_make_block(
             let pos = position(server, target, "target")?;
             let new_block = Block::new(bk, Rgb::new(r, g, b).map(|e| e.unwrap_or(255)));
             let pos = pos.0.map(|e| e.floor() as i32);
+            if let Some(old_block) = server.state.get_block(pos) {
+                record_edit(server, target, pos, old_block, new_block);
+            }
             server.state.set_block(pos, new_block);
             #[cfg(feature = "persistent_world")]
             if let Some(terrain_persistence) = server
@@ -630,6 +645,19 @@ fn handle_make_block(
     }
 }
 
+/// Remember a block that a command is about to change, so that the target can
+/// undo it
+fn record_edit(server: &Server, target: EcsEntity, pos: Vec3<i32>, before: Block, after: Block) {
+    if let Ok(uuid) = uuid(server, target, "target") {
+        let edit = BlockEdit::new(&server.state.terrain(), pos, before, after);
+        server
+            .state
+            .ecs()
+            .write_resource::<EditJournal>()
+            .record(uuid, vec![edit]);
+    }
+}
+
 fn handle_into_npc(
     server: &mut Server,
     client: EcsEntity,
@@ -779,12 +807,14 @@ fn handle_make_sprite(
         if let Ok(sk) = SpriteKind::try_from(sprite_name.as_str()) {
             let pos = position(server, target, "target")?;
             let pos = pos.0.map(|e| e.floor() as i32);
-            let new_block = server
-                .state
-                .get_block(pos)
+            let old_block = server.state.get_block(pos);
+            let new_block = old_block
                 // TODO: Make more principled.
                 .unwrap_or_else(|| Block::air(SpriteKind::Empty))
                 .with_sprite(sk);
+            if let Some(old_block) = old_block {
+                record_edit(server, target, pos, old_block, new_block);
+            }
             server.state.set_block(pos, new_block);
             #[cfg(feature = "persistent_world")]
             if let Some(terrain_persistence) = server
@@ -1501,6 +1531,7 @@ fn handle_rtsim_npc(
                         Role::Wild => "wild".to_string(),
                         Role::Monster => "monster".to_string(),
                         Role::Vehicle => "vehicle".to_string(),
+                        Role::PackAnimal => "pack_animal".to_string(),
                     },
                     format!("{:?}", npc.mode),
                     format!("{}", npc.uid),
@@ -4662,6 +4693,33 @@ fn set_skills(skill_set: &mut comp::SkillSet, preset: &str) -> CmdResult<()> {
     }
 }
 
+fn handle_loot_roll(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    let Some(name) = parse_cmd_args!(args, String) else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let choice = match name.as_str() {
+        "need" => LootRollChoice::Need,
+        "greed" => LootRollChoice::Greed,
+        "pass" => LootRollChoice::Pass,
+        _ => return Err(Content::Plain(action.help_string())),
+    };
+    insert_or_replace_component(server, target, LootPreference(choice), "target")?;
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(
+            ChatType::CommandInfo,
+            Content::localized_with_args("command-loot-roll-set", [("choice", name)]),
+        ),
+    );
+    Ok(())
+}
+
 fn handle_location(
     server: &mut Server,
     client: EcsEntity,
@@ -4794,6 +4852,7 @@ fn handle_weather_zone(
                     cloud: 0.0,
                     rain: 0.0,
                     wind: Vec2::zero(),
+                    ..Default::default()
                 });
                 Ok(())
             },
@@ -4802,6 +4861,7 @@ fn handle_weather_zone(
                     cloud: 0.4,
                     rain: 0.0,
                     wind: Vec2::zero(),
+                    ..Default::default()
                 });
                 Ok(())
             },
@@ -4810,6 +4870,7 @@ fn handle_weather_zone(
                     cloud: 0.1,
                     rain: 0.15,
                     wind: Vec2::new(1.0, -1.0),
+                    ..Default::default()
                 });
                 Ok(())
             },
@@ -4818,6 +4879,16 @@ fn handle_weather_zone(
                     cloud: 0.0,
                     rain: 0.0,
                     wind: Vec2::new(10.0, 10.0),
+                    ..Default::default()
+                });
+                Ok(())
+            },
+            "snow" => {
+                add_zone(weather::Weather {
+                    cloud: 0.3,
+                    snow: 0.3,
+                    wind: Vec2::new(2.0, -1.0),
+                    ..Default::default()
                 });
                 Ok(())
             },
@@ -4826,6 +4897,7 @@ fn handle_weather_zone(
                     cloud: 0.3,
                     rain: 0.3,
                     wind: Vec2::new(15.0, 20.0),
+                    ..Default::default()
                 });
                 Ok(())
             },
@@ -4916,6 +4988,525 @@ fn handle_scale(
     }
 }
 
+/// The areas that the target is allowed to build in
+fn build_areas_of(server: &Server, target: EcsEntity) -> Vec<Aabb<i32>> {
+    let ecs = server.state.ecs();
+    let build_areas = ecs.read_resource::<AreasContainer<BuildArea>>();
+    ecs.read_storage::<comp::CanBuild>()
+        .get(target)
+        .map(|can_build| {
+            can_build
+                .build_areas
+                .iter()
+                .filter_map(|area| build_areas.areas().get(*area).copied())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse how a schematic is mirrored (`x`, `y` or `xy`) and rotated (a multiple
+/// of 90 degrees)
+fn parse_transform(options: &[String]) -> Option<(Mirror, u8)> {
+    let mut mirror = Mirror::default();
+    let mut quarter_turns = 0;
+    for option in options {
+        match option.as_str() {
+            "x" => mirror.x = true,
+            "y" => mirror.y = true,
+            "xy" => (mirror.x, mirror.y) = (true, true),
+            degrees => match degrees.parse::<u32>() {
+                Ok(degrees) if degrees % 90 == 0 => quarter_turns = (degrees / 90 % 4) as u8,
+                _ => return None,
+            },
+        }
+    }
+    Some((mirror, quarter_turns))
+}
+
+fn handle_schematic(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    use crate::schematics::{SchematicError, Schematics, MAX_SCHEMATIC_BLOCKS};
+    use common::terrain::schematic::Schematic;
+
+    let (Some(schematic_action), name, options) =
+        parse_cmd_args!(args, String, String, ..Vec<String>)
+    else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let schematic_error = |name: &str, err: SchematicError| match err {
+        SchematicError::InvalidName => Content::localized("command-schematic-invalid-name"),
+        SchematicError::NotFound => {
+            Content::localized_with_args("command-schematic-not-found", [("name", name)])
+        },
+        SchematicError::Io(err) => {
+            Content::localized_with_args("command-schematic-failed", [("error", err.to_string())])
+        },
+        SchematicError::Invalid(err) => {
+            Content::localized_with_args("command-schematic-failed", [("error", err)])
+        },
+    };
+
+    let msg = match (schematic_action.as_str(), name) {
+        ("save", Some(name)) => {
+            let Some([x0, y0, z0, x1, y1, z1]) = options
+                .iter()
+                .map(|option| option.parse::<i32>().ok())
+                .collect::<Option<Vec<_>>>()
+                .and_then(|corners| <[i32; 6]>::try_from(corners).ok())
+            else {
+                return Err(Content::Plain(action.help_string()));
+            };
+            let bounds = Aabb {
+                min: Vec3::new(x0, y0, z0),
+                max: Vec3::new(x1, y1, z1),
+            }
+            .made_valid();
+            let blocks = (bounds.size() + 1).map(|e| e as u64).product();
+            if blocks > MAX_SCHEMATIC_BLOCKS as u64 {
+                return Err(Content::localized_with_args(
+                    "command-schematic-too-large",
+                    [("max", MAX_SCHEMATIC_BLOCKS.to_string())],
+                ));
+            }
+            if !build_areas_of(server, target)
+                .iter()
+                .any(|area| area.contains_point(bounds.min) && area.contains_point(bounds.max))
+            {
+                return Err(Content::localized("command-schematic-not-build-area"));
+            }
+            let schematic = Schematic::capture(&*server.state.terrain(), bounds)
+                .ok_or_else(|| Content::localized("command-schematic-not-loaded"))?;
+            server
+                .state
+                .ecs()
+                .read_resource::<Schematics>()
+                .save(&name, &schematic)
+                .map_err(|err| schematic_error(&name, err))?;
+            Content::localized_with_args("command-schematic-saved", [
+                ("name", name),
+                ("blocks", blocks.to_string()),
+            ])
+        },
+        ("paste", Some(name)) => {
+            let (mirror, quarter_turns) =
+                parse_transform(&options).ok_or_else(|| Content::Plain(action.help_string()))?;
+            let origin = position(server, target, "target")?
+                .0
+                .map(|e| e.floor() as i32);
+            let schematic = server
+                .state
+                .ecs()
+                .read_resource::<Schematics>()
+                .load(&name)
+                .map_err(|err| schematic_error(&name, err))?
+                .transformed(mirror, quarter_turns);
+            let uuid = uuid(server, target, "target")?;
+
+            let areas = build_areas_of(server, target);
+            let (edits, skipped) = edit_journal::apply_changes(
+                server.state.ecs(),
+                Some(&areas),
+                schematic
+                    .blocks()
+                    .map(|(rpos, block)| (origin + rpos, block, None)),
+            );
+            let placed = edits.len();
+            server
+                .state
+                .ecs()
+                .write_resource::<EditJournal>()
+                .record(uuid, edits);
+            let msg = if placed <= edit_journal::MAX_HISTORY_BLOCKS {
+                "command-schematic-pasted"
+            } else {
+                "command-schematic-pasted-no-undo"
+            };
+            Content::localized_with_args(msg, [
+                ("name", name),
+                ("blocks", placed.to_string()),
+                ("skipped", skipped.to_string()),
+            ])
+        },
+        ("list", _) => {
+            let names = server.state.ecs().read_resource::<Schematics>().list();
+            if names.is_empty() {
+                Content::localized("command-schematic-list-empty")
+            } else {
+                Content::localized_with_args("command-schematic-list", [(
+                    "names",
+                    names.join(", "),
+                )])
+            }
+        },
+        _ => return Err(Content::Plain(action.help_string())),
+    };
+
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(ChatType::CommandInfo, msg),
+    );
+    Ok(())
+}
+
+/// Parse a block from its kind followed by an optional colour
+fn parse_block(args: &[String], action: &ServerChatCommand) -> CmdResult<Block> {
+    let (kind, colour) = args
+        .split_first()
+        .ok_or_else(|| Content::Plain(action.help_string()))?;
+    let kind = BlockKind::from_str(kind).map_err(|_| {
+        Content::localized_with_args("command-invalid-block-kind", [("kind", kind.clone())])
+    })?;
+    let mut colour = colour.iter().map(|e| e.parse::<u8>());
+    let mut channel = || {
+        colour
+            .next()
+            .unwrap_or(Ok(255))
+            .map_err(|_| Content::Plain(action.help_string()))
+    };
+    Ok(Block::new(
+        kind,
+        Rgb::new(channel()?, channel()?, channel()?),
+    ))
+}
+
+fn handle_edit(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    use crate::build_tools::{BuildTools, Operation, MAX_BRUSH_SIZE, MAX_REGION_BLOCKS};
+    use common::terrain::schematic::Schematic;
+
+    let Some((edit_action, options)) = args.split_first() else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let uuid = uuid(server, target, "target")?;
+    let here = position(server, target, "target")?
+        .0
+        .map(|e| e.floor() as i32);
+    let selection = || {
+        server
+            .state
+            .ecs()
+            .read_resource::<BuildTools>()
+            .selection(uuid)
+            .ok_or_else(|| Content::localized("command-edit-no-selection"))
+    };
+    let size = |arg: Option<&String>| {
+        arg.and_then(|arg| arg.parse::<i32>().ok())
+            .filter(|size| (1..=MAX_BRUSH_SIZE).contains(size))
+            .ok_or_else(|| {
+                Content::localized_with_args("command-edit-invalid-size", [(
+                    "max",
+                    MAX_BRUSH_SIZE.to_string(),
+                )])
+            })
+    };
+    let notify = |msg| {
+        server.notify_client(
+            client,
+            ServerGeneral::server_msg(ChatType::CommandInfo, msg),
+        )
+    };
+
+    let (operation, bounds) = match edit_action.as_str() {
+        corner @ ("pos1" | "pos2") => {
+            let region = server
+                .state
+                .ecs()
+                .write_resource::<BuildTools>()
+                .set_corner(uuid, corner == "pos2", here);
+            server.notify_client(target, ServerGeneral::BuildSelection(region));
+            let blocks = region.map_or(0, |region| (region.size() + 1).map(|e| e as u64).product());
+            notify(Content::localized_with_args("command-edit-corner", [
+                ("corner", corner.to_owned()),
+                ("pos", format!("{} {} {}", here.x, here.y, here.z)),
+                ("blocks", blocks.to_string()),
+            ]));
+            return Ok(());
+        },
+        "deselect" => {
+            server
+                .state
+                .ecs()
+                .write_resource::<BuildTools>()
+                .clear_selection(uuid);
+            server.notify_client(target, ServerGeneral::BuildSelection(None));
+            notify(Content::localized("command-edit-deselected"));
+            return Ok(());
+        },
+        "cancel" => {
+            let cancelled = server.state.ecs().write_resource::<BuildTools>().cancel(
+                uuid,
+                &mut server.state.ecs().write_resource::<EditJournal>(),
+            );
+            if !cancelled {
+                return Err(Content::localized("command-edit-nothing-to-cancel"));
+            }
+            notify(Content::localized("command-edit-cancelled"));
+            return Ok(());
+        },
+        copy_or_cut @ ("copy" | "cut") => {
+            let region = selection()?;
+            let blocks = (region.size() + 1).map(|e| e as u64).product();
+            if blocks > MAX_REGION_BLOCKS {
+                return Err(Content::localized_with_args("command-edit-too-large", [(
+                    "max",
+                    MAX_REGION_BLOCKS.to_string(),
+                )]));
+            }
+            if !build_areas_of(server, target)
+                .iter()
+                .any(|area| area.contains_point(region.min) && area.contains_point(region.max))
+            {
+                return Err(Content::localized("command-edit-not-build-area"));
+            }
+            let schematic = Schematic::capture(&*server.state.terrain(), region)
+                .ok_or_else(|| Content::localized("command-edit-not-loaded"))?;
+            server
+                .state
+                .ecs()
+                .write_resource::<BuildTools>()
+                .set_clipboard(uuid, schematic);
+            if copy_or_cut == "copy" {
+                notify(Content::localized_with_args("command-edit-copied", [(
+                    "blocks",
+                    blocks.to_string(),
+                )]));
+                return Ok(());
+            }
+            (Operation::Fill(Block::empty()), region)
+        },
+        "paste" => {
+            let (mirror, quarter_turns) =
+                parse_transform(options).ok_or_else(|| Content::Plain(action.help_string()))?;
+            let schematic = server
+                .state
+                .ecs()
+                .read_resource::<BuildTools>()
+                .clipboard(uuid)
+                .ok_or_else(|| Content::localized("command-edit-empty-clipboard"))?
+                .transformed(mirror, quarter_turns);
+            let bounds = Aabb {
+                min: here,
+                max: here + schematic.size().map(|e| e as i32) - 1,
+            };
+            (Operation::Paste(schematic), bounds)
+        },
+        "fill" => (Operation::Fill(parse_block(options, action)?), selection()?),
+        "replace" => {
+            let (from, to) = options
+                .split_first()
+                .ok_or_else(|| Content::Plain(action.help_string()))?;
+            let from = BlockKind::from_str(from).map_err(|_| {
+                Content::localized_with_args("command-invalid-block-kind", [("kind", from.clone())])
+            })?;
+            let to = parse_block(to, action)?;
+            (Operation::Replace { from, to }, selection()?)
+        },
+        "hollow" => (Operation::Hollow, selection()?),
+        "walls" => (
+            Operation::Walls(parse_block(options, action)?),
+            selection()?,
+        ),
+        "sphere" => {
+            let radius = size(options.first())?;
+            let block = parse_block(options.get(1..).unwrap_or_default(), action)?;
+            let bounds = Aabb {
+                min: here - radius,
+                max: here + radius,
+            };
+            (
+                Operation::Sphere {
+                    center: here,
+                    radius,
+                    block,
+                },
+                bounds,
+            )
+        },
+        "cylinder" => {
+            let radius = size(options.first())?;
+            let height = size(options.get(1))?;
+            let block = parse_block(options.get(2..).unwrap_or_default(), action)?;
+            let bounds = Aabb {
+                min: here - Vec3::new(radius, radius, 0),
+                max: here + Vec3::new(radius, radius, height - 1),
+            };
+            (
+                Operation::Cylinder {
+                    base: here,
+                    radius,
+                    height,
+                    block,
+                },
+                bounds,
+            )
+        },
+        _ => return Err(Content::Plain(action.help_string())),
+    };
+
+    let blocks = (bounds.size() + 1).map(|e| e as u64).product();
+    if blocks > MAX_REGION_BLOCKS {
+        return Err(Content::localized_with_args("command-edit-too-large", [(
+            "max",
+            MAX_REGION_BLOCKS.to_string(),
+        )]));
+    }
+    let areas = build_areas_of(server, target);
+    let ahead = server
+        .state
+        .ecs()
+        .write_resource::<BuildTools>()
+        .start(uuid, client, operation, bounds, areas);
+    notify(Content::localized_with_args("command-edit-started", [
+        ("blocks", blocks.to_string()),
+        ("ahead", ahead.to_string()),
+    ]));
+    Ok(())
+}
+
+/// The most blocks away from the target that `/undo_area` can undo edits
+const MAX_UNDO_RADIUS: i32 = 256;
+
+/// Undo the target's most recent actions, or redo those that were undone.
+/// Players that aren't admins can only change blocks in their build areas.
+fn handle_history(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    redo: bool,
+) -> CmdResult<()> {
+    let count = parse_cmd_args!(args, u32).unwrap_or(1).max(1) as usize;
+    let uuid = uuid(server, target, "target")?;
+    let actions = {
+        let mut journal = server.state.ecs().write_resource::<EditJournal>();
+        if redo {
+            journal.take_redo(uuid, count)
+        } else {
+            journal.take_undo(uuid, count)
+        }
+    };
+    if actions.is_empty() {
+        return Err(Content::localized(if redo {
+            "command-redo-nothing"
+        } else {
+            "command-undo-nothing"
+        }));
+    }
+
+    let areas = (server.entity_admin_role(client) < Some(AdminRole::Admin))
+        .then(|| build_areas_of(server, target));
+    let (edits, skipped) = edit_journal::apply_changes(
+        server.state.ecs(),
+        areas.as_deref(),
+        edit_journal::reversal(actions.iter().flat_map(|edits| edits.iter().rev())),
+    );
+    let blocks = edits.len();
+    {
+        let mut journal = server.state.ecs().write_resource::<EditJournal>();
+        if redo {
+            journal.push_redone(uuid, edits);
+        } else {
+            journal.push_undone(uuid, edits);
+        }
+    }
+
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(
+            ChatType::CommandInfo,
+            Content::localized_with_args(
+                if redo {
+                    "command-redo-done"
+                } else {
+                    "command-undo-done"
+                },
+                [
+                    ("actions", actions.len().to_string()),
+                    ("blocks", blocks.to_string()),
+                    ("skipped", skipped.to_string()),
+                ],
+            ),
+        ),
+    );
+    Ok(())
+}
+
+fn handle_undo(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    _action: &ServerChatCommand,
+) -> CmdResult<()> {
+    handle_history(server, client, target, args, false)
+}
+
+fn handle_redo(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    _action: &ServerChatCommand,
+) -> CmdResult<()> {
+    handle_history(server, client, target, args, true)
+}
+
+fn handle_undo_area(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    let (Some(username), radius) = parse_cmd_args!(args, String, i32) else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let radius = radius.unwrap_or(16).clamp(1, MAX_UNDO_RADIUS);
+    let player_uuid = find_username(server, &username)?;
+    let center = position(server, target, "target")?
+        .0
+        .map(|e| e.floor() as i32);
+    let area = Aabb {
+        min: center - radius,
+        max: center + radius,
+    };
+
+    let edits = server
+        .state
+        .ecs()
+        .write_resource::<EditJournal>()
+        .take_in_area(player_uuid, area);
+    if edits.is_empty() {
+        return Err(Content::localized_with_args("command-undo-area-nothing", [
+            ("player", username),
+        ]));
+    }
+    let (restored, _) =
+        edit_journal::apply_changes(server.state.ecs(), None, edit_journal::reversal(&edits));
+
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(
+            ChatType::CommandInfo,
+            Content::localized_with_args("command-undo-area-done", [
+                ("player", username),
+                ("blocks", restored.len().to_string()),
+            ]),
+        ),
+    );
+    Ok(())
+}
+
 fn handle_repair_equipment(
     server: &mut Server,
     client: EcsEntity,
@@ -5113,3 +5704,325 @@ fn handle_dismount(
         Err(Content::localized("command-no-dismount"))
     }
 }
+
+fn handle_threat(
+    server: &mut Server,
+    client: EcsEntity,
+    _target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    let Some(entity_target) = parse_cmd_args!(args, EntityTarget) else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let entity = get_entity_target(entity_target, server)?;
+    let ecs = server.state.ecs();
+    let agents = ecs.read_storage::<comp::Agent>();
+    let agent = agents
+        .get(entity)
+        .ok_or_else(|| Content::Plain("Entity has no agent".to_string()))?;
+
+    let id_maps = ecs.read_resource::<common::uid::IdMaps>();
+    let players = ecs.read_storage::<comp::Player>();
+    let bodies = ecs.read_storage::<comp::Body>();
+    let uids = ecs.read_storage::<Uid>();
+    let current = agent
+        .target
+        .and_then(|target| uids.get(target.target).copied());
+
+    let mut info = String::new();
+    let _ = writeln!(&mut info, "-- Threat Table --");
+    let entries = agent.threat.entries();
+    if entries.is_empty() {
+        let _ = writeln!(&mut info, "<empty>");
+    }
+    for (uid, threat) in entries {
+        let entity = id_maps.uid_entity(uid);
+        let name = entity
+            .and_then(|e| players.get(e))
+            .map(|player| player.alias.clone())
+            .or_else(|| {
+                entity
+                    .and_then(|e| bodies.get(e))
+                    .map(|body| format!("{body:?}"))
+            })
+            .unwrap_or_else(|| "<gone>".to_string());
+        let marker = if Some(uid) == current {
+            " (target)"
+        } else {
+            ""
+        };
+        let _ = writeln!(&mut info, "{uid}: {name} {threat:.1}{marker}");
+    }
+
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(ChatType::CommandInfo, info),
+    );
+    Ok(())
+}
+
+fn handle_bounty(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    use crate::rtsim::RtSim;
+    // How close to the centre of a town you need to be to use its noticeboard
+    const NOTICEBOARD_RANGE: f32 = 64.0;
+
+    let Some(bounty_action) = parse_cmd_args!(args, String) else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let pos = position(server, target, "target")?.0;
+    let character = server
+        .state
+        .ecs()
+        .read_storage::<comp::Presence>()
+        .get(target)
+        .and_then(|presence| presence.kind.character_id())
+        .ok_or_else(|| Content::localized("command-bounty-no-character"))?;
+    let actor = common::rtsim::Actor::Character(character);
+
+    let ecs = server.state.ecs();
+    let rtsim = ecs.read_resource::<RtSim>();
+    let (site, bounty) = {
+        let data = rtsim.state().data();
+        let site = data
+            .sites
+            .iter()
+            .find(|(_, site)| {
+                site.wpos.as_::<f32>().distance_squared(pos.xy()) < NOTICEBOARD_RANGE.powi(2)
+            })
+            .map(|(id, _)| id)
+            .ok_or_else(|| Content::localized("command-bounty-no-noticeboard"))?;
+        (site, data.sites.bounty_on(site, actor))
+    };
+    if bounty == 0 {
+        return Err(Content::localized("command-bounty-none"));
+    }
+
+    let msg = match bounty_action.as_str() {
+        "info" => {
+            Content::localized_with_args("command-bounty-info", [("bounty", bounty.to_string())])
+        },
+        "pay" => {
+            let mut inventories = ecs.write_storage::<Inventory>();
+            let mut inventory = inventories
+                .get_mut(target)
+                .ok_or_else(|| Content::localized("command-bounty-no-character"))?;
+            if !crate::housing::take_coins(&mut inventory, bounty) {
+                return Err(Content::localized_with_args(
+                    "command-bounty-cannot-afford",
+                    [("bounty", bounty.to_string())],
+                ));
+            }
+            let paid = rtsim.state().data_mut().pay_bounty(site, actor);
+            Content::localized_with_args("command-bounty-paid", [("bounty", paid.to_string())])
+        },
+        _ => return Err(Content::Plain(action.help_string())),
+    };
+
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(ChatType::CommandInfo, msg),
+    );
+    Ok(())
+}
+
+fn handle_house(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: Vec<String>,
+    action: &ServerChatCommand,
+) -> CmdResult<()> {
+    use crate::housing::{self, Housing, Tenure};
+
+    let (Some(house_action), furniture) = parse_cmd_args!(args, String, String) else {
+        return Err(Content::Plain(action.help_string()));
+    };
+    let pos = position(server, target, "target")?
+        .0
+        .map(|e| e.floor() as i32);
+    let character = server
+        .state
+        .ecs()
+        .read_storage::<comp::Presence>()
+        .get(target)
+        .and_then(|presence| presence.kind.character_id())
+        .ok_or_else(|| Content::localized("command-house-no-character"))?;
+    let now = Utc::now().timestamp();
+
+    let msg = match house_action.as_str() {
+        "info" => {
+            let housing = server.state.ecs().read_resource::<Housing>();
+            let house = housing
+                .house_of(character)
+                .ok_or_else(|| Content::localized("command-house-none"))?;
+            let center = house.bounds.center();
+            let location = format!("{}, {}, {}", center.x, center.y, center.z);
+            let items = house.stored_items().to_string();
+            match house.tenure {
+                Tenure::Owned => Content::localized_with_args("command-house-info-owned", [
+                    ("location", location),
+                    ("items", items),
+                ]),
+                Tenure::Rented { until } => {
+                    let remaining = Duration::from_secs(until.saturating_sub(now).max(0) as u64);
+                    Content::localized_with_args("command-house-info-rented", [
+                        ("location", location),
+                        ("items", items),
+                        ("remaining", HumanDuration::from(remaining).to_string()),
+                    ])
+                },
+            }
+        },
+        "buy" | "rent" => {
+            let buy = house_action == "buy";
+            let price = if buy {
+                housing::HOUSE_PRICE
+            } else {
+                housing::HOUSE_RENT
+            };
+            let bounds = server
+                .world
+                .find_house(server.index.as_index_ref(), pos)
+                .ok_or_else(|| Content::localized("command-house-not-in-house"))?;
+
+            let ecs = server.state.ecs();
+            let mut housing = ecs.write_resource::<Housing>();
+            // The tenant of a house can extend their rent, or buy the house outright
+            let renewing = match housing.house_at(pos) {
+                Some(house) if house.owner == character => {
+                    if house.tenure == Tenure::Owned {
+                        return Err(Content::localized("command-house-already-owned"));
+                    }
+                    true
+                },
+                Some(_) => return Err(Content::localized("command-house-taken")),
+                None if housing.house_of(character).is_some() => {
+                    return Err(Content::localized("command-house-only-one"));
+                },
+                None => false,
+            };
+
+            let mut inventories = ecs.write_storage::<Inventory>();
+            let mut inventory = inventories
+                .get_mut(target)
+                .ok_or_else(|| Content::localized("command-house-no-character"))?;
+            if !housing::take_coins(&mut inventory, price) {
+                return Err(Content::localized_with_args(
+                    "command-house-cannot-afford",
+                    [("price", price.to_string())],
+                ));
+            }
+
+            if renewing {
+                if let Some(house) = housing.house_of_mut(character) {
+                    house.tenure = match house.tenure {
+                        Tenure::Rented { until } if !buy => Tenure::Rented {
+                            until: until.max(now) + housing::RENT_PERIOD,
+                        },
+                        _ => Tenure::Owned,
+                    };
+                }
+            } else {
+                let tenure = if buy {
+                    Tenure::Owned
+                } else {
+                    Tenure::Rented {
+                        until: now + housing::RENT_PERIOD,
+                    }
+                };
+                housing.claim(bounds, character, tenure);
+                if let Some(house) = housing.house_of(character)
+                    && ecs
+                        .write_resource::<AreasContainer<BuildArea>>()
+                        .insert(house.area_name(), house.bounds)
+                        .is_err()
+                {
+                    warn!("Build area {} for house already exists", house.area_name());
+                }
+            }
+
+            Content::localized_with_args(
+                if buy {
+                    "command-house-bought"
+                } else {
+                    "command-house-rented"
+                },
+                [("price", price.to_string())],
+            )
+        },
+        "leave" => {
+            let ecs = server.state.ecs();
+            let mut housing = ecs.write_resource::<Housing>();
+            match housing.house_of(character) {
+                None => return Err(Content::localized("command-house-none")),
+                Some(house) if house.stored_items() > 0 => {
+                    return Err(Content::localized("command-house-not-empty"));
+                },
+                Some(_) => {},
+            }
+            if let Some(house) = housing.release(character) {
+                let _ = ecs
+                    .write_resource::<AreasContainer<BuildArea>>()
+                    .remove(&house.area_name());
+                // Put the house back the way it was before the owner moved in
+                #[cfg(feature = "persistent_world")]
+                let mut terrain_persistence = ecs.try_fetch_mut::<crate::TerrainPersistence>();
+                for (pos, block) in house.original_blocks() {
+                    server.state.set_block(pos, block);
+                    #[cfg(feature = "persistent_world")]
+                    if let Some(terrain_persistence) = terrain_persistence.as_mut() {
+                        terrain_persistence.set_block(pos, block);
+                    }
+                }
+            }
+            Content::localized("command-house-left")
+        },
+        "furnish" => {
+            let Some(sprite) = furniture
+                .as_deref()
+                .and_then(|name| SpriteKind::try_from(name).ok())
+                .filter(SpriteKind::is_furniture)
+            else {
+                return Err(Content::Plain(action.help_string()));
+            };
+            if !server
+                .state
+                .ecs()
+                .read_resource::<Housing>()
+                .house_of(character)
+                .map_or(false, |house| house.contains(pos))
+            {
+                return Err(Content::localized("command-house-not-yours"));
+            }
+            let block = server
+                .state
+                .get_block(pos)
+                .filter(|block| {
+                    block.is_air() && block.get_sprite().map_or(true, |s| s == SpriteKind::Empty)
+                })
+                .ok_or_else(|| Content::localized("command-house-no-space"))?;
+            let new_block = block.with_sprite(sprite);
+            server.state.set_block(pos, new_block);
+            server
+                .state
+                .ecs()
+                .write_resource::<Housing>()
+                .set_block(pos, block, new_block);
+            Content::localized("command-house-furnished")
+        },
+        _ => return Err(Content::Plain(action.help_string())),
+    };
+
+    server.notify_client(
+        client,
+        ServerGeneral::server_msg(ChatType::CommandInfo, msg),
+    );
+    Ok(())
+}
@@ -0,0 +1,313 @@
+//! A bounded history of the blocks each player has changed while building, so
+//! that edits can be undone and redone.
+//!
+//! Edits are grouped into actions, such as breaking a single block or pasting
+//! a schematic, and are undone an action at a time. Undoing and redoing go
+//! through [`BlockChange`] like any other edit, so they are subject to the
+//! same limits.
+
+use crate::housing::Housing;
+#[cfg(feature = "persistent_world")]
+use crate::TerrainPersistence;
+use common::{
+    terrain::{Block, SpriteCfg, TerrainGrid},
+    uuid::Uuid,
+    vol::ReadVol,
+};
+use common_state::BlockChange;
+use hashbrown::HashMap;
+use specs::WorldExt;
+use std::{collections::VecDeque, sync::Arc};
+use vek::*;
+
+/// The most block edits kept for each player, the oldest actions are forgotten
+/// once there are more. Actions that change more blocks than this can't be
+/// undone at all.
+pub const MAX_HISTORY_BLOCKS: usize = 100_000;
+
+/// A single block that was changed
+#[derive(Clone, Debug)]
+pub struct BlockEdit {
+    pub pos: Vec3<i32>,
+    pub before: Block,
+    /// The configuration of the sprite that was replaced, such as the text on
+    /// a sign
+    pub before_cfg: Option<SpriteCfg>,
+    pub after: Block,
+}
+
+impl BlockEdit {
+    pub fn new(terrain: &TerrainGrid, pos: Vec3<i32>, before: Block, after: Block) -> Self {
+        Self {
+            pos,
+            before,
+            before_cfg: sprite_cfg_at(terrain, pos),
+            after,
+        }
+    }
+}
+
+#[derive(Default)]
+struct History {
+    /// Actions that can be undone, the most recent last
+    undo: VecDeque<Vec<BlockEdit>>,
+    /// Actions that can be redone, the most recently undone last
+    redo: Vec<Vec<BlockEdit>>,
+}
+
+impl History {
+    fn push_undo(&mut self, edits: Vec<BlockEdit>) {
+        if edits.len() > MAX_HISTORY_BLOCKS {
+            return;
+        }
+        self.undo.push_back(edits);
+        let mut blocks = self.undo.iter().map(Vec::len).sum::<usize>();
+        while blocks > MAX_HISTORY_BLOCKS {
+            blocks -= self.undo.pop_front().map_or(0, |edits| edits.len());
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct EditJournal {
+    histories: HashMap<Uuid, History>,
+}
+
+impl EditJournal {
+    /// Record a new action, which can no longer be followed by redoing
+    /// whatever was undone before it
+    pub fn record(&mut self, player: Uuid, edits: Vec<BlockEdit>) {
+        if edits.is_empty() {
+            return;
+        }
+        let history = self.histories.entry(player).or_default();
+        history.redo.clear();
+        history.push_undo(edits);
+    }
+
+    /// Take up to `count` of the most recent actions, most recent first
+    pub fn take_undo(&mut self, player: Uuid, count: usize) -> Vec<Vec<BlockEdit>> {
+        let Some(history) = self.histories.get_mut(&player) else {
+            return Vec::new();
+        };
+        (0..count).map_while(|_| history.undo.pop_back()).collect()
+    }
+
+    /// Take up to `count` of the most recently undone actions, most recent
+    /// first
+    pub fn take_redo(&mut self, player: Uuid, count: usize) -> Vec<Vec<BlockEdit>> {
+        let Some(history) = self.histories.get_mut(&player) else {
+            return Vec::new();
+        };
+        (0..count).map_while(|_| history.redo.pop()).collect()
+    }
+
+    /// Record the edits made by undoing, so that they can be redone
+    pub fn push_undone(&mut self, player: Uuid, edits: Vec<BlockEdit>) {
+        if !edits.is_empty() {
+            self.histories.entry(player).or_default().redo.push(edits);
+        }
+    }
+
+    /// Record the edits made by redoing, so that they can be undone again
+    pub fn push_redone(&mut self, player: Uuid, edits: Vec<BlockEdit>) {
+        if !edits.is_empty() {
+            self.histories.entry(player).or_default().push_undo(edits);
+        }
+    }
+
+    /// Take all of the edits that a player made within an area, most recent
+    /// first
+    pub fn take_in_area(&mut self, player: Uuid, area: Aabb<i32>) -> Vec<BlockEdit> {
+        let Some(history) = self.histories.get_mut(&player) else {
+            return Vec::new();
+        };
+        let mut taken = Vec::new();
+        for edits in history.undo.iter_mut().rev() {
+            let (inside, outside): (Vec<_>, Vec<_>) = edits
+                .drain(..)
+                .partition(|edit| area.contains_point(edit.pos));
+            *edits = outside;
+            taken.extend(inside.into_iter().rev());
+        }
+        history.undo.retain(|edits| !edits.is_empty());
+        taken
+    }
+}
+
+/// The changes that reverse a list of edits, given most recent first. Where a
+/// block was edited more than once, it is put back how it was before the
+/// oldest edit.
+pub fn reversal<'a>(
+    edits: impl IntoIterator<Item = &'a BlockEdit>,
+) -> Vec<(Vec3<i32>, Block, Option<SpriteCfg>)> {
+    let mut indices = HashMap::<Vec3<i32>, usize>::new();
+    let mut changes = Vec::new();
+    for edit in edits {
+        let change = (edit.pos, edit.before, edit.before_cfg.clone());
+        match indices.get(&edit.pos) {
+            Some(index) => changes[*index] = change,
+            None => {
+                indices.insert(edit.pos, changes.len());
+                changes.push(change);
+            },
+        }
+    }
+    changes
+}
+
+/// The configuration of the sprite at a position, if it has one
+pub fn sprite_cfg_at(terrain: &TerrainGrid, pos: Vec3<i32>) -> Option<SpriteCfg> {
+    terrain
+        .pos_chunk(pos)
+        .and_then(|chunk| chunk.meta().sprite_cfg_at(TerrainGrid::chunk_offs(pos)))
+        .cloned()
+}
+
+/// Put back the configuration of a sprite. This only changes the server's copy
+/// of the chunk, clients see it once they next load the chunk.
+fn set_sprite_cfg_at(terrain: &mut TerrainGrid, pos: Vec3<i32>, sprite_cfg: SpriteCfg) {
+    let key = terrain.pos_key(pos);
+    if let Some(mut chunk) = terrain.get_key_arc(key).cloned() {
+        Arc::make_mut(&mut chunk)
+            .meta_mut()
+            .set_sprite_cfg_at(TerrainGrid::chunk_offs(pos), sprite_cfg);
+        terrain.insert(key, chunk);
+    }
+}
+
+/// Change blocks as part of the next batch of block changes, skipping those
+/// outside of `areas` if any are given. Returns the edits that were made and
+/// the number of blocks that couldn't be changed.
+pub fn apply_changes(
+    ecs: &specs::World,
+    areas: Option<&[Aabb<i32>]>,
+    changes: impl IntoIterator<Item = (Vec3<i32>, Block, Option<SpriteCfg>)>,
+) -> (Vec<BlockEdit>, usize) {
+    let mut terrain = ecs.write_resource::<TerrainGrid>();
+    let mut block_change = ecs.write_resource::<BlockChange>();
+    let mut housing = ecs.write_resource::<Housing>();
+    #[cfg(feature = "persistent_world")]
+    let mut terrain_persistence = ecs.try_fetch_mut::<TerrainPersistence>();
+
+    let mut edits = Vec::new();
+    let mut skipped = 0;
+    for (pos, block, sprite_cfg) in changes {
+        if areas.map_or(false, |areas| {
+            !areas.iter().any(|area| area.contains_point(pos))
+        }) {
+            skipped += 1;
+            continue;
+        }
+        let Ok(before) = terrain.get(pos).copied() else {
+            skipped += 1;
+            continue;
+        };
+        if before == block && sprite_cfg.is_none() {
+            continue;
+        }
+        if block_change.try_set(pos, block).is_some() {
+            edits.push(BlockEdit::new(&terrain, pos, before, block));
+            if let Some(sprite_cfg) = sprite_cfg {
+                set_sprite_cfg_at(&mut terrain, pos, sprite_cfg);
+            }
+            housing.set_block(pos, before, block);
+            #[cfg(feature = "persistent_world")]
+            if let Some(terrain_persistence) = terrain_persistence.as_mut() {
+                terrain_persistence.set_block(pos, block);
+            }
+        } else {
+            skipped += 1;
+        }
+    }
+    (edits, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::terrain::BlockKind;
+
+    fn edit(x: i32, before: Block, after: Block) -> BlockEdit {
+        BlockEdit {
+            pos: Vec3::new(x, 0, 0),
+            before,
+            before_cfg: None,
+            after,
+        }
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let player = Uuid::new_v4();
+        let air = Block::empty();
+        let stone = Block::new(BlockKind::Rock, Rgb::new(100, 100, 100));
+        let mut journal = EditJournal::default();
+
+        journal.record(player, vec![edit(0, air, stone)]);
+        journal.record(player, vec![edit(0, stone, air), edit(1, air, stone)]);
+
+        let undone = journal.take_undo(player, 2);
+        assert_eq!(undone.len(), 2);
+        // The block that was edited twice goes back to how it was at first
+        let changes = reversal(undone.iter().flatten());
+        assert_eq!(
+            changes
+                .iter()
+                .map(|(pos, block, _)| (pos.x, *block))
+                .collect::<Vec<_>>(),
+            vec![(0, air), (1, air)]
+        );
+        assert!(journal.take_undo(player, 1).is_empty());
+
+        journal.push_undone(player, vec![edit(1, stone, air)]);
+        assert_eq!(journal.take_redo(player, 5).len(), 1);
+
+        // Recording a new action forgets what could have been redone
+        journal.push_undone(player, vec![edit(1, stone, air)]);
+        journal.record(player, vec![edit(2, air, stone)]);
+        assert!(journal.take_redo(player, 1).is_empty());
+    }
+
+    #[test]
+    fn test_oversized_action_is_not_kept() {
+        let player = Uuid::new_v4();
+        let air = Block::empty();
+        let stone = Block::new(BlockKind::Rock, Rgb::new(100, 100, 100));
+        let mut journal = EditJournal::default();
+        journal.record(player, vec![edit(0, air, stone)]);
+        journal.record(
+            player,
+            (0..MAX_HISTORY_BLOCKS as i32 + 1)
+                .map(|x| edit(x, stone, air))
+                .collect(),
+        );
+
+        // The oversized action can't be undone, but older actions still can
+        let undo = journal.take_undo(player, 5);
+        assert_eq!(undo.len(), 1);
+        assert_eq!(undo[0].len(), 1);
+        assert!(journal.take_undo(player, 5).is_empty());
+    }
+
+    #[test]
+    fn test_take_in_area() {
+        let player = Uuid::new_v4();
+        let air = Block::empty();
+        let stone = Block::new(BlockKind::Rock, Rgb::new(100, 100, 100));
+        let mut journal = EditJournal::default();
+        journal.record(player, vec![edit(0, air, stone), edit(50, air, stone)]);
+        journal.record(player, vec![edit(1, air, stone)]);
+
+        let area = Aabb {
+            min: Vec3::broadcast(-10),
+            max: Vec3::broadcast(10),
+        };
+        let taken = journal.take_in_area(player, area);
+        assert_eq!(
+            taken.iter().map(|edit| edit.pos.x).collect::<Vec<_>>(),
+            vec![1, 0]
+        );
+        assert_eq!(journal.take_undo(player, 5).len(), 1);
+    }
+}
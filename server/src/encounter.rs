@@ -0,0 +1,223 @@
+use common::{
+    assets::{AssetExt, AssetHandle},
+    encounter::{EncounterDef, EncounterInfo, LootRollChoice, LootRule},
+    resources::Time,
+    terrain::{Block, BlockKind, TerrainGrid},
+    uid::Uid,
+    vol::ReadVol,
+};
+use common_state::BlockChange;
+use hashbrown::HashSet;
+use rand::{seq::SliceRandom, Rng};
+use specs::Component;
+use tracing::warn;
+use vek::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EncounterState {
+    /// Nobody is fighting the boss
+    Idle,
+    Engaged {
+        since: Time,
+        enraged: bool,
+    },
+}
+
+/// Attached to the boss of an encounter, keeps track of the progress of the
+/// fight
+#[derive(Clone, Debug)]
+pub struct BossEncounter {
+    pub def: AssetHandle<EncounterDef>,
+    pub arena: Aabb<i32>,
+    pub doors: Vec<Aabb<i32>>,
+    /// Where the boss returns to when the encounter resets
+    pub home: Vec3<f32>,
+    pub state: EncounterState,
+    /// Number of phases that have begun
+    pub phase: usize,
+    /// Players that have been inside the arena while the encounter was
+    /// ongoing
+    pub participants: HashSet<Uid>,
+    /// Blocks that were replaced to seal the doors, along with what they were
+    /// before
+    sealed: Vec<(Vec3<i32>, Block)>,
+    /// When the last participant left the arena
+    pub empty_since: Option<Time>,
+}
+
+impl BossEncounter {
+    pub fn new(info: EncounterInfo, home: Vec3<f32>) -> Option<Self> {
+        let def = EncounterDef::load(&info.def)
+            .map_err(|err| warn!(?err, "Failed to load encounter {}", info.def))
+            .ok()?;
+        Some(Self {
+            def,
+            arena: info.arena,
+            doors: info.doors,
+            home,
+            state: EncounterState::Idle,
+            phase: 0,
+            participants: HashSet::new(),
+            sealed: Vec::new(),
+            empty_since: None,
+        })
+    }
+
+    pub fn is_engaged(&self) -> bool { matches!(self.state, EncounterState::Engaged { .. }) }
+
+    pub fn contains(&self, pos: Vec3<f32>) -> bool {
+        self.arena.contains_point(pos.map(|e| e.floor() as i32))
+    }
+
+    /// Fills the open parts of the doors with solid blocks, so that nobody can
+    /// enter or leave the arena
+    pub fn seal(&mut self, terrain: &TerrainGrid, block_change: &mut BlockChange) {
+        let seal = Block::new(BlockKind::GlowingRock, Rgb::new(60, 30, 90));
+        for door in &self.doors {
+            for x in door.min.x..door.max.x {
+                for y in door.min.y..door.max.y {
+                    for z in door.min.z..door.max.z {
+                        let pos = Vec3::new(x, y, z);
+                        if let Ok(block) = terrain.get(pos)
+                            && !block.is_solid()
+                            && block_change.try_set(pos, seal).is_some()
+                        {
+                            self.sealed.push((pos, *block));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Restores the blocks that were replaced when the doors were sealed
+    pub fn unseal(&mut self, block_change: &mut BlockChange) {
+        for (pos, block) in self.sealed.drain(..) {
+            block_change.set(pos, block);
+        }
+    }
+
+    /// Returns the encounter to the state it was in before anybody engaged the
+    /// boss. This doesn't touch the boss itself.
+    pub fn reset(&mut self, block_change: &mut BlockChange) {
+        self.unseal(block_change);
+        self.state = EncounterState::Idle;
+        self.phase = 0;
+        self.participants.clear();
+        self.empty_since = None;
+    }
+}
+
+impl Component for BossEncounter {
+    type Storage = specs::DenseVecStorage<Self>;
+}
+
+/// The choice a player makes for items that are rolled for at the end of
+/// encounters, players without this component greed
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LootPreference(pub LootRollChoice);
+
+impl Component for LootPreference {
+    type Storage = specs::DenseVecStorage<Self>;
+}
+
+/// Decides who gets each of the items dropped at the end of an encounter
+pub struct LootRoller {
+    rule: LootRule,
+    candidates: Vec<(Uid, LootRollChoice)>,
+    next: usize,
+}
+
+impl LootRoller {
+    pub fn new(
+        rule: LootRule,
+        mut candidates: Vec<(Uid, LootRollChoice)>,
+        rng: &mut impl Rng,
+    ) -> Self {
+        candidates.retain(|(_, choice)| *choice != LootRollChoice::Pass);
+        candidates.sort_by_key(|(uid, _)| uid.0);
+        // Don't always favour whoever happens to have the lowest uid
+        let next = if candidates.is_empty() {
+            0
+        } else {
+            rng.gen_range(0..candidates.len())
+        };
+        Self {
+            rule,
+            candidates,
+            next,
+        }
+    }
+
+    /// The participant that wins the next item, `None` if the item should be
+    /// free for all
+    pub fn next_winner(&mut self, rng: &mut impl Rng) -> Option<Uid> {
+        match self.rule {
+            LootRule::FreeForAll => None,
+            LootRule::RoundRobin => {
+                let (uid, _) = self.candidates.get(self.next)?;
+                self.next = (self.next + 1) % self.candidates.len();
+                Some(*uid)
+            },
+            LootRule::NeedGreed => {
+                let rolling = |choice| {
+                    self.candidates
+                        .iter()
+                        .filter(move |(_, c)| *c == choice)
+                        .map(|(uid, _)| *uid)
+                        .collect::<Vec<_>>()
+                };
+                let need = rolling(LootRollChoice::Need);
+                if need.is_empty() {
+                    rolling(LootRollChoice::Greed).choose(rng).copied()
+                } else {
+                    need.choose(rng).copied()
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin() {
+        let mut rng = rand::thread_rng();
+        let candidates = vec![
+            (Uid(3), LootRollChoice::Greed),
+            (Uid(1), LootRollChoice::Need),
+            (Uid(2), LootRollChoice::Pass),
+        ];
+        let mut roller = LootRoller::new(LootRule::RoundRobin, candidates, &mut rng);
+        let first = roller.next_winner(&mut rng).unwrap();
+        let second = roller.next_winner(&mut rng).unwrap();
+        assert_ne!(first, second);
+        assert!(![first, second].contains(&Uid(2)));
+        assert_eq!(roller.next_winner(&mut rng), Some(first));
+    }
+
+    #[test]
+    fn test_need_greed() {
+        let mut rng = rand::thread_rng();
+        let mut roller = LootRoller::new(
+            LootRule::NeedGreed,
+            vec![
+                (Uid(1), LootRollChoice::Greed),
+                (Uid(2), LootRollChoice::Need),
+            ],
+            &mut rng,
+        );
+        for _ in 0..10 {
+            assert_eq!(roller.next_winner(&mut rng), Some(Uid(2)));
+        }
+
+        let mut roller = LootRoller::new(
+            LootRule::NeedGreed,
+            vec![(Uid(1), LootRollChoice::Pass)],
+            &mut rng,
+        );
+        assert_eq!(roller.next_winner(&mut rng), None);
+    }
+}
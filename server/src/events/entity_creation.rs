@@ -1,6 +1,7 @@
 use crate::{
-    client::Client, events::player::handle_exit_ingame, persistence::PersistedComponents,
-    pet::tame_pet, presence::RepositionOnChunkLoad, sys, CharacterUpdater, Server, StateExt,
+    client::Client, encounter::BossEncounter, events::player::handle_exit_ingame,
+    persistence::PersistedComponents, pet::tame_pet, presence::RepositionOnChunkLoad, sys,
+    CharacterUpdater, Server, StateExt,
 };
 use common::{
     comp::{
@@ -77,6 +78,7 @@ pub fn handle_loaded_character_data(server: &mut Server, ev: UpdateCharacterData
         pets: ev.components.5,
         active_abilities: ev.components.6,
         map_marker: ev.components.7,
+        achievements: ev.components.8,
     };
     if let Some(marker) = loaded_components.map_marker {
         server.notify_client(
@@ -158,6 +160,16 @@ pub fn handle_create_npc(server: &mut Server, mut ev: CreateNpcEvent) -> EcsEnti
         entity
     };
 
+    let entity = if let Some(encounter) = ev
+        .npc
+        .encounter
+        .and_then(|info| BossEncounter::new(info, ev.pos.0))
+    {
+        entity.with(encounter)
+    } else {
+        entity
+    };
+
     let new_entity = entity.build();
 
     if let Some(rtsim_entity) = ev.npc.rtsim_entity {
@@ -3,11 +3,14 @@ use crate::rtsim::RtSim;
 use crate::{
     client::Client,
     comp::{
-        agent::{Agent, AgentEvent, Sound, SoundKind},
+        agent::{
+            Agent, AgentEvent, Sound, SoundKind, HEALING_THREAT_FACTOR, HEALING_THREAT_RADIUS,
+        },
         loot_owner::LootOwner,
         skillset::SkillGroupKind,
         BuffKind, BuffSource, PhysicsState,
     },
+    encounter::{BossEncounter, LootPreference, LootRoller},
     error,
     events::entity_creation::handle_create_npc,
     pet::tame_pet,
@@ -16,11 +19,12 @@ use crate::{
     Server, Settings, SpawnPoint,
 };
 #[cfg(feature = "worldgen")]
-use common::rtsim::{Actor, RtSimEntity};
+use common::rtsim::{Actor, RtSimEntity, WildlifeGroup};
 use common::{
     combat::{self, AttackSource, DamageContributor, DeathEffect},
     comp::{
         self,
+        achievement::{default_achievement_manifest, species_key, DeathCause},
         aura::{self, EnteredAuras},
         buff,
         chat::{KillSource, KillType},
@@ -32,6 +36,7 @@ use common::{
         BASE_ABILITY_LIMIT,
     },
     consts::TELEPORTER_RADIUS,
+    encounter::{LootRollChoice, LootRule},
     event::{
         AuraEvent, BonkEvent, BuffEvent, ChangeAbilityEvent, ChangeBodyEvent, ChangeStanceEvent,
         ChatEvent, ComboChangeEvent, CreateItemDropEvent, CreateNpcEvent, CreateObjectEvent,
@@ -163,25 +168,40 @@ impl ServerEvent for PoiseChangeEvent {
     }
 }
 
+#[derive(SystemData)]
+pub struct HealthChangeEventData<'a> {
+    entities: Entities<'a>,
+    #[cfg(feature = "worldgen")]
+    rtsim: WriteExpect<'a, RtSim>,
+    #[cfg(feature = "worldgen")]
+    world: ReadExpect<'a, Arc<World>>,
+    #[cfg(feature = "worldgen")]
+    index: ReadExpect<'a, IndexOwned>,
+    outcomes: Read<'a, EventBus<Outcome>>,
+    time: Read<'a, Time>,
+    id_maps: Read<'a, IdMaps>,
+    spatial_grid: Read<'a, CachedSpatialGrid>,
+    positions: ReadStorage<'a, Pos>,
+    uids: ReadStorage<'a, Uid>,
+    buffs: ReadStorage<'a, comp::Buffs>,
+    #[cfg(feature = "worldgen")]
+    rtsim_entities: ReadStorage<'a, RtSimEntity>,
+    #[cfg(feature = "worldgen")]
+    presences: ReadStorage<'a, Presence>,
+    agents: WriteStorage<'a, Agent>,
+    healths: WriteStorage<'a, Health>,
+}
+
 impl ServerEvent for HealthChangeEvent {
-    type SystemData<'a> = (
-        Entities<'a>,
-        Read<'a, EventBus<Outcome>>,
-        ReadStorage<'a, Pos>,
-        ReadStorage<'a, Uid>,
-        WriteStorage<'a, Agent>,
-        WriteStorage<'a, Health>,
-    );
+    type SystemData<'a> = HealthChangeEventData<'a>;
 
-    fn handle(
-        events: impl ExactSizeIterator<Item = Self>,
-        (entities, outcomes, positions, uids, mut agents, mut healths): Self::SystemData<'_>,
-    ) {
-        let mut outcomes_emitter = outcomes.emitter();
+    fn handle(events: impl ExactSizeIterator<Item = Self>, mut data: Self::SystemData<'_>) {
+        let mut outcomes_emitter = data.outcomes.emitter();
         for ev in events {
-            if let Some((mut health, pos, uid)) = (&mut healths, positions.maybe(), uids.maybe())
-                .lend_join()
-                .get(ev.entity, &entities)
+            if let Some((mut health, pos, uid)) =
+                (&mut data.healths, data.positions.maybe(), data.uids.maybe())
+                    .lend_join()
+                    .get(ev.entity, &data.entities)
             {
                 // If the change amount was not zero
                 let changed = health.change_by(ev.change);
@@ -206,9 +226,72 @@ impl ServerEvent for HealthChangeEvent {
             // TODO: Find a better way to separate direct damage from DOT here
             let damage = -ev.change.amount;
             if damage > 5.0 {
-                if let Some(agent) = agents.get_mut(ev.entity) {
+                if let Some(agent) = data.agents.get_mut(ev.entity) {
                     agent.inbox.push_back(AgentEvent::Hurt);
                 }
+
+                // Let rtsim know that one of its NPCs was attacked
+                #[cfg(feature = "worldgen")]
+                if let Some(rtsim_entity) = data.rtsim_entities.get(ev.entity).copied()
+                    && let Some(attacker) = ev
+                        .change
+                        .by
+                        .and_then(|by| data.id_maps.uid_entity(by.uid()))
+                        .and_then(|attacker| {
+                            if let Some(rtsim_entity) = data.rtsim_entities.get(attacker).copied() {
+                                Some(Actor::Npc(rtsim_entity.0))
+                            } else if let Some(PresenceKind::Character(character)) =
+                                data.presences.get(attacker).map(|p| p.kind)
+                            {
+                                Some(Actor::Character(character))
+                            } else {
+                                None
+                            }
+                        })
+                {
+                    data.rtsim.hook_rtsim_actor_hurt(
+                        &data.world,
+                        data.index.as_index_ref(),
+                        Actor::Npc(rtsim_entity.0),
+                        data.positions.get(ev.entity).map(|p| p.0),
+                        attacker,
+                    );
+                }
+            }
+
+            // Feed the threat tables of agents
+            let Some(by) = ev.change.by.map(|by| by.uid()) else {
+                continue;
+            };
+            let Some(uid) = data.uids.get(ev.entity).filter(|uid| **uid != by) else {
+                continue;
+            };
+            if damage > 0.0 {
+                if let Some(agent) = data.agents.get_mut(ev.entity) {
+                    agent.threat.add(by, damage);
+                    // Hitting an agent while taunting forces it to attack you
+                    if data
+                        .id_maps
+                        .uid_entity(by)
+                        .and_then(|attacker| data.buffs.get(attacker))
+                        .map_or(false, |buffs| buffs.contains(BuffKind::ScornfulTaunt))
+                    {
+                        agent.threat.taunt(by, data.time.0);
+                    }
+                }
+            } else if let Some(pos) = data.positions.get(ev.entity) {
+                // Healing an agent's enemy makes the healer an enemy too
+                for entity in data
+                    .spatial_grid
+                    .0
+                    .in_circle_aabr(pos.0.xy(), HEALING_THREAT_RADIUS)
+                {
+                    if let Some(agent) = data.agents.get_mut(entity)
+                        && agent.threat.get(*uid) > 0.0
+                    {
+                        agent.threat.add(by, -damage * HEALING_THREAT_FACTOR);
+                    }
+                }
             }
         }
     }
@@ -310,6 +393,13 @@ fn handle_exp_gain(
     });
 }
 
+/// How many coins each unit of goods carried by a caravan's pack animal is
+/// worth when it is dropped.
+#[cfg(feature = "worldgen")]
+const COINS_PER_CARGO: f32 = 1.0;
+#[cfg(feature = "worldgen")]
+const COINS: &str = "common.items.utility.coins";
+
 #[derive(SystemData)]
 pub struct DestroyEventData<'a> {
     entities: Entities<'a>,
@@ -338,6 +428,9 @@ pub struct DestroyEventData<'a> {
     force_updates: WriteStorage<'a, comp::ForceUpdate>,
     energies: WriteStorage<'a, Energy>,
     character_states: WriteStorage<'a, CharacterState>,
+    achievements: WriteStorage<'a, comp::Achievements>,
+    boss_encounters: WriteStorage<'a, BossEncounter>,
+    block_change: Write<'a, BlockChange>,
     players: ReadStorage<'a, Player>,
     clients: ReadStorage<'a, Client>,
     uids: ReadStorage<'a, Uid>,
@@ -355,6 +448,7 @@ pub struct DestroyEventData<'a> {
     presences: ReadStorage<'a, Presence>,
     buff_events: Read<'a, EventBus<BuffEvent>>,
     masses: ReadStorage<'a, comp::Mass>,
+    loot_preferences: ReadStorage<'a, LootPreference>,
 }
 
 /// Handle an entity dying. If it is a player, it will send a message to all
@@ -477,9 +571,30 @@ impl ServerEvent for DestroyEvent {
                     _ => KillSource::Other,
                 };
 
+                if let Some(achievements) = data.achievements.get_mut(ev.entity) {
+                    *achievements
+                        .statistics
+                        .deaths
+                        .entry(DeathCause::from(&kill_source))
+                        .or_insert(0) += 1;
+                }
+
                 chat_emitter.emit(ChatEvent(comp::UnresolvedChatMsg::death(kill_source, *uid)));
             }
 
+            // Credit the kill to whoever landed the final blow
+            if let Some(killed_body) = data.bodies.get(ev.entity)
+                && let Some(killer) = ev.cause.by.and_then(|by| data.id_maps.uid_entity(by.uid()))
+                && killer != ev.entity
+                && let Some(achievements) = data.achievements.get_mut(killer)
+            {
+                *achievements
+                    .statistics
+                    .kills
+                    .entry(species_key(killed_body))
+                    .or_insert(0) += 1;
+            }
+
             let mut exp_awards = Vec::<(Entity, f32, Option<Group>)>::new();
             // Award EXP to damage contributors
             //
@@ -651,6 +766,43 @@ impl ServerEvent for DestroyEvent {
                 });
             };
 
+            // Everyone who took part in killing a dungeon boss has cleared that dungeon
+            if data.bodies.get(ev.entity).map_or(false, |body| {
+                default_achievement_manifest().read().is_dungeon_boss(body)
+            }) {
+                let clearers = exp_awards
+                    .iter()
+                    .map(|(entity, _, _)| *entity)
+                    .collect::<HashSet<_>>();
+                for entity in clearers {
+                    if let Some(achievements) = data.achievements.get_mut(entity) {
+                        achievements.statistics.dungeons_cleared += 1;
+                    }
+                }
+            }
+
+            // A defeated boss no longer keeps anybody locked inside its arena
+            let encounter = data.boss_encounters.remove(ev.entity).map(|mut encounter| {
+                encounter.unseal(&mut data.block_change);
+                encounter
+            });
+
+            // Whatever a caravan's pack animal was carrying spills onto the ground for
+            // its killers to take
+            #[cfg(feature = "worldgen")]
+            if let Some(RtSimEntity(npc_id)) = data.rtsim_entities.get(ev.entity) {
+                let cargo = data.rtsim.state().data().caravans.cargo_of(*npc_id);
+                let coins = (cargo * COINS_PER_CARGO) as u32;
+                if coins > 0
+                    && let Ok(item_drops) = data.item_drops.entry(ev.entity)
+                {
+                    item_drops
+                        .or_insert_with(|| comp::ItemDrops(Vec::new()))
+                        .0
+                        .push((coins, comp::Item::new_from_asset_expect(COINS)));
+                }
+            }
+
             let should_delete = if data.clients.contains(ev.entity) {
                 if let Some(vel) = data.velocities.get_mut(ev.entity) {
                     vel.0 = Vec3::zero();
@@ -734,7 +886,67 @@ impl ServerEvent for DestroyEvent {
                             })
                         };
 
-                        if item_receivers.is_empty() {
+                        // Encounters can override how loot is shared between those who took
+                        // part in them
+                        let loot_roller = encounter.as_ref().and_then(|encounter| {
+                            let rule = encounter.def.read().loot_rule;
+                            (rule != LootRule::FreeForAll && !encounter.participants.is_empty())
+                                .then(|| {
+                                    let candidates = encounter
+                                        .participants
+                                        .iter()
+                                        .map(|uid| {
+                                            let choice = data
+                                                .id_maps
+                                                .uid_entity(*uid)
+                                                .and_then(|e| data.loot_preferences.get(e))
+                                                .map_or(LootRollChoice::default(), |p| p.0);
+                                            (*uid, choice)
+                                        })
+                                        .collect();
+                                    LootRoller::new(rule, candidates, &mut rand::thread_rng())
+                                })
+                        });
+
+                        if let Some(mut loot_roller) = loot_roller {
+                            let mut roll_rng = rand::thread_rng();
+                            let mut won = HashMap::<Uid, u64>::new();
+                            for item in flatten_counted_items(&items, &data.ability_map, &data.msm)
+                            {
+                                let winner = loot_roller.next_winner(&mut roll_rng);
+                                if let Some(winner) = winner {
+                                    *won.entry(winner).or_insert(0) += 1;
+                                }
+                                spawn_item(item, winner.map(LootOwnerKind::Player))
+                            }
+
+                            let participants = encounter
+                                .iter()
+                                .flat_map(|encounter| encounter.participants.iter())
+                                .filter_map(|uid| data.id_maps.uid_entity(*uid))
+                                .filter_map(|entity| data.clients.get(entity))
+                                .collect::<Vec<_>>();
+                            for (winner, count) in won {
+                                let Some(alias) = data
+                                    .id_maps
+                                    .uid_entity(winner)
+                                    .and_then(|e| data.players.get(e))
+                                    .map(|player| player.alias.clone())
+                                else {
+                                    continue;
+                                };
+                                let msg = ServerGeneral::server_msg(
+                                    comp::ChatType::Meta,
+                                    comp::Content::localized_with_args("hud-encounter-loot-won", [
+                                        ("player", comp::LocalizationArg::from(alias)),
+                                        ("count", comp::LocalizationArg::from(count)),
+                                    ]),
+                                );
+                                for client in &participants {
+                                    client.send_fallible(msg.clone());
+                                }
+                            }
+                        } else if item_receivers.is_empty() {
                             debug!("No item receivers");
                             for item in flatten_counted_items(&items, &data.ability_map, &data.msm)
                             {
@@ -802,6 +1014,19 @@ impl ServerEvent for DestroyEvent {
             #[cfg(feature = "worldgen")]
             let actor = entity_as_actor(ev.entity);
 
+            #[cfg(feature = "worldgen")]
+            let killer = ev
+                .cause
+                .by
+                .as_ref()
+                .and_then(
+                    |(DamageContributor::Solo(entity_uid)
+                     | DamageContributor::Group { entity_uid, .. })| {
+                        data.id_maps.uid_entity(*entity_uid)
+                    },
+                )
+                .and_then(entity_as_actor);
+
             #[cfg(feature = "worldgen")]
             if let Some(actor) = actor {
                 data.rtsim.hook_rtsim_actor_death(
@@ -809,16 +1034,21 @@ impl ServerEvent for DestroyEvent {
                     data.index.as_index_ref(),
                     actor,
                     data.positions.get(ev.entity).map(|p| p.0),
-                    ev.cause
-                        .by
-                        .as_ref()
-                        .and_then(
-                            |(DamageContributor::Solo(entity_uid)
-                             | DamageContributor::Group { entity_uid, .. })| {
-                                data.id_maps.uid_entity(*entity_uid)
-                            },
-                        )
-                        .and_then(entity_as_actor),
+                    killer,
+                );
+            } else if let Some(group) = data
+                .bodies
+                .get(ev.entity)
+                .zip(data.alignments.get(ev.entity))
+                .and_then(|(body, alignment)| WildlifeGroup::of(body, alignment))
+                && let Some(pos) = data.positions.get(ev.entity)
+            {
+                data.rtsim.hook_wildlife_killed(
+                    &data.world,
+                    data.index.as_index_ref(),
+                    group,
+                    pos.0,
+                    killer,
                 );
             }
 
@@ -2237,6 +2467,7 @@ pub fn transform_entity(
             alignment: _,
             pos: _,
             pets,
+            encounter: _,
         }) => {
             fn set_or_remove_component<C: specs::Component>(
                 server: &mut Server,
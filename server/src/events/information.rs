@@ -8,7 +8,7 @@ use specs::ReadExpect;
 use specs::{DispatcherBuilder, ReadStorage};
 use std::collections::HashMap;
 #[cfg(feature = "worldgen")]
-use world::IndexOwned;
+use {crate::rtsim::RtSim, world::IndexOwned};
 
 use super::{event_dispatch, ServerEvent};
 
@@ -44,15 +44,28 @@ impl ServerEvent for RequestSiteInfoEvent {
 
 #[cfg(feature = "worldgen")]
 impl ServerEvent for RequestSiteInfoEvent {
-    type SystemData<'a> = (ReadExpect<'a, IndexOwned>, ReadStorage<'a, Client>);
+    type SystemData<'a> = (
+        ReadExpect<'a, IndexOwned>,
+        ReadExpect<'a, RtSim>,
+        ReadStorage<'a, Client>,
+    );
 
-    fn handle(events: impl ExactSizeIterator<Item = Self>, (index, clients): Self::SystemData<'_>) {
+    fn handle(
+        events: impl ExactSizeIterator<Item = Self>,
+        (index, rtsim, clients): Self::SystemData<'_>,
+    ) {
         for ev in events {
             if let Some(client) = clients.get(ev.entity) {
                 let site_id = index.sites.recreate_id(ev.id);
                 let info = if let Some(site_id) = site_id {
-                    let site = index.sites.get(site_id);
-                    site.economy.get_information(site_id)
+                    // Prefer the live economy of the site over the one from worldgen
+                    rtsim
+                        .with_site_economy(index.as_index_ref(), ev.id, |economy| {
+                            economy.get_information(site_id)
+                        })
+                        .unwrap_or_else(|| {
+                            index.sites.get(site_id).economy.get_information(site_id)
+                        })
                 } else {
                     EconomyInfo {
                         id: ev.id,
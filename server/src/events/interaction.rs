@@ -1,5 +1,8 @@
 use common_state::{BlockChange, ScheduledBlockChange};
-use specs::{DispatcherBuilder, Join, ReadExpect, ReadStorage, WriteExpect, WriteStorage};
+use specs::{
+    shred, Builder, DispatcherBuilder, Join, ReadExpect, ReadStorage, SystemData, WorldExt,
+    WriteExpect, WriteStorage,
+};
 use vek::*;
 
 use common::{
@@ -10,12 +13,15 @@ use common::{
         inventory::slot::EquipSlot,
         item::{flatten_counted_items, MaterialStatManifest},
         loot_owner::LootOwnerKind,
+        pet::{is_tameable, is_valid_pet_name, PetOrder, MAX_PET_NAME_LEN},
         tool::AbilityMap,
+        Content,
     },
     consts::{MAX_INTERACT_RANGE, MAX_NPCINTERACT_RANGE, SOUND_TRAVEL_DIST_PER_VOLUME},
     event::{
         CreateItemDropEvent, CreateSpriteEvent, EventBus, MineBlockEvent, NpcInteractEvent,
-        SetLanternEvent, SetPetStayEvent, SoundEvent, TamePetEvent, ToggleSpriteLightEvent,
+        RenamePetEvent, SetLanternEvent, SetPetOrderEvent, SetPetStanceEvent, SoundEvent,
+        StablePetEvent, TamePetEvent, ToggleSpriteLightEvent, UnstablePetEvent,
     },
     link::Is,
     mounting::Mount,
@@ -25,22 +31,34 @@ use common::{
     uid::Uid,
     util::Dir,
     vol::ReadVol,
+    LoadoutBuilder,
 };
 
-use crate::{Server, Time};
+#[cfg(feature = "worldgen")]
+use crate::rtsim::RtSim;
+use crate::{client::Client, state_ext::StateExt, Server, Time};
 
-use crate::pet::tame_pet;
+use crate::pet::{restore_pet, tame_pet, PetStable};
+#[cfg(feature = "worldgen")]
+use common::rtsim::Actor;
+use common_net::msg::ServerGeneral;
 use hashbrown::{HashMap, HashSet};
 use lazy_static::lazy_static;
 use serde::Deserialize;
 use std::iter::FromIterator;
+#[cfg(feature = "worldgen")] use std::sync::Arc;
+use tracing::warn;
+#[cfg(feature = "worldgen")]
+use world::{IndexOwned, World};
 
 use super::{event_dispatch, mounting::within_mounting_range, ServerEvent};
 
 pub(super) fn register_event_systems(builder: &mut DispatcherBuilder) {
     event_dispatch::<SetLanternEvent>(builder);
     event_dispatch::<NpcInteractEvent>(builder);
-    event_dispatch::<SetPetStayEvent>(builder);
+    event_dispatch::<SetPetOrderEvent>(builder);
+    event_dispatch::<SetPetStanceEvent>(builder);
+    event_dispatch::<RenamePetEvent>(builder);
     event_dispatch::<MineBlockEvent>(builder);
     event_dispatch::<SoundEvent>(builder);
     event_dispatch::<CreateSpriteEvent>(builder);
@@ -129,9 +147,25 @@ impl ServerEvent for NpcInteractEvent {
     }
 }
 
-impl ServerEvent for SetPetStayEvent {
+/// Whether `owner` is the owner of `pet`
+fn is_pet_owner(
+    owner: specs::Entity,
+    pet: specs::Entity,
+    alignments: &ReadStorage<comp::Alignment>,
+    uids: &ReadStorage<Uid>,
+) -> bool {
+    uids.get(owner).map_or(false, |owner_uid| {
+        matches!(
+            alignments.get(pet),
+            Some(comp::Alignment::Owned(pet_owner)) if *pet_owner == *owner_uid,
+        )
+    })
+}
+
+impl ServerEvent for SetPetOrderEvent {
     type SystemData<'a> = (
         WriteStorage<'a, comp::Agent>,
+        WriteStorage<'a, comp::Pet>,
         WriteStorage<'a, comp::CharacterActivity>,
         ReadStorage<'a, comp::Pos>,
         ReadStorage<'a, comp::Alignment>,
@@ -141,28 +175,84 @@ impl ServerEvent for SetPetStayEvent {
 
     fn handle(
         events: impl ExactSizeIterator<Item = Self>,
-        (mut agents, mut character_activities, positions, alignments, is_mounts, uids): Self::SystemData<'_>,
+        (mut agents, mut pets, mut character_activities, positions, alignments, is_mounts, uids): Self::SystemData<'_>,
     ) {
-        for SetPetStayEvent(command_giver, pet, stay) in events {
-            let is_owner = uids.get(command_giver).map_or(false, |owner_uid| {
-                matches!(
-                    alignments.get(pet),
-                    Some(comp::Alignment::Owned(pet_owner)) if *pet_owner == *owner_uid,
-                )
-            });
-
+        for SetPetOrderEvent(command_giver, pet, order) in events {
             let current_pet_position = positions.get(pet).copied();
-            let stay = stay && current_pet_position.is_some();
-            if is_owner
+            let order = if current_pet_position.is_some() {
+                order
+            } else {
+                PetOrder::Follow
+            };
+            if is_pet_owner(command_giver, pet, &alignments, &uids)
                 && within_mounting_range(positions.get(command_giver), positions.get(pet))
                 && is_mounts.get(pet).is_none()
             {
+                if let Some(pet_comp) = pets.get_mut(pet) {
+                    pet_comp.order = order;
+                }
                 character_activities
                     .get_mut(pet)
-                    .map(|mut activity| activity.is_pet_staying = stay);
-                agents
-                    .get_mut(pet)
-                    .map(|s| s.stay_pos = current_pet_position.filter(|_| stay));
+                    .map(|mut activity| activity.is_pet_staying = order == PetOrder::Stay);
+                agents.get_mut(pet).map(|s| {
+                    s.stay_pos = current_pet_position.filter(|_| order != PetOrder::Follow)
+                });
+            }
+        }
+    }
+}
+
+impl ServerEvent for SetPetStanceEvent {
+    type SystemData<'a> = (
+        WriteStorage<'a, comp::Pet>,
+        ReadStorage<'a, comp::Alignment>,
+        ReadStorage<'a, Uid>,
+    );
+
+    fn handle(
+        events: impl ExactSizeIterator<Item = Self>,
+        (mut pets, alignments, uids): Self::SystemData<'_>,
+    ) {
+        for SetPetStanceEvent(command_giver, pet, stance) in events {
+            if is_pet_owner(command_giver, pet, &alignments, &uids)
+                && let Some(pet_comp) = pets.get_mut(pet)
+            {
+                pet_comp.stance = stance;
+            }
+        }
+    }
+}
+
+impl ServerEvent for RenamePetEvent {
+    type SystemData<'a> = (
+        WriteStorage<'a, comp::Stats>,
+        ReadStorage<'a, comp::Pet>,
+        ReadStorage<'a, comp::Alignment>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Client>,
+    );
+
+    fn handle(
+        events: impl ExactSizeIterator<Item = Self>,
+        (mut stats, pets, alignments, uids, clients): Self::SystemData<'_>,
+    ) {
+        for RenamePetEvent(owner, pet, name) in events {
+            if !is_pet_owner(owner, pet, &alignments, &uids) || !pets.contains(pet) {
+                continue;
+            }
+
+            if is_valid_pet_name(&name) {
+                if let Some(mut stats) = stats.get_mut(pet) {
+                    stats.name = name.trim().to_owned();
+                }
+            } else if let Some(client) = clients.get(owner) {
+                client.send_fallible(ServerGeneral::server_msg(
+                    comp::ChatType::CommandError,
+                    Content::localized_with_args("hud-pet-rename-invalid", [(
+                        "max",
+                        MAX_PET_NAME_LEN.to_string(),
+                    )]),
+                ));
             }
         }
     }
@@ -187,50 +277,49 @@ lazy_static! {
         );
 }
 
+#[derive(SystemData)]
+pub struct MineBlockData<'a> {
+    #[cfg(feature = "worldgen")]
+    rtsim: WriteExpect<'a, RtSim>,
+    #[cfg(feature = "worldgen")]
+    world: ReadExpect<'a, Arc<World>>,
+    #[cfg(feature = "worldgen")]
+    index: ReadExpect<'a, IndexOwned>,
+    block_change: WriteExpect<'a, BlockChange>,
+    terrain: ReadExpect<'a, TerrainGrid>,
+    msm: ReadExpect<'a, MaterialStatManifest>,
+    ability_map: ReadExpect<'a, AbilityMap>,
+    create_item_drop_events: ReadExpect<'a, EventBus<CreateItemDropEvent>>,
+    outcomes: ReadExpect<'a, EventBus<Outcome>>,
+    program_time: ReadExpect<'a, ProgramTime>,
+    skill_sets: WriteStorage<'a, comp::SkillSet>,
+    achievements: WriteStorage<'a, comp::Achievements>,
+    uids: ReadStorage<'a, Uid>,
+    #[cfg(feature = "worldgen")]
+    presences: ReadStorage<'a, comp::Presence>,
+}
+
 impl ServerEvent for MineBlockEvent {
-    type SystemData<'a> = (
-        WriteExpect<'a, BlockChange>,
-        ReadExpect<'a, TerrainGrid>,
-        ReadExpect<'a, MaterialStatManifest>,
-        ReadExpect<'a, AbilityMap>,
-        ReadExpect<'a, EventBus<CreateItemDropEvent>>,
-        ReadExpect<'a, EventBus<Outcome>>,
-        ReadExpect<'a, ProgramTime>,
-        WriteStorage<'a, comp::SkillSet>,
-        ReadStorage<'a, Uid>,
-    );
+    type SystemData<'a> = MineBlockData<'a>;
 
-    fn handle(
-        events: impl ExactSizeIterator<Item = Self>,
-        (
-            mut block_change,
-            terrain,
-            msm,
-            ability_map,
-            create_item_drop_events,
-            outcomes,
-            program_time,
-            mut skill_sets,
-            uids,
-        ): Self::SystemData<'_>,
-    ) {
+    fn handle(events: impl ExactSizeIterator<Item = Self>, mut data: Self::SystemData<'_>) {
         use rand::Rng;
         let mut rng = rand::thread_rng();
-        let mut create_item_drop_emitter = create_item_drop_events.emitter();
-        let mut outcome_emitter = outcomes.emitter();
+        let mut create_item_drop_emitter = data.create_item_drop_events.emitter();
+        let mut outcome_emitter = data.outcomes.emitter();
         for ev in events {
-            if block_change.can_set_block(ev.pos) {
-                let block = terrain.get(ev.pos).ok().copied();
+            if data.block_change.can_set_block(ev.pos) {
+                let block = data.terrain.get(ev.pos).ok().copied();
                 if let Some(block) =
                     block.filter(|b| b.mine_tool().map_or(false, |t| Some(t) == ev.tool))
                 {
                     // Drop item if one is recoverable from the block
                     if let Some(items) = comp::Item::try_reclaim_from_block(block) {
                         let mut items: Vec<_> =
-                            flatten_counted_items(&items, &ability_map, &msm).collect();
-                        let maybe_uid = uids.get(ev.entity).copied();
+                            flatten_counted_items(&items, &data.ability_map, &data.msm).collect();
+                        let maybe_uid = data.uids.get(ev.entity).copied();
 
-                        if let Some(mut skillset) = skill_sets.get_mut(ev.entity) {
+                        if let Some(mut skillset) = data.skill_sets.get_mut(ev.entity) {
                             if let (Some(tool), Some(uid), exp_reward @ 1..) = (
                                 ev.tool,
                                 maybe_uid,
@@ -299,17 +388,35 @@ impl ServerEvent for MineBlockEvent {
                                 pos: comp::Pos(ev.pos.map(|e| e as f32) + Vec3::new(0.5, 0.5, 0.0)),
                                 vel: comp::Vel(Vec3::zero()),
                                 ori: comp::Ori::from(Dir::random_2d(&mut rng)),
-                                item: comp::PickupItem::new(item, *program_time),
+                                item: comp::PickupItem::new(item, *data.program_time),
                                 loot_owner,
                             });
                         }
                     }
 
-                    block_change.set(ev.pos, block.into_vacant());
+                    data.block_change.set(ev.pos, block.into_vacant());
+                    if let Some(achievements) = data.achievements.get_mut(ev.entity) {
+                        achievements.statistics.blocks_mined += 1;
+                    }
                     outcome_emitter.emit(Outcome::BreakBlock {
                         pos: ev.pos,
                         color: block.get_color(),
                     });
+
+                    // Breaking blocks in a settlement might be seen as vandalism
+                    #[cfg(feature = "worldgen")]
+                    if let Some(character) = data
+                        .presences
+                        .get(ev.entity)
+                        .and_then(|presence| presence.kind.character_id())
+                    {
+                        data.rtsim.hook_mine_block(
+                            &data.world,
+                            data.index.as_index_ref(),
+                            Actor::Character(character),
+                            ev.pos,
+                        );
+                    }
                 }
             }
         }
@@ -435,3 +542,151 @@ pub fn handle_tame_pet(server: &mut Server, ev: TamePetEvent) {
     // showing taming success?
     tame_pet(server.state.ecs(), ev.pet_entity, ev.owner_entity);
 }
+
+/// Whether `entity` is close enough to a settlement to use its stable
+fn is_at_stable(server: &Server, entity: specs::Entity) -> bool {
+    #[cfg(feature = "worldgen")]
+    {
+        use common::terrain::CoordinateConversions;
+        use world::civ::SiteKind;
+
+        // Empirical, matches the radius used to determine whether a player is in
+        // town when changing battle mode
+        const STABLE_SITE_RADIUS: f32 = 9.0;
+
+        let Some(pos) = server
+            .state
+            .ecs()
+            .read_storage::<comp::Pos>()
+            .get(entity)
+            .copied()
+        else {
+            return false;
+        };
+        let chunk_pos = pos.0.xy().as_::<i32>().wpos_to_cpos();
+        server.world.civs().sites().any(|site| {
+            matches!(
+                site.kind,
+                SiteKind::Settlement
+                    | SiteKind::Refactor
+                    | SiteKind::CliffTown
+                    | SiteKind::SavannahPit
+                    | SiteKind::CoastalTown
+                    | SiteKind::DesertCity
+            ) && site.center.as_::<f32>().distance(chunk_pos.as_::<f32>()) < STABLE_SITE_RADIUS
+        })
+    }
+    // Every position is considered a settlement without worldgen
+    #[cfg(not(feature = "worldgen"))]
+    {
+        let _ = (server, entity);
+        true
+    }
+}
+
+fn notify_pet_owner(server: &Server, owner: specs::Entity, content: Content) {
+    if let Some(client) = server.state.ecs().read_storage::<Client>().get(owner) {
+        client.send_fallible(ServerGeneral::server_msg(
+            comp::ChatType::CommandError,
+            content,
+        ));
+    }
+}
+
+pub fn handle_stable_pet(server: &mut Server, StablePetEvent(owner, pet_entity): StablePetEvent) {
+    let ecs = server.state.ecs();
+    let is_owner = is_pet_owner(owner, pet_entity, &ecs.read_storage(), &ecs.read_storage());
+    if !is_owner {
+        return;
+    }
+
+    if !is_at_stable(server, owner) {
+        notify_pet_owner(
+            server,
+            owner,
+            Content::localized("hud-pet-stable-not_in_settlement"),
+        );
+        return;
+    }
+
+    let ecs = server.state.ecs();
+    let pet_data = (
+        ecs.read_storage::<comp::Pet>().get(pet_entity).cloned(),
+        ecs.read_storage::<comp::Body>().get(pet_entity).copied(),
+        ecs.read_storage::<comp::Stats>().get(pet_entity).cloned(),
+    );
+    // Only pets that can be persisted can be left at a stable
+    let (Some(mut pet), Some(body), Some(stats)) = pet_data else {
+        return;
+    };
+    if !is_tameable(&body) {
+        return;
+    }
+    pet.stabled = true;
+    pet.order = PetOrder::Follow;
+
+    if let Err(e) = server.state.delete_entity_recorded(pet_entity) {
+        warn!(?e, ?pet_entity, "Failed to delete stabled pet");
+        return;
+    }
+
+    let ecs = server.state.ecs();
+    let mut pet_stables = ecs.write_storage::<PetStable>();
+    let Some(pet_stable) = pet_stables
+        .entry(owner)
+        .ok()
+        .map(|entry| entry.or_insert_with(PetStable::default))
+    else {
+        return;
+    };
+    pet_stable.0.push((pet, body, stats));
+    if let Some(client) = ecs.read_storage::<Client>().get(owner) {
+        client.send_fallible(pet_stable.to_msg());
+    }
+}
+
+pub fn handle_unstable_pet(server: &mut Server, UnstablePetEvent(owner, index): UnstablePetEvent) {
+    if !is_at_stable(server, owner) {
+        notify_pet_owner(
+            server,
+            owner,
+            Content::localized("hud-pet-stable-not_in_settlement"),
+        );
+        return;
+    }
+
+    let Some(owner_pos) = server.state.read_component_copied::<comp::Pos>(owner) else {
+        return;
+    };
+    let pet_data = server
+        .state
+        .ecs()
+        .write_storage::<PetStable>()
+        .get_mut(owner)
+        .filter(|stable| index < stable.0.len())
+        .map(|stable| (stable.0.remove(index), stable.to_msg()));
+    let Some(((pet, body, stats), msg)) = pet_data else {
+        return;
+    };
+    if let Some(client) = server.state.ecs().read_storage::<Client>().get(owner) {
+        client.send_fallible(msg);
+    }
+
+    let pet_entity = server
+        .state
+        .create_npc(
+            owner_pos,
+            comp::Ori::from(Dir::random_2d(&mut rand::thread_rng())),
+            stats,
+            comp::SkillSet::default(),
+            Some(comp::Health::new(body)),
+            comp::Poise::new(body),
+            comp::Inventory::with_loadout(LoadoutBuilder::from_default(&body).build(), body),
+            body,
+        )
+        .with(comp::Scale(1.0))
+        .with(comp::Vel(Vec3::new(0.0, 0.0, 0.0)))
+        .build();
+
+    restore_pet(server.state.ecs(), pet_entity, owner, pet);
+}
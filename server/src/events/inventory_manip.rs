@@ -2,12 +2,13 @@ use hashbrown::HashSet;
 use rand::{seq::IteratorRandom, Rng};
 use specs::{
     join::Join, shred, DispatcherBuilder, Entities, Entity as EcsEntity, Read, ReadExpect,
-    ReadStorage, SystemData, Write, WriteStorage,
+    ReadStorage, SystemData, Write, WriteExpect, WriteStorage,
 };
 use tracing::{debug, error, warn};
 use vek::{Rgb, Vec3};
 
 use common::{
+    character::CharacterId,
     comp::{
         self,
         group::members,
@@ -35,11 +36,18 @@ use common::{
 };
 use comp::LightEmitter;
 
-use crate::client::Client;
+#[cfg(feature = "worldgen")]
+use crate::rtsim::RtSim;
+use crate::{client::Client, housing::Housing};
 use common::comp::{
     pet::is_tameable, Alignment, Body, CollectFailedReason, Group, InventoryUpdateEvent,
 };
+#[cfg(feature = "worldgen")]
+use common::rtsim::Actor;
 use common_net::msg::ServerGeneral;
+#[cfg(feature = "worldgen")] use std::sync::Arc;
+#[cfg(feature = "worldgen")]
+use world::{IndexOwned, World};
 
 use super::{entity_manipulation::emit_effect_events, event_dispatch, ServerEvent};
 
@@ -62,6 +70,22 @@ pub fn snuff_lantern(storage: &mut WriteStorage<LightEmitter>, entity: EcsEntity
     storage.remove(entity);
 }
 
+/// The container that items dropped by a character should be stored in, which
+/// is the nearest container if they are in their own house
+fn house_container(
+    housing: &Housing,
+    terrain: &common::terrain::TerrainGrid,
+    presence: Option<&comp::Presence>,
+    pos: &comp::Pos,
+) -> Option<(CharacterId, Vec3<i32>)> {
+    let character = presence?.kind.character_id()?;
+    let pos = pos.0.map(|e| e.floor() as i32);
+    let house = housing
+        .house_of(character)
+        .filter(|house| house.contains(pos))?;
+    Some((character, house.nearest_container(terrain, pos)?))
+}
+
 event_emitters! {
     struct Events[Emitters] {
         tame_pet: TamePetEvent,
@@ -77,9 +101,16 @@ event_emitters! {
 pub struct InventoryManipData<'a> {
     entities: Entities<'a>,
     events: Events<'a>,
+    #[cfg(feature = "worldgen")]
+    rtsim: WriteExpect<'a, RtSim>,
+    #[cfg(feature = "worldgen")]
+    world: ReadExpect<'a, Arc<World>>,
+    #[cfg(feature = "worldgen")]
+    index: ReadExpect<'a, IndexOwned>,
     block_change: Write<'a, common_state::BlockChange>,
     trades: Write<'a, Trades>,
     terrain: ReadExpect<'a, common::terrain::TerrainGrid>,
+    housing: WriteExpect<'a, Housing>,
     id_maps: Read<'a, IdMaps>,
     time: Read<'a, Time>,
     program_time: ReadExpect<'a, ProgramTime>,
@@ -88,8 +119,10 @@ pub struct InventoryManipData<'a> {
     inventories: WriteStorage<'a, comp::Inventory>,
     items: WriteStorage<'a, comp::PickupItem>,
     inventory_updates: WriteStorage<'a, comp::InventoryUpdate>,
+    achievements: WriteStorage<'a, comp::Achievements>,
     light_emitters: WriteStorage<'a, comp::LightEmitter>,
     positions: ReadStorage<'a, comp::Pos>,
+    presences: ReadStorage<'a, comp::Presence>,
     scales: ReadStorage<'a, comp::Scale>,
     colliders: ReadStorage<'a, comp::Collider>,
     character_states: ReadStorage<'a, comp::CharacterState>,
@@ -350,6 +383,39 @@ impl ServerEvent for InventoryManipEvent {
                         .expect("We know entity exists since we got its inventory.")
                         .or_insert_with(InventoryUpdate::default);
 
+                    // Only the owner of a house may collect from the sprites inside of it, and
+                    // containers in a house hold the items its owner stored instead of loot
+                    let character = data
+                        .presences
+                        .get(entity)
+                        .and_then(|presence| presence.kind.character_id());
+                    let is_container = block
+                        .and_then(|block| block.get_sprite())
+                        .map_or(false, |sprite| sprite.is_container());
+                    let block = match data.housing.house_at_mut(sprite_pos) {
+                        Some(house) if character != Some(house.owner) => {
+                            debug!(?sprite_pos, "Can't collect from a house that isn't yours");
+                            None
+                        },
+                        Some(house) if is_container => {
+                            if house.retrieve(
+                                sprite_pos,
+                                &mut inventory,
+                                &data.ability_map,
+                                &data.msm,
+                            ) == 0
+                                && house.has_stored_items(sprite_pos)
+                            {
+                                inventory_update.push(InventoryUpdateEvent::BlockCollectFailed {
+                                    pos: sprite_pos,
+                                    reason: CollectFailedReason::InventoryFull,
+                                });
+                            }
+                            None
+                        },
+                        _ => block,
+                    };
+
                     if let Some(block) = block {
                         if block.is_collectible() && data.block_change.can_set_block(sprite_pos) {
                             // If an item was required to collect the sprite, consume it now
@@ -404,6 +470,19 @@ impl ServerEvent for InventoryManipEvent {
 
                             // We made sure earlier the block was not already modified this tick
                             data.block_change.set(sprite_pos, block.into_vacant());
+                            data.housing
+                                .set_block(sprite_pos, block, block.into_vacant());
+
+                            // Emptying containers in a settlement might be seen as theft
+                            #[cfg(feature = "worldgen")]
+                            if is_container && let Some(character) = character {
+                                data.rtsim.hook_loot_container(
+                                    &data.world,
+                                    data.index.as_index_ref(),
+                                    Actor::Character(character),
+                                    sprite_pos,
+                                );
+                            }
 
                             // If the block was a keyhole, remove nearby door blocks
                             // TODO: Abstract this code into a generalised way to do block updates?
@@ -809,13 +888,24 @@ impl ServerEvent for InventoryManipEvent {
 
                     // FIXME: We should really require the drop and write to be atomic!
                     if let (Some(mut item), Some(pos)) = (item, data.positions.get(entity)) {
-                        item.put_in_world();
-                        dropped_items.push((
-                            *pos,
-                            data.orientations.get(entity).copied().unwrap_or_default(),
-                            PickupItem::new(item, *data.program_time),
-                            *uid,
-                        ));
+                        // Items dropped next to a container in your own house are stored in it
+                        if let Some((owner, container)) = house_container(
+                            &data.housing,
+                            &data.terrain,
+                            data.presences.get(entity),
+                            pos,
+                        ) && let Some(house) = data.housing.house_of_mut(owner)
+                        {
+                            house.store(container, item);
+                        } else {
+                            item.put_in_world();
+                            dropped_items.push((
+                                *pos,
+                                data.orientations.get(entity).copied().unwrap_or_default(),
+                                PickupItem::new(item, *data.program_time),
+                                *uid,
+                            ));
+                        }
                     }
                     data.inventory_updates
                         .insert(
@@ -837,13 +927,24 @@ impl ServerEvent for InventoryManipEvent {
 
                     // FIXME: We should really require the drop and write to be atomic!
                     if let (Some(mut item), Some(pos)) = (item, data.positions.get(entity)) {
-                        item.put_in_world();
-                        dropped_items.push((
-                            *pos,
-                            data.orientations.get(entity).copied().unwrap_or_default(),
-                            PickupItem::new(item, *data.program_time),
-                            *uid,
-                        ));
+                        // Items dropped next to a container in your own house are stored in it
+                        if let Some((owner, container)) = house_container(
+                            &data.housing,
+                            &data.terrain,
+                            data.presences.get(entity),
+                            pos,
+                        ) && let Some(house) = data.housing.house_of_mut(owner)
+                        {
+                            house.store(container, item);
+                        } else {
+                            item.put_in_world();
+                            dropped_items.push((
+                                *pos,
+                                data.orientations.get(entity).copied().unwrap_or_default(),
+                                PickupItem::new(item, *data.program_time),
+                                *uid,
+                            ));
+                        }
                     }
                     data.inventory_updates
                         .insert(
@@ -859,6 +960,8 @@ impl ServerEvent for InventoryManipEvent {
                     use comp::controller::CraftEvent;
                     use recipe::ComponentKey;
                     let recipe_book = default_recipe_book().read();
+                    // Salvaging breaks items down rather than crafting new ones
+                    let is_salvage = matches!(craft_event, CraftEvent::Salvage(_));
 
                     let get_craft_sprite = |sprite_pos: Option<VolumePos>| {
                         sprite_pos
@@ -1019,6 +1122,13 @@ impl ServerEvent for InventoryManipEvent {
                     // Attempt to insert items into inventory, dropping them if there is not enough
                     // space
                     let items_were_crafted = if let Some(crafted_items) = crafted_items {
+                        if !is_salvage && let Some(achievements) = data.achievements.get_mut(entity)
+                        {
+                            achievements.statistics.items_crafted += crafted_items
+                                .iter()
+                                .map(|item| u64::from(item.amount()))
+                                .sum::<u64>();
+                        }
                         let mut dropped: Vec<PickupItem> = Vec::new();
                         for item in crafted_items {
                             if let Err((item, _inserted)) = inventory.push(item) {
@@ -3,6 +3,8 @@ use super::{
     group_manip::{self, update_map_markers},
     ServerEvent,
 };
+#[cfg(feature = "worldgen")]
+use crate::rtsim::RtSim;
 use crate::{client::Client, Settings};
 use common::{
     comp::{
@@ -227,6 +229,8 @@ pub struct InviteResponseData<'a> {
     trades: Write<'a, Trades>,
     #[cfg(feature = "worldgen")]
     index: ReadExpect<'a, IndexOwned>,
+    #[cfg(feature = "worldgen")]
+    rtsim: ReadExpect<'a, RtSim>,
     id_maps: Read<'a, IdMaps>,
     invites: WriteStorage<'a, Invite>,
     pending_invites: WriteStorage<'a, PendingInvites>,
@@ -319,15 +323,15 @@ pub fn handle_invite_accept(data: &mut InviteResponseData, entity: Entity) {
                         .agents
                         .get(inviter)
                         .and_then(|a| {
-                            a.behavior
-                                .trade_site()
-                                .and_then(|id| data.index.get_site_prices(id))
+                            a.behavior.trade_site().and_then(|id| {
+                                data.rtsim.get_site_prices(data.index.as_index_ref(), id)
+                            })
                         })
                         .or_else(|| {
                             data.agents.get(entity).and_then(|a| {
-                                a.behavior
-                                    .trade_site()
-                                    .and_then(|id| data.index.get_site_prices(id))
+                                a.behavior.trade_site().and_then(|id| {
+                                    data.rtsim.get_site_prices(data.index.as_index_ref(), id)
+                                })
                             })
                         });
                     #[cfg(not(feature = "worldgen"))]
@@ -19,7 +19,7 @@ use self::{
         handle_shockwave, handle_shoot,
     },
     entity_manipulation::{handle_delete, handle_transform},
-    interaction::handle_tame_pet,
+    interaction::{handle_stable_pet, handle_tame_pet, handle_unstable_pet},
     mounting::{handle_mount, handle_mount_volume, handle_unmount},
     player::{
         handle_character_delete, handle_client_disconnect, handle_exit_ingame, handle_possess,
@@ -183,6 +183,8 @@ impl Server {
         self.handle_serial_events(handle_mount_volume);
         self.handle_serial_events(handle_unmount);
         self.handle_serial_events(handle_tame_pet);
+        self.handle_serial_events(handle_stable_pet);
+        self.handle_serial_events(handle_unstable_pet);
         self.handle_serial_events(handle_process_trade_action);
     }
 
@@ -1,7 +1,7 @@
 use super::Event;
 use crate::{
     client::Client, metrics::PlayerMetrics, persistence::character_updater::CharacterUpdater,
-    state_ext::StateExt, BattleModeBuffer, Server,
+    pet::PetStable, state_ext::StateExt, BattleModeBuffer, Server,
 };
 use common::{
     comp,
@@ -257,6 +257,7 @@ pub(super) fn persist_entity(state: &mut State, entity: EcsEntity) -> EcsEntity
         Some(skill_set),
         Some(inventory),
         Some(active_abilities),
+        Some(achievements),
         Some(player_uid),
         Some(player_info),
         mut character_updater,
@@ -268,6 +269,7 @@ pub(super) fn persist_entity(state: &mut State, entity: EcsEntity) -> EcsEntity
         state
             .read_storage::<comp::ability::ActiveAbilities>()
             .get(entity),
+        state.read_storage::<comp::Achievements>().get(entity),
         state.read_storage::<Uid>().get(entity),
         state.read_storage::<comp::Player>().get(entity),
         state.ecs().fetch_mut::<CharacterUpdater>(),
@@ -316,6 +318,14 @@ pub(super) fn persist_entity(state: &mut State, entity: EcsEntity) -> EcsEntity
                         },
                         _ => None,
                     })
+                    .chain(
+                        state
+                            .ecs()
+                            .read_storage::<PetStable>()
+                            .get(entity)
+                            .into_iter()
+                            .flat_map(|stable| stable.0.iter().cloned()),
+                    )
                     .collect();
 
                 character_updater.add_pending_logout_update((
@@ -326,6 +336,7 @@ pub(super) fn persist_entity(state: &mut State, entity: EcsEntity) -> EcsEntity
                     waypoint,
                     active_abilities.clone(),
                     map_marker,
+                    achievements.clone(),
                 ));
             },
             PresenceKind::Spectator => { /* Do nothing, spectators do not need persisting */ },
@@ -19,7 +19,14 @@ use specs::{world::WorldExt, Entity as EcsEntity};
 use std::{cmp::Ordering, num::NonZeroU32};
 use tracing::{error, trace};
 #[cfg(feature = "worldgen")]
-use world::IndexOwned;
+use {
+    crate::{rtsim::RtSim, state_ext::StateExt},
+    common::{
+        comp::inventory::trade_pricing::TradePricing,
+        trade::{Good, SiteId},
+    },
+    world::IndexRef,
+};
 
 pub fn notify_agent_simple(
     agents: &mut specs::WriteStorage<Agent>,
@@ -34,7 +41,8 @@ pub fn notify_agent_simple(
 #[cfg(feature = "worldgen")]
 fn notify_agent_prices(
     mut agents: specs::WriteStorage<Agent>,
-    index: &IndexOwned,
+    rtsim: &RtSim,
+    index: IndexRef,
     entity: EcsEntity,
     event: AgentEvent,
 ) {
@@ -43,7 +51,7 @@ fn notify_agent_prices(
             // Prefer using this Agent's price data, but use the counterparty's price
             // data if we don't have price data
             let prices = site_id
-                .and_then(|site_id| index.get_site_prices(site_id))
+                .and_then(|site_id| rtsim.get_site_prices(index, site_id))
                 .unwrap_or(boxval.2);
             // Box<(tid, pend, _, inventories)>) = event {
             agent
@@ -90,7 +98,30 @@ pub(super) fn handle_process_trade_action(
             if let Entry::Occupied(entry) = trades.trades.entry(trade_id) {
                 let parties = entry.get().parties;
                 if entry.get().should_commit() {
+                    #[cfg(feature = "worldgen")]
+                    let exchange = merchant_exchange(server.state.ecs(), entry.get());
                     let result = commit_trade(server.state.ecs(), entry.get());
+                    #[cfg(feature = "worldgen")]
+                    if let (TradeResult::Completed, Some((site_id, customer, goods))) =
+                        (&result, exchange)
+                    {
+                        let customer = server
+                            .state
+                            .ecs()
+                            .entity_from_uid(customer)
+                            .and_then(|customer| server.state.entity_as_actor(customer));
+                        server
+                            .state
+                            .ecs()
+                            .write_resource::<RtSim>()
+                            .hook_trade_at_site(
+                                &server.world,
+                                server.index.as_index_ref(),
+                                site_id,
+                                customer,
+                                goods,
+                            );
+                    }
                     entry.remove();
                     for party in parties.iter() {
                         if let Some(e) = server.state.ecs().entity_from_uid(*party) {
@@ -111,6 +142,8 @@ pub(super) fn handle_process_trade_action(
                     #[cfg(not(feature = "worldgen"))]
                     let prices = None;
                     let agents = server.state.ecs().read_storage::<Agent>();
+                    #[cfg(feature = "worldgen")]
+                    let rtsim = server.state.ecs().read_resource::<RtSim>();
                     // sadly there is no map and collect on arrays
                     for i in 0..2 {
                         // parties.len()) {
@@ -130,7 +163,9 @@ pub(super) fn handle_process_trade_action(
                                     agents
                                         .get(e)
                                         .and_then(|a| a.behavior.trade_site())
-                                        .and_then(|id| server.index.get_site_prices(id))
+                                        .and_then(|id| {
+                                            rtsim.get_site_prices(server.index.as_index_ref(), id)
+                                        })
                                 });
                             }
                         }
@@ -149,7 +184,8 @@ pub(super) fn handle_process_trade_action(
                             #[cfg(feature = "worldgen")]
                             notify_agent_prices(
                                 server.state.ecs().write_storage::<Agent>(),
-                                &server.index,
+                                &rtsim,
+                                server.index.as_index_ref(),
                                 e,
                                 AgentEvent::UpdatePendingTrade(Box::new((
                                     trade_id,
@@ -197,6 +233,41 @@ pub(crate) fn cancel_trades_for(state: &mut common_state::State, entity: EcsEnti
     }
 }
 
+/// The goods that the site of a merchant taking part in a trade receives
+/// (positive amounts) and hands out (negative amounts) when the trade goes
+/// through, along with the merchant's customer
+#[cfg(feature = "worldgen")]
+fn merchant_exchange(
+    ecs: &specs::World,
+    trade: &PendingTrade,
+) -> Option<(SiteId, Uid, Vec<(Good, f32)>)> {
+    let agents = ecs.read_storage::<Agent>();
+    let inventories = ecs.read_storage::<Inventory>();
+    let (merchant, site_id) = trade.parties.iter().enumerate().find_map(|(who, party)| {
+        let entity = ecs.entity_from_uid(*party)?;
+        Some((who, agents.get(entity)?.behavior.trade_site()?))
+    })?;
+
+    let mut goods = Vec::new();
+    for (who, offers) in trade.offers.iter().enumerate() {
+        let inventory = inventories.get(ecs.entity_from_uid(trade.parties[who])?)?;
+        let sign = if who == merchant { -1.0 } else { 1.0 };
+        for (slot, quantity) in offers.iter() {
+            if let Some(materials) = inventory
+                .get(*slot)
+                .and_then(|item| TradePricing::get_materials(&item.item_definition_id()))
+            {
+                goods.extend(
+                    materials
+                        .iter()
+                        .map(|(amount, good)| (*good, sign * amount * *quantity as f32)),
+                );
+            }
+        }
+    }
+    Some((site_id, trade.parties[1 - merchant], goods))
+}
+
 /// Commit a trade that both parties have agreed to, modifying their respective
 /// inventories
 fn commit_trade(ecs: &specs::World, trade: &PendingTrade) -> TradeResult {
@@ -0,0 +1,500 @@
+//! Player housing.
+//!
+//! Characters can buy or rent one of the houses generated in settlements,
+//! which gives them exclusive build rights inside of it. Blocks changed inside
+//! a house and the contents of its containers are kept here rather than in
+//! [`crate::TerrainPersistence`], so that houses persist even when terrain
+//! persistence is turned off.
+
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use common::{
+    character::CharacterId,
+    comp::{
+        inventory::item::{tool::AbilityMap, ItemDefinitionId, MaterialStatManifest},
+        Inventory, Item,
+    },
+    terrain::{Block, TerrainChunk, TerrainGrid},
+    vol::{ReadVol, RectRasterableVol, WriteVol},
+};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write as _,
+    num::NonZeroU32,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::{error, info, warn};
+use vek::*;
+
+const COINS: &str = "common.items.utility.coins";
+/// Price of buying a house outright
+pub const HOUSE_PRICE: u32 = 5000;
+/// Price of renting a house for [`RENT_PERIOD`]
+pub const HOUSE_RENT: u32 = 400;
+/// Length of a rental, in real-world seconds
+pub const RENT_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// How close to a container a character must be to store items in it
+pub const CONTAINER_RANGE: i32 = 3;
+/// Minimum time between writes of the housing file
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tenure {
+    Owned,
+    /// Rented until the given unix timestamp
+    Rented {
+        until: i64,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct House {
+    /// The interior of the house, which the owner is allowed to build in
+    pub bounds: Aabb<i32>,
+    pub owner: CharacterId,
+    pub tenure: Tenure,
+    /// Blocks that have been changed since the house was generated
+    blocks: HashMap<Vec3<i32>, Block>,
+    /// The blocks that were generated where `blocks` have been changed, so
+    /// that the changes can be reverted when the house is given up
+    #[serde(default)]
+    original: HashMap<Vec3<i32>, Block>,
+    /// Items stored in containers, keyed by the position of the container
+    storage: HashMap<Vec3<i32>, Vec<Item>>,
+}
+
+impl House {
+    /// The name of the build area that grants the owner build rights
+    pub fn area_name(&self) -> String {
+        format!("house_{}_{}", self.bounds.min.x, self.bounds.min.y)
+    }
+
+    pub fn contains(&self, pos: Vec3<i32>) -> bool { self.bounds.contains_point(pos) }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.tenure, Tenure::Rented { until } if until <= now)
+    }
+
+    pub fn stored_items(&self) -> usize { self.storage.values().map(Vec::len).sum() }
+
+    pub fn has_stored_items(&self, pos: Vec3<i32>) -> bool {
+        self.storage
+            .get(&pos)
+            .map_or(false, |items| !items.is_empty())
+    }
+
+    /// The container inside the house that is closest to `pos`, if any is
+    /// within [`CONTAINER_RANGE`]
+    pub fn nearest_container(&self, terrain: &TerrainGrid, pos: Vec3<i32>) -> Option<Vec3<i32>> {
+        let range = Vec3::broadcast(CONTAINER_RANGE);
+        let search = Aabb {
+            min: pos - range,
+            max: pos + range,
+        }
+        .intersection(self.bounds);
+        (search.min.x..=search.max.x)
+            .flat_map(|x| (search.min.y..=search.max.y).map(move |y| (x, y)))
+            .flat_map(|(x, y)| (search.min.z..=search.max.z).map(move |z| Vec3::new(x, y, z)))
+            .filter(|p| {
+                terrain
+                    .get(*p)
+                    .ok()
+                    .and_then(|block| block.get_sprite())
+                    .map_or(false, |sprite| sprite.is_container())
+            })
+            .min_by_key(|p| p.distance_squared(pos))
+    }
+
+    /// The blocks that were generated where the house has been changed
+    pub fn original_blocks(&self) -> impl Iterator<Item = (Vec3<i32>, Block)> + '_ {
+        self.original.iter().map(|(pos, block)| (*pos, *block))
+    }
+
+    pub fn store(&mut self, container: Vec3<i32>, item: Item) {
+        self.storage.entry(container).or_default().push(item);
+    }
+
+    /// Moves as many of the items stored in the container into the inventory
+    /// as will fit, returning the number of items that were moved
+    pub fn retrieve(
+        &mut self,
+        container: Vec3<i32>,
+        inventory: &mut Inventory,
+        ability_map: &AbilityMap,
+        msm: &MaterialStatManifest,
+    ) -> usize {
+        let Some(items) = self.storage.get_mut(&container) else {
+            return 0;
+        };
+        let retrieved = push_items(items, inventory, ability_map, msm);
+        if items.is_empty() {
+            self.storage.remove(&container);
+        }
+        retrieved
+    }
+}
+
+/// Moves as many of the items into the inventory as will fit, leaving the rest
+/// behind and returning the number of items that were moved
+fn push_items(
+    items: &mut Vec<Item>,
+    inventory: &mut Inventory,
+    ability_map: &AbilityMap,
+    msm: &MaterialStatManifest,
+) -> usize {
+    let mut retrieved = 0;
+    let mut remaining = Vec::new();
+    for mut item in items.drain(..) {
+        // Item configs aren't persisted, so they have to be rebuilt
+        item.update_item_state(ability_map, msm);
+        match inventory.push(item) {
+            Ok(()) => retrieved += 1,
+            Err((item, _)) => remaining.push(item),
+        }
+    }
+    *items = remaining;
+    retrieved
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RawHousing {
+    houses: Vec<House>,
+    #[serde(default)]
+    undelivered: HashMap<CharacterId, Vec<Item>>,
+}
+
+pub struct Housing {
+    path: PathBuf,
+    houses: Vec<House>,
+    /// Items that were stored in the houses of evicted characters, waiting to
+    /// be returned to them
+    undelivered: HashMap<CharacterId, Vec<Item>>,
+    modified: bool,
+    last_save: Instant,
+}
+
+impl Housing {
+    /// Load housing from the given data directory
+    pub fn new(mut data_dir: PathBuf) -> Self {
+        data_dir.push("housing.ron");
+        let path = data_dir;
+
+        let raw = match fs::File::open(&path) {
+            Ok(file) => ron::de::from_reader::<_, RawHousing>(file).unwrap_or_else(|err| {
+                let backup_path = path.with_extension("invalid.ron");
+                error!(
+                    ?err,
+                    "Failed to load housing, moving it to {:?} for you to repair.", backup_path
+                );
+                if let Err(err) = fs::rename(&path, &backup_path) {
+                    error!(?err, "Failed to rename invalid housing file");
+                }
+                RawHousing::default()
+            }),
+            Err(_) => {
+                info!(
+                    "No housing file found at {:?}, starting without houses",
+                    path
+                );
+                RawHousing::default()
+            },
+        };
+
+        Self {
+            path,
+            houses: raw.houses,
+            undelivered: raw.undelivered,
+            modified: false,
+            last_save: Instant::now(),
+        }
+    }
+
+    pub fn houses(&self) -> impl Iterator<Item = &House> { self.houses.iter() }
+
+    pub fn house_at(&self, pos: Vec3<i32>) -> Option<&House> {
+        self.houses.iter().find(|house| house.contains(pos))
+    }
+
+    pub fn house_at_mut(&mut self, pos: Vec3<i32>) -> Option<&mut House> {
+        let house = self.houses.iter_mut().find(|house| house.contains(pos))?;
+        self.modified = true;
+        Some(house)
+    }
+
+    pub fn house_of(&self, owner: CharacterId) -> Option<&House> {
+        self.houses.iter().find(|house| house.owner == owner)
+    }
+
+    pub fn house_of_mut(&mut self, owner: CharacterId) -> Option<&mut House> {
+        let house = self.houses.iter_mut().find(|house| house.owner == owner)?;
+        self.modified = true;
+        Some(house)
+    }
+
+    /// Give the house with the given interior to `owner`. Fails if the house
+    /// already belongs to somebody.
+    pub fn claim(&mut self, bounds: Aabb<i32>, owner: CharacterId, tenure: Tenure) -> bool {
+        if self.houses.iter().any(|house| house.bounds == bounds) {
+            return false;
+        }
+        self.houses.push(House {
+            bounds,
+            owner,
+            tenure,
+            blocks: HashMap::new(),
+            original: HashMap::new(),
+            storage: HashMap::new(),
+        });
+        self.modified = true;
+        true
+    }
+
+    /// Remove the house owned by `owner`, returning it
+    pub fn release(&mut self, owner: CharacterId) -> Option<House> {
+        let idx = self.houses.iter().position(|house| house.owner == owner)?;
+        self.modified = true;
+        Some(self.houses.swap_remove(idx))
+    }
+
+    /// Remove all houses whose rent has run out, returning them. The items
+    /// stored in them are kept until they can be returned to their owners
+    /// with [`Housing::deliver`].
+    pub fn evict_expired(&mut self, now: i64) -> Vec<House> {
+        let (mut expired, houses) = self
+            .houses
+            .drain(..)
+            .partition::<Vec<_>, _>(|house| house.is_expired(now));
+        self.houses = houses;
+        for house in &mut expired {
+            let items = house.storage.drain().flat_map(|(_, items)| items);
+            self.undelivered
+                .entry(house.owner)
+                .or_default()
+                .extend(items);
+            self.modified = true;
+        }
+        self.undelivered.retain(|_, items| !items.is_empty());
+        expired
+    }
+
+    /// Whether there are items from an evicted house waiting to be returned to
+    /// the character
+    pub fn has_undelivered(&self, owner: CharacterId) -> bool {
+        self.undelivered.contains_key(&owner)
+    }
+
+    /// Moves as many of the items from the character's evicted house as will
+    /// fit into their inventory, returning the number of items that were
+    /// moved
+    pub fn deliver(
+        &mut self,
+        owner: CharacterId,
+        inventory: &mut Inventory,
+        ability_map: &AbilityMap,
+        msm: &MaterialStatManifest,
+    ) -> usize {
+        let Some(items) = self.undelivered.get_mut(&owner) else {
+            return 0;
+        };
+        let delivered = push_items(items, inventory, ability_map, msm);
+        if items.is_empty() {
+            self.undelivered.remove(&owner);
+        }
+        if delivered > 0 {
+            self.modified = true;
+        }
+        delivered
+    }
+
+    /// Record a block change, if it happened inside of a house
+    pub fn set_block(&mut self, pos: Vec3<i32>, old: Block, new: Block) {
+        if let Some(house) = self.houses.iter_mut().find(|house| house.contains(pos)) {
+            house.original.entry(pos).or_insert(old);
+            house.blocks.insert(pos, new);
+            self.modified = true;
+        }
+    }
+
+    /// Apply the changes made to houses to a newly generated chunk
+    pub fn apply_changes(&self, key: Vec2<i32>, terrain_chunk: &mut TerrainChunk) {
+        let chunk_size = TerrainChunk::RECT_SIZE.map(|e| e as i32);
+        let chunk_min = key * chunk_size;
+        let chunk_aabr = Aabr {
+            min: chunk_min,
+            max: chunk_min + chunk_size - 1,
+        };
+        for house in self.houses.iter().filter(|house| {
+            let bounds = Aabr {
+                min: house.bounds.min.xy(),
+                max: house.bounds.max.xy(),
+            };
+            bounds.collides_with_aabr(chunk_aabr)
+        }) {
+            for (pos, block) in house
+                .blocks
+                .iter()
+                .filter(|(pos, _)| chunk_aabr.contains_point(pos.xy()))
+            {
+                let rpos = *pos - chunk_min.with_z(0);
+                if terrain_chunk.set(rpos, *block).is_err() {
+                    warn!(?pos, "Could not apply house block change");
+                }
+            }
+        }
+    }
+
+    /// Save the houses if they have been changed since they were last saved,
+    /// at most once every [`SAVE_INTERVAL`]
+    pub fn maintain(&mut self) {
+        if self.modified && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.modified = false;
+        self.last_save = Instant::now();
+
+        let raw = RawHousing {
+            houses: self.houses.clone(),
+            undelivered: self.undelivered.clone(),
+        };
+        let ron = match ron::ser::to_string_pretty(&raw, ron::ser::PrettyConfig::default()) {
+            Ok(ron) => ron,
+            Err(err) => {
+                error!(?err, "Failed to serialize housing");
+                return;
+            },
+        };
+        let atomic_file = AtomicFile::new(&self.path, OverwriteBehavior::AllowOverwrite);
+        if let Err(err) = atomic_file.write(|file| file.write_all(ron.as_bytes())) {
+            error!(?err, "Failed to write housing to file");
+        }
+    }
+}
+
+/// Take `amount` coins out of the inventory, fails without taking anything if
+/// there aren't enough
+pub fn take_coins(inventory: &mut Inventory, amount: u32) -> bool {
+    let is_coins = |item: &Item| item.item_definition_id() == ItemDefinitionId::Simple(COINS);
+    let held = inventory
+        .slots()
+        .flatten()
+        .filter(|item| is_coins(item))
+        .map(|item| u64::from(item.amount()))
+        .sum::<u64>();
+    if held < u64::from(amount) {
+        return false;
+    }
+
+    let mut remaining = amount;
+    let slots = inventory
+        .slots_with_id()
+        .filter(|(_, slot)| slot.as_ref().map_or(false, is_coins))
+        .map(|(slot, _)| slot)
+        .collect::<Vec<_>>();
+    for slot in slots {
+        let Some(to_take) = NonZeroU32::new(remaining) else {
+            break;
+        };
+        if let Some(Some(item)) = inventory.slot_mut(slot) {
+            let taken = item.amount().min(to_take.get());
+            if taken == item.amount() {
+                inventory.remove(slot);
+            } else {
+                let _ = item.decrease_amount(taken);
+            }
+            remaining -= taken;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn house(min: Vec3<i32>, tenure: Tenure) -> House {
+        House {
+            bounds: Aabb { min, max: min + 10 },
+            owner: CharacterId(1),
+            tenure,
+            blocks: HashMap::new(),
+            original: HashMap::new(),
+            storage: HashMap::new(),
+        }
+    }
+
+    fn housing(houses: Vec<House>) -> Housing {
+        Housing {
+            path: PathBuf::new(),
+            houses,
+            undelivered: HashMap::new(),
+            modified: false,
+            last_save: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_evict_expired() {
+        let mut housing = housing(vec![
+            house(Vec3::zero(), Tenure::Owned),
+            house(Vec3::broadcast(20), Tenure::Rented { until: 100 }),
+            house(Vec3::broadcast(40), Tenure::Rented { until: 200 }),
+        ]);
+        let evicted = housing.evict_expired(150);
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].bounds.min, Vec3::broadcast(20));
+        assert_eq!(housing.houses().count(), 2);
+        assert!(housing.house_at(Vec3::broadcast(45)).is_some());
+        assert!(housing.house_at(Vec3::broadcast(25)).is_none());
+    }
+
+    #[test]
+    fn test_evict_with_stored_items() {
+        use common::terrain::{BlockKind, SpriteKind};
+
+        let owner = CharacterId(1);
+        let container = Vec3::broadcast(2);
+        let generated = Block::air(SpriteKind::Chest);
+        let mut housing = housing(vec![house(Vec3::zero(), Tenure::Rented { until: 100 })]);
+        for _ in 0..3 {
+            housing
+                .house_of_mut(owner)
+                .unwrap()
+                .store(container, Item::new_from_asset_expect(COINS));
+        }
+        // Only the block that was generated is remembered, not intermediate changes
+        let furniture = Block::air(SpriteKind::Bed);
+        housing.set_block(container, generated, furniture);
+        housing.set_block(
+            container,
+            furniture,
+            Block::new(BlockKind::Wood, Rgb::zero()),
+        );
+
+        let evicted = housing.evict_expired(150);
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].original_blocks().collect::<Vec<_>>(), vec![(
+            container, generated
+        )]);
+        assert!(housing.house_of(owner).is_none());
+
+        // The stored items are kept for the owner rather than being destroyed
+        assert!(housing.has_undelivered(owner));
+        let ability_map = AbilityMap::load().cloned();
+        let msm = MaterialStatManifest::load().cloned();
+        let mut inventory = Inventory::with_empty();
+        assert_eq!(
+            housing.deliver(owner, &mut inventory, &ability_map, &msm),
+            3
+        );
+        assert!(!housing.has_undelivered(owner));
+        assert_eq!(
+            housing.deliver(owner, &mut inventory, &ability_map, &msm),
+            0
+        );
+    }
+}
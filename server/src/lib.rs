@@ -15,6 +15,7 @@
 )]
 
 pub mod automod;
+pub mod build_tools;
 mod character_creator;
 pub mod chat;
 pub mod chunk_generator;
@@ -23,8 +24,11 @@ pub mod client;
 pub mod cmd;
 pub mod connection_handler;
 mod data_dir;
+pub mod edit_journal;
+pub mod encounter;
 pub mod error;
 pub mod events;
+pub mod housing;
 pub mod input;
 pub mod location;
 pub mod lod;
@@ -34,6 +38,7 @@ pub mod persistence;
 mod pet;
 pub mod presence;
 pub mod rtsim;
+pub mod schematics;
 pub mod settings;
 pub mod state_ext;
 pub mod sys;
@@ -437,6 +442,12 @@ impl Server {
         state
             .ecs_mut()
             .insert(sys::PersistenceScheduler::every(Duration::from_secs(10)));
+        state
+            .ecs_mut()
+            .insert(sys::AchievementsScheduler::every(Duration::from_secs(10)));
+        state
+            .ecs_mut()
+            .insert(sys::HousingScheduler::every(Duration::from_secs(5)));
 
         // Region map (spatial structure for entity synchronization)
         state.ecs_mut().insert(RegionMap::new());
@@ -449,6 +460,10 @@ impl Server {
         state.ecs_mut().register::<wiring::Circuit>();
         state.ecs_mut().register::<Anchor>();
         state.ecs_mut().register::<comp::Pet>();
+        state.ecs_mut().register::<pet::PetStable>();
+        state.ecs_mut().register::<comp::Achievements>();
+        state.ecs_mut().register::<encounter::BossEncounter>();
+        state.ecs_mut().register::<encounter::LootPreference>();
         state.ecs_mut().register::<login_provider::PendingLogin>();
         state.ecs_mut().register::<RepositionOnChunkLoad>();
         state.ecs_mut().register::<RtSimEntity>();
@@ -515,6 +530,24 @@ impl Server {
                 .expect("The initial insert should always work.");
         }
 
+        // Load player houses and give their owners build rights inside of them
+        {
+            let housing = housing::Housing::new(data_dir.to_owned());
+            let mut build_areas = state.ecs().write_resource::<AreasContainer<BuildArea>>();
+            for house in housing.houses() {
+                if let Err(name) = build_areas.insert(house.area_name(), house.bounds) {
+                    warn!("Build area {} for house already exists", name);
+                }
+            }
+            drop(build_areas);
+            state.ecs_mut().insert(housing);
+        }
+        state
+            .ecs_mut()
+            .insert(schematics::Schematics::new(data_dir));
+        state.ecs_mut().insert(edit_journal::EditJournal::default());
+        state.ecs_mut().insert(build_tools::BuildTools::default());
+
         // Insert the world into the ECS (todo: Maybe not an Arc?)
         let world = Arc::new(world);
         state.ecs_mut().insert(Arc::clone(&world));
@@ -650,10 +683,16 @@ impl Server {
                 },
             }
             weather::init(&mut state);
+            if settings.world.weather_accumulation {
+                state
+                    .ecs_mut()
+                    .insert(weather::Accumulation::new(data_dir.to_owned()));
+            }
         }
 
         let server_constants = ServerConstants {
             day_cycle_coefficient: settings.day_cycle_coefficient(),
+            year_length: settings.year_length,
         };
 
         let this = Self {
@@ -842,6 +881,14 @@ impl Server {
         // Handle game events
         frontend_events.append(&mut self.handle_events());
 
+        // Carry on with region edits started in build mode
+        for (entity, msg) in build_tools::maintain(self.state.ecs()) {
+            self.notify_client(
+                entity,
+                ServerGeneral::server_msg(comp::ChatType::CommandInfo, msg),
+            );
+        }
+
         let before_update_terrain_and_regions = Instant::now();
 
         // Apply terrain changes and update the region map after processing server
@@ -1076,6 +1123,7 @@ impl Server {
                                         pets,
                                         active_abilities,
                                         map_marker,
+                                        achievements,
                                     } = character_data;
                                     let character_data = (
                                         body,
@@ -1086,6 +1134,7 @@ impl Server {
                                         pets,
                                         active_abilities,
                                         map_marker,
+                                        achievements,
                                     );
                                     // TODO: Does this need to be a server event? E.g. we could
                                     // just handle it here.
@@ -1218,6 +1267,19 @@ impl Server {
             .ecs()
             .try_fetch_mut::<TerrainPersistence>()
             .map(|mut t| t.maintain());
+
+        // Maintain player houses
+        self.state
+            .ecs()
+            .write_resource::<housing::Housing>()
+            .maintain();
+
+        // Save snow and ponds
+        #[cfg(feature = "worldgen")]
+        self.state
+            .ecs()
+            .try_fetch_mut::<weather::Accumulation>()
+            .map(|mut accumulation| accumulation.maintain());
     }
 
     // Run RegionMap tick to update entity region occupancy
@@ -1558,6 +1620,15 @@ impl Drop for Server {
                 terrain_persistence.unload_all()
             });
 
+        info!("Saving player houses...");
+        self.state.ecs().write_resource::<housing::Housing>().save();
+
+        #[cfg(feature = "worldgen")]
+        if let Some(mut accumulation) = self.state.ecs().try_fetch_mut::<weather::Accumulation>() {
+            info!("Saving weather accumulation...");
+            accumulation.save();
+        }
+
         #[cfg(feature = "worldgen")]
         {
             debug!("Saving rtsim state...");
@@ -0,0 +1,5 @@
+-- Persists the stance and standing order given to each pet by its owner, and
+-- whether the pet has been left at a stable
+ALTER TABLE pet ADD COLUMN stance TEXT NOT NULL DEFAULT 'Aggressive';
+ALTER TABLE pet ADD COLUMN pet_order TEXT NOT NULL DEFAULT 'Follow';
+ALTER TABLE pet ADD COLUMN stabled INTEGER NOT NULL DEFAULT 0;
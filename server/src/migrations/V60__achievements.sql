@@ -0,0 +1,14 @@
+-- Creates new achievement table, holding the lifetime statistics and unlocked
+-- achievements of each character
+CREATE TABLE "achievement" (
+      "entity_id" INT NOT NULL,
+      "statistics" TEXT NOT NULL,
+      "unlocked" TEXT NOT NULL,
+      PRIMARY KEY("entity_id"),
+      FOREIGN KEY("entity_id") REFERENCES "character"("character_id")
+);
+
+-- Inserts empty statistics for everyone
+INSERT INTO achievement
+SELECT c.character_id, '{}', '[]'
+FROM character c
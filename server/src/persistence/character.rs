@@ -11,6 +11,7 @@ use crate::{
     comp::{self, Inventory},
     persistence::{
         character::conversions::{
+            convert_achievements_from_database, convert_achievements_to_database,
             convert_active_abilities_from_database, convert_active_abilities_to_database,
             convert_body_from_database, convert_body_to_database_json,
             convert_character_from_database, convert_inventory_from_database_items,
@@ -22,7 +23,7 @@ use crate::{
         character_loader::{CharacterCreationResult, CharacterDataResult, CharacterListResult},
         character_updater::PetPersistenceData,
         error::PersistenceError::DatabaseError,
-        EditableComponents, PersistedComponents,
+        json_models, EditableComponents, PersistedComponents,
     },
 };
 use common::{
@@ -213,7 +214,10 @@ pub fn load_character_data(
         SELECT  p.pet_id,
                 p.name,
                 b.variant,
-                b.body_data
+                b.body_data,
+                p.stance,
+                p.pet_order,
+                p.stabled
         FROM    pet p
         JOIN    body b ON (p.pet_id = b.body_id)
         WHERE   p.character_id = ?1",
@@ -226,6 +230,9 @@ pub fn load_character_data(
                 name: row.get(1)?,
                 body_variant: row.get(2)?,
                 body_data: row.get(3)?,
+                stance: row.get(4)?,
+                pet_order: row.get(5)?,
+                stabled: row.get(6)?,
             })
         })?
         .filter_map(Result::ok)
@@ -242,6 +249,9 @@ pub fn load_character_data(
             {
                 let pet = comp::Pet::new_from_database(
                     NonZeroU64::new(db_pet.database_id as u64).unwrap(),
+                    json_models::db_string_to_pet_stance(&db_pet.stance),
+                    json_models::db_string_to_pet_order(&db_pet.pet_order),
+                    db_pet.stabled,
                 );
                 let pet_stats = comp::Stats::new(db_pet.name.to_owned(), pet_body);
                 Some((pet, pet_body, pet_stats))
@@ -269,6 +279,22 @@ pub fn load_character_data(
         })
     })?;
 
+    let mut stmt = connection.prepare_cached(
+        "
+            SELECT  statistics,
+                    unlocked
+            FROM    achievement
+            WHERE   entity_id = ?1",
+    )?;
+
+    let achievement_data = stmt.query_row([char_id.0], |row| {
+        Ok(CharacterAchievements {
+            entity_id: char_id.0,
+            statistics: row.get(0)?,
+            unlocked: row.get(1)?,
+        })
+    })?;
+
     let (skill_set, skill_set_persistence_load_error) =
         convert_skill_set_from_database(&skill_group_data);
     let body = convert_body_from_database(&body_data.variant, &body_data.body_data)?;
@@ -289,6 +315,7 @@ pub fn load_character_data(
             pets,
             active_abilities: convert_active_abilities_from_database(&ability_set_data),
             map_marker: char_map_marker,
+            achievements: convert_achievements_from_database(&achievement_data),
         },
         UpdateCharacterMetadata {
             skill_set_persistence_load_error,
@@ -389,6 +416,7 @@ pub fn create_character(
         pets: _,
         active_abilities,
         map_marker,
+        achievements,
     } = persisted_components;
 
     // Fetch new entity IDs for character, inventory, loadout, and overflow items
@@ -533,6 +561,23 @@ pub fn create_character(
     ])?;
     drop(stmt);
 
+    let achievements = convert_achievements_to_database(CharacterId(character_id), &achievements);
+
+    let mut stmt = transaction.prepare_cached(
+        "
+        INSERT INTO achievement (entity_id,
+                                 statistics,
+                                 unlocked)
+        VALUES (?1, ?2, ?3)",
+    )?;
+
+    stmt.execute([
+        &character_id as &dyn ToSql,
+        &achievements.statistics,
+        &achievements.unlocked,
+    ])?;
+    drop(stmt);
+
     // Insert default inventory and loadout item records
     let mut inserts = Vec::new();
 
@@ -682,6 +727,17 @@ pub fn delete_character(
     stmt.execute([&char_id.0])?;
     drop(stmt);
 
+    // Delete statistics and achievements
+    let mut stmt = transaction.prepare_cached(
+        "
+        DELETE
+        FROM    achievement
+        WHERE   entity_id = ?1",
+    )?;
+
+    stmt.execute([&char_id.0])?;
+    drop(stmt);
+
     // Delete character
     let mut stmt = transaction.prepare_cached(
         "
@@ -871,9 +927,9 @@ fn get_pseudo_container_id(
     }
 }
 
-/// Stores new pets in the database, and removes pets from the database that the
-/// player no longer has. Currently there are no actual updates to pet data
-/// since we don't store any updatable data about pets in the database.
+/// Stores new pets in the database, updates the name, stance, order and stable
+/// state of existing pets, and removes pets from the database that the player
+/// no longer has.
 fn update_pets(
     char_id: CharacterId,
     pets: Vec<PetPersistenceData>,
@@ -902,6 +958,30 @@ fn update_pets(
         }
     }
 
+    for (pet, _, stats) in pets.iter() {
+        let Some(pet_id) = pet.get_database_id().load() else {
+            continue;
+        };
+
+        #[rustfmt::skip]
+        let mut stmt = transaction.prepare_cached("
+            UPDATE  pet
+            SET     name = ?1,
+                    stance = ?2,
+                    pet_order = ?3,
+                    stabled = ?4
+            WHERE   pet_id = ?5",
+        )?;
+
+        stmt.execute([
+            &stats.name as &dyn ToSql,
+            &json_models::pet_stance_to_db_string(pet.stance),
+            &json_models::pet_order_to_db_string(pet.order),
+            &pet.stabled,
+            &(pet_id.get() as i64),
+        ])?;
+    }
+
     for (pet, body, stats) in pets
         .iter()
         .filter(|(pet, _, _)| pet.get_database_id().load().is_none())
@@ -932,11 +1012,21 @@ fn update_pets(
             INTO    pet (
                     pet_id,
                     character_id,
-                    name)
-            VALUES  (?1, ?2, ?3)",
+                    name,
+                    stance,
+                    pet_order,
+                    stabled)
+            VALUES  (?1, ?2, ?3, ?4, ?5, ?6)",
         )?;
 
-        stmt.execute([&pet_entity_id as &dyn ToSql, &char_id.0, &stats.name])?;
+        stmt.execute([
+            &pet_entity_id as &dyn ToSql,
+            &char_id.0,
+            &stats.name,
+            &json_models::pet_stance_to_db_string(pet.stance),
+            &json_models::pet_order_to_db_string(pet.order),
+            &pet.stabled,
+        ])?;
         drop(stmt);
 
         pet.get_database_id()
@@ -1010,6 +1100,7 @@ pub fn update(
     char_waypoint: Option<comp::Waypoint>,
     active_abilities: comp::ability::ActiveAbilities,
     map_marker: Option<comp::MapMarker>,
+    achievements: comp::Achievements,
     transaction: &mut Transaction,
 ) -> Result<(), PersistenceError> {
     // Run pet persistence
@@ -1182,5 +1273,29 @@ pub fn update(
         )));
     }
 
+    let achievements = convert_achievements_to_database(char_id, &achievements);
+
+    let mut stmt = transaction.prepare_cached(
+        "
+        UPDATE  achievement
+        SET     statistics = ?1,
+                unlocked = ?2
+        WHERE   entity_id = ?3
+    ",
+    )?;
+
+    let achievements_count = stmt.execute([
+        &achievements.statistics as &dyn ToSql,
+        &achievements.unlocked,
+        &char_id.0,
+    ])?;
+
+    if achievements_count != 1 {
+        return Err(PersistenceError::OtherError(format!(
+            "Error updating achievement table for char_id {}",
+            char_id.0,
+        )));
+    }
+
     Ok(())
 }
@@ -1,6 +1,6 @@
 use crate::persistence::{
     character::EntityId,
-    models::{AbilitySets, Character, Item, SkillGroup},
+    models::{AbilitySets, Character, CharacterAchievements, Item, SkillGroup},
 };
 
 use crate::persistence::{
@@ -867,3 +867,35 @@ pub fn convert_active_abilities_from_database(ability_sets: &AbilitySets) -> Act
         });
     json_models::active_abilities_from_db_model(ability_sets)
 }
+
+pub fn convert_achievements_to_database(
+    entity_id: CharacterId,
+    achievements: &Achievements,
+) -> CharacterAchievements {
+    CharacterAchievements {
+        entity_id: entity_id.0,
+        statistics: serde_json::to_string(&achievements.statistics).unwrap_or_default(),
+        unlocked: serde_json::to_string(&achievements.unlocked).unwrap_or_default(),
+    }
+}
+
+pub fn convert_achievements_from_database(achievements: &CharacterAchievements) -> Achievements {
+    let statistics = serde_json::from_str(&achievements.statistics).unwrap_or_else(|err| {
+        common_base::dev_panic!(format!(
+            "Failed to parse statistics. Error: {:#?}\nStatistics:\n{:#?}",
+            err, achievements.statistics
+        ));
+        Default::default()
+    });
+    let unlocked = serde_json::from_str(&achievements.unlocked).unwrap_or_else(|err| {
+        common_base::dev_panic!(format!(
+            "Failed to parse unlocked achievements. Error: {:#?}\nAchievements:\n{:#?}",
+            err, achievements.unlocked
+        ));
+        Default::default()
+    });
+    Achievements {
+        statistics,
+        unlocked,
+    }
+}
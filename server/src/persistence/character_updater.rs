@@ -29,6 +29,7 @@ pub type CharacterUpdateData = (
     Option<comp::Waypoint>,
     comp::ability::ActiveAbilities,
     Option<comp::MapMarker>,
+    comp::Achievements,
 );
 
 pub type PetPersistenceData = (comp::Pet, comp::Body, comp::Stats);
@@ -425,6 +426,7 @@ fn execute_batch_update(
             waypoint,
             active_abilities,
             map_marker,
+            achievements,
         )) => super::character::update(
             character_id,
             stats,
@@ -433,6 +435,7 @@ fn execute_batch_update(
             waypoint,
             active_abilities,
             map_marker,
+            achievements,
             &mut transaction,
         ),
         DatabaseActionKind::DeleteCharacter {
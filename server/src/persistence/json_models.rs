@@ -115,6 +115,58 @@ pub fn db_string_to_skill_group(skill_group_string: &str) -> comp::skillset::Ski
     }
 }
 
+pub fn pet_stance_to_db_string(stance: comp::pet::PetStance) -> String {
+    use comp::pet::PetStance::*;
+    let stance_string = match stance {
+        Passive => "Passive",
+        Defensive => "Defensive",
+        Aggressive => "Aggressive",
+    };
+    stance_string.to_string()
+}
+
+pub fn db_string_to_pet_stance(stance_string: &str) -> comp::pet::PetStance {
+    use comp::pet::PetStance::*;
+    match stance_string {
+        "Passive" => Passive,
+        "Defensive" => Defensive,
+        "Aggressive" => Aggressive,
+        _ => {
+            dev_panic!(format!(
+                "Tried to convert an unsupported pet stance from the database: {}",
+                stance_string
+            ));
+            comp::pet::PetStance::default()
+        },
+    }
+}
+
+pub fn pet_order_to_db_string(order: comp::pet::PetOrder) -> String {
+    use comp::pet::PetOrder::*;
+    let order_string = match order {
+        Follow => "Follow",
+        Stay => "Stay",
+        Guard => "Guard",
+    };
+    order_string.to_string()
+}
+
+pub fn db_string_to_pet_order(order_string: &str) -> comp::pet::PetOrder {
+    use comp::pet::PetOrder::*;
+    match order_string {
+        "Follow" => Follow,
+        "Stay" => Stay,
+        "Guard" => Guard,
+        _ => {
+            dev_panic!(format!(
+                "Tried to convert an unsupported pet order from the database: {}",
+                order_string
+            ));
+            comp::pet::PetOrder::default()
+        },
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct DatabaseAbilitySet {
     mainhand: String,
@@ -345,4 +397,31 @@ pub mod tests {
              forward compatible with migration V50.",
         );
     }
+
+    #[test]
+    fn test_pet_state_round_trip() {
+        use super::*;
+        use comp::pet::{PetOrder, PetStance};
+
+        // The defaults must match the column defaults of migration V59
+        assert_eq!(db_string_to_pet_stance("Aggressive"), PetStance::default());
+        assert_eq!(db_string_to_pet_order("Follow"), PetOrder::default());
+
+        for stance in [
+            PetStance::Passive,
+            PetStance::Defensive,
+            PetStance::Aggressive,
+        ] {
+            assert_eq!(
+                db_string_to_pet_stance(&pet_stance_to_db_string(stance)),
+                stance
+            );
+        }
+        for order in [PetOrder::Follow, PetOrder::Stay, PetOrder::Guard] {
+            assert_eq!(
+                db_string_to_pet_order(&pet_order_to_db_string(order)),
+                order
+            );
+        }
+    }
 }
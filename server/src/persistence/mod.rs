@@ -35,6 +35,7 @@ pub struct PersistedComponents {
     pub pets: Vec<PetPersistenceData>,
     pub active_abilities: comp::ActiveAbilities,
     pub map_marker: Option<comp::MapMarker>,
+    pub achievements: comp::Achievements,
 }
 
 pub type EditableComponents = (comp::Body,);
@@ -35,9 +35,18 @@ pub struct Pet {
     pub name: String,
     pub body_variant: String,
     pub body_data: String,
+    pub stance: String,
+    pub pet_order: String,
+    pub stabled: bool,
 }
 
 pub struct AbilitySets {
     pub entity_id: i64,
     pub ability_sets: String,
 }
+
+pub struct CharacterAchievements {
+    pub entity_id: i64,
+    pub statistics: String,
+    pub unlocked: String,
+}
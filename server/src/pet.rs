@@ -1,15 +1,46 @@
-use crate::{client::Client, events::shared::update_map_markers};
+use crate::{
+    client::Client, events::shared::update_map_markers,
+    persistence::character_updater::PetPersistenceData,
+};
 use common::{
     comp::{
-        self, anchor::Anchor, group::GroupManager, Agent, Alignment, Behavior, BehaviorCapability,
-        Pet, TradingBehavior,
+        self,
+        anchor::Anchor,
+        group::GroupManager,
+        pet::{PetOrder, StabledPet},
+        Agent, Alignment, Behavior, BehaviorCapability, Pet, TradingBehavior,
     },
     uid::Uid,
 };
 use common_net::msg::ServerGeneral;
-use specs::{Entity, Join, WorldExt};
+use specs::{Component, Entity, Join, WorldExt};
 use tracing::{error, warn};
 
+/// Pets that a player has left at a stable. Stabled pets don't exist in the
+/// world, they are only kept here so that they are persisted with the rest of
+/// the character until they are summoned again.
+#[derive(Clone, Debug, Default)]
+pub struct PetStable(pub Vec<PetPersistenceData>);
+
+impl PetStable {
+    /// The information about the stabled pets that is sent to the owner
+    pub fn to_msg(&self) -> ServerGeneral {
+        ServerGeneral::PetStableUpdate(
+            self.0
+                .iter()
+                .map(|(_, body, stats)| StabledPet {
+                    name: stats.name.clone(),
+                    body: *body,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Component for PetStable {
+    type Storage = specs::DenseVecStorage<Self>;
+}
+
 /// Restores a pet retrieved from the database on login, assigning it to its
 /// owner
 pub fn restore_pet(ecs: &specs::World, pet_entity: Entity, owner: Entity, pet: Pet) {
@@ -68,9 +99,18 @@ fn tame_pet_internal(ecs: &specs::World, pet_entity: Entity, owner: Entity, pet:
         .write_storage()
         .insert(pet_entity, Anchor::Entity(owner));
 
-    let _ = ecs
-        .write_storage()
-        .insert(pet_entity, pet.unwrap_or_default());
+    let mut pet = pet.unwrap_or_default();
+    pet.stabled = false;
+    let order = pet.order;
+    let pet_pos = ecs.read_storage::<comp::Pos>().get(pet_entity).copied();
+    let _ = ecs.write_storage().insert(pet_entity, pet);
+
+    if let Some(mut activity) = ecs
+        .write_storage::<comp::CharacterActivity>()
+        .get_mut(pet_entity)
+    {
+        activity.is_pet_staying = order == PetOrder::Stay;
+    }
 
     // Create an agent for this entity using its body
     if let Some(body) = ecs.read_storage().get(pet_entity) {
@@ -83,6 +123,9 @@ fn tame_pet_internal(ecs: &specs::World, pet_entity: Entity, owner: Entity, pet:
         agent.psyche.idle_wander_factor = 0.25;
         agent.psyche.aggro_range_multiplier = 0.25;
         agent.patrol_origin = None;
+        // Positions aren't persisted, so restored pets that were told to stay or
+        // guard keep doing so wherever they appear
+        agent.stay_pos = pet_pos.filter(|_| order != PetOrder::Follow);
         let _ = ecs.write_storage().insert(pet_entity, agent);
     }
 
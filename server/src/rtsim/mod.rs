@@ -6,7 +6,8 @@ use atomicwrites::{AtomicFile, OverwriteBehavior};
 use common::{
     grid::Grid,
     mounting::VolumePos,
-    rtsim::{Actor, ChunkResource, NpcId, RtSimEntity, WorldSettings},
+    rtsim::{Actor, ChunkResource, NpcId, RtSimEntity, WildlifeGroup, WorldSettings},
+    trade::{Good, SiteId, SitePrices},
 };
 use common_ecs::{dispatch, System};
 use common_state::BlockDiff;
@@ -14,7 +15,10 @@ use crossbeam_channel::{unbounded, Receiver, Sender};
 use enum_map::EnumMap;
 use rtsim::{
     data::{npc::SimulationMode, Data, ReadError},
-    event::{OnDeath, OnMountVolume, OnSetup},
+    event::{
+        DeathCause, OnDeath, OnHurt, OnLootContainer, OnMineBlock, OnMountVolume, OnSetup, OnTrade,
+        OnWildlifeKilled,
+    },
     RtState,
 };
 use specs::DispatcherBuilder;
@@ -27,7 +31,7 @@ use std::{
 };
 use tracing::{debug, error, info, trace, warn};
 use vek::*;
-use world::{IndexRef, World};
+use world::{site::economy::Economy, IndexRef, World};
 
 pub struct RtSim {
     file_path: PathBuf,
@@ -43,7 +47,7 @@ impl RtSim {
         world: &World,
         data_dir: PathBuf,
     ) -> Result<Self, ron::Error> {
-        let file_path = Self::get_file_path(data_dir);
+        let file_path = data_file(data_dir);
 
         info!("Looking for rtsim data at {}...", file_path.display());
         let data = 'load: {
@@ -57,8 +61,8 @@ impl RtSim {
                         match Data::from_reader(io::BufReader::new(file)) {
                             Err(ReadError::VersionMismatch(_)) if !ignore_version => {
                                 warn!(
-                                    "Rtsim data version mismatch (implying a breaking change), \
-                                     rtsim data will be purged"
+                                    "Rtsim data is from an unsupported version and cannot be \
+                                     migrated, rtsim data will be purged"
                                 );
                             },
                             Ok(data) | Err(ReadError::VersionMismatch(data)) => {
@@ -136,17 +140,6 @@ impl RtSim {
         Ok(this)
     }
 
-    fn get_file_path(mut data_dir: PathBuf) -> PathBuf {
-        let mut path = std::env::var("VELOREN_RTSIM")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| {
-                data_dir.push("rtsim");
-                data_dir
-            });
-        path.push("data.dat");
-        path
-    }
-
     pub fn hook_character_mount_volume(
         &mut self,
         world: &World,
@@ -200,12 +193,103 @@ impl RtSim {
                 wpos,
                 actor,
                 killer,
+                cause: DeathCause::Killed,
+            },
+            world,
+            index,
+        );
+    }
+
+    pub fn hook_rtsim_actor_hurt(
+        &mut self,
+        world: &World,
+        index: IndexRef,
+        actor: Actor,
+        wpos: Option<Vec3<f32>>,
+        attacker: Actor,
+    ) {
+        self.state.emit(
+            OnHurt {
+                actor,
+                wpos,
+                attacker,
+            },
+            world,
+            index,
+        );
+    }
+
+    /// Account for a wild creature that isn't simulated by rtsim being killed,
+    /// so that it's removed from the wildlife population of the area.
+    pub fn hook_wildlife_killed(
+        &mut self,
+        world: &World,
+        index: IndexRef,
+        group: WildlifeGroup,
+        wpos: Vec3<f32>,
+        killer: Option<Actor>,
+    ) {
+        self.state.emit(
+            OnWildlifeKilled {
+                group,
+                wpos,
+                killer,
             },
             world,
             index,
         );
     }
 
+    pub fn hook_loot_container(
+        &mut self,
+        world: &World,
+        index: IndexRef,
+        actor: Actor,
+        wpos: Vec3<i32>,
+    ) {
+        self.state
+            .emit(OnLootContainer { actor, wpos }, world, index);
+    }
+
+    pub fn hook_mine_block(
+        &mut self,
+        world: &World,
+        index: IndexRef,
+        actor: Actor,
+        wpos: Vec3<i32>,
+    ) {
+        self.state.emit(OnMineBlock { actor, wpos }, world, index);
+    }
+
+    /// Account for goods exchanged with a player at a site, positive amounts
+    /// being received by the site.
+    pub fn hook_trade_at_site(
+        &mut self,
+        world: &World,
+        index: IndexRef,
+        site_id: SiteId,
+        customer: Option<Actor>,
+        goods: impl IntoIterator<Item = (Good, f32)>,
+    ) {
+        let Some(world_site) = index.sites.recreate_id(site_id) else {
+            return;
+        };
+        let data = self.state.get_data_mut();
+        let Some(site) = data.sites.world_site_map.get(&world_site).copied() else {
+            return;
+        };
+        if let Some(economy) = data
+            .sites
+            .sites
+            .get_mut(site)
+            .and_then(|site| site.economy.as_mut())
+        {
+            economy.exchange(goods);
+        }
+
+        self.state.emit(OnTrade { site, customer }, world, index);
+    }
+
     pub fn save(&mut self, wait_until_finished: bool) {
         debug!("Saving rtsim data...");
 
@@ -245,6 +329,30 @@ impl RtSim {
         self.state.data().nature.get_chunk_resources(key)
     }
 
+    pub fn get_chunk_wildlife(&self, key: Vec2<i32>) -> EnumMap<WildlifeGroup, f32> {
+        self.state.data().nature.get_chunk_wildlife(key)
+    }
+
+    /// Call `f` with the live economy of a world site, if it has one.
+    pub fn with_site_economy<T>(
+        &self,
+        index: IndexRef,
+        site_id: SiteId,
+        f: impl FnOnce(&Economy) -> T,
+    ) -> Option<T> {
+        let world_site = index.sites.recreate_id(site_id)?;
+        let data = self.state.data();
+        let site = data.sites.world_site_map.get(&world_site)?;
+        data.sites.get(*site)?.economy.as_ref().map(f)
+    }
+
+    /// The prices at a world site, which follow its economy once it is being
+    /// simulated.
+    pub fn get_site_prices(&self, index: IndexRef, site_id: SiteId) -> Option<SitePrices> {
+        self.with_site_economy(index, site_id, Economy::get_site_prices)
+            .or_else(|| index.get_site_prices(site_id))
+    }
+
     pub fn state(&self) -> &RtState { &self.state }
 
     pub fn set_should_purge(&mut self, should_purge: bool) {
@@ -252,6 +360,18 @@ impl RtSim {
     }
 }
 
+/// The file that rtsim data is kept in.
+pub fn data_file(mut data_dir: PathBuf) -> PathBuf {
+    let mut path = std::env::var("VELOREN_RTSIM")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            data_dir.push("rtsim");
+            data_dir
+        });
+    path.push("data.dat");
+    path
+}
+
 fn save_thread(file_path: PathBuf, rx: Receiver<Data>) {
     if let Some(dir) = file_path.parent() {
         let _ = fs::create_dir_all(dir);
@@ -1,18 +1,19 @@
 #![allow(dead_code)] // TODO: Remove this when rtsim is fleshed out
 
 use super::*;
-use crate::sys::terrain::SpawnEntityData;
+use crate::{sys::terrain::SpawnEntityData, Settings};
 use common::{
     calendar::Calendar,
     comp::{self, Body, Presence, PresenceKind},
     event::{CreateNpcEvent, CreateShipEvent, DeleteEvent, EventBus, NpcBuilder},
     generation::{BodyBuilder, EntityConfig, EntityInfo},
     resources::{DeltaTime, Time, TimeOfDay},
-    rtsim::{Actor, NpcId, RtSimEntity},
+    rtsim::{Actor, NpcActivity, NpcId, RtSimEntity},
     slowjob::SlowJobPool,
     terrain::CoordinateConversions,
     trade::{Good, SiteInformation},
     util::Dir,
+    weather::WeatherGrid,
     LoadoutBuilder,
 };
 use common_ecs::{Job, Origin, Phase, System};
@@ -51,6 +52,7 @@ fn humanoid_config(profession: &Profession) -> &'static str {
         Profession::Alchemist => "common.entity.village.alchemist",
         Profession::Pirate => "common.entity.spot.pirate",
         Profession::Cultist => "common.entity.dungeon.cultist.cultist",
+        Profession::Miner => "common.entity.village.miner",
     }
 }
 
@@ -149,6 +151,23 @@ fn profession_agent_mark(profession: Option<&Profession>) -> Option<comp::agent:
     }
 }
 
+/// The entity config for a pack animal. Animals without a config of their own
+/// are loaded up like donkeys, keeping their own body.
+fn pack_animal_config(body: &Body) -> &'static str {
+    use comp::quadruped_medium::{Body as QuadrupedMediumBody, Species};
+    match body {
+        Body::QuadrupedMedium(QuadrupedMediumBody {
+            species: Species::Camel,
+            ..
+        }) => "common.entity.wild.peaceful.camel",
+        Body::QuadrupedMedium(QuadrupedMediumBody {
+            species: Species::Horse,
+            ..
+        }) => "common.entity.wild.peaceful.horse",
+        _ => "common.entity.wild.peaceful.donkey",
+    }
+}
+
 fn get_npc_entity_info(
     npc: &Npc,
     sites: &Sites,
@@ -160,15 +179,24 @@ fn get_npc_entity_info(
     let mut rng = npc.rng(Npc::PERM_ENTITY_CONFIG);
     if let Some(profession) = npc.profession() {
         let economy = npc.home.and_then(|home| {
-            let site = sites.get(home)?.world_site?;
-            index.sites.get(site).trade_information(site.id())
+            let site = sites.get(home)?;
+            let world_site = site.world_site?;
+            let mut information = index
+                .sites
+                .get(world_site)
+                .trade_information(world_site.id())?;
+            // Stock merchants from the live economy of their home
+            if let Some(economy) = &site.economy {
+                information.unconsumed_stock = economy.get_available_stock();
+            }
+            Some(information)
         });
 
         let config_asset = humanoid_config(&profession);
 
         let entity_config = EntityConfig::from_asset_expect_owned(config_asset)
             .with_body(BodyBuilder::Exact(npc.body));
-        EntityInfo::at(pos.0)
+        let info = EntityInfo::at(pos.0)
             .with_entity_config(entity_config, Some(config_asset), &mut rng, time)
             .with_alignment(if matches!(profession, Profession::Cultist) {
                 comp::Alignment::Enemy
@@ -178,9 +206,15 @@ fn get_npc_entity_info(
             .with_economy(economy.as_ref())
             .with_lazy_loadout(profession_extra_loadout(Some(&profession)))
             .with_alias(npc.get_name())
-            .with_agent_mark(profession_agent_mark(Some(&profession)))
+            .with_agent_mark(profession_agent_mark(Some(&profession)));
+        // Children haven't grown to their full size yet
+        match time {
+            Some((time_of_day, _)) => info.with_scale(npc.body_scale(*time_of_day)),
+            None => info,
+        }
     } else {
         let config_asset = match npc.body {
+            _ if npc.is_pack_animal() => pack_animal_config(&npc.body),
             Body::BirdLarge(body) => match body.species {
                 comp::bird_large::Species::Phoenix => "common.entity.wild.aggressive.phoenix",
                 comp::bird_large::Species::Cockatrice => "common.entity.wild.aggressive.cockatrice",
@@ -228,6 +262,41 @@ fn get_npc_entity_info(
     }
 }
 
+/// If a loaded NPC is heading into (or out of) a different layer of the caves,
+/// the next position along the tunnels to head toward instead, since the agent
+/// can't find its way between layers through the terrain alone.
+fn cave_waypoint(
+    world: &World,
+    index: IndexRef,
+    npc: &mut Npc,
+    to: Vec3<f32>,
+) -> Option<Vec3<f32>> {
+    // How far below the surface a position must be to count as being in a cave
+    const CAVE_DEPTH: f32 = 16.0;
+
+    let is_underground = |wpos: Vec3<f32>| {
+        world
+            .sim()
+            .get_alt_approx(wpos.xy().as_())
+            .map_or(false, |alt| wpos.z < alt - CAVE_DEPTH)
+    };
+    if !is_underground(npc.wpos) && !is_underground(to) {
+        npc.cave_route = None;
+        return None;
+    }
+    // Only look for a new route once the NPC is sent somewhere else, or leaves
+    // the route it was following
+    if npc.cave_route.as_ref().map_or(true, |route| {
+        route.target != to || route.has_strayed(npc.wpos)
+    }) {
+        npc.cave_route = Some(index.caves.route_between(npc.wpos, to, is_underground));
+    }
+    npc.cave_route
+        .as_mut()?
+        .next_waypoint(npc.wpos)
+        .map(|wpos| wpos.as_() + Vec3::new(0.5, 0.5, 0.0))
+}
+
 #[derive(Default)]
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -248,6 +317,8 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, comp::Agent>,
         ReadStorage<'a, Presence>,
         ReadExpect<'a, Calendar>,
+        ReadExpect<'a, WeatherGrid>,
+        Read<'a, Settings>,
     );
 
     const NAME: &'static str = "rtsim::tick";
@@ -273,6 +344,8 @@ impl<'a> System<'a> for Sys {
             mut agents,
             presences,
             calendar,
+            weather_grid,
+            settings,
         ): Self::SystemData,
     ) {
         let mut create_ship_emitter = create_ship_events.emitter();
@@ -287,6 +360,7 @@ impl<'a> System<'a> for Sys {
 
             // Update time of day
             data.time_of_day = *time_of_day;
+            data.year_length = settings.year_length;
 
             // Update character map (i.e: so that rtsim knows where players are)
             // TODO: Other entities too like animals? Or do we now care about that?
@@ -301,6 +375,11 @@ impl<'a> System<'a> for Sys {
                         .push((*character, wpos.0));
                 }
             }
+
+            // Update the weather at each site, so that NPCs can shelter from it
+            for site in data.sites.values_mut() {
+                site.weather = Some(weather_grid.get_interpolated(site.wpos.as_()));
+            }
         }
 
         // Tick rtsim
@@ -446,7 +525,14 @@ impl<'a> System<'a> for Sys {
                         if let Some(agent) = agent {
                             agent.rtsim_controller.personality = npc.personality;
                             agent.rtsim_controller.look_dir = npc.controller.look_dir;
-                            agent.rtsim_controller.activity = npc.controller.activity;
+                            agent.rtsim_controller.activity = match npc.controller.activity {
+                                Some(NpcActivity::Goto(tgt, speed_factor)) => {
+                                    let tgt = cave_waypoint(&world, index.as_index_ref(), npc, tgt)
+                                        .unwrap_or(tgt);
+                                    Some(NpcActivity::Goto(tgt, speed_factor))
+                                },
+                                activity => activity,
+                            };
                             agent
                                 .rtsim_controller
                                 .actions
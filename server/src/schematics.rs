@@ -0,0 +1,124 @@
+//! Schematics that builders have saved with `/schematic`.
+//!
+//! Schematics are kept in the `schematics` folder of the data directory.
+//! Those saved by the server use a versioned native format, but `.vox` models
+//! placed in the folder can be pasted too.
+
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use common::terrain::schematic::{RawSchematic, Schematic};
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+use tracing::error;
+
+/// The most blocks a schematic can be saved with
+pub const MAX_SCHEMATIC_BLOCKS: u32 = 1 << 20;
+const NATIVE_EXTENSION: &str = "schem";
+const VOX_EXTENSION: &str = "vox";
+
+#[derive(Debug)]
+pub enum SchematicError {
+    InvalidName,
+    NotFound,
+    Io(io::Error),
+    Invalid(String),
+}
+
+impl From<io::Error> for SchematicError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::NotFound {
+            SchematicError::NotFound
+        } else {
+            SchematicError::Io(err)
+        }
+    }
+}
+
+pub struct Schematics {
+    dir: PathBuf,
+}
+
+impl Schematics {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join("schematics"),
+        }
+    }
+
+    /// Names may only contain letters, digits, `-` and `_`, so that they can't
+    /// refer to files outside of the schematics folder
+    fn check_name(name: &str) -> Result<(), SchematicError> {
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            Ok(())
+        } else {
+            Err(SchematicError::InvalidName)
+        }
+    }
+
+    fn path(&self, name: &str, extension: &str) -> PathBuf {
+        self.dir.join(name).with_extension(extension)
+    }
+
+    /// The names of all schematics that can be pasted, sorted alphabetically
+    pub fn list(&self) -> Vec<String> {
+        let mut names = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let extension = path.extension()?.to_str()?;
+                if extension == NATIVE_EXTENSION || extension == VOX_EXTENSION {
+                    Some(path.file_stem()?.to_str()?.to_owned())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn save(&self, name: &str, schematic: &Schematic) -> Result<(), SchematicError> {
+        Self::check_name(name)?;
+        let bytes = bincode::serialize(&RawSchematic::from(schematic))
+            .map_err(|err| SchematicError::Invalid(err.to_string()))?;
+        fs::create_dir_all(&self.dir)?;
+        AtomicFile::new(
+            self.path(name, NATIVE_EXTENSION),
+            OverwriteBehavior::AllowOverwrite,
+        )
+        .write(|file| file.write_all(&bytes))
+        .map_err(|err| {
+            error!(?err, "Failed to write schematic {}", name);
+            match err {
+                atomicwrites::Error::Internal(err) | atomicwrites::Error::User(err) => {
+                    SchematicError::Io(err)
+                },
+            }
+        })
+    }
+
+    /// Load a schematic, preferring one in the native format over a `.vox`
+    /// model of the same name
+    pub fn load(&self, name: &str) -> Result<Schematic, SchematicError> {
+        Self::check_name(name)?;
+        match fs::read(self.path(name, NATIVE_EXTENSION)) {
+            Ok(bytes) => bincode::deserialize::<RawSchematic>(&bytes)
+                .map_err(|err| SchematicError::Invalid(err.to_string()))?
+                .try_into()
+                .map_err(|err: &str| SchematicError::Invalid(err.to_owned())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                let bytes = fs::read(self.path(name, VOX_EXTENSION))?;
+                Schematic::from_vox(&bytes).map_err(|err| SchematicError::Invalid(err.to_owned()))
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
+}
@@ -16,7 +16,7 @@ pub use whitelist::{Whitelist, WhitelistInfo, WhitelistRecord};
 use chrono::Utc;
 use common::{
     calendar::{Calendar, CalendarEvent},
-    consts::DAY_LENGTH_DEFAULT,
+    consts::{DAY_LENGTH_DEFAULT, YEAR_LENGTH_DEFAULT},
     resources::BattleMode,
     rtsim::WorldSettings,
 };
@@ -184,6 +184,8 @@ pub struct Settings {
     pub start_time: f64,
     /// Length of a day in minutes.
     pub day_length: f64,
+    /// Length of a year in in-game days.
+    pub year_length: f64,
     /// When set to None, loads the default map file (if available); otherwise,
     /// uses the value of the file options to decide how to proceed.
     pub map_file: Option<FileOpts>,
@@ -224,6 +226,7 @@ impl Default for Settings {
             server_name: "Veloren Server".into(),
             max_players: 100,
             day_length: DAY_LENGTH_DEFAULT,
+            year_length: YEAR_LENGTH_DEFAULT,
             start_time: 9.0 * 3600.0,
             map_file: None,
             max_view_distance: Some(65),
@@ -336,6 +339,15 @@ impl Settings {
             );
             self.day_length = default_values.day_length;
         }
+
+        if self.year_length < 1.0 {
+            warn!(
+                "{} Setting: year_length, Value: {}. Set year_length to it's default value of {}. \
+                 Help: year_length must be at least one day.",
+                INVALID_SETTING_MSG, self.year_length, default_values.year_length
+            );
+            self.year_length = default_values.year_length;
+        }
     }
 
     /// Derive a coefficient that is the relatively speed of the in-game
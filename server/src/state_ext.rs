@@ -6,7 +6,7 @@ use crate::{
     client::Client,
     events::{self, shared::update_map_markers},
     persistence::PersistedComponents,
-    pet::restore_pet,
+    pet::{restore_pet, PetStable},
     presence::RepositionOnChunkLoad,
     settings::Settings,
     sys::sentinel::DeletedEntities,
@@ -582,6 +582,7 @@ impl StateExt for State {
             pets,
             active_abilities,
             map_marker,
+            achievements,
         } = components;
 
         if let Some(player_uid) = self.read_component_copied::<Uid>(entity) {
@@ -652,6 +653,19 @@ impl StateExt for State {
                 self.write_component_ignore_entity_dead(entity, map_marker);
             }
 
+            if let Some(client) = self.ecs().read_storage::<Client>().get(entity) {
+                client.send_fallible(ServerGeneral::AchievementUpdate(achievements.clone()));
+            }
+            self.write_component_ignore_entity_dead(entity, achievements);
+
+            let (stabled_pets, pets): (Vec<_>, Vec<_>) =
+                pets.into_iter().partition(|(pet, _, _)| pet.stabled);
+            let pet_stable = PetStable(stabled_pets);
+            if let Some(client) = self.ecs().read_storage::<Client>().get(entity) {
+                client.send_fallible(pet_stable.to_msg());
+            }
+            self.write_component_ignore_entity_dead(entity, pet_stable);
+
             let player_pos = self.ecs().read_storage::<comp::Pos>().get(entity).copied();
             if let Some(player_pos) = player_pos {
                 trace!(
@@ -0,0 +1,69 @@
+use crate::{client::Client, sys::SysScheduler};
+use common::{
+    comp::{
+        achievement::default_achievement_manifest, Achievements, ChatType, Content, Player, Vel,
+    },
+    resources::DeltaTime,
+};
+use common_ecs::{Job, Origin, Phase, System};
+use common_net::msg::ServerGeneral;
+use specs::{Join, LendJoin, Read, ReadStorage, Write, WriteStorage};
+
+/// Speeds above this (in blocks per second) are not counted towards the
+/// distance travelled, to avoid counting being flung around by knockback or
+/// physics glitches
+const MAX_COUNTED_SPEED: f32 = 100.0;
+
+/// This system tracks the distance travelled by players, unlocks achievements
+/// once their thresholds are reached and keeps clients in sync with their
+/// character's statistics
+#[derive(Default)]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Client>,
+        ReadStorage<'a, Vel>,
+        WriteStorage<'a, Achievements>,
+        Read<'a, DeltaTime>,
+        Write<'a, SysScheduler<Self>>,
+    );
+
+    const NAME: &'static str = "achievements";
+    const ORIGIN: Origin = Origin::Server;
+    const PHASE: Phase = Phase::Create;
+
+    fn run(
+        _job: &mut Job<Self>,
+        (players, clients, velocities, mut achievements, dt, mut scheduler): Self::SystemData,
+    ) {
+        let should_sync = scheduler.should_run();
+        let manifest = default_achievement_manifest().read();
+
+        for (_, client, vel, achievements) in
+            (&players, &clients, velocities.maybe(), &mut achievements).join()
+        {
+            if let Some(speed) = vel
+                .map(|vel| vel.0.magnitude())
+                .filter(|speed| *speed < MAX_COUNTED_SPEED)
+            {
+                achievements.statistics.distance_travelled += f64::from(speed * dt.0);
+            }
+
+            let unlocked = achievements.unlock_reached(&manifest);
+            for def in unlocked.iter().filter_map(|id| manifest.get(id)) {
+                client.send_fallible(ServerGeneral::server_msg(
+                    ChatType::Meta,
+                    Content::localized_with_args("hud-achievement-unlocked", [(
+                        "title",
+                        Content::localized(&def.title),
+                    )]),
+                ));
+            }
+
+            if should_sync || !unlocked.is_empty() {
+                client.send_fallible(ServerGeneral::AchievementUpdate(achievements.clone()));
+            }
+        }
+    }
+}
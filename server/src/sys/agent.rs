@@ -227,6 +227,7 @@ impl<'a> System<'a> for Sys {
                         skill_set,
                         physics_state,
                         alignment: alignment.as_ref(),
+                        pet: read_data.pets.get(entity),
                         traversal_config,
                         scale,
                         damage: health_fraction,
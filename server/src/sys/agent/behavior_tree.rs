@@ -5,6 +5,7 @@ use common::{
             TRADE_INTERACTION_TIME,
         },
         dialogue::Subject,
+        pet::PetOrder,
         Agent, Alignment, BehaviorCapability, BehaviorState, Body, BuffKind, CharacterState,
         ControlAction, ControlEvent, Controller, InputKind, InventoryEvent, Pos, UtteranceKind,
     },
@@ -24,9 +25,9 @@ use self::interaction::{
 
 use super::{
     consts::{
-        DAMAGE_MEMORY_DURATION, FLEE_DURATION, HEALING_ITEM_THRESHOLD, MAX_PATROL_DIST,
-        MAX_STAY_DISTANCE, NORMAL_FLEE_DIR_DIST, NPC_PICKUP_RANGE, RETARGETING_THRESHOLD_SECONDS,
-        STD_AWARENESS_DECAY_RATE,
+        DAMAGE_MEMORY_DURATION, FLEE_DURATION, HEALING_ITEM_THRESHOLD, MAX_GUARD_DISTANCE,
+        MAX_PATROL_DIST, MAX_STAY_DISTANCE, NORMAL_FLEE_DIR_DIST, NPC_PICKUP_RANGE,
+        RETARGETING_THRESHOLD_SECONDS, STD_AWARENESS_DECAY_RATE,
     },
     data::{AgentData, ReadData, TargetData},
     util::{get_entity_by_id, is_dead, is_dead_or_invulnerable, is_invulnerable, stop_pursuing},
@@ -83,6 +84,7 @@ impl BehaviorTree {
                 react_on_dangerous_fall,
                 react_if_on_fire,
                 target_if_attacked,
+                target_by_threat,
                 process_inbox_sound_and_hurt,
                 process_inbox_interaction,
                 do_target_tree_if_target_else_do_idle_tree,
@@ -268,6 +270,10 @@ fn react_if_on_fire(bdata: &mut BehaviorData) -> bool {
 /// Target an entity that's attacking us if the attack was recent and we have
 /// a health component
 fn target_if_attacked(bdata: &mut BehaviorData) -> bool {
+    if bdata.agent_data.is_passive_pet() {
+        return false;
+    }
+
     match bdata.agent_data.health {
         Some(health)
             if bdata.read_data.time.0 - health.last_change.time.0 < DAMAGE_MEMORY_DURATION
@@ -290,14 +296,17 @@ fn target_if_attacked(bdata: &mut BehaviorData) -> bool {
 
                         // Determine whether the new target should be a priority
                         // over the old one (i.e: because it's either close or
-                        // because they attacked us).
-                        if bdata.agent.target.map_or(true, |target| {
-                            bdata.agent_data.is_more_dangerous_than_target(
-                                attacker,
-                                target,
-                                bdata.read_data,
-                            )
-                        }) {
+                        // because they attacked us). Once the attack has been
+                        // recorded in the threat table, that decides instead.
+                        if bdata.agent.threat.is_empty()
+                            && bdata.agent.target.map_or(true, |target| {
+                                bdata.agent_data.is_more_dangerous_than_target(
+                                    attacker,
+                                    target,
+                                    bdata.read_data,
+                                )
+                            })
+                        {
                             bdata.agent.target = Some(Target {
                                 target: attacker,
                                 hostile: true,
@@ -438,7 +447,16 @@ fn follow_if_far_away(bdata: &mut BehaviorData) -> bool {
         if let Some(tgt_pos) = bdata.read_data.positions.get(target) {
             if let Some(stay_pos) = bdata.agent.stay_pos {
                 let distance_from_stay = stay_pos.0.distance_squared(bdata.agent_data.pos.0);
-                bdata.controller.push_action(ControlAction::Sit);
+                // Guarding pets stay on their feet, ready to fight
+                if bdata
+                    .agent_data
+                    .pet
+                    .map_or(false, |pet| pet.order == PetOrder::Guard)
+                {
+                    bdata.controller.push_action(ControlAction::Stand);
+                } else {
+                    bdata.controller.push_action(ControlAction::Sit);
+                }
                 if distance_from_stay > (MAX_STAY_DISTANCE).powi(2) {
                     bdata.agent_data.follow(
                         bdata.agent,
@@ -479,7 +497,7 @@ fn attack_if_owner_hurt(bdata: &mut BehaviorData) -> bool {
                     false
                 };
             let stay = bdata.agent.stay_pos.is_some();
-            if owner_recently_attacked && !stay {
+            if owner_recently_attacked && !stay && !bdata.agent_data.is_passive_pet() {
                 bdata.agent_data.attack_target_attacker(
                     bdata.agent,
                     bdata.read_data,
@@ -571,6 +589,18 @@ fn handle_rtsim_actions(bdata: &mut BehaviorData) -> bool {
     }
 }
 
+/// Attack whoever is the biggest threat, switching targets only once someone
+/// else has become a significantly bigger threat than the current target
+fn target_by_threat(bdata: &mut BehaviorData) -> bool {
+    bdata.agent.threat.decay(bdata.read_data.dt.0);
+    if !bdata.agent.threat.is_empty() && !bdata.agent_data.is_passive_pet() {
+        bdata
+            .agent_data
+            .target_by_threat(bdata.agent, bdata.controller, bdata.read_data);
+    }
+    false
+}
+
 /// Handle timed events, like looking at the player we are talking to
 fn handle_timed_events(bdata: &mut BehaviorData) -> bool {
     let timeout = if bdata.agent.behavior.is(BehaviorState::TRADING) {
@@ -840,6 +870,10 @@ fn do_combat(bdata: &mut BehaviorData) -> bool {
                 agent.target = None;
                 agent_data.idle(agent, controller, read_data, emitters, rng);
             } else if is_invulnerable(target, read_data)
+                // Pets that stay or guard a position don't chase enemies away from it
+                || agent.stay_pos.map_or(false, |stay_pos| {
+                    stay_pos.0.distance_squared(tgt_pos.0) > MAX_GUARD_DISTANCE.powi(2)
+                })
                 || stop_pursuing(
                     dist_sqrd,
                     origin_dist_sqrd,
@@ -855,7 +889,9 @@ fn do_combat(bdata: &mut BehaviorData) -> bool {
                 let is_time_to_retarget =
                     read_data.time.0 - selected_at > RETARGETING_THRESHOLD_SECONDS;
 
-                if !in_aggro_range && is_time_to_retarget {
+                // Agents that are being threatened stick to fighting whoever threatens
+                // them the most
+                if !in_aggro_range && is_time_to_retarget && agent.threat.is_empty() {
                     agent_data.choose_target(
                         agent,
                         controller,
@@ -0,0 +1,219 @@
+use crate::{
+    client::Client,
+    encounter::{BossEncounter, EncounterState},
+};
+use common::{
+    comp::{
+        buff::{Buff, BuffChange, BuffData, BuffSource, DestInfo},
+        Agent, ChatType, Content, Health, Mass, Player, Pos, Stats, Vel,
+    },
+    encounter::EncounterBuff,
+    event::{BuffEvent, EventBus},
+    resources::Time,
+    terrain::TerrainGrid,
+    uid::{IdMaps, Uid},
+};
+use common_ecs::{Job, Origin, Phase, System};
+use common_net::msg::ServerGeneral;
+use common_state::BlockChange;
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, Write, WriteStorage};
+use vek::Vec3;
+
+/// This system drives boss encounters: it seals the arena once players engage
+/// the boss, moves the encounter through its phases, enrages the boss once
+/// the fight has gone on for too long and resets everything once all
+/// participants have left the arena
+#[derive(Default)]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Read<'a, IdMaps>,
+        ReadExpect<'a, TerrainGrid>,
+        Write<'a, BlockChange>,
+        Read<'a, EventBus<BuffEvent>>,
+        WriteStorage<'a, BossEncounter>,
+        WriteStorage<'a, Health>,
+        WriteStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Agent>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Client>,
+        ReadStorage<'a, Stats>,
+        ReadStorage<'a, Mass>,
+    );
+
+    const NAME: &'static str = "encounter";
+    const ORIGIN: Origin = Origin::Server;
+    const PHASE: Phase = Phase::Create;
+
+    fn run(
+        _job: &mut Job<Self>,
+        (
+            entities,
+            time,
+            id_maps,
+            terrain,
+            mut block_change,
+            buff_events,
+            mut encounters,
+            mut healths,
+            mut positions,
+            mut velocities,
+            mut agents,
+            players,
+            uids,
+            clients,
+            stats,
+            masses,
+        ): Self::SystemData,
+    ) {
+        let mut buff_emitter = buff_events.emitter();
+
+        for (boss, encounter) in (&entities, &mut encounters).join() {
+            if healths.get(boss).map_or(true, |health| health.is_dead) {
+                continue;
+            }
+            let def = encounter.def.read();
+
+            let inside = (&players, &uids, &positions, &healths)
+                .join()
+                .filter(|(_, _, pos, health)| !health.is_dead && encounter.contains(pos.0))
+                .map(|(_, uid, _, _)| *uid)
+                .collect::<Vec<_>>();
+
+            let announce = |encounter: &BossEncounter, content: Content| {
+                let msg = ServerGeneral::server_msg(ChatType::Meta, content);
+                for client in encounter
+                    .participants
+                    .iter()
+                    .filter_map(|uid| id_maps.uid_entity(*uid))
+                    .filter_map(|entity| clients.get(entity))
+                {
+                    client.send_fallible(msg.clone());
+                }
+            };
+
+            let buffs_for = |buffs: &[EncounterBuff]| {
+                buffs
+                    .iter()
+                    .map(|buff| BuffEvent {
+                        entity: boss,
+                        buff_change: BuffChange::Add(Buff::new(
+                            buff.kind,
+                            BuffData::new(buff.strength, None),
+                            vec![],
+                            BuffSource::World,
+                            *time,
+                            DestInfo {
+                                stats: stats.get(boss),
+                                mass: masses.get(boss),
+                            },
+                            None,
+                        )),
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            match encounter.state {
+                EncounterState::Idle => {
+                    let provoked = agents
+                        .get(boss)
+                        .map_or(false, |agent| agent.target.is_some())
+                        || healths
+                            .get(boss)
+                            .map_or(false, |health| health.fraction() < 1.0);
+                    if provoked && !inside.is_empty() {
+                        encounter.state = EncounterState::Engaged {
+                            since: *time,
+                            enraged: false,
+                        };
+                        encounter.participants.extend(inside.iter().copied());
+                        encounter.seal(&terrain, &mut block_change);
+                        announce(
+                            encounter,
+                            Content::localized_with_args("hud-encounter-engaged", [(
+                                "name",
+                                Content::localized(&def.name),
+                            )]),
+                        );
+                    }
+                },
+                EncounterState::Engaged { since, enraged } => {
+                    if inside.is_empty() {
+                        let empty_since = *encounter.empty_since.get_or_insert(*time);
+                        if time.0 - empty_since.0 < def.reset_after.0 {
+                            continue;
+                        }
+
+                        for kind in def
+                            .phases
+                            .iter()
+                            .flat_map(|phase| phase.buffs.iter())
+                            .chain(def.enrage.iter().flat_map(|enrage| enrage.buffs.iter()))
+                            .map(|buff| buff.kind)
+                        {
+                            buff_emitter.emit(BuffEvent {
+                                entity: boss,
+                                buff_change: BuffChange::RemoveByKind(kind),
+                            });
+                        }
+                        if let Some(mut health) = healths.get_mut(boss) {
+                            health.revive();
+                        }
+                        if let Some(pos) = positions.get_mut(boss) {
+                            pos.0 = encounter.home;
+                        }
+                        if let Some(vel) = velocities.get_mut(boss) {
+                            vel.0 = Vec3::zero();
+                        }
+                        if let Some(agent) = agents.get_mut(boss) {
+                            agent.target = None;
+                            agent.threat.clear();
+                        }
+                        announce(
+                            encounter,
+                            Content::localized_with_args("hud-encounter-reset", [(
+                                "name",
+                                Content::localized(&def.name),
+                            )]),
+                        );
+                        encounter.reset(&mut block_change);
+                        continue;
+                    }
+
+                    encounter.empty_since = None;
+                    encounter.participants.extend(inside.iter().copied());
+
+                    let health_fraction = healths.get(boss).map_or(1.0, |health| health.fraction());
+                    let reached = def
+                        .phases_reached(encounter.phase, health_fraction)
+                        .collect::<Vec<_>>();
+                    for (i, phase) in reached {
+                        encounter.phase = i + 1;
+                        buff_emitter.emit_many(buffs_for(&phase.buffs));
+                        if let Some(message) = &phase.message {
+                            announce(encounter, Content::localized(message));
+                        }
+                    }
+
+                    if !enraged
+                        && let Some(enrage) = &def.enrage
+                        && time.0 - since.0 >= enrage.after.0
+                    {
+                        encounter.state = EncounterState::Engaged {
+                            since,
+                            enraged: true,
+                        };
+                        buff_emitter.emit_many(buffs_for(&enrage.buffs));
+                        if let Some(message) = &enrage.message {
+                            announce(encounter, Content::localized(message));
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
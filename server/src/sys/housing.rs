@@ -0,0 +1,145 @@
+use crate::{
+    client::Client,
+    housing::Housing,
+    sys::{terrain::TerrainPersistenceData, SysScheduler},
+};
+use common::comp::{
+    item::{tool::AbilityMap, MaterialStatManifest},
+    CanBuild, ChatType, Content, Inventory, Presence,
+};
+use common_ecs::{Job, Origin, Phase, System};
+use common_net::msg::ServerGeneral;
+use common_state::{AreasContainer, BlockChange, BuildArea};
+use hashbrown::HashSet;
+use specs::{Entities, Join, LendJoin, ReadExpect, ReadStorage, Write, WriteExpect, WriteStorage};
+use tracing::warn;
+
+/// This system evicts characters from houses once their rent runs out,
+/// returning the items they stored there, and gives characters build rights
+/// inside the house they own
+#[derive(Default)]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Housing>,
+        Write<'a, AreasContainer<BuildArea>>,
+        WriteExpect<'a, BlockChange>,
+        TerrainPersistenceData<'a>,
+        ReadExpect<'a, AbilityMap>,
+        ReadExpect<'a, MaterialStatManifest>,
+        ReadStorage<'a, Presence>,
+        ReadStorage<'a, Client>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, CanBuild>,
+        Write<'a, SysScheduler<Self>>,
+    );
+
+    const NAME: &'static str = "housing";
+    const ORIGIN: Origin = Origin::Server;
+    const PHASE: Phase = Phase::Create;
+
+    #[cfg_attr(not(feature = "persistent_world"), allow(unused_mut, unused_variables))]
+    fn run(
+        _job: &mut Job<Self>,
+        (
+            entities,
+            mut housing,
+            mut build_areas,
+            mut block_change,
+            mut terrain_persistence,
+            ability_map,
+            msm,
+            presences,
+            clients,
+            mut inventories,
+            mut can_build,
+            mut scheduler,
+        ): Self::SystemData,
+    ) {
+        if !scheduler.should_run() {
+            return;
+        }
+
+        let evicted = housing.evict_expired(chrono::Utc::now().timestamp());
+        for house in &evicted {
+            if build_areas.remove(&house.area_name()).is_err() {
+                warn!(
+                    "Build area of evicted house {} was missing",
+                    house.area_name()
+                );
+            }
+            // Put the house back the way it was before the owner moved in
+            for (pos, block) in house.original_blocks() {
+                block_change.set(pos, block);
+                #[cfg(feature = "persistent_world")]
+                if let Some(terrain_persistence) = terrain_persistence.as_mut() {
+                    terrain_persistence.set_block(pos, block);
+                }
+            }
+        }
+
+        for (entity, presence, client) in (&entities, &presences, clients.maybe()).join() {
+            let Some(character) = presence.kind.character_id() else {
+                continue;
+            };
+
+            if let Some(client) = client
+                && evicted.iter().any(|house| house.owner == character)
+            {
+                client.send_fallible(ServerGeneral::server_msg(
+                    ChatType::Meta,
+                    Content::localized("hud-house-evicted"),
+                ));
+            }
+
+            // Return the items that were stored in the house, as inventory space allows
+            if housing.has_undelivered(character)
+                && let Some(mut inventory) = inventories.get_mut(entity)
+            {
+                let returned = housing.deliver(character, &mut inventory, &ability_map, &msm);
+                if let Some(client) = client
+                    && returned > 0
+                {
+                    let msg = if housing.has_undelivered(character) {
+                        "hud-house-items-partly-returned"
+                    } else {
+                        "hud-house-items-returned"
+                    };
+                    client.send_fallible(ServerGeneral::server_msg(
+                        ChatType::Meta,
+                        Content::localized_with_args(msg, [("count", returned.to_string())]),
+                    ));
+                }
+            }
+
+            let own_area = housing
+                .house_of(character)
+                .and_then(|house| build_areas.area_metas().get(&house.area_name()).copied());
+
+            if let Some(mut can_build) = can_build.get_mut(entity) {
+                // Drop the rights to areas that no longer exist, such as those of houses
+                // that have been left
+                if can_build
+                    .build_areas
+                    .iter()
+                    .any(|area| !build_areas.areas().contains(*area))
+                {
+                    can_build
+                        .build_areas
+                        .retain(|area| build_areas.areas().contains(*area));
+                }
+                if let Some(area) = own_area
+                    && !can_build.build_areas.contains(&area)
+                {
+                    can_build.build_areas.insert(area);
+                }
+            } else if let Some(area) = own_area {
+                let _ = can_build.insert(entity, CanBuild {
+                    enabled: false,
+                    build_areas: HashSet::from_iter([area]),
+                });
+            }
+        }
+    }
+}
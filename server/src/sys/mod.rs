@@ -1,7 +1,10 @@
+pub mod achievements;
 pub mod agent;
 pub mod chunk_send;
 pub mod chunk_serialize;
+pub mod encounter;
 pub mod entity_sync;
+pub mod housing;
 pub mod invite_timeout;
 pub mod item;
 pub mod loot;
@@ -28,6 +31,8 @@ use std::{
 };
 
 pub type PersistenceScheduler = SysScheduler<persistence::Sys>;
+pub type AchievementsScheduler = SysScheduler<achievements::Sys>;
+pub type HousingScheduler = SysScheduler<housing::Sys>;
 
 pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch::<melee::Sys>(dispatch_builder, &[&projectile::Sys::sys_name()]);
@@ -35,11 +40,14 @@ pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch::<agent::Sys>(dispatch_builder, &[]);
     dispatch::<terrain::Sys>(dispatch_builder, &[&msg::terrain::Sys::sys_name()]);
     dispatch::<waypoint::Sys>(dispatch_builder, &[]);
+    dispatch::<achievements::Sys>(dispatch_builder, &[]);
     dispatch::<teleporter::Sys>(dispatch_builder, &[]);
     dispatch::<invite_timeout::Sys>(dispatch_builder, &[]);
     dispatch::<persistence::Sys>(dispatch_builder, &[]);
     dispatch::<object::Sys>(dispatch_builder, &[]);
     dispatch::<wiring::Sys>(dispatch_builder, &[]);
+    dispatch::<encounter::Sys>(dispatch_builder, &[]);
+    dispatch::<housing::Sys>(dispatch_builder, &[]);
     // no dependency, as we only work once per sec anyway.
     dispatch::<chunk_serialize::Sys>(dispatch_builder, &[]);
     // don't depend on chunk_serialize, as we assume everything is done in a SlowJow
@@ -1,6 +1,11 @@
 #[cfg(feature = "persistent_world")]
 use crate::TerrainPersistence;
-use crate::{client::Client, Settings};
+use crate::{
+    client::Client,
+    edit_journal::{BlockEdit, EditJournal},
+    housing::Housing,
+    Settings,
+};
 use common::{
     comp::{
         Admin, AdminRole, CanBuild, ControlEvent, Controller, ForceUpdate, Health, Ori, Player,
@@ -20,7 +25,9 @@ use common_net::msg::{ClientGeneral, ServerGeneral};
 use common_state::{AreasContainer, BlockChange, BuildArea};
 use core::mem;
 use rayon::prelude::*;
-use specs::{Entities, Join, LendJoin, Read, ReadExpect, ReadStorage, Write, WriteStorage};
+use specs::{
+    Entities, Join, LendJoin, Read, ReadExpect, ReadStorage, Write, WriteExpect, WriteStorage,
+};
 use std::{borrow::Cow, time::Instant};
 use tracing::{debug, trace, warn};
 use vek::*;
@@ -41,6 +48,8 @@ pub type TerrainPersistenceData<'a> = core::marker::PhantomData<&'a mut ()>;
 struct RareWrites<'a, 'b> {
     block_changes: &'b mut BlockChange,
     _terrain_persistence: &'b mut TerrainPersistenceData<'a>,
+    housing: &'b mut Housing,
+    edit_journal: &'b mut EditJournal,
 }
 
 event_emitters! {
@@ -72,6 +81,7 @@ impl Sys {
         settings: &Read<'_, Settings>,
         build_areas: &Read<'_, AreasContainer<BuildArea>>,
         player_physics_setting: Option<&mut PlayerPhysicsSetting>,
+        maybe_player: Option<&Player>,
         maybe_admin: &Option<&Admin>,
         time_for_vd_changes: Instant,
         msg: ClientGeneral,
@@ -168,10 +178,26 @@ impl Sys {
                                 let new_block = old_block.into_vacant();
                                 // Take the rare writes lock as briefly as possible.
                                 let mut guard = rare_writes.lock();
-                                let _was_set =
-                                    guard.block_changes.try_set(pos, new_block).is_some();
+                                // Containers in houses can't be broken while they still hold
+                                // items
+                                if guard
+                                    .housing
+                                    .house_at(pos)
+                                    .map_or(false, |house| house.has_stored_items(pos))
+                                {
+                                    continue;
+                                }
+                                let was_set = guard.block_changes.try_set(pos, new_block).is_some();
+                                if was_set {
+                                    guard.housing.set_block(pos, *old_block, new_block);
+                                    if let Some(player) = maybe_player {
+                                        guard.edit_journal.record(player.uuid(), vec![
+                                            BlockEdit::new(terrain, pos, *old_block, new_block),
+                                        ]);
+                                    }
+                                }
                                 #[cfg(feature = "persistent_world")]
-                                if _was_set {
+                                if was_set {
                                     if let Some(terrain_persistence) =
                                         guard._terrain_persistence.as_mut()
                                     {
@@ -187,20 +213,27 @@ impl Sys {
                 if let Some(comp_can_build) = can_build.get(entity) {
                     if comp_can_build.enabled {
                         for area in comp_can_build.build_areas.iter() {
-                            if build_areas
+                            if let Some(old_block) = build_areas
                                 .areas()
                                 .get(*area)
                                 // TODO: Make this an exclusive check on the upper bound of the AABB
                                 // Vek defaults to inclusive which is not optimal
                                 .filter(|aabb| aabb.contains_point(pos))
-                                .is_some()
+                                .and_then(|_| terrain.get(pos).ok())
                             {
                                 // Take the rare writes lock as briefly as possible.
                                 let mut guard = rare_writes.lock();
-                                let _was_set =
-                                    guard.block_changes.try_set(pos, new_block).is_some();
+                                let was_set = guard.block_changes.try_set(pos, new_block).is_some();
+                                if was_set {
+                                    guard.housing.set_block(pos, *old_block, new_block);
+                                    if let Some(player) = maybe_player {
+                                        guard.edit_journal.record(player.uuid(), vec![
+                                            BlockEdit::new(terrain, pos, *old_block, new_block),
+                                        ]);
+                                    }
+                                }
                                 #[cfg(feature = "persistent_world")]
-                                if _was_set {
+                                if was_set {
                                     if let Some(terrain_persistence) =
                                         guard._terrain_persistence.as_mut()
                                     {
@@ -300,6 +333,8 @@ impl<'a> System<'a> for Sys {
         Read<'a, AreasContainer<BuildArea>>,
         Write<'a, PlayerPhysicsSettings>,
         TerrainPersistenceData<'a>,
+        WriteExpect<'a, Housing>,
+        WriteExpect<'a, EditJournal>,
         ReadStorage<'a, Player>,
         ReadStorage<'a, Admin>,
     );
@@ -333,6 +368,8 @@ impl<'a> System<'a> for Sys {
             build_areas,
             mut player_physics_settings_,
             mut terrain_persistence,
+            mut housing,
+            mut edit_journal,
             players,
             admins,
         ): Self::SystemData,
@@ -344,6 +381,8 @@ impl<'a> System<'a> for Sys {
         let rare_writes = parking_lot::Mutex::new(RareWrites {
             block_changes: &mut block_changes,
             _terrain_persistence: &mut terrain_persistence,
+            housing: &mut housing,
+            edit_journal: &mut edit_journal,
         });
 
         let player_physics_settings = &*player_physics_settings_;
@@ -410,6 +449,7 @@ impl<'a> System<'a> for Sys {
                             &settings,
                             &build_areas,
                             new_player_physics_setting.as_mut(),
+                            maybe_player,
                             &maybe_admin,
                             time_for_vd_changes,
                             msg,
@@ -358,7 +358,8 @@ impl<'a> System<'a> for Sys {
                             material_stats: (*read_data.material_stats).clone(),
                             ability_map: (*read_data.ability_map).clone(),
                             server_constants: ServerConstants {
-                                day_cycle_coefficient: read_data.settings.day_cycle_coefficient()
+                                day_cycle_coefficient: read_data.settings.day_cycle_coefficient(),
+                                year_length: read_data.settings.year_length,
                             },
                             description,
                             active_plugins,
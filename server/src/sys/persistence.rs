@@ -1,9 +1,9 @@
-use crate::{persistence::character_updater, sys::SysScheduler};
+use crate::{persistence::character_updater, pet::PetStable, sys::SysScheduler};
 use common::{
     comp::{
         pet::{is_tameable, Pet},
-        ActiveAbilities, Alignment, Body, Inventory, MapMarker, Presence, PresenceKind, SkillSet,
-        Stats, Waypoint,
+        Achievements, ActiveAbilities, Alignment, Body, Inventory, MapMarker, Presence,
+        PresenceKind, SkillSet, Stats, Waypoint,
     },
     uid::Uid,
 };
@@ -25,8 +25,10 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Waypoint>,
         ReadStorage<'a, MapMarker>,
         ReadStorage<'a, Pet>,
+        ReadStorage<'a, PetStable>,
         ReadStorage<'a, Stats>,
         ReadStorage<'a, ActiveAbilities>,
+        ReadStorage<'a, Achievements>,
         WriteExpect<'a, character_updater::CharacterUpdater>,
         Write<'a, SysScheduler<Self>>,
     );
@@ -47,8 +49,10 @@ impl<'a> System<'a> for Sys {
             player_waypoints,
             map_markers,
             pets,
+            pet_stables,
             stats,
             active_abilities,
+            achievements,
             mut updater,
             mut scheduler,
         ): Self::SystemData,
@@ -63,6 +67,8 @@ impl<'a> System<'a> for Sys {
                     player_waypoints.maybe(),
                     &active_abilities,
                     map_markers.maybe(),
+                    pet_stables.maybe(),
+                    &achievements,
                 )
                     .join()
                     .filter_map(
@@ -74,6 +80,8 @@ impl<'a> System<'a> for Sys {
                             waypoint,
                             active_abilities,
                             map_marker,
+                            pet_stable,
+                            achievements,
                         )| match presence.kind {
                             PresenceKind::LoadingCharacter(_char_id) => {
                                 error!(
@@ -97,6 +105,7 @@ impl<'a> System<'a> for Sys {
                                         },
                                         _ => None,
                                     })
+                                    .chain(pet_stable.into_iter().flat_map(|s| s.0.iter().cloned()))
                                     .collect();
 
                                 Some((
@@ -107,6 +116,7 @@ impl<'a> System<'a> for Sys {
                                     waypoint.cloned(),
                                     active_abilities.clone(),
                                     map_marker.cloned(),
+                                    achievements.clone(),
                                 ))
                             },
                             PresenceKind::Spectator | PresenceKind::Possessor => None,
@@ -9,7 +9,7 @@ use world::{IndexOwned, World};
 #[cfg(feature = "worldgen")] use crate::rtsim;
 use crate::{
     chunk_generator::ChunkGenerator, chunk_serialize::ChunkSendEntry, client::Client,
-    presence::RepositionOnChunkLoad, settings::Settings, ChunkRequest, Tick,
+    housing::Housing, presence::RepositionOnChunkLoad, settings::Settings, ChunkRequest, Tick,
 };
 use common::{
     calendar::Calendar,
@@ -17,6 +17,7 @@ use common::{
         self, agent, biped_small, bird_medium, BehaviorCapability, ForceUpdate, Pos, Presence,
         Waypoint,
     },
+    encounter::EncounterInfo,
     event::{CreateNpcEvent, CreateSpecialEntityEvent, EmitExt, EventBus, NpcBuilder},
     event_emitters,
     generation::{EntityInfo, SpecialEntity},
@@ -79,6 +80,7 @@ pub struct Data<'a> {
     rtsim: RtSimData<'a>,
     #[cfg(feature = "persistent_world")]
     terrain_persistence: TerrainPersistenceData<'a>,
+    housing: ReadExpect<'a, Housing>,
     positions: WriteStorage<'a, Pos>,
     presences: ReadStorage<'a, Presence>,
     clients: ReadStorage<'a, Client>,
@@ -130,7 +132,6 @@ impl<'a> System<'a> for Sys {
         // Also, send the chunk data to anybody that is close by.
         let mut new_chunks = Vec::new();
         'insert_terrain_chunks: while let Some((key, res)) = data.chunk_generator.recv_new_chunk() {
-            #[allow(unused_mut)]
             let (mut chunk, supplement) = match res {
                 Ok((chunk, supplement)) => (chunk, supplement),
                 Err(Some(entity)) => {
@@ -153,6 +154,10 @@ impl<'a> System<'a> for Sys {
                 terrain_persistence.apply_changes(key, &mut chunk);
             }
 
+            // Apply changes made to player houses, these are kept regardless of whether
+            // terrain persistence is enabled
+            data.housing.apply_changes(key, &mut chunk);
+
             // Arcify the chunk
             let chunk = Arc::new(chunk);
 
@@ -390,6 +395,7 @@ pub struct NpcData {
     pub scale: comp::Scale,
     pub loot: LootSpec<String>,
     pub pets: Vec<(NpcData, Vec3<f32>)>,
+    pub encounter: Option<EncounterInfo>,
 }
 
 /// Convinient structure to use when you need to create new npc
@@ -427,6 +433,7 @@ impl SpawnEntityData {
             make_loadout,
             trading_information: economy,
             pets,
+            encounter,
         } = entity;
 
         if let Some(special) = special_entity {
@@ -552,6 +559,7 @@ impl SpawnEntityData {
                     })
                     .collect()
             },
+            encounter,
         })
     }
 
@@ -578,6 +586,7 @@ impl NpcData {
             scale,
             loot,
             pets,
+            encounter,
         } = self;
 
         (
@@ -593,7 +602,8 @@ impl NpcData {
                     pets.into_iter()
                         .map(|(pet, offset)| (pet.to_npc_builder().0, offset))
                         .collect::<Vec<_>>(),
-                ),
+                )
+                .with_encounter(encounter),
             pos,
         )
     }
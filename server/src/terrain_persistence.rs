@@ -3,14 +3,14 @@ use common::{
     terrain::{Block, TerrainChunk},
     vol::{RectRasterableVol, WriteVol},
 };
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use schnellru::{Limiter, LruMap};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     any::{type_name, Any},
     fs::File,
     io::{self, Read as _, Write as _},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tracing::{debug, error, info, warn};
 use vek::*;
@@ -36,13 +36,8 @@ impl TerrainPersistence {
     ///
     /// If the `VELOREN_TERRAIN` environment variable is set, this will be used
     /// as the persistence directory instead.
-    pub fn new(mut data_dir: PathBuf) -> Self {
-        let path = std::env::var("VELOREN_TERRAIN")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| {
-                data_dir.push("terrain");
-                data_dir
-            });
+    pub fn new(data_dir: PathBuf) -> Self {
+        let path = terrain_dir(data_dir);
 
         std::fs::create_dir_all(&path).expect("Failed to create terrain persistence directory");
 
@@ -90,11 +85,7 @@ impl TerrainPersistence {
         // reliable strategy should be implemented here.
     }
 
-    fn path_for(&self, key: Vec2<i32>) -> PathBuf {
-        let mut path = self.path.clone();
-        path.push(format!("chunk_{}_{}.dat", key.x, key.y));
-        path
-    }
+    fn path_for(&self, key: Vec2<i32>) -> PathBuf { chunk_path(&self.path, key) }
 
     fn load_chunk(&mut self, key: Vec2<i32>) -> &mut LoadedChunk {
         let path = self.path_for(key);
@@ -318,6 +309,314 @@ impl ByBlockLimiter {
     }
 }
 
+/// The directory that [`TerrainPersistence`] keeps its chunks in, given the
+/// server's data directory.
+///
+/// If the `VELOREN_TERRAIN` environment variable is set, this will be used
+/// instead.
+pub fn terrain_dir(mut data_dir: PathBuf) -> PathBuf {
+    std::env::var("VELOREN_TERRAIN")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            data_dir.push("terrain");
+            data_dir
+        })
+}
+
+fn chunk_path(dir: &Path, key: Vec2<i32>) -> PathBuf {
+    dir.join(format!("chunk_{}_{}.dat", key.x, key.y))
+}
+
+/// The chunk key of a file in the terrain directory, if it is a chunk file
+fn chunk_key_of(path: &Path) -> Option<Vec2<i32>> {
+    let (x, y) = path
+        .file_name()?
+        .to_str()?
+        .strip_prefix("chunk_")?
+        .strip_suffix(".dat")?
+        .split_once('_')?;
+    Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+fn chunk_size() -> Vec2<i32> { TerrainChunk::RECT_SIZE.map(|e| e as i32) }
+
+fn wpos_of(key: Vec2<i32>, rpos: Vec3<i32>) -> Vec2<i32> { key * chunk_size() + rpos.xy() }
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+fn read_chunk(path: &Path) -> io::Result<Chunk> {
+    let bytes = std::fs::read(path)?;
+    Chunk::deserialize_from(io::Cursor::new(bytes))
+        .ok_or_else(|| invalid_data(format!("{:?} is corrupt or too new", path)))
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    AtomicFile::new(path, OverwriteBehavior::AllowOverwrite)
+        .write(|file| file.write_all(bytes))
+        .map_err(|err| match err {
+            atomicwrites::Error::Internal(err) | atomicwrites::Error::User(err) => err,
+        })
+}
+
+fn encode_chunk(chunk: &Chunk) -> io::Result<Vec<u8>> {
+    bincode::serialize::<version::Current>(&chunk.clone().prepare_raw()).map_err(invalid_data)
+}
+
+/// Magic bytes and version of the terrain archive format, see
+/// [`Snapshot::export`].
+const ARCHIVE_VERSION: u64 = 1 | (0x7E44A5C1F0 << 16);
+
+#[derive(Serialize, Deserialize)]
+struct RawArchive {
+    version: u64,
+    region: Aabr<i32>,
+    /// Chunks encoded in the same format as the files in the terrain
+    /// directory, so that old archives can be loaded by the same loaders
+    chunks: Vec<(Vec2<i32>, Vec<u8>)>,
+}
+
+/// The number of blocks that differ between two snapshots of a chunk
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkDiff {
+    /// Blocks that were modified in the newer snapshot only
+    pub added: usize,
+    /// Blocks that were modified in the older snapshot only, having since
+    /// been reset to what was generated
+    pub removed: usize,
+    /// Blocks that were modified in both snapshots, but differ
+    pub changed: usize,
+}
+
+impl ChunkDiff {
+    pub fn is_empty(&self) -> bool { self.added + self.removed + self.changed == 0 }
+}
+
+/// The modified blocks of many chunks, read from a terrain directory or from
+/// an archive made with [`Snapshot::export`].
+///
+/// This is used by tooling to move builds between servers and to repair
+/// regions of the world, and works on the files directly. The server must not
+/// be running while its terrain directory is modified, otherwise any loaded
+/// chunks will be written back over the changes.
+#[derive(Default)]
+pub struct Snapshot {
+    region: Option<Aabr<i32>>,
+    chunks: HashMap<Vec2<i32>, Chunk>,
+}
+
+impl Snapshot {
+    /// Load a snapshot from either a terrain directory or an archive.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if path.is_dir() {
+            Self::load_dir(path)
+        } else {
+            Self::load_archive(path)
+        }
+    }
+
+    fn load_dir(dir: &Path) -> io::Result<Self> {
+        let mut chunks = HashMap::default();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some(key) = chunk_key_of(&path) {
+                chunks.insert(key, read_chunk(&path)?);
+            }
+        }
+        Ok(Self {
+            region: None,
+            chunks,
+        })
+    }
+
+    fn load_archive(path: &Path) -> io::Result<Self> {
+        let raw = bincode::deserialize_from::<_, RawArchive>(io::BufReader::new(File::open(path)?))
+            .map_err(invalid_data)?;
+        if raw.version != ARCHIVE_VERSION {
+            return Err(invalid_data(format!(
+                "{:?} is not a terrain archive, or is too new",
+                path
+            )));
+        }
+        let chunks = raw
+            .chunks
+            .into_iter()
+            .map(|(key, bytes)| {
+                Chunk::deserialize_from(io::Cursor::new(bytes))
+                    .map(|chunk| (key, chunk))
+                    .ok_or_else(|| {
+                        invalid_data(format!("Chunk {:?} of {:?} is corrupt", key, path))
+                    })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            region: Some(raw.region),
+            chunks,
+        })
+    }
+
+    /// The region of the world (in block coordinates, inclusive) that this
+    /// snapshot was taken from, if it is an archive.
+    pub fn region(&self) -> Option<Aabr<i32>> { self.region }
+
+    /// The number of modified blocks in the snapshot
+    pub fn len(&self) -> usize { self.chunks.values().map(Chunk::len).sum() }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Only keep the blocks within a region of the world (in block
+    /// coordinates, inclusive).
+    pub fn extract(&self, region: Aabr<i32>) -> Self {
+        let chunks = self
+            .chunks
+            .iter()
+            .filter_map(|(key, chunk)| {
+                let blocks = chunk
+                    .blocks()
+                    .filter(|(rpos, _)| region.contains_point(wpos_of(*key, *rpos)))
+                    .collect::<HashMap<_, _>>();
+                (!blocks.is_empty()).then_some((*key, Chunk { blocks }))
+            })
+            .collect();
+        Self {
+            region: Some(region),
+            chunks,
+        }
+    }
+
+    /// Move the snapshot by a number of chunks, for instance to place builds
+    /// somewhere else in a world with a different seed.
+    pub fn translate(self, offset: Vec2<i32>) -> Self {
+        let block_offset = offset * chunk_size();
+        Self {
+            region: self.region.map(|region| Aabr {
+                min: region.min + block_offset,
+                max: region.max + block_offset,
+            }),
+            chunks: self
+                .chunks
+                .into_iter()
+                .map(|(key, chunk)| (key + offset, chunk))
+                .collect(),
+        }
+    }
+
+    /// Write the snapshot to a single archive file.
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        let region = self.region.unwrap_or_else(|| {
+            let mut keys = self.chunks.keys();
+            let first = keys.next().copied().unwrap_or_default();
+            let keys = keys.fold(Aabr::new_empty(first), |region, key| {
+                region.expanded_to_contain_point(*key)
+            });
+            Aabr {
+                min: keys.min * chunk_size(),
+                max: (keys.max + 1) * chunk_size() - 1,
+            }
+        });
+        let mut chunks = self
+            .chunks
+            .iter()
+            .map(|(key, chunk)| Ok((*key, encode_chunk(chunk)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        chunks.sort_by_key(|(key, _)| (key.x, key.y));
+
+        let bytes = bincode::serialize(&RawArchive {
+            version: ARCHIVE_VERSION,
+            region,
+            chunks,
+        })
+        .map_err(invalid_data)?;
+        write_atomic(path, &bytes)
+    }
+
+    /// Compare this snapshot to a newer one, chunk by chunk. Only chunks that
+    /// differ are returned, ordered by their position.
+    pub fn diff(&self, newer: &Self, region: Option<Aabr<i32>>) -> Vec<(Vec2<i32>, ChunkDiff)> {
+        let empty = Chunk::default();
+        let mut keys = self
+            .chunks
+            .keys()
+            .chain(newer.chunks.keys())
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|key| (key.x, key.y));
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let old = self.chunks.get(&key).unwrap_or(&empty);
+                let new = newer.chunks.get(&key).unwrap_or(&empty);
+                let in_region = |rpos: &Vec3<i32>| {
+                    region.map_or(true, |r| r.contains_point(wpos_of(key, *rpos)))
+                };
+
+                let mut diff = ChunkDiff::default();
+                for (rpos, block) in old.blocks().filter(|(rpos, _)| in_region(rpos)) {
+                    match new.blocks.get(&rpos) {
+                        None => diff.removed += 1,
+                        Some(new_block) if *new_block != block => diff.changed += 1,
+                        Some(_) => {},
+                    }
+                }
+                diff.added = new
+                    .blocks()
+                    .filter(|(rpos, _)| in_region(rpos) && !old.blocks.contains_key(rpos))
+                    .count();
+
+                (!diff.is_empty()).then_some((key, diff))
+            })
+            .collect()
+    }
+
+    /// Make a region of the world (in block coordinates, inclusive) in the
+    /// given terrain directory match this snapshot, returning the number of
+    /// chunk files that were changed.
+    ///
+    /// Blocks in the region that aren't in the snapshot are reset to what
+    /// the world generator produces.
+    pub fn restore(&self, dir: &Path, region: Aabr<i32>) -> io::Result<usize> {
+        let region = region.made_valid();
+        let min_key = region.min.map2(chunk_size(), |e, sz| e.div_euclid(sz));
+        let max_key = region.max.map2(chunk_size(), |e, sz| e.div_euclid(sz));
+
+        let mut changed = 0;
+        for x in min_key.x..=max_key.x {
+            for y in min_key.y..=max_key.y {
+                let key = Vec2::new(x, y);
+                let path = chunk_path(dir, key);
+                let old = match read_chunk(&path) {
+                    Ok(chunk) => chunk,
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => Chunk::default(),
+                    Err(err) => return Err(err),
+                };
+
+                let in_region = |rpos: &Vec3<i32>| region.contains_point(wpos_of(key, *rpos));
+                let mut chunk = old.clone();
+                chunk.blocks.retain(|rpos, _| !in_region(rpos));
+                if let Some(restored) = self.chunks.get(&key) {
+                    chunk
+                        .blocks
+                        .extend(restored.blocks().filter(|(rpos, _)| in_region(rpos)));
+                }
+                if chunk.blocks == old.blocks {
+                    continue;
+                }
+
+                if chunk.blocks.is_empty() {
+                    std::fs::remove_file(&path)?;
+                } else {
+                    write_atomic(&path, &encode_chunk(&chunk)?)?;
+                }
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+}
+
 /// # Adding a new chunk format version
 ///
 /// Chunk formats are designed to be backwards-compatible when loading, but are
@@ -469,3 +768,63 @@ mod version {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::terrain::BlockKind;
+
+    fn snapshot(blocks: &[(Vec3<i32>, Block)]) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        for (wpos, block) in blocks {
+            let key = wpos.xy().map2(chunk_size(), |e, sz| e.div_euclid(sz));
+            snapshot
+                .chunks
+                .entry(key)
+                .or_default()
+                .blocks
+                .insert(*wpos - (key * chunk_size()).with_z(0), *block);
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_extract_and_diff() {
+        let stone = Block::new(BlockKind::Rock, Rgb::new(128, 128, 128));
+        let old = snapshot(&[
+            (Vec3::new(1, 1, 10), stone),
+            (Vec3::new(40, 1, 10), stone),
+            (Vec3::new(-5, -5, 10), stone),
+        ]);
+        let new = snapshot(&[
+            (Vec3::new(1, 1, 10), Block::empty()),
+            (Vec3::new(40, 1, 10), stone),
+            (Vec3::new(2, 2, 10), stone),
+        ]);
+
+        let region = Aabr {
+            min: Vec2::new(-8, -8),
+            max: Vec2::new(8, 8),
+        };
+        let extracted = old.extract(region);
+        assert_eq!(extracted.len(), 2);
+        assert_eq!(extracted.region(), Some(region));
+
+        assert_eq!(old.diff(&new, None), vec![
+            (Vec2::new(-1, -1), ChunkDiff {
+                removed: 1,
+                ..Default::default()
+            }),
+            (Vec2::new(0, 0), ChunkDiff {
+                added: 1,
+                changed: 1,
+                ..Default::default()
+            }),
+        ]);
+        assert!(old.diff(&old, None).is_empty());
+
+        let moved = new.translate(Vec2::new(2, 0));
+        assert_eq!(moved.diff(&snapshot(&[]), None).len(), 2);
+        assert!(moved.chunks.contains_key(&Vec2::new(3, 0)));
+    }
+}
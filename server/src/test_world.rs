@@ -2,7 +2,7 @@ use common::{
     calendar::Calendar,
     generation::ChunkSupplement,
     resources::TimeOfDay,
-    rtsim::ChunkResource,
+    rtsim::{ChunkResource, WildlifeGroup},
     terrain::{
         Block, BlockKind, MapSizeLg, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainChunkSize,
     },
@@ -52,6 +52,7 @@ impl World {
         _index: IndexRef,
         chunk_pos: Vec2<i32>,
         _rtsim_resources: Option<EnumMap<ChunkResource, f32>>,
+        _rtsim_wildlife: Option<EnumMap<WildlifeGroup, f32>>,
         // TODO: misleading name
         mut _should_continue: impl FnMut() -> bool,
         _time: Option<(TimeOfDay, Calendar)>,
@@ -87,4 +88,9 @@ impl World {
         // Test world has no locations
         None
     }
+
+    pub fn find_house(&self, _index: IndexRef, _wpos: Vec3<i32>) -> Option<Aabb<i32>> {
+        // Test world has no houses
+        None
+    }
 }
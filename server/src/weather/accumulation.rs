@@ -0,0 +1,462 @@
+//! Snow and water that build up on the terrain during bad weather.
+//!
+//! When enabled in [`common::rtsim::WorldSettings`], columns of terrain around
+//! players are sampled every so often. Snow piles up on exposed surfaces
+//! while it is snowing and melts once it gets warm again, and rain fills small
+//! depressions with water that evaporates when the weather clears. The blocks
+//! that were replaced are remembered so they can be put back, and the changes
+//! are recorded in [`crate::TerrainPersistence`] if it is enabled. Without
+//! terrain persistence, they are simply reverted when the chunk is unloaded.
+
+use crate::{client::Client, sys::SysScheduler};
+use atomicwrites::{AtomicFile, OverwriteBehavior};
+use common::{
+    comp::Pos,
+    terrain::{sprite::Category, Block, BlockKind, SpriteKind, TerrainGrid},
+    vol::ReadVol,
+    weather::{Weather, WeatherGrid},
+};
+use common_ecs::{Job, Origin, Phase, System};
+use common_state::BlockChange;
+use hashbrown::HashMap;
+use rand::{seq::SliceRandom, thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use specs::{Join, ReadExpect, ReadStorage, Write};
+use std::{
+    fs,
+    io::Write as _,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use tracing::{error, info};
+use vek::*;
+
+#[cfg(feature = "persistent_world")]
+use crate::TerrainPersistence;
+#[cfg(feature = "persistent_world")]
+type TerrainPersistenceData<'a> = Option<Write<'a, TerrainPersistence>>;
+#[cfg(not(feature = "persistent_world"))]
+type TerrainPersistenceData<'a> = ();
+
+/// How many columns are sampled each time the system runs
+const SAMPLES_PER_TICK: usize = 32;
+/// The most blocks that will be changed each time the system runs
+const MAX_CHANGES_PER_TICK: usize = 16;
+/// How far from players columns are sampled, in blocks
+const SAMPLE_RADIUS: i32 = 64;
+/// How deep snow can pile up, in blocks
+const MAX_SNOW_DEPTH: usize = 3;
+/// How deep ponds can get, in blocks
+const MAX_POND_DEPTH: usize = 2;
+/// Below this amount of precipitation nothing accumulates
+const MIN_PRECIPITATION: f32 = 0.1;
+/// Minimum time between writes of the accumulation file
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const SNOW_COLOR: Rgb<u8> = Rgb::new(255, 255, 255);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Layer {
+    Snow,
+    Water,
+}
+
+impl Layer {
+    fn block(self) -> Block {
+        match self {
+            Layer::Snow => Block::new(BlockKind::Snow, SNOW_COLOR),
+            Layer::Water => Block::water(SpriteKind::Empty),
+        }
+    }
+
+    fn max_depth(self) -> usize {
+        match self {
+            Layer::Snow => MAX_SNOW_DEPTH,
+            Layer::Water => MAX_POND_DEPTH,
+        }
+    }
+
+    /// The chance of a layer being added to a column this tick
+    fn grow_chance(self, weather: &Weather) -> f32 {
+        let precipitation = match self {
+            Layer::Snow => weather.snow,
+            Layer::Water => weather.rain,
+        };
+        if precipitation < MIN_PRECIPITATION {
+            0.0
+        } else {
+            precipitation.min(1.0)
+        }
+    }
+
+    /// The chance of a layer being removed from a column this tick, snow melts
+    /// and ponds evaporate faster the warmer it is
+    fn shrink_chance(self, weather: &Weather) -> f32 {
+        let dry = match self {
+            Layer::Snow => weather.snow < MIN_PRECIPITATION,
+            Layer::Water => weather.rain < MIN_PRECIPITATION,
+        };
+        if dry && !weather.is_freezing() {
+            (weather.temperature / 20.0).clamp(0.05, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Column {
+    kind: Layer,
+    /// Height of the lowest layer
+    base: i32,
+    /// The blocks that were replaced by each layer, from the bottom up
+    replaced: Vec<Block>,
+}
+
+impl Column {
+    fn top(&self) -> Option<i32> {
+        (!self.replaced.is_empty()).then(|| self.base + self.replaced.len() as i32 - 1)
+    }
+}
+
+/// A single block to be changed, which is only remembered once the change
+/// has been accepted by [`BlockChange`]
+struct Change {
+    wpos: Vec2<i32>,
+    kind: Layer,
+    pos: Vec3<i32>,
+    block: Block,
+    /// The block being replaced, or `None` if a layer is being removed
+    replaced: Option<Block>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RawAccumulation {
+    columns: HashMap<Vec2<i32>, Column>,
+}
+
+pub struct Accumulation {
+    path: PathBuf,
+    columns: HashMap<Vec2<i32>, Column>,
+    modified: bool,
+    last_save: Instant,
+}
+
+impl Accumulation {
+    /// Load accumulated snow and water from the given data directory
+    pub fn new(mut data_dir: PathBuf) -> Self {
+        data_dir.push("accumulation.ron");
+        let path = data_dir;
+
+        let raw = match fs::File::open(&path) {
+            Ok(file) => ron::de::from_reader::<_, RawAccumulation>(file).unwrap_or_else(|err| {
+                let backup_path = path.with_extension("invalid.ron");
+                error!(
+                    ?err,
+                    "Failed to load weather accumulation, moving it to {:?} for you to repair.",
+                    backup_path
+                );
+                if let Err(err) = fs::rename(&path, &backup_path) {
+                    error!(?err, "Failed to rename invalid weather accumulation file");
+                }
+                RawAccumulation::default()
+            }),
+            Err(_) => {
+                info!(
+                    "No weather accumulation file found at {:?}, starting without snow or ponds",
+                    path
+                );
+                RawAccumulation::default()
+            },
+        };
+
+        Self {
+            path,
+            columns: raw.columns,
+            modified: false,
+            last_save: Instant::now(),
+        }
+    }
+
+    /// Stop tracking layers that are no longer in the terrain, either because
+    /// the chunk was regenerated without them or because they were dug out
+    fn forget_stale(&mut self, terrain: &TerrainGrid, wpos: Vec2<i32>) {
+        let Some(column) = self.columns.get_mut(&wpos) else {
+            return;
+        };
+        let placed = column.kind.block();
+        while let Some(top) = column.top() {
+            match terrain.get(wpos.with_z(top)) {
+                Ok(block) if *block == placed => return,
+                // Unloaded, we can't tell yet
+                Err(_) => return,
+                Ok(_) => {
+                    column.replaced.pop();
+                    self.modified = true;
+                },
+            }
+        }
+        self.columns.remove(&wpos);
+        self.modified = true;
+    }
+
+    /// Decide how a column should change in the given weather, if at all
+    fn plan(
+        &self,
+        terrain: &TerrainGrid,
+        wpos: Vec2<i32>,
+        weather: &Weather,
+        rng: &mut impl Rng,
+    ) -> Option<Change> {
+        let (kind, above, depth) = match self.columns.get(&wpos) {
+            Some(column) => {
+                let top = column.top()?;
+                if rng.gen::<f32>() < column.kind.shrink_chance(weather) {
+                    return Some(Change {
+                        wpos,
+                        kind: column.kind,
+                        pos: wpos.with_z(top),
+                        block: *column.replaced.last()?,
+                        replaced: None,
+                    });
+                }
+                (column.kind, top + 1, column.replaced.len())
+            },
+            None => {
+                let kind = if weather.is_freezing() {
+                    Layer::Snow
+                } else {
+                    Layer::Water
+                };
+                let surface = surface(terrain, wpos)?;
+                let ground = terrain.get(surface).ok()?;
+                // Leave natural snow and bodies of water alone
+                if !ground.is_filled() || matches!(ground.kind(), BlockKind::Snow) {
+                    return None;
+                }
+                (kind, surface.z + 1, 0)
+            },
+        };
+
+        if depth >= kind.max_depth() || rng.gen::<f32>() >= kind.grow_chance(weather) {
+            return None;
+        }
+        let pos = wpos.with_z(above);
+        let replaced = *terrain.get(pos).ok()?;
+        if !is_replaceable(&replaced) {
+            return None;
+        }
+        // Ponds can only form where the water has nowhere to flow
+        if kind == Layer::Water
+            && ![
+                Vec2::unit_x(),
+                -Vec2::unit_x(),
+                Vec2::unit_y(),
+                -Vec2::unit_y(),
+            ]
+            .into_iter()
+            .all(|dir| {
+                terrain
+                    .get(pos + dir.with_z(0))
+                    .map_or(false, |block| block.is_filled() || block.is_liquid())
+            })
+        {
+            return None;
+        }
+
+        Some(Change {
+            wpos,
+            kind,
+            pos,
+            block: kind.block(),
+            replaced: Some(replaced),
+        })
+    }
+
+    /// Remember a change that was made to the terrain
+    fn apply(&mut self, change: Change) {
+        self.modified = true;
+        match change.replaced {
+            Some(replaced) => {
+                self.columns
+                    .entry(change.wpos)
+                    .or_insert_with(|| Column {
+                        kind: change.kind,
+                        base: change.pos.z,
+                        replaced: Vec::new(),
+                    })
+                    .replaced
+                    .push(replaced);
+            },
+            None => {
+                if let Some(column) = self.columns.get_mut(&change.wpos) {
+                    column.replaced.pop();
+                    if column.replaced.is_empty() {
+                        self.columns.remove(&change.wpos);
+                    }
+                }
+            },
+        }
+    }
+
+    /// Save the accumulation if it has been changed since it was last saved,
+    /// at most once every [`SAVE_INTERVAL`]
+    pub fn maintain(&mut self) {
+        if self.modified && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    pub fn save(&mut self) {
+        self.modified = false;
+        self.last_save = Instant::now();
+
+        let raw = RawAccumulation {
+            columns: self.columns.clone(),
+        };
+        let ron = match ron::ser::to_string_pretty(&raw, ron::ser::PrettyConfig::default()) {
+            Ok(ron) => ron,
+            Err(err) => {
+                error!(?err, "Failed to serialize weather accumulation");
+                return;
+            },
+        };
+        let atomic_file = AtomicFile::new(&self.path, OverwriteBehavior::AllowOverwrite);
+        if let Err(err) = atomic_file.write(|file| file.write_all(ron.as_bytes())) {
+            error!(?err, "Failed to write weather accumulation to file");
+        }
+    }
+}
+
+/// Whether snow or water may take the place of this block
+fn is_replaceable(block: &Block) -> bool {
+    block.is_air()
+        && block.sprite_category().map_or(true, |category| {
+            matches!(category, Category::Void | Category::Plant)
+        })
+}
+
+/// The highest solid or liquid block in the column
+fn surface(terrain: &TerrainGrid, wpos: Vec2<i32>) -> Option<Vec3<i32>> {
+    let chunk = terrain.get_key(terrain.pos_key(wpos.with_z(0)))?;
+    (chunk.get_min_z()..chunk.get_max_z())
+        .rev()
+        .map(|z| wpos.with_z(z))
+        .find(|pos| {
+            terrain
+                .get(*pos)
+                .map_or(false, |block| block.is_filled() || block.is_liquid())
+        })
+}
+
+#[cfg_attr(not(feature = "persistent_world"), allow(unused_variables))]
+fn persist(terrain_persistence: &mut TerrainPersistenceData, pos: Vec3<i32>, block: Block) {
+    #[cfg(feature = "persistent_world")]
+    if let Some(terrain_persistence) = terrain_persistence.as_mut() {
+        terrain_persistence.set_block(pos, block);
+    }
+}
+
+/// This system piles up snow and fills ponds around players according to the
+/// weather, if [`Accumulation`] is enabled
+#[derive(Default)]
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Option<Write<'a, Accumulation>>,
+        Write<'a, SysScheduler<Self>>,
+        ReadExpect<'a, WeatherGrid>,
+        ReadExpect<'a, TerrainGrid>,
+        Write<'a, BlockChange>,
+        TerrainPersistenceData<'a>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Client>,
+    );
+
+    const NAME: &'static str = "weather_accumulation";
+    const ORIGIN: Origin = Origin::Server;
+    const PHASE: Phase = Phase::Create;
+
+    fn run(
+        _job: &mut Job<Self>,
+        (
+            accumulation,
+            mut scheduler,
+            weather_grid,
+            terrain,
+            mut block_change,
+            mut terrain_persistence,
+            positions,
+            clients,
+        ): Self::SystemData,
+    ) {
+        let Some(mut accumulation) = accumulation else {
+            return;
+        };
+        if !scheduler.should_run() {
+            return;
+        }
+
+        let players = (&positions, &clients)
+            .join()
+            .map(|(pos, _)| pos.0.xy().as_::<i32>())
+            .collect::<Vec<_>>();
+        let mut rng = thread_rng();
+        let mut changes = 0;
+        for _ in 0..SAMPLES_PER_TICK {
+            if changes >= MAX_CHANGES_PER_TICK {
+                break;
+            }
+            let Some(center) = players.choose(&mut rng) else {
+                break;
+            };
+            let wpos = center
+                + Vec2::new(
+                    rng.gen_range(-SAMPLE_RADIUS..=SAMPLE_RADIUS),
+                    rng.gen_range(-SAMPLE_RADIUS..=SAMPLE_RADIUS),
+                );
+
+            accumulation.forget_stale(&terrain, wpos);
+            let weather = weather_grid.get_interpolated(wpos.as_());
+            let Some(change) = accumulation.plan(&terrain, wpos, &weather, &mut rng) else {
+                continue;
+            };
+            if block_change.try_set(change.pos, change.block).is_some() {
+                persist(&mut terrain_persistence, change.pos, change.block);
+                accumulation.apply(change);
+                changes += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_only_melt_when_warm_and_dry() {
+        let blizzard = Weather {
+            snow: 0.8,
+            temperature: -5.0,
+            ..Default::default()
+        };
+        let thaw = Weather {
+            temperature: 10.0,
+            ..Default::default()
+        };
+        let downpour = Weather {
+            rain: 0.8,
+            temperature: 10.0,
+            ..Default::default()
+        };
+
+        assert!(Layer::Snow.grow_chance(&blizzard) > 0.0);
+        assert_eq!(Layer::Snow.shrink_chance(&blizzard), 0.0);
+        assert_eq!(Layer::Snow.grow_chance(&thaw), 0.0);
+        assert!(Layer::Snow.shrink_chance(&thaw) > 0.0);
+        assert!(Layer::Water.grow_chance(&downpour) > 0.0);
+        assert_eq!(Layer::Water.shrink_chance(&downpour), 0.0);
+        assert!(Layer::Water.shrink_chance(&thaw) > 0.0);
+    }
+}
@@ -1,24 +1,35 @@
-use common_ecs::dispatch;
+use common_ecs::{dispatch, System};
 use common_state::State;
 use specs::DispatcherBuilder;
+use std::time::Duration;
 
+mod accumulation;
 mod sim;
 mod tick;
 
+pub use accumulation::Accumulation;
 pub use tick::WeatherJob;
 
 /// How often the weather is updated, in seconds
 const WEATHER_DT: f32 = 5.0;
+/// How often snow and water accumulate on the terrain
+const ACCUMULATION_INTERVAL: Duration = Duration::from_secs(1);
 
 #[cfg(feature = "worldgen")]
 pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch::<tick::Sys>(dispatch_builder, &[]);
+    dispatch::<accumulation::Sys>(dispatch_builder, &[&tick::Sys::sys_name()]);
 }
 
 #[cfg(feature = "worldgen")]
 pub fn init(state: &mut State) {
-    use crate::weather::sim::LightningCells;
+    use crate::{sys::SysScheduler, weather::sim::LightningCells};
 
     state.ecs_mut().insert(None::<WeatherJob>);
     state.ecs_mut().insert(LightningCells::default());
+    state
+        .ecs_mut()
+        .insert(SysScheduler::<accumulation::Sys>::every(
+            ACCUMULATION_INTERVAL,
+        ));
 }
@@ -1,11 +1,11 @@
 use common::{
     grid::Grid,
     resources::TimeOfDay,
-    weather::{Weather, WeatherGrid, CELL_SIZE, CHUNKS_PER_CELL},
+    weather::{daily_warmth, seasonal_warmth, Weather, WeatherGrid, CELL_SIZE, CHUNKS_PER_CELL},
 };
 use noise::{NoiseFn, SuperSimplex, Turbulence};
 use vek::*;
-use world::World;
+use world::{World, CONFIG};
 
 use crate::weather::WEATHER_DT;
 
@@ -18,12 +18,29 @@ struct WeatherZone {
     time_to_live: f32,
 }
 
+/// Average temperature across the world and the year, in degrees Celsius
+const MEAN_TEMPERATURE: f32 = 10.0;
+/// How far the temperature of the hottest and coldest biomes lies from the mean
+const BIOME_TEMPERATURE_RANGE: f32 = 20.0;
+/// How much colder it gets per block of altitude above sea level
+const LAPSE_RATE: f32 = 0.005;
+/// How far midsummer and midwinter temperatures lie from the yearly average
+const SEASONAL_TEMPERATURE_RANGE: f32 = 10.0;
+/// How far afternoon and night temperatures lie from the daily average
+const DAILY_TEMPERATURE_RANGE: f32 = 5.0;
+
 struct CellConsts {
     humidity: f32,
+    /// Average biome temperature of the cell, between -1 and 1
+    temperature: f32,
+    /// Average altitude of the cell above sea level
+    altitude: f32,
 }
 
 pub struct WeatherSim {
     size: Vec2<u32>,
+    /// Length of the year, in in-game days
+    year_length: f64,
     consts: Grid<CellConsts>,
     zones: Grid<Option<WeatherZone>>,
 }
@@ -35,15 +52,18 @@ pub struct LightningCells {
 }
 
 impl WeatherSim {
-    pub fn new(size: Vec2<u32>, world: &World) -> Self {
+    pub fn new(size: Vec2<u32>, world: &World, year_length: f64) -> Self {
         Self {
             size,
+            year_length,
             consts: Grid::from_raw(
                 size.as_(),
                 (0..size.x * size.y)
                     .map(|i| Vec2::new(i % size.x, i / size.x))
                     .map(|p| {
                         let mut humid_sum = 0.0;
+                        let mut temp_sum = 0.0;
+                        let mut alt_sum = 0.0;
 
                         for y in 0..CHUNKS_PER_CELL {
                             for x in 0..CHUNKS_PER_CELL {
@@ -51,12 +71,17 @@ impl WeatherSim {
                                 if let Some(chunk) = world.sim().get(chunk_pos.as_()) {
                                     let env = chunk.get_environment();
                                     humid_sum += env.humid;
+                                    temp_sum += env.temp;
+                                    alt_sum += (chunk.alt - CONFIG.sea_level).max(0.0);
                                 }
                             }
                         }
-                        let average_humid = humid_sum / (CHUNKS_PER_CELL * CHUNKS_PER_CELL) as f32;
+                        let chunks = (CHUNKS_PER_CELL * CHUNKS_PER_CELL) as f32;
+                        let average_humid = humid_sum / chunks;
                         CellConsts {
                             humidity: average_humid.powf(0.2).min(1.0),
+                            temperature: temp_sum / chunks,
+                            altitude: alt_sum / chunks,
                         }
                     })
                     .collect::<Vec<_>>(),
@@ -102,6 +127,9 @@ impl WeatherSim {
 
         let rain_nz = SuperSimplex::new();
 
+        let seasonal_warmth = seasonal_warmth(time_of_day, self.year_length);
+        let daily_warmth = daily_warmth(time_of_day);
+
         let mut lightning_cells = Vec::new();
         for (point, cell) in out.iter_mut() {
             if let Some(zone) = &mut self.zones[point] {
@@ -140,6 +168,7 @@ impl WeatherSim {
                     * self.consts[point].humidity
                     * 2.5)
                     .powf(0.75);
+                cell.snow = 0.0;
                 cell.wind = Vec2::new(
                     rain_nz.get(spos.into_array()).powi(3) as f32,
                     rain_nz.get((spos + 1.0).into_array()).powi(3) as f32,
@@ -147,6 +176,20 @@ impl WeatherSim {
                     * (1.0 - pressure);
             }
 
+            let consts = &self.consts[point];
+            cell.temperature = MEAN_TEMPERATURE
+                + consts.temperature * BIOME_TEMPERATURE_RANGE
+                - consts.altitude * LAPSE_RATE
+                + seasonal_warmth * SEASONAL_TEMPERATURE_RANGE
+                // Clouds keep days cooler and nights warmer
+                + daily_warmth * DAILY_TEMPERATURE_RANGE * (1.0 - cell.cloud.min(1.0) * 0.5);
+
+            // Below freezing, rain comes down as snow instead
+            if cell.is_freezing() {
+                cell.snow = (cell.snow + cell.rain).min(1.0);
+                cell.rain = 0.0;
+            }
+
             if cell.rain > 0.2 && cell.cloud > 0.15 {
                 lightning_cells.push(point);
             }
@@ -158,3 +201,52 @@ impl WeatherSim {
 
     pub fn size(&self) -> Vec2<u32> { self.size }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weather_sim(temperature: f32) -> WeatherSim {
+        let size = Vec2::new(4, 4);
+        WeatherSim {
+            size,
+            year_length: common::consts::YEAR_LENGTH_DEFAULT,
+            consts: Grid::populate_from(size.as_(), |_| CellConsts {
+                humidity: 1.0,
+                temperature,
+                altitude: 0.0,
+            }),
+            zones: Grid::new(size.as_(), None),
+        }
+    }
+
+    #[test]
+    fn snow_stays_bounded_and_thaws() {
+        let mut sim = weather_sim(-2.0);
+        let mut out = WeatherGrid::new(sim.size());
+        let storm = Weather {
+            cloud: 1.0,
+            rain: 1.0,
+            ..Default::default()
+        };
+        sim.add_zone(storm, Vec2::broadcast(2.0), 10.0, WEATHER_DT * 3.0);
+
+        // Snow doesn't pile up in the weather itself, however long it's snowing for
+        for tick in 0..20 {
+            sim.tick(TimeOfDay(tick as f64 * WEATHER_DT as f64), &mut out);
+            for (_, cell) in out.iter() {
+                assert!(cell.is_freezing());
+                assert_eq!(cell.rain, 0.0);
+                assert!((0.0..=1.0).contains(&cell.snow), "snow was {}", cell.snow);
+            }
+            if tick < 3 {
+                assert!(out.iter().all(|(_, cell)| cell.snow == 1.0));
+            }
+        }
+
+        // Once it warms up, the snow stops
+        sim.consts = weather_sim(2.0).consts;
+        sim.tick(TimeOfDay(20.0 * WEATHER_DT as f64), &mut out);
+        assert!(out.iter().all(|(_, cell)| cell.snow == 0.0));
+    }
+}
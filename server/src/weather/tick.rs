@@ -14,7 +14,7 @@ use std::{mem, sync::Arc};
 use vek::Vec2;
 use world::World;
 
-use crate::{client::Client, Tick};
+use crate::{client::Client, Settings, Tick};
 
 use super::{
     sim::{LightningCells, WeatherSim},
@@ -58,6 +58,7 @@ impl<'a> System<'a> for Sys {
         WriteExpect<'a, SlowJobPool>,
         Read<'a, EventBus<Outcome>>,
         ReadExpect<'a, Arc<World>>,
+        ReadExpect<'a, Settings>,
         ReadStorage<'a, Client>,
         ReadStorage<'a, comp::Pos>,
     );
@@ -80,6 +81,7 @@ impl<'a> System<'a> for Sys {
             slow_job_pool,
             outcomes,
             world,
+            settings,
             clients,
             positions,
         ): Self::SystemData,
@@ -91,7 +93,7 @@ impl<'a> System<'a> for Sys {
                 let (weather_tx, weather_rx) = crossbeam_channel::bounded(1);
 
                 let weather_size = world.sim().get_size() / common::weather::CHUNKS_PER_CELL;
-                let mut sim = WeatherSim::new(weather_size, &world);
+                let mut sim = WeatherSim::new(weather_size, &world, settings.year_length);
                 *grid = WeatherGrid::new(sim.size());
                 *lightning_cells = sim.tick(*game_time, &mut grid);
 
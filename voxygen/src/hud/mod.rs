@@ -2832,11 +2832,14 @@ impl Hud {
             // Weather
             let weather = client.weather_at_player();
             Text::new(&format!(
-                "Weather({kind}): {{cloud: {cloud:.2}, rain: {rain:.2}, wind: <{wind_x:.0}, \
-                 {wind_y:.0}>}}",
+                "Weather({kind}, {season}): {{cloud: {cloud:.2}, rain: {rain:.2}, snow: \
+                 {snow:.2}, temperature: {temperature:.0}°C, wind: <{wind_x:.0}, {wind_y:.0}>}}",
                 kind = weather.get_kind(),
+                season = client.season(),
                 cloud = weather.cloud,
                 rain = weather.rain,
+                snow = weather.snow,
+                temperature = weather.temperature,
                 wind_x = weather.wind.x,
                 wind_y = weather.wind.y
             ))
@@ -178,6 +178,7 @@ impl Screen {
                                     },
                                     WorldGenerateStage::EconomySimulation => i18n.get_msg("hud-init-stage-server-economysim"),
                                     WorldGenerateStage::SpotGeneration => i18n.get_msg("hud-init-stage-server-spotgen"),
+                                    WorldGenerateStage::CaveGraph => i18n.get_msg("hud-init-stage-server-cavegraph"),
                                 },
                                 ServerInitStage::StartingSystems => i18n.get_msg("hud-init-stage-server-starting"),
                             }
@@ -1644,6 +1644,52 @@ impl Scene {
         });
     }
 
+    /// Show the region selected in build mode as a box, and remove it once the
+    /// selection is cleared
+    pub fn maintain_build_selection(
+        &mut self,
+        client: &Client,
+        shown: &mut Option<(Aabb<i32>, Vec<DebugShapeId>)>,
+    ) {
+        let selection = client.build_selection();
+        if shown.as_ref().map(|(aabb, _)| *aabb) == selection {
+            return;
+        }
+        if let Some((_, ids)) = shown.take() {
+            ids.into_iter().for_each(|id| self.debug.remove_shape(id));
+        }
+        if let Some(aabb) = selection {
+            const LINE_WIDTH: f32 = 0.1;
+            // Blocks extend to one past their position
+            let (min, max) = (aabb.min.as_::<f32>(), (aabb.max + 1).as_::<f32>());
+            let corner = |i: usize| {
+                Vec3::new(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                )
+            };
+            // Join each corner to its neighbours along each axis
+            let ids = (0..8)
+                .flat_map(|i| {
+                    [1, 2, 4]
+                        .into_iter()
+                        .filter(move |axis| i & axis == 0)
+                        .map(move |axis| (i, i | axis))
+                })
+                .map(|(a, b)| {
+                    let id = self
+                        .debug
+                        .add_shape(DebugShape::Line([corner(a), corner(b)], LINE_WIDTH));
+                    self.debug
+                        .set_context(id, [0.0; 4], [1.0, 0.84, 0.2, 0.9], [0.0, 0.0, 0.0, 1.0]);
+                    id
+                })
+                .collect();
+            *shown = Some((aabb, ids));
+        }
+    }
+
     pub fn maintain_debug_vectors(&mut self, client: &Client, lines: &mut PlayerDebugLines) {
         lines
             .chunk_normal
@@ -124,6 +124,7 @@ pub struct SessionState {
     hitboxes: HashMap<specs::Entity, DebugShapeId>,
     lines: PlayerDebugLines,
     tracks: HashMap<Vec2<i32>, Vec<DebugShapeId>>,
+    build_selection: Option<(Aabb<i32>, Vec<DebugShapeId>)>,
 }
 
 /// Represents an active game session (i.e., the one being played).
@@ -196,6 +197,7 @@ impl SessionState {
             hitboxes: HashMap::new(),
             metadata,
             tracks: HashMap::new(),
+            build_selection: None,
             lines: Default::default(),
         }
     }
@@ -249,6 +251,8 @@ impl SessionState {
             &mut self.tracks,
         );
         self.scene.maintain_debug_vectors(&client, &mut self.lines);
+        self.scene
+            .maintain_build_selection(&client, &mut self.build_selection);
 
         // All this camera code is just to determine if it's underwater for the sfx
         // filter
@@ -31,6 +31,7 @@ fn cave(c: &mut Criterion) {
                     index.as_index_ref(),
                     entrance,
                     None,
+                    None,
                     || false,
                     None,
                 ));
@@ -64,6 +65,7 @@ fn cave(c: &mut Criterion) {
                     index.as_index_ref(),
                     chunk,
                     None,
+                    None,
                     || false,
                     None,
                 ));
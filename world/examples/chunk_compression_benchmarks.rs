@@ -783,7 +783,8 @@ fn main() {
             .map(|v| v + sitepos.as_())
             .enumerate()
         {
-            let chunk = world.generate_chunk(index.as_index_ref(), spiralpos, None, || false, None);
+            let chunk =
+                world.generate_chunk(index.as_index_ref(), spiralpos, None, None, || false, None);
             if let Ok((chunk, _)) = chunk {
                 let uncompressed = bincode::serialize(&chunk).unwrap();
                 let n = uncompressed.len();
@@ -0,0 +1,394 @@
+//! Renders the world map as PNG tiles for web map viewers.
+//!
+//! Each layer is written to `<out>/<layer>/<z>/<x>/<y>.png`, where zoom level
+//! 0 is the whole map in a single tile and every further level doubles the
+//! resolution. The `base` layer is the topographic map shown by the client,
+//! while the other layers are transparent overlays meant to be stacked on top
+//! of it.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, ValueEnum};
+use common::{
+    grid::Grid,
+    terrain::{
+        map::{MapConfig, MapSample},
+        uniform_idx_as_vec2, BiomeKind, CoordinateConversions,
+    },
+};
+use common_net::msg::world_msg::{SiteInfo, SiteKind};
+use image::{imageops, Rgba, RgbaImage};
+use tracing::{info, Level};
+use tracing_subscriber::EnvFilter;
+use vek::*;
+use veloren_world::{
+    sim::{
+        get_horizon_map, sample_pos, sample_wpos, FileOpts, WorldOpts, DEFAULT_WORLD_MAP,
+        DEFAULT_WORLD_SEED,
+    },
+    util::NEIGHBORS,
+    IndexOwned, World, CONFIG,
+};
+
+#[derive(Parser)]
+struct Cli {
+    /// Seed of the world
+    #[arg(long, default_value_t = DEFAULT_WORLD_SEED)]
+    seed: u32,
+    /// Map file to load, the default world is used if not given
+    #[arg(long)]
+    map: Option<PathBuf>,
+    /// Directory the tiles are written to
+    #[arg(short, long, default_value = "map_tiles")]
+    out: PathBuf,
+    /// Pixels per chunk at the highest zoom level
+    #[arg(long, default_value_t = 2)]
+    scale: u32,
+    /// Width and height of each tile in pixels
+    #[arg(long, default_value_t = 256)]
+    tile_size: u32,
+    /// Layers to render
+    #[arg(short, long, value_enum, value_delimiter = ',', default_value = "base")]
+    layers: Vec<Layer>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Layer {
+    /// Topographic map
+    Base,
+    /// Towns, dungeons and other sites
+    Sites,
+    Roads,
+    Rivers,
+    Biomes,
+    /// Cave entrances
+    Caves,
+}
+
+impl Layer {
+    fn name(self) -> &'static str {
+        match self {
+            Layer::Base => "base",
+            Layer::Sites => "sites",
+            Layer::Roads => "roads",
+            Layer::Rivers => "rivers",
+            Layer::Biomes => "biomes",
+            Layer::Caves => "caves",
+        }
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+    let scale = cli.scale.max(1);
+
+    let threadpool = rayon::ThreadPoolBuilder::new().build().unwrap();
+    info!("Loading world");
+    let (world, index) = World::generate(
+        cli.seed,
+        WorldOpts {
+            seed_elements: true,
+            world_file: match cli.map {
+                Some(path) => FileOpts::Load(path),
+                None => FileOpts::LoadAsset(DEFAULT_WORLD_MAP.into()),
+            },
+            calendar: None,
+        },
+        &threadpool,
+        &|_| {},
+    );
+    let map_data = world.get_map_data(index.as_index_ref(), &threadpool);
+    let canvas = Canvas {
+        chunks: world.sim().map_size_lg().chunks().map(u32::from),
+        scale,
+    };
+
+    for layer in cli.layers {
+        info!("Rendering {} layer", layer.name());
+        let image = match layer {
+            Layer::Base => render_base(&world, &index, &map_data.rgba, canvas),
+            Layer::Sites => render_sites(&map_data.sites, canvas, |kind| {
+                !matches!(kind, SiteKind::Cave)
+            }),
+            Layer::Caves => render_sites(&map_data.sites, canvas, |kind| {
+                matches!(kind, SiteKind::Cave)
+            }),
+            Layer::Roads => render_roads(&world, canvas),
+            Layer::Rivers => render_rivers(&world, canvas),
+            Layer::Biomes => render_biomes(&world, canvas),
+        };
+        write_tiles(&image, &cli.out.join(layer.name()), cli.tile_size.max(1));
+    }
+    info!("Finished writing tiles to: {}", cli.out.display());
+}
+
+/// The size of the rendered map, and how to find positions on it
+#[derive(Clone, Copy)]
+struct Canvas {
+    chunks: Vec2<u32>,
+    scale: u32,
+}
+
+impl Canvas {
+    fn size(&self) -> Vec2<u32> { self.chunks * self.scale }
+
+    fn image(&self) -> RgbaImage { RgbaImage::new(self.size().x, self.size().y) }
+
+    /// Position of a world position on the image, with north at the top
+    fn pixel(&self, wpos: Vec2<i32>) -> Vec2<f32> {
+        let pos = wpos.as_::<f32>().wpos_to_cpos() * self.scale as f32;
+        Vec2::new(pos.x, self.size().y as f32 - pos.y)
+    }
+
+    /// The pixels covered by a chunk
+    fn chunk_pixels(&self, cpos: Vec2<i32>) -> impl Iterator<Item = (u32, u32)> {
+        let scale = self.scale;
+        let min = Vec2::new(
+            cpos.x as u32 * scale,
+            (self.chunks.y - cpos.y as u32 - 1) * scale,
+        );
+        (0..scale).flat_map(move |y| (0..scale).map(move |x| (min.x + x, min.y + y)))
+    }
+
+    fn chunk_positions(&self) -> impl Iterator<Item = Vec2<i32>> {
+        let chunks = self.chunks.as_::<i32>();
+        (0..chunks.y).flat_map(move |y| (0..chunks.x).map(move |x| Vec2::new(x, y)))
+    }
+}
+
+/// The topographic map, as drawn by the client
+fn render_base(world: &World, index: &IndexOwned, rgba: &Grid<u32>, canvas: Canvas) -> RgbaImage {
+    let index_ref = index.as_index_ref();
+    let sampler = world.sim();
+    let map_size_lg = sampler.map_size_lg();
+
+    let horizons = get_horizon_map(
+        map_size_lg,
+        Aabr {
+            min: Vec2::zero(),
+            max: map_size_lg.chunks().map(|e| e as i32),
+        },
+        CONFIG.sea_level,
+        CONFIG.sea_level + sampler.max_height,
+        |posi| {
+            let sample = sampler.get(uniform_idx_as_vec2(map_size_lg, posi)).unwrap();
+
+            sample.basement.max(sample.water_alt)
+        },
+        |a| a,
+        |h| h,
+    )
+    .ok();
+
+    let mut map_config = MapConfig::orthographic(map_size_lg, 0.0..=sampler.max_height);
+    map_config.horizons = horizons.as_ref();
+    map_config.is_shaded = true;
+    map_config.is_stylized_topo = true;
+
+    let mut image = canvas.image();
+    map_config.generate(
+        |pos| {
+            let default_sample = sample_pos(&map_config, sampler, index_ref, None, pos);
+            let [r, g, b, _a] = rgba[pos].to_le_bytes();
+
+            MapSample {
+                rgb: Rgb::new(r, g, b),
+                ..default_sample
+            }
+        },
+        |wpos| sample_wpos(&map_config, sampler, wpos),
+        |pos, (r, g, b, a)| {
+            for (x, y) in canvas.chunk_pixels(pos.as_()) {
+                image.put_pixel(x, y, Rgba([r, g, b, a]));
+            }
+        },
+    );
+    image
+}
+
+fn render_sites(
+    sites: &[SiteInfo],
+    canvas: Canvas,
+    filter: impl Fn(&SiteKind) -> bool,
+) -> RgbaImage {
+    let mut image = canvas.image();
+    let radius = (canvas.scale as f32 * 1.5).max(2.0);
+    for site in sites.iter().filter(|site| filter(&site.kind)) {
+        draw_marker(
+            &mut image,
+            canvas.pixel(site.wpos),
+            radius,
+            site_color(&site.kind),
+        );
+    }
+    image
+}
+
+fn render_roads(world: &World, canvas: Canvas) -> RgbaImage {
+    let mut image = canvas.image();
+    let color = Rgba([140, 90, 40, 255]);
+    // The centre of the path through a chunk
+    let way_center = |cpos: Vec2<i32>| {
+        world
+            .sim()
+            .get(cpos)
+            .map(|chunk| cpos.cpos_to_wpos_center() + chunk.path.0.offset.map(i32::from))
+    };
+    for cpos in canvas.chunk_positions() {
+        let Some(chunk) = world.sim().get(cpos) else {
+            continue;
+        };
+        let way = chunk.path.0;
+        if !way.is_way() {
+            continue;
+        }
+        let from = cpos.cpos_to_wpos_center() + way.offset.map(i32::from);
+        for (i, dir) in NEIGHBORS.iter().enumerate() {
+            // Each connection is stored by both chunks, so only draw half of them
+            if i >= 4 || way.neighbors & (1 << i) == 0 {
+                continue;
+            }
+            if let Some(to) = way_center(cpos + *dir) {
+                draw_line(&mut image, canvas.pixel(from), canvas.pixel(to), color);
+            }
+        }
+    }
+    image
+}
+
+fn render_rivers(world: &World, canvas: Canvas) -> RgbaImage {
+    let mut image = canvas.image();
+    let color = Rgba([40, 110, 220, 255]);
+    for cpos in canvas.chunk_positions() {
+        let Some(chunk) = world.sim().get(cpos) else {
+            continue;
+        };
+        if !chunk.river.is_river() {
+            continue;
+        }
+        if let Some(downhill) = chunk.downhill {
+            let from = cpos.cpos_to_wpos_center();
+            let to = downhill.wpos_to_cpos().cpos_to_wpos_center();
+            draw_line(&mut image, canvas.pixel(from), canvas.pixel(to), color);
+        }
+    }
+    image
+}
+
+fn render_biomes(world: &World, canvas: Canvas) -> RgbaImage {
+    let mut image = canvas.image();
+    for cpos in canvas.chunk_positions() {
+        let Some(chunk) = world.sim().get(cpos) else {
+            continue;
+        };
+        let color = biome_color(chunk.get_biome());
+        for (x, y) in canvas.chunk_pixels(cpos) {
+            image.put_pixel(x, y, color);
+        }
+    }
+    image
+}
+
+fn site_color(kind: &SiteKind) -> Rgba<u8> {
+    match kind {
+        SiteKind::Town => Rgba([255, 220, 60, 255]),
+        SiteKind::Dungeon { .. } | SiteKind::Cultist | SiteKind::Terracotta => {
+            Rgba([200, 40, 40, 255])
+        },
+        SiteKind::Castle | SiteKind::ChapelSite => Rgba([180, 180, 200, 255]),
+        SiteKind::Cave | SiteKind::DwarvenMine => Rgba([90, 60, 40, 255]),
+        SiteKind::Tree => Rgba([40, 160, 60, 255]),
+        SiteKind::Bridge => Rgba([140, 90, 40, 255]),
+        SiteKind::Gnarling | SiteKind::Adlet | SiteKind::Haniwa => Rgba([230, 120, 30, 255]),
+    }
+}
+
+/// Biomes are drawn half transparent so that the terrain below shows through
+fn biome_color(biome: BiomeKind) -> Rgba<u8> {
+    let [r, g, b] = match biome {
+        BiomeKind::Void => return Rgba([0; 4]),
+        BiomeKind::Lake => [60, 120, 220],
+        BiomeKind::Ocean => [20, 50, 160],
+        BiomeKind::Grassland => [120, 200, 80],
+        BiomeKind::Mountain => [130, 130, 130],
+        BiomeKind::Snowland => [240, 240, 255],
+        BiomeKind::Desert => [230, 200, 110],
+        BiomeKind::Swamp => [80, 110, 60],
+        BiomeKind::Jungle => [20, 140, 40],
+        BiomeKind::Forest => [40, 100, 40],
+        BiomeKind::Savannah => [200, 180, 80],
+        BiomeKind::Taiga => [70, 120, 100],
+    };
+    Rgba([r, g, b, 128])
+}
+
+fn draw_line(image: &mut RgbaImage, from: Vec2<f32>, to: Vec2<f32>, color: Rgba<u8>) {
+    let steps = from.distance(to).ceil().max(1.0) as usize;
+    for i in 0..=steps {
+        put_pixel(image, Lerp::lerp(from, to, i as f32 / steps as f32), color);
+    }
+}
+
+fn draw_marker(image: &mut RgbaImage, center: Vec2<f32>, radius: f32, color: Rgba<u8>) {
+    let r = radius.ceil() as i32;
+    for y in -r..=r {
+        for x in -r..=r {
+            let offset = Vec2::new(x, y).as_::<f32>();
+            if offset.magnitude() <= radius {
+                put_pixel(image, center + offset, color);
+            }
+        }
+    }
+}
+
+fn put_pixel(image: &mut RgbaImage, pos: Vec2<f32>, color: Rgba<u8>) {
+    let (x, y) = (pos.x.floor(), pos.y.floor());
+    if x >= 0.0 && y >= 0.0 && (x as u32) < image.width() && (y as u32) < image.height() {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Cut the image into tiles for every zoom level, from a single tile at zoom
+/// level 0 up to the full resolution of the image
+fn write_tiles(image: &RgbaImage, dir: &Path, tile_size: u32) {
+    let longest = image.width().max(image.height());
+    let max_zoom = (longest as f32 / tile_size as f32).log2().ceil().max(0.0) as u32;
+
+    // Pad the image to a square so that every zoom level has whole tiles
+    let side = tile_size << max_zoom;
+    let mut padded = RgbaImage::new(side, side);
+    imageops::replace(&mut padded, image, 0, 0);
+
+    for zoom in (0..=max_zoom).rev() {
+        let tiles = 1 << zoom;
+        let level = if zoom == max_zoom {
+            padded.clone()
+        } else {
+            imageops::resize(
+                &padded,
+                tile_size * tiles,
+                tile_size * tiles,
+                imageops::FilterType::Triangle,
+            )
+        };
+        for x in 0..tiles {
+            let column = dir.join(zoom.to_string()).join(x.to_string());
+            fs::create_dir_all(&column).expect("Could not create tile directory");
+            for y in 0..tiles {
+                let tile =
+                    imageops::crop_imm(&level, x * tile_size, y * tile_size, tile_size, tile_size)
+                        .to_image();
+                tile.save(column.join(y.to_string()).with_extension("png"))
+                    .expect("Could not write tile");
+            }
+        }
+    }
+}
@@ -97,9 +97,14 @@ fn generate(db_path: &str, ymin: Option<i32>, ymax: Option<i32>) -> Result<(), B
             }
             println!("Generating chunk at ({}, {})", x, y);
             let start_time = SystemTime::now();
-            if let Ok((chunk, _supplement)) =
-                world.generate_chunk(index.as_index_ref(), Vec2::new(x, y), None, || false, None)
-            {
+            if let Ok((chunk, _supplement)) = world.generate_chunk(
+                index.as_index_ref(),
+                Vec2::new(x, y),
+                None,
+                None,
+                || false,
+                None,
+            ) {
                 let end_time = SystemTime::now();
                 // TODO: can kiddo be made to work without the `Float` bound, so we can use
                 // `KdTree<u8, (), 3>` (currently it uses 15 bytes per point instead of 3)?
@@ -1,5 +1,8 @@
 use crate::{
-    layer::wildlife::{self, DensityFn, SpawnEntry},
+    layer::{
+        cave::CaveGraph,
+        wildlife::{self, DensityFn, SpawnEntry},
+    },
     site::{economy::TradeInformation, Site},
     Colors, Features,
 };
@@ -22,6 +25,7 @@ pub struct Index {
     pub sites: Store<Site>,
     pub trade: TradeInformation,
     pub wildlife_spawns: Vec<(AssetHandle<SpawnEntry>, DensityFn)>,
+    pub caves: CaveGraph,
     colors: AssetHandle<Arc<Colors>>,
     features: AssetHandle<Arc<Features>>,
 }
@@ -77,6 +81,7 @@ impl Index {
             sites: Store::default(),
             trade: Default::default(),
             wildlife_spawns,
+            caves: CaveGraph::default(),
             colors,
             features,
         }
@@ -1,25 +1,31 @@
 use crate::{
+    sim::WorldSim,
     site::SiteKind,
     util::{
         close_fast as close, sampler::Sampler, FastNoise2d, RandomField, RandomPerm, SmallCache,
         StructureGen2d, LOCALITY, SQUARE_4,
     },
-    Canvas, CanvasInfo, ColumnSample, Land,
+    Canvas, CanvasInfo, ColumnSample, IndexRef, Land,
 };
 use common::{
+    astar::Astar,
     generation::EntityInfo,
+    store::{Id, Store},
     terrain::{
         quadratic_nearest_point, river_spline_coeffs, Block, BlockKind, CoordinateConversions,
         SpriteKind, TerrainChunkSize,
     },
     vol::RectVolSize,
 };
+use fxhash::FxHasher64;
+use hashbrown::HashMap;
 use itertools::Itertools;
 use noise::NoiseFn;
 use rand::prelude::*;
 use std::{
     cmp::Ordering,
     f64::consts::PI,
+    hash::BuildHasherDefault,
     ops::{Add, Mul, Range, Sub},
 };
 use vek::*;
@@ -304,7 +310,6 @@ pub(crate) fn tunnels_at<'a>(
     level: u32,
     land: &'a Land,
 ) -> impl Iterator<Item = Tunnel> + 'a {
-    let rand = RandomField::new(37 + level);
     let col_cell = to_cell(wpos - CELL_SIZE / 4, level);
     LOCALITY
         .into_iter()
@@ -313,34 +318,48 @@ pub(crate) fn tunnels_at<'a>(
             Some(current_cell_pos).zip(node_at(current_cell_pos, level, land))
         })
         .flat_map(move |(current_cell_pos, current_cell)| {
-            [Vec2::new(1, 1), Vec2::new(1, -1)]
-                .into_iter()
-                .filter(move |rpos| {
-                    let mid = (current_cell_pos * 2 + rpos) / 2;
-                    rand.chance(mid.with_z(0), 0.5) ^ (rpos.y == -1)
-                })
-                .chain([Vec2::new(1, 0), Vec2::new(0, 1)])
-                .filter_map(move |rpos| {
-                    let other_cell_pos = current_cell_pos + rpos;
-                    Some(other_cell_pos).zip(node_at(other_cell_pos, level, land))
-                })
-                .filter(move |(other_cell_pos, _)| {
-                    rand.chance((current_cell_pos + other_cell_pos).with_z(7), 0.3)
-                })
-                .map(move |(_other_cell_pos, other_cell)| Tunnel {
-                    a: current_cell,
-                    b: other_cell,
-                    curve: RandomField::new(13)
-                        .get_f32(current_cell.wpos.with_z(0))
-                        .powf(0.25)
-                        .mul(
-                            if RandomField::new(14).chance(current_cell.wpos.with_z(0), 0.5) {
-                                1.0
-                            } else {
-                                -1.0
-                            },
-                        ),
-                })
+            tunnels_from_cell(current_cell_pos, current_cell, level, land).map(|(_, tunnel)| tunnel)
+        })
+}
+
+/// The tunnels within a level that start at the node of a cell, along with the
+/// cells that they lead to
+fn tunnels_from_cell<'a>(
+    current_cell_pos: Vec2<i32>,
+    current_cell: Node,
+    level: u32,
+    land: &'a Land,
+) -> impl Iterator<Item = (Vec2<i32>, Tunnel)> + 'a {
+    let rand = RandomField::new(37 + level);
+    [Vec2::new(1, 1), Vec2::new(1, -1)]
+        .into_iter()
+        .filter(move |rpos| {
+            let mid = (current_cell_pos * 2 + rpos) / 2;
+            rand.chance(mid.with_z(0), 0.5) ^ (rpos.y == -1)
+        })
+        .chain([Vec2::new(1, 0), Vec2::new(0, 1)])
+        .filter_map(move |rpos| {
+            let other_cell_pos = current_cell_pos + rpos;
+            Some(other_cell_pos).zip(node_at(other_cell_pos, level, land))
+        })
+        .filter(move |(other_cell_pos, _)| {
+            rand.chance((current_cell_pos + other_cell_pos).with_z(7), 0.3)
+        })
+        .map(move |(other_cell_pos, other_cell)| {
+            (other_cell_pos, Tunnel {
+                a: current_cell,
+                b: other_cell,
+                curve: RandomField::new(13)
+                    .get_f32(current_cell.wpos.with_z(0))
+                    .powf(0.25)
+                    .mul(
+                        if RandomField::new(14).chance(current_cell.wpos.with_z(0), 0.5) {
+                            1.0
+                        } else {
+                            -1.0
+                        },
+                    ),
+            })
         })
 }
 
@@ -417,6 +436,309 @@ pub fn tunnel_bounds_at<'a>(
     tunnel_bounds_at_from(wpos2d, info, land, all_tunnels_at(wpos2d, info, land))
 }
 
+/// The distance between the waypoints along each tunnel of the [`CaveGraph`]
+const WAYPOINT_SPACING: f64 = 48.0;
+
+/// A node of the [`CaveGraph`], where tunnels meet
+pub struct CaveNode {
+    /// A position on the floor of the cave
+    pub wpos: Vec3<i32>,
+    /// How many layers below the surface the node is. Nodes on level 0 are
+    /// entrances from the surface.
+    pub level: u32,
+    pub biome: CaveBiome,
+    tunnels: Vec<usize>,
+}
+
+impl CaveNode {
+    pub fn is_entrance(&self) -> bool { self.level == 0 }
+}
+
+/// A tunnel of the [`CaveGraph`] between two nodes
+pub struct CaveTunnel {
+    pub ends: (Id<CaveNode>, Id<CaveNode>),
+    /// Positions on the floor along the tunnel, from the first end to the
+    /// second, not including the ends themselves
+    pub waypoints: Vec<Vec3<i32>>,
+    /// The length of the tunnel when following its waypoints
+    pub length: f32,
+}
+
+/// A coarse map of the cave network, so that NPCs can find their way through
+/// caves (and between their layers) without pathfinding through every block.
+#[derive(Default)]
+pub struct CaveGraph {
+    nodes: Store<CaveNode>,
+    tunnels: Vec<CaveTunnel>,
+}
+
+impl CaveGraph {
+    pub fn generate(index: IndexRef, sim: &WorldSim) -> Self {
+        let land = Land::from_sim(sim);
+        let mut graph = Self::default();
+        let mut node_ids = HashMap::new();
+        let size_wpos = land.size().as_::<i32>().cpos_to_wpos();
+
+        CanvasInfo::with_mock_canvas_info(index, sim, |info| {
+            // Find the node of a cell, adding it if it hasn't been seen yet
+            let mut node_id =
+                |graph: &mut Self, level: u32, cell: Vec2<i32>, node: Node, tunnel: &Tunnel| {
+                    *node_ids.entry((level, cell)).or_insert_with(|| {
+                        let wpos = node
+                            .wpos
+                            .with_z(tunnel.floor_at(node.wpos.map(|e| e as f64 + 0.5), info));
+                        graph.nodes.insert(CaveNode {
+                            wpos,
+                            level,
+                            biome: tunnel.biome_at(wpos, info).dominant(),
+                            tunnels: Vec::new(),
+                        })
+                    })
+                };
+
+            for level in 0..=LAYERS {
+                let max_cell = to_cell(size_wpos, level);
+                for cell in
+                    (0..=max_cell.x).flat_map(|x| (0..=max_cell.y).map(move |y| Vec2::new(x, y)))
+                {
+                    let Some(node) = node_at(cell, level, &land) else {
+                        continue;
+                    };
+                    // Tunnels within the first level are only ever the ones leading down
+                    let within = (level > 0)
+                        .then(|| tunnels_from_cell(cell, node, level, &land))
+                        .into_iter()
+                        .flatten()
+                        .map(|(other_cell, tunnel)| (level, other_cell, tunnel));
+                    let below = (level < LAYERS)
+                        .then(|| tunnel_below_from_cell(cell, level, &land))
+                        .flatten()
+                        .map(|tunnel| {
+                            let below_cell =
+                                to_cell(to_wpos(cell, level) + CELL_SIZE / 2, level + 1);
+                            (level + 1, below_cell, tunnel)
+                        });
+
+                    for (other_level, other_cell, tunnel) in within.chain(below) {
+                        let a = node_id(&mut graph, level, cell, tunnel.a, &tunnel);
+                        let b = node_id(&mut graph, other_level, other_cell, tunnel.b, &tunnel);
+                        let waypoints = tunnel.waypoints(info);
+                        graph.add_tunnel(a, b, waypoints);
+                    }
+                }
+            }
+        });
+
+        graph
+    }
+
+    fn add_tunnel(&mut self, a: Id<CaveNode>, b: Id<CaveNode>, waypoints: Vec<Vec3<i32>>) {
+        let length = std::iter::once(self.nodes[a].wpos)
+            .chain(waypoints.iter().copied())
+            .chain(std::iter::once(self.nodes[b].wpos))
+            .tuple_windows()
+            .map(|(p, q)| p.as_::<f32>().distance(q.as_()))
+            .sum();
+        let idx = self.tunnels.len();
+        self.tunnels.push(CaveTunnel {
+            ends: (a, b),
+            waypoints,
+            length,
+        });
+        self.nodes[a].tunnels.push(idx);
+        self.nodes[b].tunnels.push(idx);
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (Id<CaveNode>, &CaveNode)> { self.nodes.iter() }
+
+    pub fn node(&self, id: Id<CaveNode>) -> &CaveNode { &self.nodes[id] }
+
+    pub fn entrances(&self) -> impl Iterator<Item = (Id<CaveNode>, &CaveNode)> {
+        self.nodes().filter(|(_, node)| node.is_entrance())
+    }
+
+    /// The tunnels leading away from a node, along with the nodes at their
+    /// other ends
+    pub fn tunnels_from(
+        &self,
+        id: Id<CaveNode>,
+    ) -> impl Iterator<Item = (Id<CaveNode>, &CaveTunnel)> + '_ {
+        self.nodes[id].tunnels.iter().map(move |idx| {
+            let tunnel = &self.tunnels[*idx];
+            let other = if tunnel.ends.0 == id {
+                tunnel.ends.1
+            } else {
+                tunnel.ends.0
+            };
+            (other, tunnel)
+        })
+    }
+
+    /// The node closest to a position, matching the filter
+    pub fn nearest(
+        &self,
+        wpos: Vec3<f32>,
+        mut filter: impl FnMut(&CaveNode) -> bool,
+    ) -> Option<Id<CaveNode>> {
+        self.nodes()
+            .filter(|(_, node)| filter(node))
+            .min_by_key(|(_, node)| node.wpos.as_::<f32>().distance_squared(wpos) as i64)
+            .map(|(id, _)| id)
+    }
+
+    /// Find a route through the caves from one node to another, as the
+    /// positions to walk through in order (including both nodes)
+    pub fn route(&self, start: Id<CaveNode>, end: Id<CaveNode>) -> Option<Vec<Vec3<i32>>> {
+        const MAX_ITERS: usize = 4096;
+
+        let end_wpos = self.nodes[end].wpos.as_::<f32>();
+        let heuristic =
+            |node: &Id<CaveNode>, _: &Id<CaveNode>| self.nodes[*node].wpos.as_().distance(end_wpos);
+        let neighbors = |node: &Id<CaveNode>| {
+            self.tunnels_from(*node)
+                .map(|(other, tunnel)| (other, tunnel.length))
+        };
+        let mut astar = Astar::new(
+            MAX_ITERS,
+            start,
+            BuildHasherDefault::<FxHasher64>::default(),
+        );
+        let (path, _) = astar
+            .poll(MAX_ITERS, heuristic, neighbors, |node| *node == end)
+            .into_path()?;
+
+        let mut route = vec![self.nodes[start].wpos];
+        for (a, b) in path.iter().tuple_windows() {
+            let (_, tunnel) = self
+                .tunnels_from(*a)
+                .filter(|(other, _)| other == b)
+                .min_by(|(_, x), (_, y)| x.length.total_cmp(&y.length))?;
+            if tunnel.ends.0 == *a {
+                route.extend(tunnel.waypoints.iter().copied());
+            } else {
+                route.extend(tunnel.waypoints.iter().rev().copied());
+            }
+            route.push(self.nodes[*b].wpos);
+        }
+        Some(route)
+    }
+
+    /// Find a route through the caves for heading from one position to
+    /// another in a different layer. Positions that aren't underground are
+    /// reached through the nearest entrance.
+    ///
+    /// The route is empty if both positions are in the same layer (or both on
+    /// the surface), or if there's no way through the caves between them.
+    pub fn route_between(
+        &self,
+        from: Vec3<f32>,
+        to: Vec3<f32>,
+        is_underground: impl Fn(Vec3<f32>) -> bool,
+    ) -> CaveRoute {
+        let node_near = |wpos: Vec3<f32>| {
+            if is_underground(wpos) {
+                self.nearest(wpos, |node| !node.is_entrance())
+            } else {
+                self.nearest(wpos, CaveNode::is_entrance)
+            }
+        };
+        let points = node_near(from)
+            .zip(node_near(to))
+            .filter(|(start, end)| self.nodes[*start].level != self.nodes[*end].level)
+            .and_then(|(start, end)| self.route(start, end))
+            .unwrap_or_default();
+        CaveRoute {
+            target: to,
+            start: from,
+            points,
+            next: 0,
+        }
+    }
+}
+
+/// A route through the caves toward a position, found with
+/// [`CaveGraph::route_between`], and how far along it an NPC has got.
+#[derive(Clone, Debug)]
+pub struct CaveRoute {
+    /// The position that the route leads toward
+    pub target: Vec3<f32>,
+    /// Where the route was found from
+    start: Vec3<f32>,
+    points: Vec<Vec3<i32>>,
+    /// The index of the point being walked toward
+    next: usize,
+}
+
+impl CaveRoute {
+    /// How close to a point of the route counts as having reached it
+    const REACHED_DIST: f32 = 4.0;
+    /// How far from the point being walked toward counts as having left the
+    /// route
+    const STRAY_DIST: f32 = WAYPOINT_SPACING as f32 * 2.0;
+
+    /// The next position to walk toward from `wpos`, moving on along the route
+    /// once each point is reached (or we're on our way past it). Returns
+    /// `None` once the whole route has been walked.
+    pub fn next_waypoint(&mut self, wpos: Vec3<f32>) -> Option<Vec3<i32>> {
+        while let Some(point) = self.points.get(self.next).map(|p| p.as_::<f32>()) {
+            let passing = self.points.get(self.next + 1).map_or(false, |next| {
+                next.as_::<f32>().distance(wpos) < next.as_().distance(point)
+            });
+            if point.distance(wpos) < Self::REACHED_DIST || passing {
+                self.next += 1;
+            } else {
+                break;
+            }
+        }
+        self.points.get(self.next).copied()
+    }
+
+    /// Whether `wpos` is too far from the route for it to still be followed
+    pub fn has_strayed(&self, wpos: Vec3<f32>) -> bool {
+        let anchor = self
+            .points
+            .get(self.next)
+            .or(self.points.last())
+            .map_or(self.start, |point| point.as_());
+        anchor.distance(wpos) > Self::STRAY_DIST
+    }
+}
+
+impl Tunnel {
+    /// Evenly spaced positions on the floor along the tunnel, not including
+    /// its ends
+    fn waypoints(&self, info: &CanvasInfo) -> Vec<Vec3<i32>> {
+        let start = self.a.wpos.map(|e| e as f64 + 0.5);
+        let end = self.b.wpos.map(|e| e as f64 + 0.5);
+        let spline = river_spline_coeffs(start, self.ctrl_offset(), end);
+        let n = (start.distance(end) / WAYPOINT_SPACING).ceil().max(1.0) as usize;
+        (1..n)
+            .map(|i| {
+                let t = i as f64 / n as f64;
+                let wposf = spline.x * t * t + spline.y * t + spline.z;
+                wposf.as_().with_z(self.floor_at(wposf, info))
+            })
+            .collect()
+    }
+
+    /// The height of the floor of the tunnel at a position along it
+    fn floor_at(&self, wposf: Vec2<f64>, info: &CanvasInfo) -> i32 {
+        self.z_range_at(wposf, *info).map_or_else(
+            || {
+                // Not inside of the tunnel, so guess from the depth of its ends
+                let (to_a, to_b) = (
+                    wposf.distance(self.a.wpos.as_()),
+                    wposf.distance(self.b.wpos.as_()),
+                );
+                let t = to_a / (to_a + to_b).max(1.0);
+                let depth = Lerp::lerp(self.a.depth as f64, self.b.depth as f64, t);
+                info.land().get_alt_approx(wposf.as_()) as i32 - depth as i32
+            },
+            |(z_range, ..)| z_range.start,
+        )
+    }
+}
+
 pub fn apply_caves_to(canvas: &mut Canvas, rng: &mut impl Rng) {
     let info = canvas.info();
     let land = info.land();
@@ -512,6 +834,39 @@ pub struct Biome {
     depth: f32,
 }
 
+impl Biome {
+    /// The kind of cave that is most prominent here
+    pub fn dominant(&self) -> CaveBiome {
+        [
+            (CaveBiome::Barren, self.barren),
+            (CaveBiome::Mushroom, self.mushroom),
+            (CaveBiome::Fire, self.fire),
+            (CaveBiome::Leafy, self.leafy),
+            (CaveBiome::Dusty, self.dusty),
+            (CaveBiome::Icy, self.icy),
+            (CaveBiome::Snowy, self.snowy),
+            (CaveBiome::Crystal, self.crystal),
+            (CaveBiome::Sandy, self.sandy),
+        ]
+        .into_iter()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map_or(CaveBiome::Barren, |(biome, _)| biome)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CaveBiome {
+    Barren,
+    Mushroom,
+    Fire,
+    Leafy,
+    Dusty,
+    Icy,
+    Snowy,
+    Crystal,
+    Sandy,
+}
+
 #[derive(Clone)]
 enum CaveStructure {
     Mushroom(Mushroom),
@@ -1858,3 +2213,116 @@ fn apply_entity_spawns<R: Rng>(canvas: &mut Canvas, wpos: Vec3<i32>, biome: &Bio
         canvas.spawn(EntityInfo::at(wpos.map(|e| e as f32)).into_waypoint());
     } */
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_node(graph: &mut CaveGraph, wpos: Vec3<i32>, level: u32) -> Id<CaveNode> {
+        graph.nodes.insert(CaveNode {
+            wpos,
+            level,
+            biome: CaveBiome::Barren,
+            tunnels: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_cave_route() {
+        let mut graph = CaveGraph::default();
+        let entrance = add_node(&mut graph, Vec3::new(0, 0, 100), 0);
+        let shallow = add_node(&mut graph, Vec3::new(100, 0, 0), 1);
+        let deep = add_node(&mut graph, Vec3::new(200, 0, -100), 2);
+        let detour = add_node(&mut graph, Vec3::new(0, 1000, 0), 1);
+        let lonely = add_node(&mut graph, Vec3::new(500, 500, 0), 1);
+        graph.add_tunnel(entrance, shallow, vec![Vec3::new(50, 0, 50)]);
+        graph.add_tunnel(deep, shallow, vec![
+            Vec3::new(170, 0, -70),
+            Vec3::new(130, 0, -30),
+        ]);
+        graph.add_tunnel(entrance, detour, Vec::new());
+        graph.add_tunnel(detour, deep, Vec::new());
+
+        assert_eq!(
+            graph.entrances().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![entrance]
+        );
+        assert_eq!(
+            graph.nearest(Vec3::new(190.0, 10.0, -90.0), |_| true),
+            Some(deep)
+        );
+        // The tunnel from the deep node is walked backwards
+        assert_eq!(
+            graph.route(entrance, deep),
+            Some(vec![
+                Vec3::new(0, 0, 100),
+                Vec3::new(50, 0, 50),
+                Vec3::new(100, 0, 0),
+                Vec3::new(130, 0, -30),
+                Vec3::new(170, 0, -70),
+                Vec3::new(200, 0, -100),
+            ])
+        );
+        assert_eq!(graph.route(entrance, lonely), None);
+    }
+
+    #[test]
+    fn test_cave_route_between_layers() {
+        let mut graph = CaveGraph::default();
+        let entrance = add_node(&mut graph, Vec3::new(0, 0, 100), 0);
+        let shallow = add_node(&mut graph, Vec3::new(100, 0, 0), 1);
+        let deep = add_node(&mut graph, Vec3::new(200, 0, -100), 2);
+        graph.add_tunnel(entrance, shallow, vec![Vec3::new(50, 0, 50)]);
+        graph.add_tunnel(shallow, deep, vec![Vec3::new(150, 0, -50)]);
+        let is_underground = |wpos: Vec3<f32>| wpos.z < 90.0;
+
+        // From the first layer down to the second
+        let deep_wpos = Vec3::new(205.0, 0.0, -100.0);
+        let mut route = graph.route_between(Vec3::new(102.0, 1.0, 0.0), deep_wpos, is_underground);
+        assert_eq!(route.target, deep_wpos);
+        assert_eq!(
+            route.next_waypoint(Vec3::new(102.0, 1.0, 0.0)),
+            Some(Vec3::new(150, 0, -50))
+        );
+        // Having set off along the tunnel, there's no turning back
+        assert_eq!(
+            route.next_waypoint(Vec3::new(110.0, 0.0, -10.0)),
+            Some(Vec3::new(150, 0, -50))
+        );
+        assert!(!route.has_strayed(Vec3::new(110.0, 0.0, -10.0)));
+        assert_eq!(
+            route.next_waypoint(Vec3::new(151.0, 0.0, -50.0)),
+            Some(Vec3::new(200, 0, -100))
+        );
+        assert_eq!(route.next_waypoint(Vec3::new(200.0, 0.0, -99.0)), None);
+        assert!(route.has_strayed(Vec3::new(500.0, 0.0, -100.0)));
+
+        // Back up again, from partway along the tunnel
+        let mut route = graph.route_between(
+            Vec3::new(160.0, 0.0, -60.0),
+            Vec3::new(95.0, 0.0, 0.0),
+            is_underground,
+        );
+        assert_eq!(
+            route.next_waypoint(Vec3::new(160.0, 0.0, -60.0)),
+            Some(Vec3::new(150, 0, -50))
+        );
+
+        // From the surface, the way in is through the entrance
+        let mut route = graph.route_between(
+            Vec3::new(-50.0, 0.0, 100.0),
+            Vec3::new(100.0, 0.0, 0.0),
+            is_underground,
+        );
+        assert_eq!(
+            route.next_waypoint(Vec3::new(-50.0, 0.0, 100.0)),
+            Some(Vec3::new(0, 0, 100))
+        );
+
+        // Within a single layer, the agent can find its own way
+        let from = Vec3::new(190.0, 0.0, -100.0);
+        let mut route = graph.route_between(from, Vec3::new(210.0, 0.0, -100.0), is_underground);
+        assert_eq!(route.next_waypoint(from), None);
+        assert!(!route.has_strayed(from));
+    }
+}
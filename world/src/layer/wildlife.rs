@@ -4,10 +4,12 @@ use common::{
     calendar::{Calendar, CalendarEvent},
     generation::{ChunkSupplement, EntityInfo},
     resources::TimeOfDay,
+    rtsim::WildlifeGroup,
     terrain::{BiomeKind, Block},
     time::DayPeriod,
     vol::{BaseVol, ReadVol, RectSizedVol, WriteVol},
 };
+use enum_map::EnumMap;
 use rand::prelude::*;
 use serde::Deserialize;
 use std::f32;
@@ -559,6 +561,8 @@ pub fn apply_wildlife_supplement<'a, R: Rng>(
     chunk: &SimChunk,
     supplement: &mut ChunkSupplement,
     time: Option<&(TimeOfDay, Calendar)>,
+    // How plentiful each group of wildlife is in this area, relative to its natural level
+    rtsim_wildlife: Option<&EnumMap<WildlifeGroup, f32>>,
 ) {
     let scatter = &index.wildlife_spawns;
     // Configurable density multiplier
@@ -624,6 +628,16 @@ pub fn apply_wildlife_supplement<'a, R: Rng>(
                     (wpos2d.map(|e| e as f32) + 0.5).with_z(desired_alt),
                     dynamic_rng,
                 );
+
+                // Wildlife that has been hunted out of an area spawns less often, while
+                // wildlife that has flourished spawns in larger groups
+                let abundance = rtsim_wildlife
+                    .zip(WildlifeGroup::of(&entity.body, &entity.alignment))
+                    .map_or(1.0, |(wildlife, group)| wildlife[group]);
+                if !dynamic_rng.gen_bool(abundance.clamp(0.0, 1.0) as f64) {
+                    continue;
+                }
+                let group_size = (group_size as f32 * abundance.max(1.0)).round() as u8;
                 for e in 0..group_size {
                     // Choose a nearby position
                     let offs_wpos2d = (Vec2::new(
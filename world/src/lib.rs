@@ -53,7 +53,7 @@ use common::{
     generation::{ChunkSupplement, EntityInfo, SpecialEntity},
     lod,
     resources::TimeOfDay,
-    rtsim::ChunkResource,
+    rtsim::{ChunkResource, WildlifeGroup},
     terrain::{
         Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainChunkSize, TerrainGrid,
     },
@@ -94,6 +94,7 @@ pub enum WorldGenerateStage {
     WorldCivGenerate(WorldCivStage),
     EconomySimulation,
     SpotGeneration,
+    CaveGraph,
 }
 
 pub struct World {
@@ -145,6 +146,20 @@ impl World {
             report_stage(WorldGenerateStage::SpotGeneration);
             Spot::generate(&mut sim);
 
+            report_stage(WorldGenerateStage::CaveGraph);
+            index.caves = {
+                let colors = index.colors();
+                let features = index.features();
+                layer::cave::CaveGraph::generate(
+                    IndexRef {
+                        colors: &colors,
+                        features: &features,
+                        index: &index,
+                    },
+                    &sim,
+                )
+            };
+
             (Self { sim, civs }, IndexOwned::new(index))
         })
     }
@@ -346,7 +361,7 @@ impl World {
         // Unwrapping because generate_chunk only returns err when should_continue evals
         // to true
         let (tc, _cs) = self
-            .generate_chunk(index, chunk_pos, None, || false, None)
+            .generate_chunk(index, chunk_pos, None, None, || false, None)
             .unwrap();
 
         tc.find_accessible_pos(spawn_wpos, ascending)
@@ -358,6 +373,7 @@ impl World {
         index: IndexRef,
         chunk_pos: Vec2<i32>,
         rtsim_resources: Option<EnumMap<ChunkResource, f32>>,
+        rtsim_wildlife: Option<EnumMap<WildlifeGroup, f32>>,
         // TODO: misleading name
         mut should_continue: impl FnMut() -> bool,
         time: Option<(TimeOfDay, Calendar)>,
@@ -586,6 +602,7 @@ impl World {
             sim_chunk,
             &mut supplement,
             time.as_ref(),
+            rtsim_wildlife.as_ref(),
         );
 
         // Apply site supplementary information
@@ -806,4 +823,20 @@ impl World {
         let sim_chunk = self.sim.get(chunk_pos)?;
         sim_chunk.get_location_name(&index.sites, &self.civs.pois, wpos2d)
     }
+
+    /// Find the interior of the house that contains the given position, if
+    /// there is one
+    pub fn find_house(&self, index: IndexRef, wpos: Vec3<i32>) -> Option<Aabb<i32>> {
+        let sim_chunk = self.sim.get(wpos.xy().wpos_to_cpos())?;
+        sim_chunk.sites.iter().find_map(|site| {
+            let site2 = index.sites.get(*site).site2()?;
+            let plot = site2.wpos_tile(wpos.xy()).plot?;
+            match site2.plot(plot).kind() {
+                site2::PlotKind::House(house) => {
+                    Some(house.interior()).filter(|interior| interior.contains_point(wpos))
+                },
+                _ => None,
+            }
+        })
+    }
 }
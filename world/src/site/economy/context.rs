@@ -1,11 +1,13 @@
 /// this contains global housekeeping info during simulation
 use crate::{
     site::{
-        economy::{Economy, DAYS_PER_MONTH, DAYS_PER_YEAR, INTER_SITE_TRADE},
-        SiteKind,
+        economy::{Economy, TradeInformation, DAYS_PER_MONTH, DAYS_PER_YEAR, INTER_SITE_TRADE},
+        Site, SiteKind,
     },
+    util::DHashMap,
     Index,
 };
+use common::store::Id;
 use rayon::prelude::*;
 use tracing::{debug, info};
 
@@ -268,6 +270,42 @@ fn tick(index: &mut Index, dt: f32, _env: &mut Environment) {
     index.time += dt;
 }
 
+/// Advance the economies of sites by `dt` days once the world is running.
+///
+/// This mirrors the simulation performed during world generation, but works on
+/// economies that live outside of the (immutable at runtime) [`Index`].
+pub fn tick_live(
+    economies: &mut DHashMap<Id<Site>, &mut Economy>,
+    trade: &mut TradeInformation,
+    dt: f32,
+) {
+    if INTER_SITE_TRADE {
+        // move deliverables to recipient cities
+        for (id, deliv) in trade.deliveries.drain() {
+            if let Some(economy) = economies.get_mut(&id) {
+                economy.deliveries.extend(deliv);
+            }
+        }
+    }
+    for (site_id, economy) in economies.iter_mut() {
+        economy.tick(*site_id, dt);
+    }
+    if INTER_SITE_TRADE {
+        // distribute orders (travelling merchants)
+        for economy in economies.values_mut() {
+            for (i, mut v) in economy.orders.drain() {
+                trade.orders.entry(i).or_default().append(&mut v);
+            }
+        }
+        // trade at sites
+        for (site, orders) in trade.orders.iter_mut() {
+            if let Some(economy) = economies.get_mut(site) {
+                economy.trade_at_site(*site, orders, &mut trade.deliveries);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{sim, util::seed_expan};
@@ -466,6 +504,23 @@ mod tests {
         });
     }
 
+    #[test]
+    /// test that the persisted state of an economy survives a round trip
+    fn test_economy_persistence() {
+        let mut economy = crate::site::economy::Economy::default();
+        economy.exchange([(Good::Coin, 250.0), (Good::Food, -50.0)]);
+        let saved = ron::ser::to_string(&economy).expect("economy should serialize");
+        let loaded: crate::site::economy::Economy =
+            ron::de::from_str(&saved).expect("economy should deserialize");
+        assert_eq!(loaded.population(), economy.population());
+        for (good, amount) in economy.stocks.iter() {
+            assert_eq!(loaded.stocks[good], *amount);
+        }
+        for (labor, amount) in economy.labors.iter() {
+            assert_eq!(loaded.labors[labor], *amount);
+        }
+    }
+
     struct Simenv {
         index: crate::index::Index,
         rng: ChaChaRng,
@@ -264,14 +264,14 @@ impl<V: Copy + Default> LaborMap<V> {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct AreaResources {
     pub resource_sum: GoodMap<f32>,
     pub resource_chunks: GoodMap<f32>,
     pub chunks: u32,
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct NaturalResources {
     // resources per distance, we should increase labor cost for far resources
     pub per_area: Vec<AreaResources>,
@@ -352,6 +352,10 @@ impl Labor {
 
     pub fn is_everyone(&self) -> bool { self.0 == DUMMY_LABOR.0 }
 
+    pub fn name(&self) -> Option<&'static str> {
+        LABOR.get(self.0 as usize).map(|l| l.name.as_str())
+    }
+
     pub fn orders_everyone() -> impl Iterator<Item = &'static (GoodIndex, f32)> {
         LABOR
             .get(DUMMY_LABOR.0 as usize)
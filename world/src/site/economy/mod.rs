@@ -13,6 +13,7 @@ use common::{
 };
 use hashbrown::HashMap;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{cmp::Ordering::Less, convert::TryFrom};
 use tracing::{debug, info, trace, warn};
 
@@ -21,7 +22,7 @@ mod map_types;
 pub use map_types::Labor;
 use map_types::{GoodIndex, GoodMap, LaborIndex, LaborMap, NaturalResources};
 mod context;
-pub use context::simulate_economy;
+pub use context::{simulate_economy, tick_live};
 mod cache;
 
 const INTER_SITE_TRADE: bool = true;
@@ -29,13 +30,13 @@ const DAYS_PER_MONTH: f32 = 30.0;
 const DAYS_PER_YEAR: f32 = 12.0 * DAYS_PER_MONTH;
 const GENERATE_CSV: bool = false;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TradeOrder {
     customer: Id<Site>,
     amount: GoodMap<f32>, // positive for orders, negative for exchange
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct TradeDelivery {
     supplier: Id<Site>,
     amount: GoodMap<f32>, // positive for orders, negative for exchange
@@ -43,13 +44,45 @@ pub struct TradeDelivery {
     supply: GoodMap<f32>, // maximum amount available, at the time of interaction
 }
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct TradeInformation {
     orders: DHashMap<Id<Site>, Vec<TradeOrder>>, // per provider
     deliveries: DHashMap<Id<Site>, Vec<TradeDelivery>>, // per receiver
 }
 
-#[derive(Debug)]
+impl TradeDelivery {
+    /// The site that the goods are coming from.
+    pub fn supplier(&self) -> Id<Site> { self.supplier }
+
+    /// The total amount of goods being delivered.
+    pub fn total_amount(&self) -> f32 { self.amount.iter().map(|(_, a)| a.max(0.0)).sum() }
+
+    /// Lose a proportion of the goods before they reach the receiver.
+    pub fn lose(&mut self, proportion: f32) {
+        let kept = (1.0 - proportion).clamp(0.0, 1.0);
+        self.amount = self.amount.map(|_, a| a * kept);
+    }
+}
+
+impl TradeInformation {
+    /// Take the deliveries that are waiting to be made, along with the site
+    /// that each is for. Deliveries that are not taken are made instantly
+    /// the next time the economies tick.
+    pub fn take_deliveries(&mut self) -> Vec<(Id<Site>, TradeDelivery)> {
+        self.deliveries
+            .drain()
+            .flat_map(|(receiver, deliveries)| deliveries.into_iter().map(move |d| (receiver, d)))
+            .collect()
+    }
+
+    /// Make a delivery the next time the economies tick, without anybody
+    /// carrying it there.
+    pub fn deliver(&mut self, receiver: Id<Site>, delivery: TradeDelivery) {
+        self.deliveries.entry(receiver).or_default().push(delivery);
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct NeighborInformation {
     id: Id<Site>,
     //travel_distance: usize,
@@ -65,7 +98,7 @@ lazy_static! {
     static ref TRANSPORTATION_INDEX: GoodIndex = Transportation.try_into().unwrap_or_default();
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Economy {
     /// Population
     pop: f32,
@@ -143,6 +176,97 @@ impl Default for Economy {
     }
 }
 
+/// The parts of an [`Economy`] that change while it is being simulated.
+///
+/// Everything else is derived from the world during generation and gets
+/// restored with [`Economy::link`] after loading.
+#[derive(Serialize, Deserialize)]
+struct EconomyState {
+    pop: f32,
+    stocks: HashMap<Good, f32>,
+    unconsumed_stock: HashMap<Good, f32>,
+    values: HashMap<Good, f32>,
+    labor_values: HashMap<Good, f32>,
+    last_exports: HashMap<Good, f32>,
+    // Professions are stored by name so that changes to the profession list don't
+    // mix up the labor of different professions
+    labors: HashMap<String, f32>,
+    yields: HashMap<String, f32>,
+    productivity: HashMap<String, f32>,
+}
+
+impl Serialize for Economy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let goods = |map: &GoodMap<f32>| map.iter().map(|(g, a)| (Good::from(g), *a)).collect();
+        let optional_goods = |map: &GoodMap<Option<f32>>| {
+            map.iter()
+                .filter_map(|(g, a)| a.map(|a| (Good::from(g), a)))
+                .collect()
+        };
+        let labors = |map: &LaborMap<f32>| {
+            map.iter()
+                .filter_map(|(l, a)| Some((l.name()?.to_string(), *a)))
+                .collect()
+        };
+
+        EconomyState {
+            pop: self.pop,
+            stocks: goods(&self.stocks),
+            unconsumed_stock: goods(&self.unconsumed_stock),
+            values: optional_goods(&self.values),
+            labor_values: optional_goods(&self.labor_values),
+            last_exports: goods(&self.last_exports),
+            labors: labors(&self.labors),
+            yields: labors(&self.yields),
+            productivity: labors(&self.productivity),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Economy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = EconomyState::deserialize(deserializer)?;
+
+        let goods = |map: &mut GoodMap<f32>, state: &HashMap<Good, f32>| {
+            for (good, amount) in state {
+                if let Ok(good) = GoodIndex::try_from(*good) {
+                    map[good] = *amount;
+                }
+            }
+        };
+        let optional_goods = |state: &HashMap<Good, f32>| {
+            GoodMap::from_iter(
+                state.iter().filter_map(|(good, value)| {
+                    Some((GoodIndex::try_from(*good).ok()?, Some(*value)))
+                }),
+                None,
+            )
+        };
+        let labors = |map: &mut LaborMap<f32>, state: &HashMap<String, f32>| {
+            for (labor, amount) in map.iter_mut() {
+                if let Some(saved) = labor.name().and_then(|name| state.get(name)) {
+                    *amount = *saved;
+                }
+            }
+        };
+
+        let mut economy = Economy {
+            pop: state.pop,
+            values: optional_goods(&state.values),
+            labor_values: optional_goods(&state.labor_values),
+            ..Default::default()
+        };
+        goods(&mut economy.stocks, &state.stocks);
+        goods(&mut economy.unconsumed_stock, &state.unconsumed_stock);
+        goods(&mut economy.last_exports, &state.last_exports);
+        labors(&mut economy.labors, &state.labors);
+        labors(&mut economy.yields, &state.yields);
+        labors(&mut economy.productivity, &state.productivity);
+        Ok(economy)
+    }
+}
+
 impl Economy {
     const MINIMUM_PRICE: f32 = 0.1;
     const STARTING_COIN: f32 = 1000.0;
@@ -150,6 +274,10 @@ impl Economy {
 
     pub fn population(&self) -> f32 { self.pop }
 
+    /// Receive goods that have been carried to the site. They are added to
+    /// the stocks the next time the economy ticks.
+    pub fn receive(&mut self, delivery: TradeDelivery) { self.deliveries.push(delivery); }
+
     pub fn get_available_stock(&self) -> HashMap<Good, f32> {
         self.unconsumed_stock
             .iter()
@@ -297,6 +425,25 @@ impl Economy {
         }
     }
 
+    /// Restore the parts of a loaded economy that are derived from the world
+    /// during generation (natural resources and trading partners), taking them
+    /// from the freshly generated economy of the same site.
+    pub fn link(&mut self, generated: &Economy) {
+        self.natural_resources = generated.natural_resources.clone();
+        self.neighbors = generated.neighbors.clone();
+    }
+
+    /// Account for goods exchanged with a player at this site. Positive
+    /// amounts are received by the site, negative amounts are handed out.
+    pub fn exchange(&mut self, goods: impl IntoIterator<Item = (Good, f32)>) {
+        for (good, amount) in goods {
+            if let Ok(good) = GoodIndex::try_from(good) {
+                self.stocks[good] = (self.stocks[good] + amount).max(0.0);
+                self.unconsumed_stock[good] = (self.unconsumed_stock[good] + amount).max(0.0);
+            }
+        }
+    }
+
     pub fn add_neighbor(&mut self, id: Id<Site>, _distance: usize) {
         self.neighbors.push(NeighborInformation {
             id,
@@ -10,6 +10,7 @@ use common::{
     assets::{self, AssetExt, AssetHandle},
     astar::Astar,
     comp::misc::PortalData,
+    encounter::EncounterInfo,
     generation::{ChunkSupplement, EntityInfo, SpecialEntity},
     resources::Secs,
     store::{Id, Store},
@@ -206,6 +207,8 @@ pub struct Room {
     pillars: Option<i32>, // Pillars with the given separation
     pits: Option<i32>,    // Pits filled with lava
     difficulty: u32,
+    /// The boss encounter that takes place in this room, if any
+    encounter: Option<&'static str>,
 }
 
 impl Room {
@@ -277,6 +280,7 @@ impl Room {
         tile_wcenter: Vec3<i32>,
         wpos2d: Vec2<i32>,
         tile_pos: Vec2<i32>,
+        encounter: impl FnOnce() -> Option<EncounterInfo>,
     ) {
         let boss_spawn_tile = self.area.center();
         // Don't spawn the boss in a pillar
@@ -293,8 +297,14 @@ impl Room {
                 4 => boss_4(dynamic_rng, tile_wcenter),
                 _ => boss_fallback(dynamic_rng, tile_wcenter),
             };
+            let encounter = encounter();
 
             for entity in entities {
+                let entity = if let Some(encounter) = &encounter {
+                    entity.with_encounter(encounter.clone())
+                } else {
+                    entity
+                };
                 supplement.add_entity(entity);
             }
         }
@@ -363,6 +373,7 @@ impl Floor {
             pillars: None,
             pits: None,
             difficulty,
+            encounter: None,
         });
         if final_level {
             // Boss room
@@ -378,6 +389,7 @@ impl Floor {
                 pillars: Some(2),
                 pits: None,
                 difficulty,
+                encounter: boss_encounter(difficulty),
             });
         } else {
             // Create downstairs room
@@ -390,6 +402,7 @@ impl Floor {
                 pillars: None,
                 pits: None,
                 difficulty,
+                encounter: None,
             });
             this.tiles.set(
                 new_stair_tile - tile_offset,
@@ -479,6 +492,7 @@ impl Floor {
                     pillars: Some(ctx.rng.gen_range(2..=4)),
                     pits: None,
                     difficulty: self.difficulty,
+                    encounter: None,
                 }),
                 //// Lava platforming room
                 //1 => self.create_room(Room {
@@ -490,6 +504,7 @@ impl Floor {
                 //    pillars: None,
                 //    pits: Some(1),
                 //    difficulty: self.difficulty,
+                //    encounter: None,
                 //}),
                 // Fight room with enemies in it
                 _ => self.create_room(Room {
@@ -505,6 +520,7 @@ impl Floor {
                     },
                     pits: None,
                     difficulty: self.difficulty,
+                    encounter: None,
                 }),
             };
         }
@@ -594,8 +610,8 @@ impl Floor {
                             .map(|e| e.div_euclid(TILE_SIZE) * TILE_SIZE + TILE_SIZE / 2),
                     );
 
-                if let Some(Tile::Room(room)) = self.tiles.get(tile_pos) {
-                    let room = &self.rooms[*room];
+                if let Some(Tile::Room(room_id)) = self.tiles.get(tile_pos) {
+                    let room = &self.rooms[*room_id];
 
                     let tile_wcenter = origin
                         + Vec3::from(
@@ -624,6 +640,7 @@ impl Floor {
                             tile_wcenter,
                             wpos2d,
                             tile_pos,
+                            || self.encounter_info(*room_id, origin),
                         ),
                         RoomKind::Peaceful | RoomKind::LavaPlatforming => {},
                     }
@@ -658,6 +675,62 @@ impl Floor {
 
     fn total_depth(&self) -> i32 { self.solid_depth + self.hollow_depth }
 
+    fn tunnel_height(&self) -> f32 { if self.final_level { 16.0 } else { 8.0 } }
+
+    /// The arena and doors of the encounter that takes place in the given
+    /// room, `origin` being the position of the floor's origin in the world
+    fn encounter_info(&self, room_id: Id<Room>, origin: Vec3<i32>) -> Option<EncounterInfo> {
+        let room = &self.rooms[room_id];
+        let def = room.encounter?;
+        let floor_z = origin.z;
+        let tile_corner = |tile: Vec2<i32>| origin.xy() + (self.tile_offset + tile) * TILE_SIZE;
+        let in_room =
+            |tile: Vec2<i32>| matches!(self.tiles.get(tile), Some(Tile::Room(r)) if *r == room_id);
+
+        let arena = Aabb {
+            min: tile_corner(Vec2::new(room.area.x, room.area.y)).with_z(floor_z),
+            max: tile_corner(Vec2::new(
+                room.area.x + room.area.w,
+                room.area.y + room.area.h,
+            ))
+            .with_z(floor_z + self.hollow_depth),
+        };
+
+        // Any opening into a passable tile outside the room is a door, sealed with
+        // a slab that straddles the edge between the two tiles
+        let door_height = self.tunnel_height() as i32 + 1;
+        let mut doors = Vec::new();
+        for x in room.area.x..room.area.x + room.area.w {
+            for y in room.area.y..room.area.y + room.area.h {
+                let tile = Vec2::new(x, y);
+                if !in_room(tile) {
+                    continue;
+                }
+                for dir in CARDINALS {
+                    let neighbor = tile + dir;
+                    if in_room(neighbor)
+                        || !self.tiles.get(neighbor).map_or(false, |t| t.is_passable())
+                    {
+                        continue;
+                    }
+                    let across = dir.map(|e| e.abs());
+                    let along = dir.map(|e| i32::from(e == 0));
+                    let edge = tile_corner(tile) + dir.map(|e| e.max(0)) * TILE_SIZE;
+                    doors.push(Aabb {
+                        min: (edge - across).with_z(floor_z),
+                        max: (edge + across + along * TILE_SIZE).with_z(floor_z + door_height),
+                    });
+                }
+            }
+        }
+
+        Some(EncounterInfo {
+            def: def.to_owned(),
+            arena,
+            doors,
+        })
+    }
+
     // Find orientation of a position relative to another position
     #[allow(clippy::collapsible_else_if)]
     fn relative_ori(pos1: Vec2<i32>, pos2: Vec2<i32>) -> u8 {
@@ -734,6 +807,15 @@ fn enemy_fallback(dynamic_rng: &mut impl Rng, tile_wcenter: Vec3<i32>) -> Vec<En
     entities
 }
 
+/// The encounter that the boss of a dungeon with the given difficulty opts into
+fn boss_encounter(difficulty: u32) -> Option<&'static str> {
+    match difficulty {
+        2 => Some("common.encounter.dungeon.sahagin"),
+        4 => Some("common.encounter.dungeon.myrmidon"),
+        _ => None,
+    }
+}
+
 fn boss_2(dynamic_rng: &mut impl Rng, tile_wcenter: Vec3<i32>) -> Vec<EntityInfo> {
     vec![
         EntityInfo::at(tile_wcenter.map(|e| e as f32)).with_asset_expect(
@@ -950,7 +1032,7 @@ impl Floor {
         }));
 
         let wall_thickness = 3.0;
-        let tunnel_height = if self.final_level { 16.0 } else { 8.0 };
+        let tunnel_height = self.tunnel_height();
         let pillar_thickness: i32 = 4;
 
         // Several primitives and fills use the tile information for finding the nearest
@@ -97,6 +97,16 @@ impl House {
     pub fn z_range(&self) -> Range<i32> { self.alt..self.alt + self.levels as i32 * STOREY }
 
     pub fn roof_color(&self) -> Rgb<u8> { self.roof_color }
+
+    /// The space inside the walls of the house, from the ground floor up to
+    /// just beneath the roof
+    pub fn interior(&self) -> Aabb<i32> {
+        let alt = self.alt + 1;
+        Aabb {
+            min: (self.bounds.min + 1).with_z(alt),
+            max: (self.bounds.max - 1).with_z(alt + STOREY * self.levels as i32 - 1),
+        }
+    }
 }
 
 const STOREY: i32 = 5;
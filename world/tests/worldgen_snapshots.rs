@@ -0,0 +1,179 @@
+//! Checks that worldgen still produces the same world for a fixed seed.
+//!
+//! A small world is generated for each case, and its heightmap, sites and a
+//! sample of chunks are compared to the snapshots in `tests/snapshots`. When a
+//! change to worldgen is intended, update the snapshots with
+//! `cargo bless-worldgen` and check them in along with the change.
+
+use common::{
+    resources::MapKind,
+    terrain::TerrainChunkSize,
+    vol::{IntoVolIterator, RectVolSize},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    hash::{Hash, Hasher},
+    path::PathBuf,
+};
+use vek::*;
+use veloren_world::{
+    sim::{FileOpts, GenOpts, WorldOpts},
+    World,
+};
+
+/// How many chunks are generated for each case, spread across the map
+const SAMPLED_CHUNKS: i32 = 4;
+
+struct Case {
+    name: &'static str,
+    seed: u32,
+    opts: GenOpts,
+}
+
+fn cases() -> [Case; 2] {
+    [
+        Case {
+            name: "square",
+            seed: 1,
+            opts: GenOpts {
+                x_lg: 6,
+                y_lg: 6,
+                scale: 1.0,
+                map_kind: MapKind::Square,
+                erosion_quality: 0.5,
+            },
+        },
+        Case {
+            name: "circle",
+            seed: 2,
+            opts: GenOpts {
+                x_lg: 6,
+                y_lg: 6,
+                scale: 1.0,
+                map_kind: MapKind::Circle,
+                erosion_quality: 0.5,
+            },
+        },
+    ]
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Snapshot {
+    /// A hash of the altitude and water altitude of every chunk
+    heightmap: u64,
+    /// The kind of every site and the position of its centre
+    sites: Vec<(String, [i32; 2])>,
+    /// A hash of the kind of every block of each sampled chunk, by chunk
+    /// position
+    chunks: Vec<([i32; 2], u64)>,
+}
+
+impl Snapshot {
+    fn generate(case: &Case) -> Self {
+        let threadpool = rayon::ThreadPoolBuilder::new().build().unwrap();
+        let (world, index) = World::generate(
+            case.seed,
+            WorldOpts {
+                seed_elements: true,
+                world_file: FileOpts::Generate(case.opts.clone()),
+                calendar: None,
+            },
+            &threadpool,
+            &|_| {},
+        );
+        let size = world.sim().get_size().as_::<i32>();
+
+        // FxHash is used because, unlike the standard library's hasher, it is
+        // guaranteed not to change between Rust versions
+        let mut heightmap = fxhash::FxHasher64::default();
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let chunk = world.sim().get(Vec2::new(x, y)).unwrap();
+                chunk.alt.to_bits().hash(&mut heightmap);
+                chunk.water_alt.to_bits().hash(&mut heightmap);
+            }
+        }
+
+        let sites = world
+            .civs()
+            .sites()
+            .map(|site| (format!("{:?}", site.kind), site.center.into_array()))
+            .collect();
+
+        let chunks = (0..SAMPLED_CHUNKS)
+            .flat_map(|y| (0..SAMPLED_CHUNKS).map(move |x| Vec2::new(x, y)))
+            .map(|i| (i * 2 + 1) * size / (SAMPLED_CHUNKS * 2))
+            .map(|chunk_pos| {
+                let (chunk, _) = world
+                    .generate_chunk(index.as_index_ref(), chunk_pos, None, None, || false, None)
+                    .unwrap();
+                let mut blocks = fxhash::FxHasher64::default();
+                chunk.get_min_z().hash(&mut blocks);
+                let min = Vec3::new(0, 0, chunk.get_min_z());
+                let max = TerrainChunkSize::RECT_SIZE
+                    .as_::<i32>()
+                    .with_z(chunk.get_max_z());
+                // Sprites are left out because some of them, like chests, are placed
+                // with an rng that is seeded differently every time
+                for (_, block) in chunk.vol_iter(min, max) {
+                    (block.kind() as u8).hash(&mut blocks);
+                }
+                (chunk_pos.into_array(), blocks.finish())
+            })
+            .collect();
+
+        Self {
+            heightmap: heightmap.finish(),
+            sites,
+            chunks,
+        }
+    }
+
+    fn path(case: &Case) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/snapshots")
+            .join(case.name)
+            .with_extension("ron")
+    }
+
+    fn save(&self, case: &Case) {
+        let path = Self::path(case);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let ron = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+        fs::write(path, ron + "\n").unwrap();
+    }
+}
+
+#[test]
+fn worldgen_matches_snapshots() {
+    for case in cases() {
+        let snapshot = Snapshot::generate(&case);
+        let path = Snapshot::path(&case);
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "No snapshot for the {} case at {} ({}), record it with `cargo bless-worldgen` \
+                 and check it in",
+                case.name,
+                path.display(),
+                e
+            )
+        });
+        let expected: Snapshot = ron::from_str(&expected).unwrap();
+        assert_eq!(
+            snapshot, expected,
+            "Worldgen for the {} case has changed, if this is intended update the snapshots with \
+             `cargo bless-worldgen`",
+            case.name
+        );
+    }
+}
+
+/// Run by `cargo bless-worldgen` to accept changes to worldgen
+#[test]
+#[ignore]
+fn bless() {
+    for case in cases() {
+        Snapshot::generate(&case).save(&case);
+    }
+}